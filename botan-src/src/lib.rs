@@ -19,6 +19,128 @@ fn env_name_for(opt: &'static str) -> String {
     format!("BOTAN_CONFIGURE_{}", to_var)
 }
 
+// Settings which are needed to cross compile for some targets, used
+// unless overridden by the corresponding environment variable
+fn target_default(opt: &str) -> Option<String> {
+    let target = env::var("TARGET").unwrap_or_default();
+
+    if target == "wasm32-unknown-emscripten" {
+        // Emscripten emulates /dev/urandom using the host's secure
+        // random source, which makes the system RNG available
+        return match opt {
+            "--os" => Some("emscripten".to_string()),
+            "--cpu" => Some("wasm".to_string()),
+            "--cc" => Some("emcc".to_string()),
+            "--with-os-features" => Some("dev_random".to_string()),
+            _ => None,
+        };
+    }
+
+    if target.contains("-android") {
+        return android_default(&target, opt);
+    }
+
+    if target.contains("-apple-ios") {
+        return ios_default(&target, opt);
+    }
+
+    None
+}
+
+fn botan_cpu(target: &str) -> Option<&'static str> {
+    match target.split('-').next() {
+        Some("aarch64") => Some("arm64"),
+        Some("armv7") | Some("thumbv7neon") => Some("armv7"),
+        Some("i686") => Some("x86_32"),
+        Some("x86_64") => Some("x86_64"),
+        _ => None,
+    }
+}
+
+// The NDK's compiler and archiver, found through ANDROID_NDK_HOME (or
+// ANDROID_NDK_ROOT), for the API level in ANDROID_PLATFORM (default 21)
+fn android_default(target: &str, opt: &str) -> Option<String> {
+    let ndk_tool = |tool: &str| -> Option<String> {
+        let ndk = env::var("ANDROID_NDK_HOME")
+            .or_else(|_| env::var("ANDROID_NDK_ROOT"))
+            .ok()?;
+        let host = match env::consts::OS {
+            "macos" => "darwin-x86_64",
+            "windows" => "windows-x86_64",
+            _ => "linux-x86_64",
+        };
+        let bin = PathBuf::from(ndk)
+            .join("toolchains/llvm/prebuilt")
+            .join(host)
+            .join("bin")
+            .join(tool);
+        Some(pathbuf_to_string!(bin))
+    };
+
+    match opt {
+        "--os" => Some("android".to_string()),
+        "--cpu" => botan_cpu(target).map(str::to_string),
+        "--cc" => Some("clang".to_string()),
+        "--cc-bin" => {
+            let api = env::var("ANDROID_PLATFORM")
+                .unwrap_or_else(|_| "21".to_string())
+                .trim_start_matches("android-")
+                .to_string();
+            // The NDK's compilers are named after the clang triple
+            let triple = target.replacen("armv7-", "armv7a-", 1);
+            let triple = triple.replacen("thumbv7neon-", "armv7a-", 1);
+            ndk_tool(&format!("{}{}-clang++", triple, api))
+        }
+        "--ar-command" => ndk_tool("llvm-ar"),
+        _ => None,
+    }
+}
+
+// Bitcode is deprecated by Apple and never emitted by rustc, so it is
+// explicitly disabled, keeping the static library linkable by any Xcode.
+// The deployment target is read from IPHONEOS_DEPLOYMENT_TARGET as rustc
+// does.
+fn ios_default(target: &str, opt: &str) -> Option<String> {
+    let simulator = target.ends_with("-sim") || target.starts_with("x86_64-");
+
+    match opt {
+        "--os" => Some("ios".to_string()),
+        "--cpu" => botan_cpu(target).map(str::to_string),
+        "--cc" => Some("clang".to_string()),
+        "--cc-abi-flags" => {
+            let arch = if target.starts_with("x86_64-") {
+                "x86_64"
+            } else {
+                "arm64"
+            };
+            let version =
+                env::var("IPHONEOS_DEPLOYMENT_TARGET").unwrap_or_else(|_| "10.0".to_string());
+            let sdk = if simulator {
+                "iphonesimulator"
+            } else {
+                "iphoneos"
+            };
+            let sdk_path = Command::new("xcrun")
+                .arg("--sdk")
+                .arg(sdk)
+                .arg("--show-sdk-path")
+                .output()
+                .ok()
+                .filter(|out| out.status.success())
+                .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())?;
+
+            Some(format!(
+                "--target={}-apple-ios{}{} -isysroot {} -fembed-bitcode=off",
+                arch,
+                version,
+                if simulator { "-simulator" } else { "" },
+                sdk_path
+            ))
+        }
+        _ => None,
+    }
+}
+
 fn configure(build_dir: &str) {
     let mut configure = Command::new("python");
     configure.arg("configure.py");
@@ -75,6 +197,9 @@ fn configure(build_dir: &str) {
         if let Ok(arg_val) = env::var(env_name) {
             let arg = format!("{}={}", arg_name, arg_val);
             configure.arg(arg);
+        } else if let Some(arg_val) = target_default(arg_name) {
+            let arg = format!("{}={}", arg_name, arg_val);
+            configure.arg(arg);
         }
     }
 
@@ -85,6 +210,15 @@ fn configure(build_dir: &str) {
         }
     }
 
+    // A comma separated list of modules for a minimal build, which
+    // includes only those (and their dependencies) along with the FFI
+    if let Ok(modules) = env::var("BOTAN_MODULES") {
+        if !modules.is_empty() {
+            configure.arg("--minimized-build");
+            configure.arg(format!("--enable-modules=ffi,{}", modules));
+        }
+    }
+
     let status = configure
         .spawn()
         .expect(BUILD_ERROR_MSG)
@@ -1,14 +1,25 @@
-#[cfg(feature = "vendored")]
-fn os_uses_gnu_libstdcpp() -> bool {
+use std::env;
+
+// The C++ standard library to link with a static build of Botan, as
+// arguments for cargo:rustc-link-lib
+fn cpp_stdlib() -> &'static [&'static str] {
     /*
      * Possibly other OSes should default to libstdc++ as well.  But
      * given macOS, iOS, Android, FreeBSD, etc should all use libc++
      * probably defaulting to libc++ when in doubt is the correct move.
+     *
+     * Build scripts are compiled for the host, so the target OS has to
+     * be read from the environment rather than using cfg!
      */
-    if cfg!(any(target_os = "linux")) {
-        true
-    } else {
-        false
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+
+    match target_os.as_str() {
+        "linux" => &["dylib=stdc++"],
+        // emcc links the C++ runtime itself
+        "emscripten" => &[],
+        // Linked statically so that apps need not ship libc++_shared.so
+        "android" => &["static=c++_static", "static=c++abi"],
+        _ => &["dylib=c++"],
     }
 }
 
@@ -23,25 +34,110 @@ fn botan_lib_major_version() -> i32 {
     }
 }
 
+// Read an environment variable, and rebuild if it changes
+#[cfg(not(feature = "vendored"))]
+fn env_setting(name: &str) -> Option<String> {
+    println!("cargo:rerun-if-env-changed={}", name);
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+// Whether to link statically: the `static` feature, unless overridden
+// by BOTAN_STATIC
+#[cfg(not(feature = "vendored"))]
+fn link_statically() -> bool {
+    match env_setting("BOTAN_STATIC").as_deref() {
+        Some("0") | Some("false") | Some("no") => false,
+        Some(_) => true,
+        None => cfg!(feature = "static"),
+    }
+}
+
+fn link_botan(lib_name: &str, statically: bool) {
+    if statically {
+        println!("cargo:rustc-link-lib=static={}", lib_name);
+        for stdlib in cpp_stdlib() {
+            println!("cargo:rustc-link-lib={}", stdlib);
+        }
+    } else {
+        println!("cargo:rustc-link-lib={}", lib_name);
+    }
+}
+
+#[cfg(all(feature = "pkg-config", not(feature = "vendored")))]
+fn probe_pkg_config(lib_name: &str, statically: bool) -> bool {
+    if env_setting("BOTAN_NO_PKG_CONFIG").is_some() {
+        return false;
+    }
+
+    let min_version = if botan_lib_major_version() == 3 {
+        "3.0.0"
+    } else {
+        "2.8.0"
+    };
+
+    match pkg_config::Config::new()
+        .atleast_version(min_version)
+        .statik(statically)
+        .probe(lib_name)
+    {
+        Ok(_) => true,
+        Err(e) => {
+            println!(
+                "cargo:warning=pkg-config could not find {}: {}",
+                lib_name, e
+            );
+            false
+        }
+    }
+}
+
+#[cfg(not(any(feature = "pkg-config", feature = "vendored")))]
+fn probe_pkg_config(_lib_name: &str, _statically: bool) -> bool {
+    false
+}
+
+#[cfg(all(feature = "dlopen", any(feature = "vendored", feature = "static")))]
+compile_error!("The dlopen feature cannot be combined with vendored or static");
+
+// With the `dlopen` feature the library is opened at runtime, so only
+// the system's dynamic loader is linked
+fn link_loader() {
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    if target_os == "linux" || target_os == "android" {
+        println!("cargo:rustc-link-lib=dylib=dl");
+    }
+}
+
 fn main() {
+    if cfg!(feature = "dlopen") {
+        link_loader();
+        return;
+    }
+
     #[cfg(feature = "vendored")]
     {
+        // BOTAN_MODULES is read by botan_src
+        println!("cargo:rerun-if-env-changed=BOTAN_MODULES");
         let (lib_dir, _) = botan_src::build();
         println!("cargo:vendored=1");
         println!("cargo:rustc-link-search=native={}", &lib_dir);
-        println!(
-            "cargo:rustc-link-lib=static=botan-{}",
-            botan_lib_major_version()
-        );
-
-        if os_uses_gnu_libstdcpp() {
-            println!("cargo:rustc-flags=-l dylib=stdc++");
-        } else {
-            println!("cargo:rustc-flags=-l dylib=c++");
-        }
+        link_botan(&format!("botan-{}", botan_lib_major_version()), true);
     }
     #[cfg(not(feature = "vendored"))]
     {
-        println!("cargo:rustc-link-lib=botan-{}", botan_lib_major_version());
+        let lib_name = env_setting("BOTAN_LIB_NAME")
+            .unwrap_or_else(|| format!("botan-{}", botan_lib_major_version()));
+        let statically = link_statically();
+
+        // A library in an explicitly given directory, for example one
+        // cross compiled for a mobile target, takes precedence over
+        // whatever pkg-config finds
+        if let Some(lib_dir) = env_setting("BOTAN_LIB_DIR") {
+            println!("cargo:rustc-link-search=native={}", lib_dir);
+        } else if probe_pkg_config(&lib_name, statically) {
+            return;
+        }
+
+        link_botan(&lib_name, statically);
     }
 }
@@ -3,13 +3,20 @@ use cty::{c_char, c_int};
 pub enum botan_cipher_struct {}
 pub type botan_cipher_t = *mut botan_cipher_struct;
 
-extern "C" {
+botan_ffi! {
     pub fn botan_cipher_init(cipher: *mut botan_cipher_t, name: *const c_char, flags: u32)
         -> c_int;
     pub fn botan_cipher_valid_nonce_length(cipher: botan_cipher_t, nl: usize) -> c_int;
     pub fn botan_cipher_get_tag_length(cipher: botan_cipher_t, tag_size: *mut usize) -> c_int;
     pub fn botan_cipher_get_default_nonce_length(cipher: botan_cipher_t, nl: *mut usize) -> c_int;
     pub fn botan_cipher_get_update_granularity(cipher: botan_cipher_t, ug: *mut usize) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_cipher_get_ideal_update_granularity(
+        cipher: botan_cipher_t,
+        ug: *mut usize,
+    ) -> c_int;
+
     pub fn botan_cipher_query_keylen(
         cipher: botan_cipher_t,
         out_minimum_keylength: *mut usize,
@@ -53,6 +60,7 @@ extern "C" {
         outlen: *mut usize,
     ) -> c_int;
 
+    pub fn botan_cipher_reset(cipher: botan_cipher_t) -> c_int;
     pub fn botan_cipher_clear(cipher: botan_cipher_t) -> c_int;
     pub fn botan_cipher_destroy(cipher: botan_cipher_t) -> c_int;
 
@@ -0,0 +1,293 @@
+//! Loading the library at runtime
+//!
+//! With the `dlopen` feature nothing is linked at build time. Instead the
+//! library is opened the first time any function is called, and each
+//! function is looked up by name the first time it is called. A function
+//! which the library does not have (or every function, if the library
+//! could not be found) returns `BOTAN_FFI_ERROR_NOT_IMPLEMENTED`, or zero
+//! or an empty string for the few functions which do not return an error
+//! code.
+//!
+//! The library is found using, in order: a path given to `load_library`
+//! before any other call, the `BOTAN_LIBRARY` environment variable, and
+//! the usual names of the library on the platform.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use cty::{c_char, c_int, c_void};
+
+use crate::errors::{
+    BOTAN_FFI_ERROR_BAD_PARAMETER, BOTAN_FFI_ERROR_INVALID_OBJECT_STATE,
+    BOTAN_FFI_ERROR_NOT_IMPLEMENTED, BOTAN_FFI_SUCCESS,
+};
+
+#[cfg(unix)]
+mod os {
+    use cty::{c_char, c_int, c_void};
+
+    const RTLD_NOW: c_int = 2;
+
+    extern "C" {
+        fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+        pub(super) fn getenv(name: *const c_char) -> *const c_char;
+    }
+
+    pub(super) unsafe fn open(path: *const c_char) -> *mut c_void {
+        dlopen(path, RTLD_NOW)
+    }
+
+    pub(super) unsafe fn symbol(lib: *mut c_void, name: *const c_char) -> *mut c_void {
+        dlsym(lib, name)
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use cty::{c_char, c_void};
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn LoadLibraryA(filename: *const c_char) -> *mut c_void;
+        fn GetProcAddress(module: *mut c_void, name: *const c_char) -> *mut c_void;
+    }
+
+    extern "C" {
+        pub(super) fn getenv(name: *const c_char) -> *const c_char;
+    }
+
+    pub(super) unsafe fn open(path: *const c_char) -> *mut c_void {
+        LoadLibraryA(path)
+    }
+
+    pub(super) unsafe fn symbol(lib: *mut c_void, name: *const c_char) -> *mut c_void {
+        GetProcAddress(lib, name)
+    }
+}
+
+#[cfg(feature = "botan3")]
+const MAJOR: &[u8] = b"3";
+#[cfg(not(feature = "botan3"))]
+const MAJOR: &[u8] = b"2";
+
+// The suffix of the unversioned name of the library, and the parts
+// before and after the minor version in its versioned names
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "ios"))))]
+const NAME_PARTS: (&[u8], &[u8], &[u8]) = (b".so", b".so.", b"");
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+const NAME_PARTS: (&[u8], &[u8], &[u8]) = (b".dylib", b".", b".dylib");
+
+// Minor versions tried when looking for a versioned library name
+const MAX_MINOR: usize = 40;
+
+// The library handle: 0 if not yet opened, 1 if it could not be opened
+static LIBRARY: AtomicUsize = AtomicUsize::new(0);
+
+const UNRESOLVED: usize = 0;
+const MISSING: usize = 1;
+
+// A nul terminated name, built on the stack
+struct Name {
+    buf: [u8; 64],
+    len: usize,
+}
+
+impl Name {
+    fn new() -> Name {
+        Name {
+            buf: [0; 64],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) -> &mut Name {
+        let n = bytes.len().min(self.buf.len() - 1 - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&bytes[..n]);
+        self.len += n;
+        self
+    }
+
+    fn push_number(&mut self, n: usize) -> &mut Name {
+        if n >= 10 {
+            self.push_number(n / 10);
+        }
+        self.push(&[b'0' + (n % 10) as u8])
+    }
+
+    fn as_ptr(&self) -> *const c_char {
+        self.buf.as_ptr() as *const c_char
+    }
+}
+
+unsafe fn open_default() -> *mut c_void {
+    let env = os::getenv(b"BOTAN_LIBRARY\0".as_ptr() as *const c_char);
+    if !env.is_null() && *env != 0 {
+        return os::open(env);
+    }
+
+    #[cfg(unix)]
+    {
+        let (suffix, before_minor, after_minor) = NAME_PARTS;
+
+        // For example libbotan-3.so
+        let mut name = Name::new();
+        name.push(b"libbotan-").push(MAJOR).push(suffix);
+        let lib = os::open(name.as_ptr());
+        if !lib.is_null() {
+            return lib;
+        }
+
+        // For example libbotan-3.so.6 or libbotan-3.6.dylib, newest first
+        for minor in (0..=MAX_MINOR).rev() {
+            let mut name = Name::new();
+            name.push(b"libbotan-")
+                .push(MAJOR)
+                .push(before_minor)
+                .push_number(minor)
+                .push(after_minor);
+            let lib = os::open(name.as_ptr());
+            if !lib.is_null() {
+                return lib;
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        let mut name = Name::new();
+        name.push(b"botan-").push(MAJOR).push(b".dll");
+        for name in &[name.as_ptr(), b"botan.dll\0".as_ptr() as *const c_char] {
+            let lib = os::open(*name);
+            if !lib.is_null() {
+                return lib;
+            }
+        }
+    }
+
+    core::ptr::null_mut()
+}
+
+fn library() -> *mut c_void {
+    match LIBRARY.load(Ordering::Acquire) {
+        UNRESOLVED => {
+            let lib = unsafe { open_default() };
+            let value = if lib.is_null() { MISSING } else { lib as usize };
+            // If another thread got there first, use its handle; opening
+            // a library twice returns the same handle anyway
+            match LIBRARY.compare_exchange(UNRESOLVED, value, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => lib,
+                Err(MISSING) => core::ptr::null_mut(),
+                Err(existing) => existing as *mut c_void,
+            }
+        }
+        MISSING => core::ptr::null_mut(),
+        lib => lib as *mut c_void,
+    }
+}
+
+/// Load the library from `path`, a nul terminated file name
+///
+/// This must be called before any other function of this crate. Returns
+/// `BOTAN_FFI_ERROR_INVALID_OBJECT_STATE` if a library was already loaded,
+/// and `BOTAN_FFI_ERROR_NOT_IMPLEMENTED` if `path` could not be opened; in
+/// the latter case the library can still be loaded from another path.
+pub fn load_library(path: &[u8]) -> c_int {
+    if path.last() != Some(&0) {
+        return BOTAN_FFI_ERROR_BAD_PARAMETER;
+    }
+    if LIBRARY.load(Ordering::Acquire) != UNRESOLVED {
+        return BOTAN_FFI_ERROR_INVALID_OBJECT_STATE;
+    }
+
+    let lib = unsafe { os::open(path.as_ptr() as *const c_char) };
+    if lib.is_null() {
+        return BOTAN_FFI_ERROR_NOT_IMPLEMENTED;
+    }
+    match LIBRARY.compare_exchange(
+        UNRESOLVED,
+        lib as usize,
+        Ordering::AcqRel,
+        Ordering::Acquire,
+    ) {
+        Ok(_) => BOTAN_FFI_SUCCESS,
+        Err(_) => BOTAN_FFI_ERROR_INVALID_OBJECT_STATE,
+    }
+}
+
+/// Return true if the library has been (or can now be) loaded
+pub fn library_loaded() -> bool {
+    !library().is_null()
+}
+
+/// Return true if the loaded library has the function `name`, given as a
+/// nul terminated string
+pub fn symbol_available(name: &[u8]) -> bool {
+    if name.last() != Some(&0) {
+        return false;
+    }
+    let lib = library();
+    !lib.is_null() && unsafe { !os::symbol(lib, name.as_ptr() as *const c_char).is_null() }
+}
+
+// A function of the library, looked up on first use
+pub(crate) struct Symbol {
+    name: &'static [u8],
+    addr: AtomicUsize,
+}
+
+impl Symbol {
+    pub(crate) const fn new(name: &'static [u8]) -> Symbol {
+        Symbol {
+            name,
+            addr: AtomicUsize::new(UNRESOLVED),
+        }
+    }
+
+    pub(crate) fn get(&self) -> Option<*mut c_void> {
+        match self.addr.load(Ordering::Acquire) {
+            UNRESOLVED => {
+                let lib = library();
+                let addr = if lib.is_null() {
+                    core::ptr::null_mut()
+                } else {
+                    unsafe { os::symbol(lib, self.name.as_ptr() as *const c_char) }
+                };
+                let value = if addr.is_null() {
+                    MISSING
+                } else {
+                    addr as usize
+                };
+                self.addr.store(value, Ordering::Release);
+                if addr.is_null() {
+                    None
+                } else {
+                    Some(addr)
+                }
+            }
+            MISSING => None,
+            addr => Some(addr as *mut c_void),
+        }
+    }
+}
+
+// The value returned by a function which the library does not have
+pub(crate) trait Missing {
+    fn missing() -> Self;
+}
+
+impl Missing for c_int {
+    fn missing() -> c_int {
+        BOTAN_FFI_ERROR_NOT_IMPLEMENTED
+    }
+}
+
+impl Missing for u32 {
+    fn missing() -> u32 {
+        0
+    }
+}
+
+impl Missing for *const c_char {
+    fn missing() -> *const c_char {
+        b"\0".as_ptr() as *const c_char
+    }
+}
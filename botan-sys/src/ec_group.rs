@@ -0,0 +1,79 @@
+#[cfg(feature = "botan3")]
+use cty::{c_char, c_int};
+
+#[cfg(feature = "botan3")]
+use mp::botan_mp_t;
+#[cfg(feature = "botan3")]
+use oid::botan_asn1_oid_t;
+
+pub enum botan_ec_group_struct {}
+pub type botan_ec_group_t = *mut botan_ec_group_struct;
+
+botan_ffi! {
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_destroy(ec_group: botan_ec_group_t) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_supports_application_specific_group(out: *mut c_int) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_supports_named_group(name: *const c_char, out: *mut c_int) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_from_params(
+        ec_group: *mut botan_ec_group_t,
+        oid: botan_asn1_oid_t,
+        p: botan_mp_t,
+        a: botan_mp_t,
+        b: botan_mp_t,
+        base_x: botan_mp_t,
+        base_y: botan_mp_t,
+        order: botan_mp_t,
+    ) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_from_ber(
+        ec_group: *mut botan_ec_group_t,
+        ber: *const u8,
+        ber_len: usize,
+    ) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_from_pem(ec_group: *mut botan_ec_group_t, pem: *const c_char) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_from_oid(ec_group: *mut botan_ec_group_t, oid: botan_asn1_oid_t)
+        -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_from_name(ec_group: *mut botan_ec_group_t, name: *const c_char) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_get_curve_oid(
+        oid: *mut botan_asn1_oid_t,
+        ec_group: botan_ec_group_t,
+    ) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_get_p(p: *mut botan_mp_t, ec_group: botan_ec_group_t) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_get_a(a: *mut botan_mp_t, ec_group: botan_ec_group_t) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_get_b(b: *mut botan_mp_t, ec_group: botan_ec_group_t) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_get_g_x(g_x: *mut botan_mp_t, ec_group: botan_ec_group_t) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_get_g_y(g_y: *mut botan_mp_t, ec_group: botan_ec_group_t) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_get_order(order: *mut botan_mp_t, ec_group: botan_ec_group_t) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_group_equal(curve1: botan_ec_group_t, curve2: botan_ec_group_t) -> c_int;
+
+}
@@ -1,6 +1,6 @@
 use cty::{c_char, c_int};
 
-extern "C" {
+botan_ffi! {
 
     pub fn botan_pbkdf(
         pbkdf_algo: *const c_char,
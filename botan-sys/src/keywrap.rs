@@ -1,6 +1,6 @@
 use cty::c_int;
 
-extern "C" {
+botan_ffi! {
 
     pub fn botan_key_wrap3394(
         input: *const u8,
@@ -3,8 +3,44 @@
 
 extern crate cty;
 
+// Declare the functions of the library. Normally these are linked at
+// build time; with the `dlopen` feature each becomes a wrapper which
+// looks up the function when first called.
+#[cfg(not(feature = "dlopen"))]
+macro_rules! botan_ffi {
+    ($($(#[$attr:meta])* pub fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty;)*) => {
+        extern "C" {
+            $($(#[$attr])* pub fn $name($($arg: $ty),*) -> $ret;)*
+        }
+    };
+}
+
+#[cfg(feature = "dlopen")]
+macro_rules! botan_ffi {
+    ($($(#[$attr:meta])* pub fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty;)*) => {
+        $(
+            $(#[$attr])*
+            #[allow(clippy::missing_safety_doc, clippy::too_many_arguments, non_snake_case)]
+            pub unsafe fn $name($($arg: $ty),*) -> $ret {
+                static SYMBOL: crate::dlopen::Symbol =
+                    crate::dlopen::Symbol::new(concat!(stringify!($name), "\0").as_bytes());
+                match SYMBOL.get() {
+                    Some(addr) => {
+                        let f: unsafe extern "C" fn($($ty),*) -> $ret = core::mem::transmute(addr);
+                        f($($arg),*)
+                    }
+                    None => <$ret as crate::dlopen::Missing>::missing(),
+                }
+            }
+        )*
+    };
+}
+
 mod block;
 mod cipher;
+#[cfg(feature = "dlopen")]
+pub mod dlopen;
+mod ec_group;
 mod errors;
 mod fpe;
 mod hash;
@@ -12,17 +48,21 @@ mod kdf;
 mod keywrap;
 mod mac;
 mod mp;
+mod oid;
 mod otp;
 mod passhash;
 mod pk_ops;
 mod pubkey;
 mod rng;
+#[cfg(feature = "tpm2")]
+mod tpm2;
 mod utils;
 mod version;
 mod x509;
 
 pub use block::*;
 pub use cipher::*;
+pub use ec_group::*;
 pub use errors::*;
 pub use fpe::*;
 pub use hash::*;
@@ -30,11 +70,14 @@ pub use kdf::*;
 pub use keywrap::*;
 pub use mac::*;
 pub use mp::*;
+pub use oid::*;
 pub use otp::*;
 pub use passhash::*;
 pub use pk_ops::*;
 pub use pubkey::*;
 pub use rng::*;
+#[cfg(feature = "tpm2")]
+pub use tpm2::*;
 pub use utils::*;
 pub use version::*;
 pub use x509::*;
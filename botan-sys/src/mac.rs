@@ -3,7 +3,7 @@ use cty::{c_char, c_int};
 pub enum botan_mac_struct {}
 pub type botan_mac_t = *mut botan_mac_struct;
 
-extern "C" {
+botan_ffi! {
 
     pub fn botan_mac_init(mac: *mut botan_mac_t, mac_name: *const c_char, flags: u32) -> c_int;
 
@@ -20,6 +20,9 @@ extern "C" {
         mod_keylen: *mut usize,
     ) -> c_int;
 
+    #[cfg(feature = "botan3")]
+    pub fn botan_mac_set_nonce(mac: botan_mac_t, nonce: *const u8, nonce_len: usize) -> c_int;
+
     pub fn botan_mac_update(mac: botan_mac_t, buf: *const u8, len: usize) -> c_int;
     pub fn botan_mac_final(mac: botan_mac_t, out: *mut u8) -> c_int;
     pub fn botan_mac_clear(mac: botan_mac_t) -> c_int;
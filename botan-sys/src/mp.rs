@@ -5,7 +5,7 @@ use rng::botan_rng_t;
 pub enum botan_mp_struct {}
 pub type botan_mp_t = *mut botan_mp_struct;
 
-extern "C" {
+botan_ffi! {
 
     pub fn botan_mp_init(mp: *mut botan_mp_t) -> c_int;
     pub fn botan_mp_destroy(mp: botan_mp_t) -> c_int;
@@ -0,0 +1,21 @@
+#[cfg(feature = "botan3")]
+use cty::{c_char, c_int};
+
+pub enum botan_asn1_oid_struct {}
+pub type botan_asn1_oid_t = *mut botan_asn1_oid_struct;
+
+botan_ffi! {
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_oid_destroy(oid: botan_asn1_oid_t) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_oid_from_string(oid: *mut botan_asn1_oid_t, oid_str: *const c_char) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_oid_register(oid: botan_asn1_oid_t, name: *const c_char) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_oid_equal(a: botan_asn1_oid_t, b: botan_asn1_oid_t) -> c_int;
+
+}
@@ -2,7 +2,7 @@ use cty::{c_char, c_int};
 
 use rng::botan_rng_t;
 
-extern "C" {
+botan_ffi! {
 
     pub fn botan_bcrypt_generate(
         out: *mut u8,
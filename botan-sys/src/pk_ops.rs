@@ -18,7 +18,17 @@ pub type botan_pk_op_verify_t = *mut botan_pk_op_verify_struct;
 pub enum botan_pk_op_ka_struct {}
 pub type botan_pk_op_ka_t = *mut botan_pk_op_ka_struct;
 
-extern "C" {
+#[cfg(feature = "botan3")]
+pub enum botan_pk_op_kem_encrypt_struct {}
+#[cfg(feature = "botan3")]
+pub type botan_pk_op_kem_encrypt_t = *mut botan_pk_op_kem_encrypt_struct;
+
+#[cfg(feature = "botan3")]
+pub enum botan_pk_op_kem_decrypt_struct {}
+#[cfg(feature = "botan3")]
+pub type botan_pk_op_kem_decrypt_t = *mut botan_pk_op_kem_decrypt_struct;
+
+botan_ffi! {
     pub fn botan_pk_op_encrypt_create(
         op: *mut botan_pk_op_encrypt_t,
         key: botan_pubkey_t,
@@ -124,4 +134,62 @@ extern "C" {
         pkcs_id_len: *mut usize,
     ) -> c_int;
 
+    #[cfg(feature = "botan3")]
+    pub fn botan_pk_op_kem_encrypt_create(
+        op: *mut botan_pk_op_kem_encrypt_t,
+        key: botan_pubkey_t,
+        kdf: *const c_char,
+    ) -> c_int;
+    #[cfg(feature = "botan3")]
+    pub fn botan_pk_op_kem_encrypt_destroy(op: botan_pk_op_kem_encrypt_t) -> c_int;
+    #[cfg(feature = "botan3")]
+    pub fn botan_pk_op_kem_encrypt_shared_key_length(
+        op: botan_pk_op_kem_encrypt_t,
+        desired_shared_key_length: usize,
+        output_shared_key_length: *mut usize,
+    ) -> c_int;
+    #[cfg(feature = "botan3")]
+    pub fn botan_pk_op_kem_encrypt_encapsulated_key_length(
+        op: botan_pk_op_kem_encrypt_t,
+        output_encapsulated_key_length: *mut usize,
+    ) -> c_int;
+    #[cfg(feature = "botan3")]
+    pub fn botan_pk_op_kem_encrypt_create_shared_key(
+        op: botan_pk_op_kem_encrypt_t,
+        rng: botan_rng_t,
+        salt: *const u8,
+        salt_len: usize,
+        desired_shared_key_len: usize,
+        shared_key: *mut u8,
+        shared_key_len: *mut usize,
+        encapsulated_key: *mut u8,
+        encapsulated_key_len: *mut usize,
+    ) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_pk_op_kem_decrypt_create(
+        op: *mut botan_pk_op_kem_decrypt_t,
+        key: botan_privkey_t,
+        kdf: *const c_char,
+    ) -> c_int;
+    #[cfg(feature = "botan3")]
+    pub fn botan_pk_op_kem_decrypt_destroy(op: botan_pk_op_kem_decrypt_t) -> c_int;
+    #[cfg(feature = "botan3")]
+    pub fn botan_pk_op_kem_decrypt_shared_key_length(
+        op: botan_pk_op_kem_decrypt_t,
+        desired_shared_key_length: usize,
+        output_shared_key_length: *mut usize,
+    ) -> c_int;
+    #[cfg(feature = "botan3")]
+    pub fn botan_pk_op_kem_decrypt_shared_key(
+        op: botan_pk_op_kem_decrypt_t,
+        salt: *const u8,
+        salt_len: usize,
+        encapsulated_key: *const u8,
+        encapsulated_key_len: usize,
+        desired_shared_key_len: usize,
+        shared_key: *mut u8,
+        shared_key_len: *mut usize,
+    ) -> c_int;
+
 }
@@ -1,7 +1,11 @@
 use cty::{c_char, c_int};
 
+#[cfg(feature = "botan3")]
+use ec_group::botan_ec_group_t;
 use mp::botan_mp_t;
 use rng::botan_rng_t;
+#[cfg(feature = "botan3")]
+use utils::{botan_view_bin_fn, botan_view_ctx};
 
 pub enum botan_pubkey_struct {}
 pub type botan_pubkey_t = *mut botan_pubkey_struct;
@@ -9,14 +13,30 @@ pub type botan_pubkey_t = *mut botan_pubkey_struct;
 pub enum botan_privkey_struct {}
 pub type botan_privkey_t = *mut botan_privkey_struct;
 
-extern "C" {
+botan_ffi! {
     pub fn botan_privkey_create(
         key: *mut botan_privkey_t,
         algo_name: *const c_char,
         algo_params: *const c_char,
         rng: botan_rng_t,
     ) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_ec_privkey_create(
+        key: *mut botan_privkey_t,
+        algo_name: *const c_char,
+        ec_group: botan_ec_group_t,
+        rng: botan_rng_t,
+    ) -> c_int;
+
     pub fn botan_privkey_check_key(key: botan_privkey_t, rng: botan_rng_t, flags: u32) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_privkey_stateful_operation(key: botan_privkey_t, out: *mut c_int) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_privkey_remaining_operations(key: botan_privkey_t, out: *mut u64) -> c_int;
+
     pub fn botan_privkey_create_rsa(
         key: *mut botan_privkey_t,
         rng: botan_rng_t,
@@ -262,4 +282,58 @@ extern "C" {
         hash_algo: *const c_char,
         key: botan_pubkey_t,
     ) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_privkey_load_frodokem(
+        key: *mut botan_privkey_t,
+        privkey: *const u8,
+        key_len: usize,
+        frodo_mode: *const c_char,
+    ) -> c_int;
+    #[cfg(feature = "botan3")]
+    pub fn botan_pubkey_load_frodokem(
+        key: *mut botan_pubkey_t,
+        pubkey: *const u8,
+        key_len: usize,
+        frodo_mode: *const c_char,
+    ) -> c_int;
+    #[cfg(feature = "botan3")]
+    pub fn botan_privkey_load_classic_mceliece(
+        key: *mut botan_privkey_t,
+        privkey: *const u8,
+        key_len: usize,
+        cmce_mode: *const c_char,
+    ) -> c_int;
+    #[cfg(feature = "botan3")]
+    pub fn botan_pubkey_load_classic_mceliece(
+        key: *mut botan_pubkey_t,
+        pubkey: *const u8,
+        key_len: usize,
+        cmce_mode: *const c_char,
+    ) -> c_int;
+
+    #[cfg(feature = "botan3")]
+    pub fn botan_pubkey_view_der(
+        key: botan_pubkey_t,
+        ctx: botan_view_ctx,
+        view: botan_view_bin_fn,
+    ) -> c_int;
+    #[cfg(feature = "botan3")]
+    pub fn botan_pubkey_view_raw(
+        key: botan_pubkey_t,
+        ctx: botan_view_ctx,
+        view: botan_view_bin_fn,
+    ) -> c_int;
+    #[cfg(feature = "botan3")]
+    pub fn botan_privkey_view_der(
+        key: botan_privkey_t,
+        ctx: botan_view_ctx,
+        view: botan_view_bin_fn,
+    ) -> c_int;
+    #[cfg(feature = "botan3")]
+    pub fn botan_privkey_view_raw(
+        key: botan_privkey_t,
+        ctx: botan_view_ctx,
+        view: botan_view_bin_fn,
+    ) -> c_int;
 }
@@ -3,7 +3,7 @@ use cty::{c_char, c_int};
 pub enum botan_rng_struct {}
 pub type botan_rng_t = *mut botan_rng_struct;
 
-extern "C" {
+botan_ffi! {
 
     pub fn botan_rng_init(rng: *mut botan_rng_t, rng_type: *const c_char) -> c_int;
 
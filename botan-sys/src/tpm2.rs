@@ -0,0 +1,45 @@
+use cty::{c_char, c_int};
+
+use rng::botan_rng_t;
+
+pub enum botan_tpm2_ctx_struct {}
+pub type botan_tpm2_ctx_t = *mut botan_tpm2_ctx_struct;
+
+pub enum botan_tpm2_session_struct {}
+pub type botan_tpm2_session_t = *mut botan_tpm2_session_struct;
+
+botan_ffi! {
+
+    pub fn botan_tpm2_supports_crypto_backend() -> c_int;
+
+    pub fn botan_tpm2_ctx_init(
+        ctx_out: *mut botan_tpm2_ctx_t,
+        tcti_nameconf: *const c_char,
+    ) -> c_int;
+
+    pub fn botan_tpm2_ctx_init_ex(
+        ctx_out: *mut botan_tpm2_ctx_t,
+        tcti_name: *const c_char,
+        tcti_conf: *const c_char,
+    ) -> c_int;
+
+    pub fn botan_tpm2_ctx_enable_crypto_backend(ctx: botan_tpm2_ctx_t, rng: botan_rng_t) -> c_int;
+
+    pub fn botan_tpm2_ctx_destroy(ctx: botan_tpm2_ctx_t) -> c_int;
+
+    pub fn botan_tpm2_rng_init(
+        rng_out: *mut botan_rng_t,
+        ctx: botan_tpm2_ctx_t,
+        s1: botan_tpm2_session_t,
+        s2: botan_tpm2_session_t,
+        s3: botan_tpm2_session_t,
+    ) -> c_int;
+
+    pub fn botan_tpm2_unauthenticated_session_init(
+        session_out: *mut botan_tpm2_session_t,
+        ctx: botan_tpm2_ctx_t,
+    ) -> c_int;
+
+    pub fn botan_tpm2_session_destroy(session: botan_tpm2_session_t) -> c_int;
+
+}
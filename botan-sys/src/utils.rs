@@ -1,6 +1,13 @@
 use cty::{c_char, c_int, c_void};
 
-extern "C" {
+#[cfg(feature = "botan3")]
+pub type botan_view_ctx = *mut c_void;
+
+#[cfg(feature = "botan3")]
+pub type botan_view_bin_fn =
+    extern "C" fn(view_ctx: botan_view_ctx, data: *const u8, len: usize) -> c_int;
+
+botan_ffi! {
 
     pub fn botan_constant_time_compare(x: *const u8, y: *const u8, len: usize) -> c_int;
 
@@ -21,7 +21,7 @@ pub enum X509KeyConstraints {
     DECIPHER_ONLY = 128,
 }
 
-extern "C" {
+botan_ffi! {
     pub fn botan_x509_cert_load(
         cert_obj: *mut botan_x509_cert_t,
         cert: *const u8,
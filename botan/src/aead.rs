@@ -0,0 +1,403 @@
+use crate::utils::*;
+
+use crate::cipher::{Cipher, CipherDirection};
+use crate::rng::RandomNumberGenerator;
+use crate::symmetric::{XChaChaNonce, XCHACHA_NONCE_LENGTH};
+
+fn check_aead(cipher: Cipher, direction: CipherDirection) -> Result<Cipher> {
+    if cipher.direction()? != direction {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            format!("Expected a cipher in the {:?} direction", direction),
+        ));
+    }
+
+    if cipher.tag_length() == 0 {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            format!("{} is not an AEAD", cipher.algo_name()?),
+        ));
+    }
+
+    Ok(cipher)
+}
+
+macro_rules! aead_common {
+    ($name:ident, $direction:expr) => {
+        impl $name {
+            /// Create a new AEAD object
+            ///
+            /// # Errors
+            /// Fails if the algorithm is not known, or is not an AEAD
+            pub fn new<N: AsRef<str>>(name: N) -> Result<$name> {
+                $name::from_cipher(Cipher::new(name, $direction)?)
+            }
+
+            /// Wrap an existing cipher object
+            ///
+            /// # Errors
+            /// Fails if the cipher is not an AEAD, or was created for the
+            /// other direction
+            pub fn from_cipher(cipher: Cipher) -> Result<$name> {
+                Ok($name {
+                    cipher: check_aead(cipher, $direction)?,
+                })
+            }
+
+            /// Create an AEAD using AES-256 in SIV mode, which is nonce
+            /// misuse resistant and takes a 64 byte key
+            pub fn aes256_siv() -> Result<$name> {
+                $name::from_cipher(Cipher::aes256_siv($direction)?)
+            }
+
+            /// Create an AEAD using AES-256 in GCM-SIV mode, which is nonce
+            /// misuse resistant and takes a 32 byte key
+            ///
+            /// # Errors
+            /// Fails with `ErrorType::NotImplemented` if the library was
+            /// built without GCM-SIV
+            pub fn aes256_gcm_siv() -> Result<$name> {
+                $name::from_cipher(Cipher::aes256_gcm_siv($direction)?)
+            }
+
+            /// Return the underlying cipher object
+            #[must_use]
+            pub fn into_cipher(self) -> Cipher {
+                self.cipher
+            }
+
+            /// Return the name of this algorithm
+            pub fn algo_name(&self) -> Result<String> {
+                self.cipher.algo_name()
+            }
+
+            /// Return the length of the authentication tag in bytes
+            #[must_use]
+            pub fn tag_length(&self) -> usize {
+                self.cipher.tag_length()
+            }
+
+            /// Return the default nonce length for this algorithm
+            #[must_use]
+            pub fn default_nonce_length(&self) -> usize {
+                self.cipher.default_nonce_length()
+            }
+
+            /// Return true if repeating a nonce with this algorithm does
+            /// not break its security
+            ///
+            /// See `Cipher::is_misuse_resistant`.
+            #[must_use]
+            pub fn is_misuse_resistant(&self) -> bool {
+                self.cipher.is_misuse_resistant()
+            }
+
+            /// Query if a particular nonce size is valid for this algorithm
+            pub fn valid_nonce_length(&self, l: usize) -> Result<bool> {
+                self.cipher.valid_nonce_length(l)
+            }
+
+            /// Return information about the key lengths supported
+            pub fn key_spec(&self) -> Result<KeySpec> {
+                self.cipher.key_spec()
+            }
+
+            /// Set the key
+            pub fn set_key(&mut self, key: &[u8]) -> Result<()> {
+                self.cipher.set_key(key)
+            }
+
+            /// Set the associated data for the next message
+            ///
+            /// The key must already be set.
+            pub fn set_associated_data(&mut self, ad: &[u8]) -> Result<()> {
+                self.cipher.set_associated_data(ad)
+            }
+
+            /// Append to the associated data of the current message
+            ///
+            /// See `Cipher::add_associated_data`.
+            pub fn add_associated_data(&mut self, ad: &[u8]) -> Result<()> {
+                self.cipher.add_associated_data(ad)
+            }
+
+            /// Return true if associated data can be added after `start`
+            #[must_use]
+            pub fn supports_late_associated_data(&self) -> bool {
+                self.cipher.supports_late_associated_data()
+            }
+
+            /// Begin processing a new message with the specified nonce
+            pub fn start(&mut self, nonce: &[u8]) -> Result<()> {
+                self.cipher.start(nonce)
+            }
+
+            /// Process part of a message started with `start`
+            pub fn update(&mut self, msg: &[u8]) -> Result<Vec<u8>> {
+                self.cipher.update(msg)
+            }
+
+            /// Process the end of a message started with `start`
+            pub fn finish(&mut self, msg: &[u8]) -> Result<Vec<u8>> {
+                self.cipher.finish(msg)
+            }
+
+            /// Clear all state associated with the key
+            pub fn clear(&mut self) -> Result<()> {
+                self.cipher.clear()
+            }
+        }
+    };
+}
+
+#[derive(Debug)]
+/// An AEAD which can only encrypt
+///
+/// Unlike `Cipher`, which takes a direction when created, the
+/// operation is part of the type, so passing an encryptor where a
+/// decryptor is expected is a compile time error.
+///
+/// # Examples
+/// ```
+/// let mut enc = botan::AeadEncryptor::new(botan::AeadAlgo::Aes128Gcm).unwrap();
+/// let mut dec = botan::AeadDecryptor::new(botan::AeadAlgo::Aes128Gcm).unwrap();
+/// enc.set_key(&[0; 16]).unwrap();
+/// dec.set_key(&[0; 16]).unwrap();
+///
+/// let nonce = [0; 12];
+/// let ctext = enc.encrypt(&nonce, b"hello").unwrap();
+/// assert_eq!(dec.decrypt(&nonce, &ctext).unwrap(), b"hello");
+/// ```
+pub struct AeadEncryptor {
+    cipher: Cipher,
+}
+
+#[derive(Debug)]
+/// An AEAD which can only decrypt
+///
+/// See `AeadEncryptor`.
+pub struct AeadDecryptor {
+    cipher: Cipher,
+}
+
+// The nonce used by encrypt_without_nonce: empty where the algorithm
+// allows it (as SIV does), otherwise all zeros
+fn fixed_nonce(cipher: &Cipher) -> Result<Vec<u8>> {
+    if !cipher.is_misuse_resistant() {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            format!(
+                "{} is not misuse resistant, so every message needs a unique nonce",
+                cipher.algo_name()?
+            ),
+        ));
+    }
+
+    if cipher.valid_nonce_length(0)? {
+        Ok(Vec::new())
+    } else {
+        Ok(vec![0; cipher.default_nonce_length()])
+    }
+}
+
+aead_common!(AeadEncryptor, CipherDirection::Encrypt);
+aead_common!(AeadDecryptor, CipherDirection::Decrypt);
+
+impl AeadEncryptor {
+    /// Encrypt a message with the provided nonce, returning the
+    /// ciphertext followed by the tag
+    ///
+    /// The key must already have been set.
+    pub fn encrypt(&mut self, nonce: &[u8], ptext: &[u8]) -> Result<Vec<u8>> {
+        self.cipher.process(nonce, ptext)
+    }
+
+    /// Encrypt a message in place, appending the tag
+    pub fn encrypt_in_place(&mut self, nonce: &[u8], buf: &mut Vec<u8>) -> Result<()> {
+        self.cipher.process_in_place(nonce, buf)
+    }
+
+    /// Encrypt a message with a fresh random nonce of the default length,
+    /// returning the nonce followed by the ciphertext and tag
+    ///
+    /// This avoids managing nonces, at the cost of a few bytes per
+    /// message. With a 12 byte nonce a single key should not encrypt more
+    /// than about 2^32 messages, unless the algorithm is misuse resistant.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let mut enc = botan::AeadEncryptor::new(botan::AeadAlgo::Aes256Gcm).unwrap();
+    /// let mut dec = botan::AeadDecryptor::new(botan::AeadAlgo::Aes256Gcm).unwrap();
+    /// enc.set_key(&[1; 32]).unwrap();
+    /// dec.set_key(&[1; 32]).unwrap();
+    ///
+    /// let sealed = enc.encrypt_with_random_nonce(&mut rng, b"hello").unwrap();
+    /// assert_eq!(sealed.len(), 12 + 5 + 16);
+    /// assert_eq!(dec.decrypt_with_prepended_nonce(&sealed).unwrap(), b"hello");
+    /// ```
+    pub fn encrypt_with_random_nonce(
+        &mut self,
+        rng: &mut RandomNumberGenerator,
+        ptext: &[u8],
+    ) -> Result<Vec<u8>> {
+        let mut out = self.cipher.generate_nonce(rng)?;
+        let ctext = self.cipher.process(&out, ptext)?;
+        out.extend_from_slice(&ctext);
+        Ok(out)
+    }
+
+    /// Encrypt a message deterministically, without a nonce
+    ///
+    /// Only allowed for misuse resistant algorithms, since the same
+    /// message (with the same associated data) always encrypts to the
+    /// same ciphertext. This is appropriate for example for encrypting
+    /// keys, or values which are looked up by their ciphertext.
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::BadParameter` unless `is_misuse_resistant`
+    ///
+    /// # Examples
+    /// ```
+    /// let mut enc = botan::AeadEncryptor::aes256_siv().unwrap();
+    /// enc.set_key(&[1; 64]).unwrap();
+    /// let ctext = enc.encrypt_without_nonce(b"key material").unwrap();
+    /// assert_eq!(enc.encrypt_without_nonce(b"key material").unwrap(), ctext);
+    /// ```
+    pub fn encrypt_without_nonce(&mut self, ptext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = fixed_nonce(&self.cipher)?;
+        self.cipher.process(&nonce, ptext)
+    }
+}
+
+impl AeadDecryptor {
+    /// Decrypt and authenticate a ciphertext with the provided nonce
+    ///
+    /// The key must already have been set.
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::BadAuthCode` if the ciphertext or
+    /// associated data was modified
+    pub fn decrypt(&mut self, nonce: &[u8], ctext: &[u8]) -> Result<Vec<u8>> {
+        self.cipher.process(nonce, ctext)
+    }
+
+    /// Decrypt and authenticate a ciphertext in place, removing the tag
+    pub fn decrypt_in_place(&mut self, nonce: &[u8], buf: &mut Vec<u8>) -> Result<()> {
+        self.cipher.process_in_place(nonce, buf)
+    }
+
+    /// Decrypt the output of `AeadEncryptor::encrypt_with_random_nonce`
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::InvalidInput` if the input is too short to
+    /// hold a nonce and tag, or with `ErrorType::BadAuthCode` if it was
+    /// modified
+    pub fn decrypt_with_prepended_nonce(&mut self, sealed: &[u8]) -> Result<Vec<u8>> {
+        let nonce_len = self.cipher.default_nonce_length();
+        if sealed.len() < nonce_len + self.cipher.tag_length() {
+            return Err(Error::with_message(
+                ErrorType::InvalidInput,
+                "Ciphertext is too short to contain a nonce and tag".to_owned(),
+            ));
+        }
+        let (nonce, ctext) = sealed.split_at(nonce_len);
+        self.cipher.process(nonce, ctext)
+    }
+
+    /// Decrypt the output of `AeadEncryptor::encrypt_without_nonce`
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::BadParameter` unless `is_misuse_resistant`,
+    /// or with `ErrorType::BadAuthCode` if the ciphertext was modified
+    pub fn decrypt_without_nonce(&mut self, ctext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = fixed_nonce(&self.cipher)?;
+        self.cipher.process(&nonce, ctext)
+    }
+}
+
+/// XChaCha20Poly1305, ChaCha20Poly1305 with a 24 byte nonce
+///
+/// This is the construction of libsodium's
+/// `crypto_aead_xchacha20poly1305_ietf` functions. The key and nonce
+/// are fixed size arrays, so passing one of the wrong length is a
+/// compile time error, and the nonce is long enough to be chosen at
+/// random for every message.
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let mut aead = botan::XChaCha20Poly1305::new(&[0x80; 32]).unwrap();
+///
+/// let nonce = botan::XChaChaNonce::generate(&mut rng).unwrap();
+/// let ctext = aead.encrypt(&nonce, b"header", b"hello").unwrap();
+/// assert_eq!(ctext.len(), 5 + 16);
+/// assert_eq!(aead.decrypt(&nonce, b"header", &ctext).unwrap(), b"hello");
+/// assert!(aead.decrypt(&nonce, b"other", &ctext).is_err());
+/// ```
+#[derive(Debug)]
+pub struct XChaCha20Poly1305 {
+    enc: AeadEncryptor,
+    dec: AeadDecryptor,
+}
+
+impl XChaCha20Poly1305 {
+    /// Create an object using the 32 byte `key`
+    pub fn new(key: &[u8; 32]) -> Result<XChaCha20Poly1305> {
+        // The library switches to XChaCha when given a 24 byte nonce
+        let mut enc = AeadEncryptor::new("ChaCha20Poly1305")?;
+        let mut dec = AeadDecryptor::new("ChaCha20Poly1305")?;
+        enc.set_key(key)?;
+        dec.set_key(key)?;
+        Ok(XChaCha20Poly1305 { enc, dec })
+    }
+
+    /// Encrypt `ptext`, authenticating it along with `ad`, and return the
+    /// ciphertext followed by the 16 byte tag
+    pub fn encrypt(&mut self, nonce: &XChaChaNonce, ad: &[u8], ptext: &[u8]) -> Result<Vec<u8>> {
+        self.enc.set_associated_data(ad)?;
+        self.enc.encrypt(nonce.as_bytes(), ptext)
+    }
+
+    /// Encrypt `ptext` with a random nonce, returning the nonce followed
+    /// by the ciphertext and tag
+    pub fn encrypt_with_random_nonce(
+        &mut self,
+        rng: &mut RandomNumberGenerator,
+        ad: &[u8],
+        ptext: &[u8],
+    ) -> Result<Vec<u8>> {
+        let nonce = XChaChaNonce::generate(rng)?;
+        let mut out = nonce.as_bytes().to_vec();
+        out.extend_from_slice(&self.encrypt(&nonce, ad, ptext)?);
+        Ok(out)
+    }
+
+    /// Decrypt and authenticate `ctext` along with `ad`
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::BadAuthCode` if the ciphertext or
+    /// associated data was modified
+    pub fn decrypt(&mut self, nonce: &XChaChaNonce, ad: &[u8], ctext: &[u8]) -> Result<Vec<u8>> {
+        self.dec.set_associated_data(ad)?;
+        self.dec.decrypt(nonce.as_bytes(), ctext)
+    }
+
+    /// Decrypt the output of `encrypt_with_random_nonce`
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::InvalidInput` if the input is too short to
+    /// hold a nonce and tag, or with `ErrorType::BadAuthCode` if it was
+    /// modified
+    pub fn decrypt_with_prepended_nonce(&mut self, ad: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+        if sealed.len() < XCHACHA_NONCE_LENGTH + self.dec.tag_length() {
+            return Err(Error::with_message(
+                ErrorType::InvalidInput,
+                "Ciphertext is too short to contain a nonce and tag".to_owned(),
+            ));
+        }
+        let (nonce, ctext) = sealed.split_at(XCHACHA_NONCE_LENGTH);
+        self.dec.set_associated_data(ad)?;
+        self.dec.decrypt(nonce, ctext)
+    }
+}
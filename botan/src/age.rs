@@ -0,0 +1,655 @@
+use crate::utils::*;
+
+use core::str::FromStr;
+
+use crate::cipher::{Cipher, CipherDirection};
+use crate::codec::{base64_decode, base64_encode};
+use crate::kdf::kdf;
+use crate::mac::MsgAuthCode;
+use crate::memutils::{const_time_compare, scrub_mem};
+use crate::pbkdf::scrypt;
+use crate::pk_ops::KeyAgreement;
+use crate::pubkey::Privkey;
+use crate::rng::RandomNumberGenerator;
+
+const AGE_VERSION_LINE: &str = "age-encryption.org/v1";
+const AGE_X25519_LABEL: &[u8] = b"age-encryption.org/v1/X25519";
+const AGE_SCRYPT_LABEL: &[u8] = b"age-encryption.org/v1/scrypt";
+
+const AGE_RECIPIENT_HRP: &str = "age";
+const AGE_IDENTITY_HRP: &str = "age-secret-key-";
+
+const FILE_KEY_LENGTH: usize = 16;
+const PAYLOAD_NONCE_LENGTH: usize = 16;
+const CHUNK_SIZE: usize = 64 * 1024;
+const TAG_LENGTH: usize = 16;
+const COLUMNS: usize = 64;
+
+fn age_error(msg: &str) -> Error {
+    Error::with_message(ErrorType::InvalidInput, msg.to_owned())
+}
+
+const BECH32_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GEN: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut chk = 1u32;
+    for &v in values {
+        let b = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ (v as u32);
+        for (i, g) in GEN.iter().enumerate() {
+            if (b >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn bech32_hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut out: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    out.push(0);
+    out.extend(hrp.bytes().map(|c| c & 31));
+    out
+}
+
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>> {
+    let mut acc = 0u32;
+    let mut bits = 0u32;
+    let mut out = Vec::new();
+    let maxv = (1u32 << to) - 1;
+
+    for &v in data {
+        acc = (acc << from) | (v as u32);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & maxv) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & maxv) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & maxv) != 0 {
+        return Err(age_error("Invalid Bech32 padding"));
+    }
+
+    Ok(out)
+}
+
+fn bech32_encode(hrp: &str, data: &[u8]) -> Result<String> {
+    let data = convert_bits(data, 8, 5, true)?;
+
+    let mut values = bech32_hrp_expand(hrp);
+    values.extend_from_slice(&data);
+    values.extend_from_slice(&[0; 6]);
+    let polymod = bech32_polymod(&values) ^ 1;
+
+    let mut out = hrp.to_owned();
+    out.push('1');
+    for &d in &data {
+        out.push(BECH32_CHARSET[d as usize] as char);
+    }
+    for i in 0..6 {
+        out.push(BECH32_CHARSET[((polymod >> (5 * (5 - i))) & 31) as usize] as char);
+    }
+    Ok(out)
+}
+
+fn bech32_decode(expected_hrp: &str, s: &str) -> Result<Vec<u8>> {
+    let lower = s.to_ascii_lowercase();
+    if lower != s && s.to_ascii_uppercase() != s {
+        return Err(age_error("Mixed case Bech32 string"));
+    }
+
+    let sep = lower
+        .rfind('1')
+        .ok_or_else(|| age_error("Invalid Bech32 string"))?;
+    let (hrp, data) = (&lower[..sep], &lower[sep + 1..]);
+
+    if hrp != expected_hrp || data.len() < 6 {
+        return Err(age_error("Unexpected Bech32 prefix"));
+    }
+
+    let mut values = Vec::with_capacity(data.len());
+    for c in data.bytes() {
+        match BECH32_CHARSET.iter().position(|&x| x == c) {
+            Some(v) => values.push(v as u8),
+            None => return Err(age_error("Invalid Bech32 character")),
+        }
+    }
+
+    let mut check = bech32_hrp_expand(hrp);
+    check.extend_from_slice(&values);
+    if bech32_polymod(&check) != 1 {
+        return Err(age_error("Invalid Bech32 checksum"));
+    }
+
+    values.truncate(values.len() - 6);
+    convert_bits(&values, 5, 8, false)
+}
+
+// age uses standard base64 without padding
+fn b64_encode(x: &[u8]) -> Result<String> {
+    Ok(base64_encode(x)?.trim_end_matches('=').to_owned())
+}
+
+fn b64_decode(x: &str) -> Result<Vec<u8>> {
+    if x.len() % 4 == 1
+        || !x
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'+' || c == b'/')
+    {
+        return Err(age_error("Invalid base64 encoding"));
+    }
+    let mut padded = x.to_owned();
+    while padded.len() % 4 != 0 {
+        padded.push('=');
+    }
+    base64_decode(&padded)
+}
+
+fn hkdf_sha256(ikm: &[u8], salt: &[u8], info: &[u8]) -> Result<Vec<u8>> {
+    kdf("HKDF(SHA-256)", 32, ikm, salt, info)
+}
+
+fn chacha20poly1305(
+    key: &[u8],
+    nonce: &[u8],
+    direction: CipherDirection,
+    input: &[u8],
+) -> Result<Vec<u8>> {
+    let mut cipher = Cipher::new("ChaCha20Poly1305", direction)?;
+    cipher.set_key(key)?;
+    cipher.process(nonce, input)
+}
+
+fn x25519(key: &Privkey, point: &[u8]) -> Result<Vec<u8>> {
+    let mut ka = KeyAgreement::new(key, "Raw")?;
+    let shared = ka.agree(0, point, &[])?;
+    if shared.iter().all(|&b| b == 0) {
+        return Err(age_error("Invalid X25519 share"));
+    }
+    Ok(shared)
+}
+
+/// An age X25519 recipient ("age1...")
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AgeRecipient {
+    key: Vec<u8>,
+}
+
+impl AgeRecipient {
+    /// Return the Bech32 encoding of this recipient
+    pub fn encode(&self) -> Result<String> {
+        bech32_encode(AGE_RECIPIENT_HRP, &self.key)
+    }
+}
+
+impl FromStr for AgeRecipient {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<AgeRecipient> {
+        let key = bech32_decode(AGE_RECIPIENT_HRP, s)?;
+        if key.len() != 32 {
+            return Err(age_error("Invalid age recipient"));
+        }
+        Ok(AgeRecipient { key })
+    }
+}
+
+/// An age X25519 identity ("AGE-SECRET-KEY-1...")
+#[derive(Debug)]
+pub struct AgeIdentity {
+    key: Privkey,
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for AgeIdentity {}
+
+impl AgeIdentity {
+    /// Generate a new random identity
+    pub fn generate(rng: &mut RandomNumberGenerator) -> Result<AgeIdentity> {
+        let key = Privkey::create("X25519", "", rng)?;
+        Ok(AgeIdentity { key })
+    }
+
+    /// Return the recipient corresponding to this identity
+    pub fn recipient(&self) -> Result<AgeRecipient> {
+        let key = self.key.key_agreement_key()?;
+        Ok(AgeRecipient { key })
+    }
+
+    /// Return the Bech32 encoding of this identity
+    pub fn encode(&self) -> Result<String> {
+        let mut key = self.key.get_x25519_key()?;
+        let encoded = bech32_encode(AGE_IDENTITY_HRP, &key);
+        scrub_mem(&mut key);
+        Ok(encoded?.to_ascii_uppercase())
+    }
+}
+
+impl FromStr for AgeIdentity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<AgeIdentity> {
+        let mut key = bech32_decode(AGE_IDENTITY_HRP, s)?;
+        let identity = if key.len() == 32 {
+            Privkey::load_x25519(&key).map(|key| AgeIdentity { key })
+        } else {
+            Err(age_error("Invalid age identity"))
+        };
+        scrub_mem(&mut key);
+        identity
+    }
+}
+
+struct Stanza {
+    kind: String,
+    args: Vec<String>,
+    body: Vec<u8>,
+}
+
+impl Stanza {
+    fn write(&self, out: &mut String) -> Result<()> {
+        out.push_str("-> ");
+        out.push_str(&self.kind);
+        for arg in &self.args {
+            out.push(' ');
+            out.push_str(arg);
+        }
+        out.push('\n');
+
+        let body = b64_encode(&self.body)?;
+        let mut rest = body.as_str();
+        loop {
+            let take = core::cmp::min(COLUMNS, rest.len());
+            out.push_str(&rest[..take]);
+            out.push('\n');
+            rest = &rest[take..];
+            if take < COLUMNS {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Header {
+    stanzas: Vec<Stanza>,
+    mac: Vec<u8>,
+    // header bytes covered by the MAC
+    mac_input_len: usize,
+    payload_offset: usize,
+}
+
+fn next_line<'a>(data: &'a [u8], offset: &mut usize) -> Result<&'a str> {
+    let rest = &data[*offset..];
+    let end = rest
+        .iter()
+        .position(|&c| c == b'\n')
+        .ok_or_else(|| age_error("Truncated age header"))?;
+    *offset += end + 1;
+    core::str::from_utf8(&rest[..end]).map_err(Error::conversion_error)
+}
+
+fn parse_header(data: &[u8]) -> Result<Header> {
+    let mut offset = 0;
+
+    if next_line(data, &mut offset)? != AGE_VERSION_LINE {
+        return Err(age_error("Unsupported age version"));
+    }
+
+    let mut stanzas = Vec::new();
+
+    loop {
+        let line_start = offset;
+        let line = next_line(data, &mut offset)?;
+
+        if line.get(..4) == Some("--- ") {
+            let mac = b64_decode(&line[4..])?;
+            return Ok(Header {
+                stanzas,
+                mac,
+                mac_input_len: line_start + 3,
+                payload_offset: offset,
+            });
+        }
+
+        if line.get(..3) != Some("-> ") {
+            return Err(age_error("Malformed age header"));
+        }
+
+        let mut args = line[3..].split(' ').map(|s| s.to_owned());
+        let kind = args
+            .next()
+            .filter(|k| !k.is_empty())
+            .ok_or_else(|| age_error("Malformed age stanza"))?;
+        let args: Vec<String> = args.collect();
+        if args.iter().any(|a| a.is_empty()) {
+            return Err(age_error("Malformed age stanza"));
+        }
+
+        let mut body = String::new();
+        loop {
+            let line = next_line(data, &mut offset)?;
+            if line.len() > COLUMNS {
+                return Err(age_error("Malformed age stanza"));
+            }
+            body.push_str(line);
+            if line.len() < COLUMNS {
+                break;
+            }
+        }
+
+        stanzas.push(Stanza {
+            kind,
+            args,
+            body: b64_decode(&body)?,
+        });
+    }
+}
+
+fn header_mac(file_key: &[u8], header: &[u8]) -> Result<Vec<u8>> {
+    let mut mac_key = hkdf_sha256(file_key, &[], b"header")?;
+    let mac = MsgAuthCode::new("HMAC(SHA-256)")?;
+    let rc = mac.set_key(&mac_key);
+    scrub_mem(&mut mac_key);
+    rc?;
+    mac.update(header)?;
+    mac.finish()
+}
+
+fn payload_nonce(counter: u64, last: bool) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[3..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = if last { 1 } else { 0 };
+    nonce
+}
+
+fn seal(
+    stanzas: &[Stanza],
+    file_key: &[u8],
+    ptext: &[u8],
+    rng: &mut RandomNumberGenerator,
+) -> Result<Vec<u8>> {
+    let mut header = String::new();
+    header.push_str(AGE_VERSION_LINE);
+    header.push('\n');
+    for stanza in stanzas {
+        stanza.write(&mut header)?;
+    }
+    header.push_str("---");
+
+    let mac = header_mac(file_key, header.as_bytes())?;
+    header.push(' ');
+    header.push_str(&b64_encode(&mac)?);
+    header.push('\n');
+
+    let nonce = rng.read(PAYLOAD_NONCE_LENGTH)?;
+    let mut payload_key = hkdf_sha256(file_key, &nonce, b"payload")?;
+
+    let mut out = header.into_bytes();
+    out.extend_from_slice(&nonce);
+
+    let mut cipher = Cipher::new("ChaCha20Poly1305", CipherDirection::Encrypt)?;
+    let rc = cipher.set_key(&payload_key);
+    scrub_mem(&mut payload_key);
+    rc?;
+
+    let mut chunks = ptext.chunks(CHUNK_SIZE).peekable();
+    let mut counter = 0u64;
+
+    if chunks.peek().is_none() {
+        out.extend_from_slice(&cipher.process(&payload_nonce(0, true), &[])?);
+    }
+
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        out.extend_from_slice(&cipher.process(&payload_nonce(counter, last), chunk)?);
+        counter += 1;
+    }
+
+    Ok(out)
+}
+
+fn open(header: &Header, file_key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    let mac = header_mac(file_key, &data[..header.mac_input_len])?;
+    if !const_time_compare(&mac, &header.mac) {
+        return Err(Error::with_message(
+            ErrorType::BadAuthCode,
+            "age header MAC is not valid".to_owned(),
+        ));
+    }
+
+    let payload = &data[header.payload_offset..];
+    if payload.len() < PAYLOAD_NONCE_LENGTH + TAG_LENGTH {
+        return Err(age_error("Truncated age payload"));
+    }
+
+    let (nonce, mut payload) = payload.split_at(PAYLOAD_NONCE_LENGTH);
+    let mut payload_key = hkdf_sha256(file_key, nonce, b"payload")?;
+
+    let mut cipher = Cipher::new("ChaCha20Poly1305", CipherDirection::Decrypt)?;
+    let rc = cipher.set_key(&payload_key);
+    scrub_mem(&mut payload_key);
+    rc?;
+
+    let mut out = Vec::with_capacity(payload.len());
+    let mut counter = 0u64;
+
+    loop {
+        let take = core::cmp::min(CHUNK_SIZE + TAG_LENGTH, payload.len());
+        let (chunk, rest) = payload.split_at(take);
+        let last = rest.is_empty();
+
+        if chunk.len() < TAG_LENGTH || (last && counter > 0 && chunk.len() == TAG_LENGTH) {
+            return Err(age_error("Truncated age payload"));
+        }
+
+        out.extend_from_slice(&cipher.process(&payload_nonce(counter, last), chunk)?);
+
+        if last {
+            return Ok(out);
+        }
+
+        payload = rest;
+        counter += 1;
+    }
+}
+
+/// Encrypt a file to one or more age X25519 recipients
+///
+/// The output is the binary age format and can be decrypted by any
+/// age v1 implementation.
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let identity = botan::AgeIdentity::generate(&mut rng).unwrap();
+/// let recipient = identity.recipient().unwrap();
+/// let ctext = botan::age_encrypt(&[recipient], b"hello age", &mut rng).unwrap();
+/// assert_eq!(botan::age_decrypt(&[identity], &ctext).unwrap(), b"hello age");
+/// ```
+pub fn age_encrypt(
+    recipients: &[AgeRecipient],
+    ptext: &[u8],
+    rng: &mut RandomNumberGenerator,
+) -> Result<Vec<u8>> {
+    if recipients.is_empty() {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            "No age recipients provided".to_owned(),
+        ));
+    }
+
+    let mut file_key = rng.read(FILE_KEY_LENGTH)?;
+    let mut stanzas = Vec::with_capacity(recipients.len());
+
+    for recipient in recipients {
+        let ephemeral = Privkey::create("X25519", "", rng)?;
+        let share = ephemeral.key_agreement_key()?;
+        let mut shared = x25519(&ephemeral, &recipient.key)?;
+
+        let mut salt = share.clone();
+        salt.extend_from_slice(&recipient.key);
+        let mut wrap_key = hkdf_sha256(&shared, &salt, AGE_X25519_LABEL)?;
+        scrub_mem(&mut shared);
+
+        let body = chacha20poly1305(&wrap_key, &[0; 12], CipherDirection::Encrypt, &file_key);
+        scrub_mem(&mut wrap_key);
+
+        stanzas.push(Stanza {
+            kind: "X25519".to_owned(),
+            args: vec![b64_encode(&share)?],
+            body: body?,
+        });
+    }
+
+    let out = seal(&stanzas, &file_key, ptext, rng);
+    scrub_mem(&mut file_key);
+    out
+}
+
+// Unwrap the file key of an X25519 stanza; any failure means the stanza
+// was not encrypted to this identity
+fn x25519_unwrap(identity: &AgeIdentity, share: &[u8], body: &[u8]) -> Result<Vec<u8>> {
+    let mut shared = x25519(&identity.key, share)?;
+    let mut salt = share.to_vec();
+    salt.extend_from_slice(&identity.key.key_agreement_key()?);
+    let wrap_key = hkdf_sha256(&shared, &salt, AGE_X25519_LABEL);
+    scrub_mem(&mut shared);
+
+    let mut wrap_key = wrap_key?;
+    let file_key = chacha20poly1305(&wrap_key, &[0; 12], CipherDirection::Decrypt, body);
+    scrub_mem(&mut wrap_key);
+    file_key
+}
+
+/// Decrypt an age file using any of the provided identities
+///
+/// Stanzas which cannot be unwrapped with any of the identities are
+/// skipped. Files with an scrypt stanza alongside other stanzas are
+/// rejected, as the age specification requires.
+pub fn age_decrypt(identities: &[AgeIdentity], ctext: &[u8]) -> Result<Vec<u8>> {
+    let header = parse_header(ctext)?;
+
+    if header.stanzas.len() > 1 && header.stanzas.iter().any(|s| s.kind == "scrypt") {
+        return Err(age_error("An scrypt stanza must be the only stanza"));
+    }
+
+    for stanza in &header.stanzas {
+        if stanza.kind != "X25519" {
+            continue;
+        }
+        if stanza.args.len() != 1 || stanza.body.len() != FILE_KEY_LENGTH + TAG_LENGTH {
+            return Err(age_error("Malformed X25519 stanza"));
+        }
+
+        let share = b64_decode(&stanza.args[0])?;
+        if share.len() != 32 {
+            return Err(age_error("Malformed X25519 stanza"));
+        }
+
+        for identity in identities {
+            if let Ok(mut file_key) = x25519_unwrap(identity, &share, &stanza.body) {
+                let ptext = open(&header, &file_key, ctext);
+                scrub_mem(&mut file_key);
+                return ptext;
+            }
+        }
+    }
+
+    Err(Error::with_message(
+        ErrorType::InvalidInput,
+        "No matching age identity".to_owned(),
+    ))
+}
+
+fn scrypt_wrap_key(passphrase: &str, salt: &[u8], log_n: u8) -> Result<Vec<u8>> {
+    let mut full_salt = AGE_SCRYPT_LABEL.to_vec();
+    full_salt.extend_from_slice(salt);
+    scrypt(32, passphrase, &full_salt, 1 << log_n, 8, 1)
+}
+
+/// Encrypt a file with an age scrypt passphrase stanza
+///
+/// The scrypt work factor is 2^`log_n`; age itself defaults to 18.
+pub fn age_encrypt_with_passphrase(
+    passphrase: &str,
+    log_n: u8,
+    ptext: &[u8],
+    rng: &mut RandomNumberGenerator,
+) -> Result<Vec<u8>> {
+    if log_n == 0 || log_n > 30 {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            "Invalid scrypt work factor".to_owned(),
+        ));
+    }
+
+    let mut file_key = rng.read(FILE_KEY_LENGTH)?;
+    let salt = rng.read(16)?;
+
+    let mut wrap_key = scrypt_wrap_key(passphrase, &salt, log_n)?;
+    let body = chacha20poly1305(&wrap_key, &[0; 12], CipherDirection::Encrypt, &file_key);
+    scrub_mem(&mut wrap_key);
+
+    let stanza = Stanza {
+        kind: "scrypt".to_owned(),
+        args: vec![b64_encode(&salt)?, format!("{}", log_n)],
+        body: body?,
+    };
+
+    let out = seal(&[stanza], &file_key, ptext, rng);
+    scrub_mem(&mut file_key);
+    out
+}
+
+/// Decrypt a file encrypted with an age scrypt passphrase stanza
+///
+/// Files with a work factor above 2^`max_log_n` are rejected.
+pub fn age_decrypt_with_passphrase(
+    passphrase: &str,
+    max_log_n: u8,
+    ctext: &[u8],
+) -> Result<Vec<u8>> {
+    let header = parse_header(ctext)?;
+
+    let stanza = match header.stanzas.as_slice() {
+        [stanza] if stanza.kind == "scrypt" => stanza,
+        _ => return Err(age_error("Expected a single scrypt stanza")),
+    };
+
+    if stanza.args.len() != 2 || stanza.body.len() != FILE_KEY_LENGTH + TAG_LENGTH {
+        return Err(age_error("Malformed scrypt stanza"));
+    }
+
+    let salt = b64_decode(&stanza.args[0])?;
+    let log_n: u8 = stanza.args[1]
+        .parse()
+        .map_err(|_| age_error("Malformed scrypt stanza"))?;
+
+    if salt.len() != 16 || log_n == 0 || stanza.args[1].starts_with('0') {
+        return Err(age_error("Malformed scrypt stanza"));
+    }
+
+    if log_n > max_log_n || log_n > 30 {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            "scrypt work factor too large".to_owned(),
+        ));
+    }
+
+    let mut wrap_key = scrypt_wrap_key(passphrase, &salt, log_n)?;
+    let file_key = chacha20poly1305(&wrap_key, &[0; 12], CipherDirection::Decrypt, &stanza.body);
+    scrub_mem(&mut wrap_key);
+
+    let mut file_key = file_key?;
+    let ptext = open(&header, &file_key, ctext);
+    scrub_mem(&mut file_key);
+    ptext
+}
@@ -0,0 +1,191 @@
+#[cfg(feature = "no-std")]
+use crate::utils::String;
+
+macro_rules! algorithm_enum {
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            $($(#[$vmeta:meta])* $variant:ident => $botan_name:expr,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[non_exhaustive]
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$vmeta])* $variant,)*
+            /// Any other algorithm, using the name the library expects
+            Custom(String),
+        }
+
+        impl $name {
+            /// Return the name the library uses for this algorithm
+            #[must_use]
+            pub fn name(&self) -> &str {
+                match self {
+                    $($name::$variant => $botan_name,)*
+                    $name::Custom(name) => name,
+                }
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                self.name()
+            }
+        }
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+
+        #[cfg(feature = "arbitrary")]
+        impl<'a> arbitrary::Arbitrary<'a> for $name {
+            fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<$name> {
+                Ok(u.choose(&[$($name::$variant,)*])?.clone())
+            }
+        }
+
+        #[cfg(feature = "proptest")]
+        impl proptest::arbitrary::Arbitrary for $name {
+            type Parameters = ();
+            type Strategy = proptest::sample::Select<$name>;
+
+            fn arbitrary_with(_: ()) -> Self::Strategy {
+                proptest::sample::select(vec![$($name::$variant,)*])
+            }
+        }
+    };
+}
+
+algorithm_enum! {
+    /// Hash functions, for use with `HashFunction::new`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let hash = botan::HashFunction::new(botan::HashAlgo::Sha256).unwrap();
+    /// assert_eq!(hash.algo_name().unwrap(), "SHA-256");
+    /// let hash = botan::HashFunction::new(botan::HashAlgo::Custom("Skein-512(256)".to_owned()));
+    /// ```
+    HashAlgo {
+        /// SHA-1
+        Sha1 => "SHA-1",
+        /// SHA-224
+        Sha224 => "SHA-224",
+        /// SHA-256
+        Sha256 => "SHA-256",
+        /// SHA-384
+        Sha384 => "SHA-384",
+        /// SHA-512
+        Sha512 => "SHA-512",
+        /// SHA-512/256
+        Sha512_256 => "SHA-512-256",
+        /// SHA-3 with 224 bit output
+        Sha3_224 => "SHA-3(224)",
+        /// SHA-3 with 256 bit output
+        Sha3_256 => "SHA-3(256)",
+        /// SHA-3 with 384 bit output
+        Sha3_384 => "SHA-3(384)",
+        /// SHA-3 with 512 bit output
+        Sha3_512 => "SHA-3(512)",
+        /// BLAKE2b with 256 bit output
+        Blake2b256 => "BLAKE2b(256)",
+        /// BLAKE2b with 512 bit output
+        Blake2b512 => "BLAKE2b(512)",
+        /// RIPEMD-160
+        Ripemd160 => "RIPEMD-160",
+        /// SM3
+        Sm3 => "SM3",
+        /// MD5
+        Md5 => "MD5",
+    }
+}
+
+algorithm_enum! {
+    /// Message authentication codes, for use with `MsgAuthCode::new`
+    MacAlgo {
+        /// HMAC with SHA-256
+        HmacSha256 => "HMAC(SHA-256)",
+        /// HMAC with SHA-384
+        HmacSha384 => "HMAC(SHA-384)",
+        /// HMAC with SHA-512
+        HmacSha512 => "HMAC(SHA-512)",
+        /// CMAC with AES-128
+        CmacAes128 => "CMAC(AES-128)",
+        /// CMAC with AES-256
+        CmacAes256 => "CMAC(AES-256)",
+        /// Poly1305 (the key must only be used once)
+        Poly1305 => "Poly1305",
+        /// SipHash-2-4
+        SipHash24 => "SipHash(2,4)",
+    }
+}
+
+algorithm_enum! {
+    /// Block ciphers, for use with `BlockCipher::new` and
+    /// `Cipher::new_padded`
+    BlockCipherAlgo {
+        /// AES with a 128 bit key
+        Aes128 => "AES-128",
+        /// AES with a 192 bit key
+        Aes192 => "AES-192",
+        /// AES with a 256 bit key
+        Aes256 => "AES-256",
+        /// ARIA with a 128 bit key
+        Aria128 => "ARIA-128",
+        /// ARIA with a 256 bit key
+        Aria256 => "ARIA-256",
+        /// Camellia with a 128 bit key
+        Camellia128 => "Camellia-128",
+        /// Camellia with a 256 bit key
+        Camellia256 => "Camellia-256",
+        /// Serpent
+        Serpent => "Serpent",
+        /// Twofish
+        Twofish => "Twofish",
+        /// SM4
+        Sm4 => "SM4",
+        /// Triple DES
+        TripleDes => "TripleDES",
+    }
+}
+
+algorithm_enum! {
+    /// Authenticated encryption modes, for use with `Cipher::new`
+    ///
+    /// Other cipher modes can be used with `Custom`, or with
+    /// `Cipher::new_padded`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let cipher = botan::Cipher::new(botan::AeadAlgo::Aes256Gcm, botan::CipherDirection::Encrypt).unwrap();
+    /// assert_eq!(cipher.tag_length(), 16);
+    /// ```
+    AeadAlgo {
+        /// AES-128 in GCM mode
+        Aes128Gcm => "AES-128/GCM",
+        /// AES-256 in GCM mode
+        Aes256Gcm => "AES-256/GCM",
+        /// AES-128 in OCB mode
+        Aes128Ocb => "AES-128/OCB",
+        /// AES-256 in OCB mode
+        Aes256Ocb => "AES-256/OCB",
+        /// AES-128 in EAX mode
+        Aes128Eax => "AES-128/EAX",
+        /// AES-256 in EAX mode
+        Aes256Eax => "AES-256/EAX",
+        /// AES-128 in CCM mode
+        Aes128Ccm => "AES-128/CCM",
+        /// AES-256 in CCM mode
+        Aes256Ccm => "AES-256/CCM",
+        /// AES-128 in SIV mode (nonce misuse resistant)
+        Aes128Siv => "AES-128/SIV",
+        /// AES-256 in SIV mode (nonce misuse resistant)
+        Aes256Siv => "AES-256/SIV",
+        /// ChaCha20Poly1305 (RFC 8439)
+        ChaCha20Poly1305 => "ChaCha20Poly1305",
+    }
+}
@@ -0,0 +1,196 @@
+use crate::utils::*;
+
+use crate::hash::HashFunction;
+use crate::pk_ops::Verifier;
+use crate::pubkey::Pubkey;
+
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// A message, its signature, and the key to verify it with
+pub type BatchItem<'a> = (&'a [u8], &'a [u8], &'a Pubkey);
+
+fn verify_one(item: &BatchItem<'_>, padding: &str) -> Result<bool> {
+    let (msg, sig, key) = *item;
+    let mut verifier = Verifier::new(key, padding)?;
+    verifier.update(msg)?;
+    verifier.finish(sig)
+}
+
+type Results<R> = Vec<(usize, R)>;
+
+struct Work<'a, T, R> {
+    items: &'a [T],
+    f: &'a (dyn Fn(&T) -> R + Sync),
+    next: AtomicUsize,
+}
+
+impl<'a, T, R> Work<'a, T, R> {
+    fn run(&self) -> Results<R> {
+        let mut results = Vec::new();
+        loop {
+            let i = self.next.fetch_add(1, Ordering::Relaxed);
+            if i >= self.items.len() {
+                return results;
+            }
+            results.push((i, (self.f)(&self.items[i])));
+        }
+    }
+}
+
+// Allows worker threads to borrow the work description. This is sound
+// because JoinOnDrop ensures every worker has finished before
+// parallel_map returns, even if it unwinds.
+struct WorkPtr(*const ());
+
+unsafe impl Send for WorkPtr {}
+
+unsafe fn run_work<T, R>(ptr: *const ()) -> Results<R> {
+    (*(ptr as *const Work<'_, T, R>)).run()
+}
+
+struct JoinOnDrop<R> {
+    handles: Vec<thread::JoinHandle<Results<R>>>,
+}
+
+impl<R> JoinOnDrop<R> {
+    fn join_all(&mut self) -> Results<R> {
+        let mut results = Vec::new();
+        let mut panicked = false;
+        for handle in self.handles.drain(..) {
+            match handle.join() {
+                Ok(r) => results.extend(r),
+                Err(_) => panicked = true,
+            }
+        }
+        if panicked {
+            panic!("batch worker thread panicked");
+        }
+        results
+    }
+}
+
+impl<R> Drop for JoinOnDrop<R> {
+    fn drop(&mut self) {
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+// Apply f to each item using up to `threads` threads (including the
+// calling thread), returning the results in the same order as items
+fn parallel_map<T: Sync, R: Send + 'static>(
+    items: &[T],
+    threads: usize,
+    f: &(dyn Fn(&T) -> R + Sync),
+) -> Vec<R> {
+    if threads <= 1 || items.len() <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let work = Work {
+        items,
+        f,
+        next: AtomicUsize::new(0),
+    };
+
+    let mut workers = JoinOnDrop {
+        handles: Vec::new(),
+    };
+
+    let run: unsafe fn(*const ()) -> Results<R> = run_work::<T, R>;
+
+    for _ in 1..core::cmp::min(threads, items.len()) {
+        let ptr = WorkPtr(&work as *const Work<'_, T, R> as *const ());
+        // Safety: see WorkPtr
+        let spawned = thread::Builder::new().spawn(move || unsafe { run(ptr.0) });
+
+        match spawned {
+            Ok(handle) => workers.handles.push(handle),
+            // Continue with however many threads could be started
+            Err(_) => break,
+        }
+    }
+
+    let mut results = work.run();
+    results.extend(workers.join_all());
+
+    let mut ordered: Vec<Option<R>> = (0..items.len()).map(|_| None).collect();
+    for (i, r) in results {
+        ordered[i] = Some(r);
+    }
+
+    ordered
+        .into_iter()
+        .map(|r| r.expect("every item was processed"))
+        .collect()
+}
+
+/// Verify many signatures using several threads
+///
+/// Each item is a message, its signature and the public key to check it
+/// against; every signature is checked with the same `padding`, for
+/// example `"Pure"` for Ed25519 or `"EMSA1(SHA-256)"` for ECDSA.
+/// Returns one result per item, in the same order, with the same
+/// meaning as `Verifier::finish`.
+///
+/// Up to `threads` threads are used, including the calling thread.
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::Privkey::create("Ed25519", "", &mut rng).unwrap();
+/// let pubkey = key.pubkey().unwrap();
+/// let sig = key.sign(b"message", "Pure", &mut rng).unwrap();
+///
+/// let items = [
+///     (&b"message"[..], &sig[..], &pubkey),
+///     (&b"forgery"[..], &sig[..], &pubkey),
+/// ];
+/// let results = botan::verify_batch(&items, "Pure", 2);
+/// assert_eq!(results[0], Ok(true));
+/// assert_eq!(results[1], Ok(false));
+/// ```
+pub fn verify_batch(items: &[BatchItem<'_>], padding: &str, threads: usize) -> Vec<Result<bool>> {
+    parallel_map(items, threads, &|item| verify_one(item, padding))
+}
+
+fn hash_file(path: &Path, hash: &HashFunction) -> Result<Vec<u8>> {
+    let file = std::fs::File::open(path)?;
+    let mut hash = hash.duplicate()?;
+    read_chunks(file, |chunk| hash.update(chunk))?;
+    hash.finish()
+}
+
+/// Hash the contents of many files using several threads
+///
+/// Returns one result per path, in the same order, containing either
+/// the digest of the file or the error which occurred while reading
+/// it. Files are read in fixed size chunks, so memory use does not
+/// depend on the size of the files.
+///
+/// Up to `threads` threads are used, including the calling thread.
+///
+/// # Errors
+/// Fails if the hash function is not known
+///
+/// # Examples
+///
+/// ```
+/// let digests = botan::hash_files(&["Cargo.toml", "no-such-file"], "SHA-256", 2).unwrap();
+/// assert_eq!(digests[0].as_ref().unwrap().len(), 32);
+/// assert!(digests[1].is_err());
+/// ```
+pub fn hash_files<P: AsRef<Path> + Sync>(
+    paths: &[P],
+    algo: &str,
+    threads: usize,
+) -> Result<Vec<Result<Vec<u8>>>> {
+    let hash = HashFunction::new(algo)?;
+    Ok(parallel_map(paths, threads, &|path| {
+        hash_file(path.as_ref(), &hash)
+    }))
+}
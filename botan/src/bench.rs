@@ -0,0 +1,165 @@
+//! Measuring the speed of algorithms
+//!
+//! Each function processes 4 KiB messages repeatedly for (at least) the
+//! requested duration, and reports how many bytes were processed. This
+//! is intended for choosing between algorithms at runtime, for example
+//! picking whichever AEAD is fastest on the current machine; it is not
+//! a substitute for careful benchmarking.
+
+use crate::utils::*;
+
+use crate::cipher::{Cipher, CipherDirection};
+use crate::hash::HashFunction;
+use crate::mac::MsgAuthCode;
+
+use std::time::{Duration, Instant};
+
+const MESSAGE_SIZE: usize = 4096;
+
+/// The result of benchmarking an algorithm
+#[derive(Clone, Debug)]
+pub struct Measurement {
+    name: String,
+    bytes: u64,
+    elapsed: Duration,
+}
+
+impl Measurement {
+    /// Return the name of the algorithm which was measured
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the total number of bytes processed
+    #[must_use]
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Return how long processing took
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Return the throughput, in bytes per second
+    #[must_use]
+    pub fn bytes_per_second(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.bytes as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+fn measure(
+    name: &str,
+    runtime: Duration,
+    mut op: impl FnMut() -> Result<()>,
+) -> Result<Measurement> {
+    let start = Instant::now();
+    let mut bytes = 0;
+
+    loop {
+        op()?;
+        bytes += MESSAGE_SIZE as u64;
+
+        let elapsed = start.elapsed();
+        if elapsed >= runtime {
+            return Ok(Measurement {
+                name: name.to_owned(),
+                bytes,
+                elapsed,
+            });
+        }
+    }
+}
+
+fn test_key(spec: &KeySpec) -> Vec<u8> {
+    let len = [16, 32]
+        .iter()
+        .cloned()
+        .find(|l| spec.is_valid_keylength(*l))
+        .unwrap_or_else(|| spec.minimum_keylength());
+    vec![0; len]
+}
+
+/// Measure the speed of a hash function
+///
+/// # Examples
+///
+/// ```
+/// let m = botan::bench::hash("SHA-256", std::time::Duration::from_millis(10)).unwrap();
+/// assert!(m.bytes_per_second() > 0.0);
+/// ```
+pub fn hash(name: &str, runtime: Duration) -> Result<Measurement> {
+    let mut hash = HashFunction::new(name)?;
+    let msg = vec![0; MESSAGE_SIZE];
+
+    measure(name, runtime, || hash.update(&msg))
+}
+
+/// Measure the speed of a MAC
+///
+/// A key of all zeros is used.
+pub fn mac(name: &str, runtime: Duration) -> Result<Measurement> {
+    let mac = MsgAuthCode::new(name)?;
+    mac.set_key(&test_key(&mac.key_spec()?))?;
+    let msg = vec![0; MESSAGE_SIZE];
+
+    measure(name, runtime, || mac.update(&msg))
+}
+
+/// Measure the speed of encryption with a cipher mode
+///
+/// Each message is encrypted separately, using a key and nonce of all
+/// zeros, so for AEADs this includes the cost of computing the tag.
+pub fn cipher(name: &str, runtime: Duration) -> Result<Measurement> {
+    let mut cipher = Cipher::new(name, CipherDirection::Encrypt)?;
+    cipher.set_key(&test_key(&cipher.key_spec()?))?;
+    let nonce = vec![0; cipher.default_nonce_length()];
+    let mut buf = Vec::with_capacity(MESSAGE_SIZE + 64);
+
+    measure(name, runtime, || {
+        buf.clear();
+        buf.resize(MESSAGE_SIZE, 0);
+        cipher.process_in_place(&nonce, &mut buf)
+    })
+}
+
+/// Return the fastest of several cipher modes
+///
+/// Each cipher is measured for `runtime`. Ciphers which are not
+/// supported by the library are skipped; if none are supported, None
+/// is returned.
+///
+/// # Examples
+///
+/// ```
+/// let fastest = botan::bench::fastest_cipher(
+///     &["AES-256/GCM", "ChaCha20Poly1305"],
+///     std::time::Duration::from_millis(10),
+/// );
+/// assert!(fastest.is_some());
+/// ```
+#[must_use]
+pub fn fastest_cipher(names: &[&str], runtime: Duration) -> Option<Measurement> {
+    let mut fastest: Option<Measurement> = None;
+
+    for name in names {
+        if let Ok(m) = cipher(name, runtime) {
+            let faster = match &fastest {
+                Some(f) => m.bytes_per_second() > f.bytes_per_second(),
+                None => true,
+            };
+            if faster {
+                fastest = Some(m);
+            }
+        }
+    }
+
+    fastest
+}
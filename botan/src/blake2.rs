@@ -0,0 +1,180 @@
+use crate::utils::*;
+
+use crate::hash::HashFunction;
+use crate::mac::MsgAuthCode;
+use crate::memutils::SecretVec;
+
+const MAX_OUTPUT_LENGTH: usize = 64;
+const MAX_KEY_LENGTH: usize = 64;
+
+/// Creates a `Blake2b` with non-default parameters
+///
+/// Created by `Blake2b::builder`. The output length defaults to 64
+/// bytes, and the key to empty. The library does not implement the
+/// salt and personalization parameters, so they cannot be set.
+///
+/// # Examples
+/// ```
+/// let mut hash = botan::Blake2b::builder().output_length(32).build().unwrap();
+/// hash.update(b"abc").unwrap();
+/// assert_eq!(hash.finish().unwrap().len(), 32);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Blake2bBuilder {
+    output_length: usize,
+    key: SecretVec,
+}
+
+enum Blake2bState {
+    Unkeyed(HashFunction),
+    Keyed(MsgAuthCode),
+}
+
+/// BLAKE2b (RFC 7693), with a choice of output length and an optional key
+///
+/// A keyed BLAKE2b is a MAC, and requires Botan 3.x.
+///
+/// # Examples
+/// ```
+/// let mut hash = botan::Blake2b::new(32).unwrap();
+/// hash.update(b"abc").unwrap();
+/// assert_eq!(
+///     botan::hex_encode(&hash.finish().unwrap()).unwrap(),
+///     "BDDD813C634239723171EF3FEE98579B94964E3BB1CB3E427262C8C068D52319"
+/// );
+/// ```
+pub struct Blake2b {
+    state: Blake2bState,
+    output_length: usize,
+}
+
+impl core::fmt::Debug for Blake2b {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Blake2b")
+            .field("output_length", &self.output_length)
+            .field("keyed", &matches!(self.state, Blake2bState::Keyed(_)))
+            .finish()
+    }
+}
+
+fn check_length(what: &str, len: usize, max: usize) -> Result<()> {
+    if len > max {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            format!("BLAKE2b {} is at most {} bytes, not {}", what, max, len),
+        ));
+    }
+    Ok(())
+}
+
+impl Blake2b {
+    /// Create an unkeyed BLAKE2b with an output of `output_length`
+    /// bytes, between 1 and 64
+    pub fn new(output_length: usize) -> Result<Blake2b> {
+        Blake2b::builder().output_length(output_length).build()
+    }
+
+    /// Create a BLAKE2b keyed with `key` (at most 64 bytes), with an
+    /// output of `output_length` bytes
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "botan3")]
+    /// # {
+    /// let mut mac = botan::Blake2b::new_keyed(&[0x42; 32], 32).unwrap();
+    /// mac.update(b"message").unwrap();
+    /// let tag = mac.finish().unwrap();
+    /// assert_eq!(tag.len(), 32);
+    /// # }
+    /// ```
+    pub fn new_keyed(key: &[u8], output_length: usize) -> Result<Blake2b> {
+        Blake2b::builder()
+            .key(key)
+            .output_length(output_length)
+            .build()
+    }
+
+    /// Return a builder for setting the output length and key
+    #[must_use]
+    pub fn builder() -> Blake2bBuilder {
+        Blake2bBuilder {
+            output_length: MAX_OUTPUT_LENGTH,
+            key: SecretVec::default(),
+        }
+    }
+
+    /// Return the output length in bytes
+    #[must_use]
+    pub fn output_length(&self) -> usize {
+        self.output_length
+    }
+
+    /// Return true if the hash is keyed
+    #[must_use]
+    pub fn is_keyed(&self) -> bool {
+        matches!(self.state, Blake2bState::Keyed(_))
+    }
+
+    /// Add input to the hash
+    pub fn update(&mut self, data: &[u8]) -> Result<()> {
+        match &mut self.state {
+            Blake2bState::Unkeyed(hash) => hash.update(data),
+            Blake2bState::Keyed(mac) => mac.update(data),
+        }
+    }
+
+    /// Return the hash of the input so far, and start a new one with the
+    /// same parameters
+    pub fn finish(&mut self) -> Result<Vec<u8>> {
+        match &mut self.state {
+            Blake2bState::Unkeyed(hash) => hash.finish(),
+            Blake2bState::Keyed(mac) => mac.finish(),
+        }
+    }
+}
+
+impl Blake2bBuilder {
+    /// Set the output length in bytes, between 1 and 64
+    #[must_use]
+    pub fn output_length(mut self, output_length: usize) -> Blake2bBuilder {
+        self.output_length = output_length;
+        self
+    }
+
+    /// Set the key, at most 64 bytes; an empty key means unkeyed
+    #[must_use]
+    pub fn key(mut self, key: &[u8]) -> Blake2bBuilder {
+        self.key = SecretVec::from(key);
+        self
+    }
+
+    /// Create the hash
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::BadParameter` if a length is out of range,
+    /// or with `ErrorType::NotImplemented` for a key with Botan 2.x
+    pub fn build(&self) -> Result<Blake2b> {
+        if self.output_length == 0 {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "BLAKE2b output length must be at least 1 byte".to_owned(),
+            ));
+        }
+        check_length("output length", self.output_length, MAX_OUTPUT_LENGTH)?;
+        check_length("key", self.key.len(), MAX_KEY_LENGTH)?;
+
+        let name = format!("BLAKE2b({})", self.output_length * 8);
+        let state = if self.key.is_empty() {
+            Blake2bState::Unkeyed(HashFunction::new(&name)?)
+        } else {
+            let mac = MsgAuthCode::new(&name)?;
+            mac.set_key(&self.key)?;
+            Blake2bState::Keyed(mac)
+        };
+
+        Ok(Blake2b {
+            state,
+            output_length: self.output_length,
+        })
+    }
+}
@@ -0,0 +1,296 @@
+use crate::utils::*;
+
+use crate::hash::HashFunction;
+use crate::mp::MPI;
+use crate::pk_ops::{Signer, Verifier};
+use crate::pubkey::{Privkey, Pubkey};
+use crate::rng::RandomNumberGenerator;
+
+const HASH: &str = "SHA-384";
+const HASH_LEN: usize = 48;
+const PREFIX_LEN: usize = 32;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// The RSA blind signature variants of RFC 9474
+pub enum BlindRsaVariant {
+    /// RSABSSA-SHA384-PSS-Randomized
+    Sha384PssRandomized,
+    /// RSABSSA-SHA384-PSSZERO-Randomized
+    Sha384PssZeroRandomized,
+    /// RSABSSA-SHA384-PSS-Deterministic
+    Sha384PssDeterministic,
+    /// RSABSSA-SHA384-PSSZERO-Deterministic
+    Sha384PssZeroDeterministic,
+}
+
+impl BlindRsaVariant {
+    fn salt_len(self) -> usize {
+        match self {
+            BlindRsaVariant::Sha384PssRandomized | BlindRsaVariant::Sha384PssDeterministic => {
+                HASH_LEN
+            }
+            BlindRsaVariant::Sha384PssZeroRandomized
+            | BlindRsaVariant::Sha384PssZeroDeterministic => 0,
+        }
+    }
+
+    fn randomized(self) -> bool {
+        match self {
+            BlindRsaVariant::Sha384PssRandomized | BlindRsaVariant::Sha384PssZeroRandomized => true,
+            BlindRsaVariant::Sha384PssDeterministic
+            | BlindRsaVariant::Sha384PssZeroDeterministic => false,
+        }
+    }
+
+    fn padding(self) -> &'static str {
+        match self.salt_len() {
+            0 => "PSSR(SHA-384,MGF1,0)",
+            _ => "PSSR(SHA-384,MGF1,48)",
+        }
+    }
+}
+
+/// RSA blind signatures (RFC 9474)
+///
+/// The client prepares and blinds a message, the server signs the
+/// blinded message without learning it, and the client unblinds the
+/// result into an ordinary RSA-PSS signature of the prepared message.
+///
+/// # Examples
+///
+/// ```
+/// use botan::{BlindRsa, BlindRsaVariant};
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::Privkey::create("RSA", "2048", &mut rng).unwrap();
+/// let pubkey = key.pubkey().unwrap();
+/// let brsa = BlindRsa::new(BlindRsaVariant::Sha384PssRandomized);
+///
+/// // Client
+/// let msg = brsa.prepare(b"message", &mut rng).unwrap();
+/// let (blinded, inverse) = brsa.blind(&pubkey, &msg, &mut rng).unwrap();
+/// // Server
+/// let blind_sig = brsa.blind_sign(&key, &blinded).unwrap();
+/// // Client
+/// let sig = brsa.unblind(&pubkey, &msg, &blind_sig, &inverse).unwrap();
+///
+/// assert!(brsa.verify(&pubkey, &msg, &sig).unwrap());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BlindRsa {
+    variant: BlindRsaVariant,
+}
+
+fn rsa_params(key: &Pubkey) -> Result<(MPI, MPI, usize)> {
+    let algo = key.algo_name()?;
+    if algo != "RSA" {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            format!("Blind signatures require an RSA key, not {}", algo),
+        ));
+    }
+    let n = key.get_field("n")?;
+    let e = key.get_field("e")?;
+    let k_len = n.byte_count()?;
+    Ok((n, e, k_len))
+}
+
+fn os2ip(bytes: &[u8], k_len: usize, n: &MPI) -> Result<MPI> {
+    if bytes.len() != k_len {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            "Unexpected input length for blind RSA".to_owned(),
+        ));
+    }
+    let x = MPI::new_from_bytes(bytes)?;
+    if x >= *n {
+        return Err(Error::with_message(
+            ErrorType::InvalidInput,
+            "Input is not smaller than the RSA modulus".to_owned(),
+        ));
+    }
+    Ok(x)
+}
+
+fn hash(parts: &[&[u8]]) -> Result<Vec<u8>> {
+    let mut h = HashFunction::new(HASH)?;
+    for part in parts {
+        h.update(part)?;
+    }
+    h.finish()
+}
+
+fn mgf1(seed: &[u8], len: usize) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(len + HASH_LEN);
+    let mut counter = 0u32;
+    while out.len() < len {
+        out.extend_from_slice(&hash(&[seed, &counter.to_be_bytes()])?);
+        counter += 1;
+    }
+    out.truncate(len);
+    Ok(out)
+}
+
+// EMSA-PSS-ENCODE from RFC 8017 section 9.1.1
+fn emsa_pss_encode(
+    msg: &[u8],
+    em_bits: usize,
+    salt_len: usize,
+    rng: &mut RandomNumberGenerator,
+) -> Result<Vec<u8>> {
+    let spare_bits = em_bits % 8;
+    let em_len = em_bits / 8 + if spare_bits > 0 { 1 } else { 0 };
+    if em_len < HASH_LEN + salt_len + 2 {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            "RSA key is too small for blind signatures".to_owned(),
+        ));
+    }
+
+    let m_hash = hash(&[msg])?;
+    let salt = rng.read(salt_len)?;
+    let h = hash(&[&[0u8; 8], &m_hash, &salt])?;
+
+    let db_len = em_len - HASH_LEN - 1;
+    let mut db = vec![0u8; db_len - salt_len - 1];
+    db.push(0x01);
+    db.extend_from_slice(&salt);
+
+    for (d, m) in db.iter_mut().zip(mgf1(&h, db_len)?) {
+        *d ^= m;
+    }
+    db[0] &= 0xFF >> (8 * em_len - em_bits);
+
+    let mut em = db;
+    em.extend_from_slice(&h);
+    em.push(0xBC);
+    Ok(em)
+}
+
+impl BlindRsa {
+    /// Create a new blind signature context for the given variant
+    #[must_use]
+    pub fn new(variant: BlindRsaVariant) -> BlindRsa {
+        BlindRsa { variant }
+    }
+
+    /// Return the variant in use
+    #[must_use]
+    pub fn variant(&self) -> BlindRsaVariant {
+        self.variant
+    }
+
+    /// Prepare a message for signing
+    ///
+    /// The randomized variants prefix the message with 32 random bytes.
+    /// The prepared message is what is signed, and must be kept for
+    /// `blind`, `unblind` and `verify`.
+    pub fn prepare(&self, msg: &[u8], rng: &mut RandomNumberGenerator) -> Result<Vec<u8>> {
+        if !self.variant.randomized() {
+            return Ok(msg.to_vec());
+        }
+        let mut out = rng.read(PREFIX_LEN)?;
+        out.extend_from_slice(msg);
+        Ok(out)
+    }
+
+    /// Blind a prepared message
+    ///
+    /// Returns the blinded message to send to the signer, and the
+    /// inverse of the blinding factor, which must be kept secret and
+    /// passed to `unblind`.
+    pub fn blind(
+        &self,
+        key: &Pubkey,
+        prepared_msg: &[u8],
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<(Vec<u8>, MPI)> {
+        let (n, e, k_len) = rsa_params(key)?;
+
+        let em = emsa_pss_encode(
+            prepared_msg,
+            n.bit_count()? - 1,
+            self.variant.salt_len(),
+            rng,
+        )?;
+        let m = MPI::new_from_bytes(&em)?;
+
+        let one = MPI::new_from_u32(1)?;
+        if MPI::gcd(&m, &n)? != one {
+            return Err(Error::with_message(
+                ErrorType::InvalidInput,
+                "Encoded message is not invertible modulo n".to_owned(),
+            ));
+        }
+
+        let mut r = MPI::new()?;
+        r.random_range(rng, &one, &n)?;
+        let inverse = MPI::modular_inverse(&r, &n)?;
+        if inverse.is_zero()? {
+            return Err(Error::with_message(
+                ErrorType::InternalError,
+                "Blinding factor is not invertible".to_owned(),
+            ));
+        }
+
+        let x = MPI::powmod(&r, &e, &n)?;
+        let z = MPI::mulmod(&m, &x, &n)?;
+        Ok((z.to_bytes_padded(k_len)?, inverse))
+    }
+
+    /// Sign a blinded message (performed by the signer)
+    ///
+    /// The signature is checked before it is returned, so a faulty
+    /// computation cannot leak the private key.
+    pub fn blind_sign(&self, key: &Privkey, blinded_msg: &[u8]) -> Result<Vec<u8>> {
+        let (n, e, k_len) = rsa_params(&key.pubkey()?)?;
+        let m = os2ip(blinded_msg, k_len, &n)?;
+
+        let mut signer = Signer::raw_unsafe(key)?;
+        let mut rng = RandomNumberGenerator::new_system()?;
+        let s = MPI::new_from_bytes(&signer.sign_message(blinded_msg, &mut rng)?)?;
+
+        if MPI::powmod(&s, &e, &n)? != m {
+            return Err(Error::with_message(
+                ErrorType::InternalError,
+                "Blind signature failed verification".to_owned(),
+            ));
+        }
+
+        s.to_bytes_padded(k_len)
+    }
+
+    /// Unblind a signature returned by `blind_sign`
+    ///
+    /// The result is checked to be a valid RSA-PSS signature of the
+    /// prepared message.
+    pub fn unblind(
+        &self,
+        key: &Pubkey,
+        prepared_msg: &[u8],
+        blind_sig: &[u8],
+        inverse: &MPI,
+    ) -> Result<Vec<u8>> {
+        let (n, _, k_len) = rsa_params(key)?;
+        let z = os2ip(blind_sig, k_len, &n)?;
+        let sig = MPI::mulmod(&z, inverse, &n)?.to_bytes_padded(k_len)?;
+
+        if !self.verify(key, prepared_msg, &sig)? {
+            return Err(Error::with_message(
+                ErrorType::InvalidVerifier,
+                "Unblinded signature is not valid".to_owned(),
+            ));
+        }
+
+        Ok(sig)
+    }
+
+    /// Verify a signature of a prepared message
+    ///
+    /// This is ordinary RSA-PSS verification with SHA-384.
+    pub fn verify(&self, key: &Pubkey, prepared_msg: &[u8], sig: &[u8]) -> Result<bool> {
+        rsa_params(key)?;
+        let mut verifier = Verifier::new(key, self.variant.padding())?;
+        verifier.update(prepared_msg)?;
+        verifier.finish(sig)
+    }
+}
@@ -1,12 +1,19 @@
 use crate::utils::*;
 use botan_sys::*;
 
-#[derive(Debug)]
+use crate::metadata::{lookup, Metadata};
+use crate::probe::AlgorithmKind;
+use crate::symmetric::SymmetricKey;
+
 /// A raw block cipher interface (ie ECB mode)
 ///
 /// Warning: you almost certainly want an AEAD cipher mode instead
+///
+/// Block ciphers cannot be cloned, as the FFI has no way of copying a
+/// key schedule.
 pub struct BlockCipher {
     obj: botan_block_cipher_t,
+    algo_name: String,
     block_size: usize,
     min_keylen: usize,
     max_keylen: usize,
@@ -15,6 +22,30 @@ pub struct BlockCipher {
 
 botan_impl_drop!(BlockCipher, botan_block_cipher_destroy);
 
+impl core::fmt::Debug for BlockCipher {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("BlockCipher")
+            .field("algo_name", &self.algo_name)
+            .field("block_size", &self.block_size)
+            .field("key", &format_args!("[REDACTED]"))
+            .finish()
+    }
+}
+
+// Encrypting and decrypting blocks does not modify the key schedule
+unsafe impl Send for BlockCipher {}
+unsafe impl Sync for BlockCipher {}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for BlockCipher {
+    fn zeroize(&mut self) {
+        let _ = self.clear();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for BlockCipher {}
+
 impl BlockCipher {
     /// Create a new block cipher instance, failing if the cipher is unknown
     ///
@@ -26,29 +57,51 @@ impl BlockCipher {
     /// let no_such_cipher = botan::BlockCipher::new("SuperCipher9000");
     /// assert!(no_such_cipher.is_err());
     /// ```
-    pub fn new(name: &str) -> Result<BlockCipher> {
+    pub fn new<N: AsRef<str>>(name: N) -> Result<BlockCipher> {
+        let name = name.as_ref();
         let obj = botan_init!(botan_block_cipher_init, make_cstr(name)?.as_ptr())?;
 
-        let block_size = {
-            let rc = unsafe { botan_block_cipher_block_size(obj) };
-            if rc < 0 {
-                return Err(Error::from_rc(rc));
-            }
-            rc as usize
-        };
+        let metadata = lookup(AlgorithmKind::BlockCipher, 0, name, || {
+            let block_size = {
+                let rc = unsafe { botan_block_cipher_block_size(obj) };
+                if rc < 0 {
+                    return Err(Error::from_rc(rc));
+                }
+                rc as usize
+            };
 
-        let (min_keylen, max_keylen, mod_keylen) =
-            botan_usize3!(botan_block_cipher_get_keyspec, obj)?;
+            Ok(Metadata {
+                algo_name: call_botan_ffi_returning_string(32, &|out_buf, out_len| unsafe {
+                    botan_block_cipher_name(obj, out_buf as *mut c_char, out_len)
+                })?,
+                block_size,
+                keyspec: botan_usize3!(botan_block_cipher_get_keyspec, obj)?,
+                ..Default::default()
+            })
+        })?;
+
+        let (min_keylen, max_keylen, mod_keylen) = metadata.keyspec;
 
         Ok(BlockCipher {
             obj,
-            block_size,
+            algo_name: metadata.algo_name,
+            block_size: metadata.block_size,
             min_keylen,
             max_keylen,
             mod_keylen,
         })
     }
 
+    /// Create a new AES-128 block cipher
+    pub fn aes128() -> Result<BlockCipher> {
+        BlockCipher::new("AES-128")
+    }
+
+    /// Create a new AES-256 block cipher
+    pub fn aes256() -> Result<BlockCipher> {
+        BlockCipher::new("AES-256")
+    }
+
     /// Return the block size of the cipher, in bytes
     ///
     /// # Examples
@@ -71,9 +124,7 @@ impl BlockCipher {
     /// assert_eq!(cipher.algo_name().unwrap(), "AES-128");
     /// ```
     pub fn algo_name(&self) -> Result<String> {
-        call_botan_ffi_returning_string(32, &|out_buf, out_len| unsafe {
-            botan_block_cipher_name(self.obj, out_buf as *mut c_char, out_len)
-        })
+        Ok(self.algo_name.clone())
     }
 
     /// Return information about the key lengths supported by this object
@@ -81,6 +132,11 @@ impl BlockCipher {
         KeySpec::new(self.min_keylen, self.max_keylen, self.mod_keylen)
     }
 
+    // Adds the algorithm and operation to errors
+    fn context(&self, operation: &'static str) -> impl Fn(Error) -> Error + '_ {
+        move |e| e.in_context(&self.algo_name, operation)
+    }
+
     /// Set the key for the cipher.
     ///
     /// # Errors
@@ -101,6 +157,12 @@ impl BlockCipher {
             key.as_ptr(),
             key.len()
         )
+        .map_err(self.context("set key"))
+    }
+
+    /// Set the key for the cipher
+    pub fn set_symmetric_key(&mut self, key: &SymmetricKey) -> Result<()> {
+        self.set_key(key.as_bytes())
     }
 
     /// Encrypt some blocks of data
@@ -139,7 +201,8 @@ impl BlockCipher {
             return Err(Error::with_message(
                 ErrorType::InvalidInput,
                 "Invalid input size".to_string(),
-            ));
+            )
+            .in_context(&self.algo_name, "encrypt"));
         }
 
         let blocks = buf.len() / self.block_size;
@@ -151,6 +214,7 @@ impl BlockCipher {
             buf.as_mut_ptr(),
             blocks
         )
+        .map_err(self.context("encrypt"))
     }
 
     /// Decrypt some blocks of data
@@ -189,7 +253,8 @@ impl BlockCipher {
             return Err(Error::with_message(
                 ErrorType::InvalidInput,
                 "Invalid input size".to_string(),
-            ));
+            )
+            .in_context(&self.algo_name, "decrypt"));
         }
 
         let blocks = buf.len() / self.block_size;
@@ -201,6 +266,7 @@ impl BlockCipher {
             buf.as_mut_ptr(),
             blocks
         )
+        .map_err(self.context("decrypt"))
     }
 
     /// Clear the key set on the cipher from memory. After this, the
@@ -216,6 +282,6 @@ impl BlockCipher {
     /// assert!(cipher.encrypt_blocks(&vec![0; 16]).is_err());
     /// ```
     pub fn clear(&mut self) -> Result<()> {
-        botan_call!(botan_block_cipher_clear, self.obj)
+        botan_call!(botan_block_cipher_clear, self.obj).map_err(self.context("clear"))
     }
 }
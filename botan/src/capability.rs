@@ -0,0 +1,158 @@
+use crate::utils::*;
+use botan_sys::*;
+
+/// Features which are only present in some versions of Botan 3.x
+///
+/// The `botan3` feature makes these available at compile time, but the
+/// library linked at runtime may be an older 3.x release. The functions
+/// which use them check first, and fail with
+/// `ErrorType::RequiresNewerVersion` when the library is too old, rather
+/// than calling into an FFI which does not have them.
+///
+/// # Examples
+///
+/// ```
+/// for cap in botan::Capability::all() {
+///     let (major, minor) = cap.required_version();
+///     println!("{} (Botan {}.{}): {}", cap.name(), major, minor, cap.is_supported());
+/// }
+/// ```
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+pub enum Capability {
+    /// HSS/LMS keys
+    HssLms,
+    /// FrodoKEM keys
+    FrodoKem,
+    /// Classic McEliece keys
+    ClassicMcEliece,
+    /// ML-KEM (FIPS 203) keys
+    MlKem,
+    /// ML-DSA (FIPS 204) keys
+    MlDsa,
+    /// SLH-DSA (FIPS 205) keys
+    SlhDsa,
+    /// Access to a TPM 2.0
+    Tpm2,
+    /// The raw encoding of keys, with `Privkey::raw_bytes` and
+    /// `Pubkey::raw_bytes`
+    RawKeyEncoding,
+    /// `EcGroup`, and creating keys over one
+    EcGroup,
+    /// Querying the state of stateful keys, with `Privkey::is_stateful`
+    /// and `Privkey::remaining_operations`
+    StatefulKeys,
+}
+
+const ALL_CAPABILITIES: [Capability; 10] = [
+    Capability::HssLms,
+    Capability::FrodoKem,
+    Capability::ClassicMcEliece,
+    Capability::MlKem,
+    Capability::MlDsa,
+    Capability::SlhDsa,
+    Capability::Tpm2,
+    Capability::RawKeyEncoding,
+    Capability::EcGroup,
+    Capability::StatefulKeys,
+];
+
+impl Capability {
+    /// Return all capabilities, in order of the version which added them
+    pub fn all() -> impl Iterator<Item = Capability> {
+        ALL_CAPABILITIES.iter().cloned()
+    }
+
+    /// Return the capability needed to create keys of the named
+    /// algorithm, if it is one which only newer versions support
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(botan::Capability::for_algorithm("ML-KEM"), Some(botan::Capability::MlKem));
+    /// assert_eq!(botan::Capability::for_algorithm("RSA"), None);
+    /// ```
+    #[must_use]
+    pub fn for_algorithm(algo: &str) -> Option<Capability> {
+        match algo {
+            "FrodoKEM" => Some(Capability::FrodoKem),
+            "ClassicMcEliece" => Some(Capability::ClassicMcEliece),
+            "HSS-LMS" => Some(Capability::HssLms),
+            "ML-KEM" => Some(Capability::MlKem),
+            "ML-DSA" => Some(Capability::MlDsa),
+            "SLH-DSA" => Some(Capability::SlhDsa),
+            _ => None,
+        }
+    }
+
+    /// Return a short description of the capability
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Capability::HssLms => "HSS/LMS",
+            Capability::FrodoKem => "FrodoKEM",
+            Capability::ClassicMcEliece => "Classic McEliece",
+            Capability::MlKem => "ML-KEM",
+            Capability::MlDsa => "ML-DSA",
+            Capability::SlhDsa => "SLH-DSA",
+            Capability::Tpm2 => "TPM 2.0",
+            Capability::RawKeyEncoding => "raw key encodings",
+            Capability::EcGroup => "elliptic curve groups",
+            Capability::StatefulKeys => "stateful key queries",
+        }
+    }
+
+    /// Return the first version of Botan, as (major, minor), which
+    /// supports this capability
+    #[must_use]
+    pub fn required_version(&self) -> (u32, u32) {
+        match self {
+            Capability::HssLms => (3, 5),
+            Capability::FrodoKem => (3, 6),
+            Capability::ClassicMcEliece => (3, 6),
+            Capability::MlKem => (3, 6),
+            Capability::MlDsa => (3, 6),
+            Capability::SlhDsa => (3, 6),
+            Capability::Tpm2 => (3, 6),
+            Capability::RawKeyEncoding => (3, 6),
+            Capability::EcGroup => (3, 8),
+            Capability::StatefulKeys => (3, 8),
+        }
+    }
+
+    /// Return true if the library linked at runtime is recent enough
+    ///
+    /// Whether an algorithm is available also depends on the modules
+    /// the library was built with; see `is_available`.
+    #[must_use]
+    pub fn is_supported(&self) -> bool {
+        let (major, minor) = self.required_version();
+        let (linked_major, linked_minor) = linked_version();
+        linked_major > major || (linked_major == major && linked_minor >= minor)
+    }
+
+    /// Fail with `ErrorType::RequiresNewerVersion` unless the library
+    /// linked at runtime supports this capability
+    pub fn require(&self) -> Result<()> {
+        if self.is_supported() {
+            return Ok(());
+        }
+
+        let (major, minor) = self.required_version();
+        let (linked_major, linked_minor) = linked_version();
+        Err(Error::with_message(
+            ErrorType::RequiresNewerVersion,
+            format!(
+                "Support for {} requires Botan {}.{} but {}.{} is in use",
+                self.name(),
+                major,
+                minor,
+                linked_major,
+                linked_minor
+            ),
+        ))
+    }
+}
+
+fn linked_version() -> (u32, u32) {
+    unsafe { (botan_version_major(), botan_version_minor()) }
+}
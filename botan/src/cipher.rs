@@ -1,20 +1,36 @@
 use crate::utils::*;
 use botan_sys::*;
 
-#[derive(Debug)]
+use crate::metadata::{lookup, Metadata};
+use crate::probe::AlgorithmKind;
+use crate::rng::RandomNumberGenerator;
+use crate::symmetric::{Nonce, SymmetricKey};
+
 /// A symmetric cipher
+///
+/// This type does not implement `Clone`, since the library provides no
+/// way of copying a keyed cipher object. To avoid repeatedly creating
+/// objects, see `Pool`.
+///
+/// For AEADs, `AeadEncryptor` and `AeadDecryptor` check the direction
+/// at compile time instead.
 pub struct Cipher {
     obj: botan_cipher_t,
+    algo_name: String,
     direction: CipherDirection,
     tag_length: usize,
     update_granularity: usize,
+    ideal_update_granularity: usize,
     default_nonce_length: usize,
     min_keylen: usize,
     max_keylen: usize,
     mod_keylen: usize,
+    late_associated_data: bool,
+    associated_data: Vec<u8>,
+    in_message: bool,
 }
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
 /// Which direction the cipher processes in
 pub enum CipherDirection {
     /// Encrypt
@@ -23,16 +39,188 @@ pub enum CipherDirection {
     Decrypt,
 }
 
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+/// Unauthenticated block cipher modes which take a padding scheme
+pub enum PaddedMode {
+    /// Cipher block chaining
+    Cbc,
+    /// Electronic codebook; this is almost never what you want
+    ///
+    /// Botan 3 removed ECB mode, so with the `botan3` feature it fails
+    /// with `ErrorType::NotImplemented`; `BlockCipher` can be used for
+    /// raw block operations instead.
+    Ecb,
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+/// Padding schemes for `PaddedMode`
+pub enum CipherPadding {
+    /// PKCS #7 padding
+    Pkcs7,
+    /// ANSI X9.23 padding
+    X923,
+    /// A one bit followed by zero bits (ISO/IEC 7816-4)
+    OneAndZeros,
+    /// ESP padding (RFC 4303)
+    Esp,
+    /// Ciphertext stealing (CBC only); the message must be at least one
+    /// block long
+    CiphertextStealing,
+    /// No padding; the message must be a multiple of the block size
+    NoPadding,
+}
+
+impl PaddedMode {
+    fn botan_name(self) -> &'static str {
+        match self {
+            PaddedMode::Cbc => "CBC",
+            PaddedMode::Ecb => "ECB",
+        }
+    }
+}
+
+impl CipherPadding {
+    fn botan_name(self) -> &'static str {
+        match self {
+            CipherPadding::Pkcs7 => "PKCS7",
+            CipherPadding::X923 => "X9.23",
+            CipherPadding::OneAndZeros => "OneAndZeros",
+            CipherPadding::Esp => "ESP",
+            CipherPadding::CiphertextStealing => "CTS",
+            CipherPadding::NoPadding => "NoPadding",
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+/// Block cipher modes, for use with `CipherBuilder`
+pub enum CipherMode {
+    /// Galois/counter mode (AEAD)
+    Gcm,
+    /// Offset codebook mode (AEAD)
+    Ocb,
+    /// EAX mode (AEAD)
+    Eax,
+    /// Counter with CBC-MAC (AEAD)
+    Ccm,
+    /// Synthetic IV mode (AEAD, nonce misuse resistant)
+    Siv,
+    /// Cipher block chaining, taking a padding scheme
+    Cbc,
+    /// Electronic codebook, taking a padding scheme; this is almost
+    /// never what you want
+    ///
+    /// Botan 3 removed ECB mode, so with the `botan3` feature building
+    /// it fails with `ErrorType::NotImplemented`.
+    Ecb,
+    /// Cipher feedback
+    Cfb,
+    /// Counter mode
+    Ctr,
+}
+
+impl CipherMode {
+    fn botan_name(self) -> &'static str {
+        match self {
+            CipherMode::Gcm => "GCM",
+            CipherMode::Ocb => "OCB",
+            CipherMode::Eax => "EAX",
+            CipherMode::Ccm => "CCM",
+            CipherMode::Siv => "SIV",
+            CipherMode::Cbc => "CBC",
+            CipherMode::Ecb => "ECB",
+            CipherMode::Cfb => "CFB",
+            CipherMode::Ctr => "CTR",
+        }
+    }
+
+    fn padded_mode(self) -> Option<PaddedMode> {
+        match self {
+            CipherMode::Cbc => Some(PaddedMode::Cbc),
+            CipherMode::Ecb => Some(PaddedMode::Ecb),
+            _ => None,
+        }
+    }
+
+    fn takes_tag_length(self) -> bool {
+        matches!(
+            self,
+            CipherMode::Gcm | CipherMode::Ocb | CipherMode::Eax | CipherMode::Ccm
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+/// Creates a `Cipher` from separately specified parameters
+///
+/// Created by `Cipher::builder`. The block cipher, mode and direction
+/// must be set; the tag length and padding are optional, and the
+/// library defaults are used if they are not given.
+///
+/// # Examples
+/// ```
+/// use botan::{BlockCipherAlgo, Cipher, CipherDirection, CipherMode};
+/// let cipher = Cipher::builder()
+///     .block_cipher(BlockCipherAlgo::Aes256)
+///     .mode(CipherMode::Gcm)
+///     .tag_len(12)
+///     .direction(CipherDirection::Encrypt)
+///     .build()
+///     .unwrap();
+/// assert_eq!(cipher.algo_name().unwrap(), "AES-256/GCM(12)");
+/// assert_eq!(cipher.tag_length(), 12);
+/// ```
+pub struct CipherBuilder {
+    block_cipher: Option<String>,
+    mode: Option<CipherMode>,
+    tag_len: Option<usize>,
+    padding: Option<CipherPadding>,
+    direction: Option<CipherDirection>,
+}
+
 botan_impl_drop!(Cipher, botan_cipher_destroy);
 
+impl core::fmt::Debug for Cipher {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Cipher")
+            .field("algo_name", &self.algo_name)
+            .field("direction", &self.direction)
+            .field("tag_length", &self.tag_length)
+            .field("default_nonce_length", &self.default_nonce_length)
+            .field("key", &format_args!("[REDACTED]"))
+            .finish()
+    }
+}
+
+unsafe impl Send for Cipher {}
+unsafe impl Sync for Cipher {}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Cipher {
+    fn zeroize(&mut self) {
+        let _ = self.clear();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for Cipher {}
+
 impl Cipher {
+    /// Return a builder for creating a cipher from separately specified
+    /// parameters, rather than a single name
+    #[must_use]
+    pub fn builder() -> CipherBuilder {
+        CipherBuilder::default()
+    }
+
     /// Create a new cipher object in the specified direction
     ///
     /// # Examples
     /// ```
     /// let aes_gcm = botan::Cipher::new("AES-128/GCM", botan::CipherDirection::Encrypt).unwrap();
     /// ```
-    pub fn new(name: &str, direction: CipherDirection) -> Result<Cipher> {
+    pub fn new<N: AsRef<str>>(name: N, direction: CipherDirection) -> Result<Cipher> {
+        let name = name.as_ref();
         let flag = if direction == CipherDirection::Encrypt {
             0u32
         } else {
@@ -41,24 +229,180 @@ impl Cipher {
 
         let obj = botan_init!(botan_cipher_init, make_cstr(name)?.as_ptr(), flag)?;
 
-        let tag_length = botan_usize!(botan_cipher_get_tag_length, obj)?;
-        let update_granularity = botan_usize!(botan_cipher_get_update_granularity, obj)?;
-        let default_nonce_length = botan_usize!(botan_cipher_get_default_nonce_length, obj)?;
+        let metadata = lookup(AlgorithmKind::Cipher, flag, name, || {
+            let update_granularity = botan_usize!(botan_cipher_get_update_granularity, obj)?;
+
+            #[cfg(feature = "botan3")]
+            let ideal_update_granularity =
+                botan_usize!(botan_cipher_get_ideal_update_granularity, obj)?;
+
+            #[cfg(not(feature = "botan3"))]
+            let ideal_update_granularity = update_granularity;
+
+            Ok(Metadata {
+                algo_name: call_botan_ffi_returning_string(32, &|out_buf, out_len| unsafe {
+                    botan_cipher_name(obj, out_buf as *mut c_char, out_len)
+                })?,
+                tag_length: botan_usize!(botan_cipher_get_tag_length, obj)?,
+                update_granularity,
+                ideal_update_granularity,
+                default_nonce_length: botan_usize!(botan_cipher_get_default_nonce_length, obj)?,
+                keyspec: botan_usize3!(botan_cipher_get_keyspec, obj)?,
+                ..Default::default()
+            })
+        })?;
 
-        let (min_keylen, max_keylen, mod_keylen) = botan_usize3!(botan_cipher_get_keyspec, obj)?;
+        let (min_keylen, max_keylen, mod_keylen) = metadata.keyspec;
+        let late_associated_data = allows_late_associated_data(&metadata.algo_name);
 
         Ok(Cipher {
             obj,
+            algo_name: metadata.algo_name,
             direction,
-            tag_length,
-            update_granularity,
-            default_nonce_length,
+            tag_length: metadata.tag_length,
+            update_granularity: metadata.update_granularity,
+            ideal_update_granularity: metadata.ideal_update_granularity,
+            default_nonce_length: metadata.default_nonce_length,
             min_keylen,
             max_keylen,
             mod_keylen,
+            late_associated_data,
+            associated_data: Vec::new(),
+            in_message: false,
         })
     }
 
+    /// Create a new cipher object using an unauthenticated block cipher
+    /// mode with the specified padding
+    ///
+    /// This avoids having to know the spelling of mode and padding
+    /// names which `new` expects.
+    ///
+    /// # Errors
+    /// Fails if the combination of mode and padding is not valid, or if
+    /// `block_cipher` is not a known block cipher. ECB mode fails with
+    /// `ErrorType::NotImplemented` with Botan 3.
+    ///
+    /// # Examples
+    /// ```
+    /// use botan::{Cipher, CipherDirection, CipherPadding, PaddedMode};
+    /// let cbc = Cipher::new_padded("AES-128", PaddedMode::Cbc, CipherPadding::Pkcs7, CipherDirection::Encrypt).unwrap();
+    /// assert_eq!(cbc.algo_name().unwrap(), "AES-128/CBC/PKCS7");
+    /// assert!(Cipher::new_padded("AES-128", PaddedMode::Ecb, CipherPadding::CiphertextStealing, CipherDirection::Encrypt).is_err());
+    /// ```
+    pub fn new_padded<N: AsRef<str>>(
+        block_cipher: N,
+        mode: PaddedMode,
+        padding: CipherPadding,
+        direction: CipherDirection,
+    ) -> Result<Cipher> {
+        let block_cipher = block_cipher.as_ref();
+        if block_cipher.contains('/') {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "Expected the name of a block cipher, not a cipher mode".to_owned(),
+            ));
+        }
+
+        if mode == PaddedMode::Ecb && padding == CipherPadding::CiphertextStealing {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "Ciphertext stealing can only be used with CBC mode".to_owned(),
+            ));
+        }
+
+        #[cfg(feature = "botan3")]
+        {
+            if mode == PaddedMode::Ecb {
+                return Err(Error::with_message(
+                    ErrorType::NotImplemented,
+                    "ECB mode is not supported by Botan 3".to_owned(),
+                ));
+            }
+        }
+
+        let name = format!(
+            "{}/{}/{}",
+            block_cipher,
+            mode.botan_name(),
+            padding.botan_name()
+        );
+        Cipher::new(&name, direction)
+    }
+
+    /// Create a new cipher using AES-256 in GCM mode
+    ///
+    /// # Examples
+    /// ```
+    /// let gcm = botan::Cipher::aes256_gcm(botan::CipherDirection::Encrypt).unwrap();
+    /// assert_eq!(gcm.tag_length(), 16);
+    /// ```
+    pub fn aes256_gcm(direction: CipherDirection) -> Result<Cipher> {
+        Cipher::new("AES-256/GCM", direction)
+    }
+
+    /// Create a new cipher using AES-128 in GCM mode
+    pub fn aes128_gcm(direction: CipherDirection) -> Result<Cipher> {
+        Cipher::new("AES-128/GCM", direction)
+    }
+
+    /// Create a new cipher using AES-256 in OCB mode
+    pub fn aes256_ocb(direction: CipherDirection) -> Result<Cipher> {
+        Cipher::new("AES-256/OCB", direction)
+    }
+
+    /// Create a new cipher using AES-256 in SIV mode (RFC 5297)
+    ///
+    /// SIV is nonce misuse resistant: repeating a nonce only reveals
+    /// whether the same message was encrypted twice. The nonce may also
+    /// be empty, giving deterministic encryption. It takes a 64 byte key.
+    pub fn aes256_siv(direction: CipherDirection) -> Result<Cipher> {
+        Cipher::new("AES-256/SIV", direction)
+    }
+
+    /// Create a new cipher using AES-128 in SIV mode (RFC 5297), which
+    /// takes a 32 byte key
+    pub fn aes128_siv(direction: CipherDirection) -> Result<Cipher> {
+        Cipher::new("AES-128/SIV", direction)
+    }
+
+    /// Create a new cipher using AES-256 in GCM-SIV mode (RFC 8452)
+    ///
+    /// GCM-SIV is nonce misuse resistant like SIV, but faster, and takes
+    /// a 12 byte nonce.
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::NotImplemented` if the library was built
+    /// without GCM-SIV
+    pub fn aes256_gcm_siv(direction: CipherDirection) -> Result<Cipher> {
+        Cipher::gcm_siv("AES-256/GCM-SIV", direction)
+    }
+
+    /// Create a new cipher using AES-128 in GCM-SIV mode (RFC 8452)
+    ///
+    /// See `aes256_gcm_siv`.
+    pub fn aes128_gcm_siv(direction: CipherDirection) -> Result<Cipher> {
+        Cipher::gcm_siv("AES-128/GCM-SIV", direction)
+    }
+
+    fn gcm_siv(name: &str, direction: CipherDirection) -> Result<Cipher> {
+        Cipher::new(name, direction).map_err(|e| {
+            if e.error_type() == ErrorType::NotImplemented {
+                Error::with_message(
+                    ErrorType::NotImplemented,
+                    format!("{} is not supported by this build of the library", name),
+                )
+            } else {
+                e
+            }
+        })
+    }
+
+    /// Create a new cipher using ChaCha20Poly1305
+    pub fn chacha20_poly1305(direction: CipherDirection) -> Result<Cipher> {
+        Cipher::new("ChaCha20Poly1305", direction)
+    }
+
     /// Return the name of this algorithm which may or may not exactly
     /// match what was provided to new()
     ///
@@ -69,9 +413,7 @@ impl Cipher {
     /// assert_eq!(cipher.algo_name().unwrap(), "AES-128/GCM(16)");
     /// ```
     pub fn algo_name(&self) -> Result<String> {
-        call_botan_ffi_returning_string(32, &|out_buf, out_len| unsafe {
-            botan_cipher_name(self.obj, out_buf as *mut c_char, out_len)
-        })
+        Ok(self.algo_name.clone())
     }
 
     /// Return the direction this cipher object is operating in
@@ -112,11 +454,30 @@ impl Cipher {
         self.tag_length
     }
 
-    /// update_granularity
+    /// Return the update granularity of the cipher, in bytes
+    ///
+    /// Input passed to `update` must be a multiple of this length.
     pub fn update_granularity(&self) -> usize {
         self.update_granularity
     }
 
+    /// Return the preferred update length of the cipher, in bytes
+    ///
+    /// This is a multiple of `update_granularity()`; passing input in
+    /// pieces of (a multiple of) this size allows implementations which
+    /// process many blocks in parallel to run at full speed. With Botan
+    /// 2.x this is the same as the update granularity.
+    ///
+    /// # Examples
+    /// ```
+    /// let aes_gcm = botan::Cipher::new("AES-128/GCM", botan::CipherDirection::Encrypt).unwrap();
+    /// assert_eq!(aes_gcm.ideal_update_granularity() % aes_gcm.update_granularity(), 0);
+    /// ```
+    #[must_use]
+    pub fn ideal_update_granularity(&self) -> usize {
+        self.ideal_update_granularity
+    }
+
     /// Return the default nonce length for the cipher. Some ciphers only
     /// support a single nonce size. Others support variable sizes, but some
     /// particular size (typically 96 bits) is handled particularly efficiently.
@@ -136,6 +497,51 @@ impl Cipher {
         KeySpec::new(self.min_keylen, self.max_keylen, self.mod_keylen)
     }
 
+    /// Generate a random key of a valid length for this cipher
+    ///
+    /// See `KeySpec::generate_key`.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let mut aes_gcm = botan::Cipher::new("AES-256/GCM", botan::CipherDirection::Encrypt).unwrap();
+    /// let key = aes_gcm.generate_key(&mut rng).unwrap();
+    /// assert_eq!(key.len(), 32);
+    /// aes_gcm.set_key(&key).unwrap();
+    /// ```
+    pub fn generate_key(&self, rng: &mut RandomNumberGenerator) -> Result<Vec<u8>> {
+        self.key_spec()?.generate_key(rng)
+    }
+
+    /// Generate a random nonce of the default length for this cipher
+    ///
+    /// Most AEADs default to a 12 byte nonce, so a single key should
+    /// not be used with more than about 2^32 random nonces.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let aes_gcm = botan::Cipher::new("AES-256/GCM", botan::CipherDirection::Encrypt).unwrap();
+    /// let nonce = aes_gcm.generate_nonce(&mut rng).unwrap();
+    /// assert_eq!(nonce.len(), aes_gcm.default_nonce_length());
+    /// ```
+    pub fn generate_nonce(&self, rng: &mut RandomNumberGenerator) -> Result<Vec<u8>> {
+        rng.read(self.default_nonce_length)
+    }
+
+    // Adds the algorithm and operation to errors, so that failures can
+    // be told apart in applications using several ciphers
+    fn context(&self, operation: &'static str) -> impl Fn(Error) -> Error + '_ {
+        move |e| e.in_context(&self.algo_name, operation)
+    }
+
+    fn direction_name(&self) -> &'static str {
+        match self.direction {
+            CipherDirection::Encrypt => "encrypt",
+            CipherDirection::Decrypt => "decrypt",
+        }
+    }
+
     /// Set the key for the cipher
     ///
     /// # Examples
@@ -144,13 +550,26 @@ impl Cipher {
     /// aes_gcm.set_key(&vec![0; 16]).unwrap();
     /// ```
     pub fn set_key(&mut self, key: &[u8]) -> Result<()> {
-        botan_call!(botan_cipher_set_key, self.obj, key.as_ptr(), key.len())?;
-        Ok(())
+        botan_call!(botan_cipher_set_key, self.obj, key.as_ptr(), key.len())
+            .map_err(self.context("set key"))
+    }
+
+    /// Set the key for the cipher
+    pub fn set_symmetric_key(&mut self, key: &SymmetricKey) -> Result<()> {
+        self.set_key(key.as_bytes())
     }
 
     /// Set the associated data for the cipher. This only works for AEAD modes.
     /// The key must already be set to set the AD.
     ///
+    /// This replaces any associated data set earlier. The associated data
+    /// is kept for following messages until it is set again.
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::InvalidObjectState` if a message was started
+    /// and the mode requires the associated data before the message (see
+    /// `supports_late_associated_data`)
+    ///
     /// # Examples
     /// ```
     /// let mut aes_gcm = botan::Cipher::new("AES-128/GCM", botan::CipherDirection::Encrypt).unwrap();
@@ -158,15 +577,119 @@ impl Cipher {
     /// aes_gcm.set_associated_data(&[1,2,3]).unwrap();
     /// ```
     pub fn set_associated_data(&mut self, ad: &[u8]) -> Result<()> {
+        self._set_associated_data(ad.to_vec())
+            .map_err(self.context("set associated data"))
+    }
+
+    /// Append to the associated data of the current message
+    ///
+    /// The associated data is the concatenation of all segments added
+    /// since the last message was finished (or since `set_associated_data`),
+    /// so it can be computed incrementally, for example from protocol
+    /// headers as they are parsed. The key must already be set.
+    ///
+    /// With SIV, OCB and CCM the associated data may also be added
+    /// after `start`, as long as it is before `finish`. This is not the
+    /// case for the other AEADs, which use it when the message starts.
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::InvalidObjectState` if a message was started
+    /// and the mode requires the associated data before the message
+    ///
+    /// # Examples
+    /// ```
+    /// let mut siv = botan::Cipher::aes256_siv(botan::CipherDirection::Encrypt).unwrap();
+    /// siv.set_key(&[0; 64]).unwrap();
+    /// siv.start(&[0; 16]).unwrap();
+    /// siv.add_associated_data(b"version 1;").unwrap();
+    /// siv.add_associated_data(b"length 5").unwrap();
+    /// let ctext = siv.finish(b"hello").unwrap();
+    ///
+    /// let mut gcm = botan::Cipher::aes128_gcm(botan::CipherDirection::Encrypt).unwrap();
+    /// gcm.set_key(&[0; 16]).unwrap();
+    /// gcm.add_associated_data(b"version 1;").unwrap();
+    /// gcm.start(&[0; 12]).unwrap();
+    /// assert!(gcm.add_associated_data(b"length 5").is_err());
+    /// ```
+    pub fn add_associated_data(&mut self, ad: &[u8]) -> Result<()> {
+        let mut combined = Vec::with_capacity(self.associated_data.len() + ad.len());
+        combined.extend_from_slice(&self.associated_data);
+        combined.extend_from_slice(ad);
+        self._set_associated_data(combined)
+            .map_err(self.context("add associated data"))
+    }
+
+    fn _set_associated_data(&mut self, ad: Vec<u8>) -> Result<()> {
+        if self.in_message && !self.late_associated_data {
+            return Err(Error::with_message(
+                ErrorType::InvalidObjectState,
+                format!(
+                    "{} requires the associated data to be set before the message is started",
+                    self.algo_name
+                ),
+            ));
+        }
+
         botan_call!(
             botan_cipher_set_associated_data,
             self.obj,
             ad.as_ptr(),
             ad.len()
         )?;
+        self.associated_data = ad;
         Ok(())
     }
 
+    /// Return true if associated data can be set after `start`, while
+    /// a message is being processed
+    ///
+    /// This is the case for SIV, OCB and CCM, which only use the
+    /// associated data when the message is finished.
+    ///
+    /// # Examples
+    /// ```
+    /// let siv = botan::Cipher::aes256_siv(botan::CipherDirection::Encrypt).unwrap();
+    /// assert!(siv.supports_late_associated_data());
+    /// let gcm = botan::Cipher::aes256_gcm(botan::CipherDirection::Encrypt).unwrap();
+    /// assert!(!gcm.supports_late_associated_data());
+    /// ```
+    #[must_use]
+    pub fn supports_late_associated_data(&self) -> bool {
+        self.late_associated_data
+    }
+
+    /// Return true if this is a nonce misuse resistant AEAD (SIV or
+    /// GCM-SIV), for which repeating a nonce only reveals whether the
+    /// same message was encrypted twice, rather than breaking security
+    ///
+    /// # Examples
+    /// ```
+    /// let siv = botan::Cipher::aes256_siv(botan::CipherDirection::Encrypt).unwrap();
+    /// assert!(siv.is_misuse_resistant());
+    /// let gcm = botan::Cipher::aes256_gcm(botan::CipherDirection::Encrypt).unwrap();
+    /// assert!(!gcm.is_misuse_resistant());
+    /// ```
+    #[must_use]
+    pub fn is_misuse_resistant(&self) -> bool {
+        let mode = cipher_mode(&self.algo_name);
+        mode.starts_with("SIV") || mode.starts_with("GCM-SIV")
+    }
+
+    // Called when a message has been processed, after which added
+    // associated data begins a new segment list
+    fn end_message(&mut self) {
+        self.in_message = false;
+        self.associated_data.clear();
+    }
+
+    /// Encrypt or decrypt a message with a nonce whose length was
+    /// checked when it was created
+    ///
+    /// Otherwise the same as `process`.
+    pub fn process_with_nonce(&mut self, nonce: &Nonce, msg: &[u8]) -> Result<Vec<u8>> {
+        self.process(nonce.as_bytes(), msg)
+    }
+
     /// Encrypt or decrypt a message with the provided nonce. The key must
     /// already have been set.
     ///
@@ -179,6 +702,12 @@ impl Cipher {
     /// let ctext = aes_gcm.process(&nonce, &msg);
     /// ```
     pub fn process(&mut self, nonce: &[u8], msg: &[u8]) -> Result<Vec<u8>> {
+        let result = self._process(nonce, msg);
+        self.end_message();
+        result.map_err(self.context(self.direction_name()))
+    }
+
+    fn _process(&mut self, nonce: &[u8], msg: &[u8]) -> Result<Vec<u8>> {
         botan_call!(botan_cipher_start, self.obj, nonce.as_ptr(), nonce.len())?;
 
         let flags = 1u32; // only supporting one-shot processing here
@@ -208,16 +737,119 @@ impl Cipher {
         Ok(output)
     }
 
-    /// start processing a message
+    /// Encrypt or decrypt a message in place with the provided nonce
+    ///
+    /// When encrypting, `buf` is extended to hold the authentication tag
+    /// (or padding); when decrypting it is shortened to the length of
+    /// the plaintext. Unlike `process`, the message is not copied into a
+    /// new vector. If an error occurs `buf` is returned to its original
+    /// length, but its contents are unspecified.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut aes_gcm = botan::Cipher::new("AES-128/GCM", botan::CipherDirection::Encrypt).unwrap();
+    /// aes_gcm.set_key(&vec![0; 16]).unwrap();
+    /// let nonce = vec![0; aes_gcm.default_nonce_length()];
+    /// let mut buf = b"hello world".to_vec();
+    /// aes_gcm.process_in_place(&nonce, &mut buf).unwrap();
+    /// assert_eq!(buf.len(), 11 + aes_gcm.tag_length());
+    /// ```
+    pub fn process_in_place(&mut self, nonce: &[u8], buf: &mut Vec<u8>) -> Result<()> {
+        let result = self._process_in_place(nonce, buf);
+        self.end_message();
+        result.map_err(self.context(self.direction_name()))
+    }
+
+    fn _process_in_place(&mut self, nonce: &[u8], buf: &mut Vec<u8>) -> Result<()> {
+        botan_call!(botan_cipher_start, self.obj, nonce.as_ptr(), nonce.len())?;
+
+        let input_len = buf.len();
+
+        if self.direction == CipherDirection::Encrypt {
+            buf.resize(input_len + self.final_expansion(), 0);
+        }
+
+        let mut output_written = 0;
+        let mut input_consumed = 0;
+
+        // The library copies the input before writing any output, so the
+        // same buffer can be used for both
+        let ptr = buf.as_mut_ptr();
+        let rc = unsafe {
+            botan_cipher_update(
+                self.obj,
+                1u32,
+                ptr,
+                buf.len(),
+                &mut output_written,
+                ptr as *const u8,
+                input_len,
+                &mut input_consumed,
+            )
+        };
+
+        if rc != 0 {
+            buf.truncate(input_len);
+            return Err(Error::from_rc_in(rc, "botan_cipher_update"));
+        }
+
+        assert!(input_consumed == input_len);
+        assert!(output_written <= buf.len());
+
+        buf.truncate(output_written);
+        Ok(())
+    }
+
+    /// Start processing a new message with the provided nonce
+    ///
+    /// The key is kept, so a single keyed object can process any number
+    /// of messages, each begun by calling `start` with a fresh nonce.
+    /// Any message which was in progress is abandoned.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut aes_gcm = botan::Cipher::new("AES-128/GCM", botan::CipherDirection::Encrypt).unwrap();
+    /// aes_gcm.set_key(&vec![0; 16]).unwrap();
+    /// aes_gcm.start(&[1; 12]).unwrap();
+    /// let ctext1 = aes_gcm.finish(b"first message").unwrap();
+    /// aes_gcm.start(&[2; 12]).unwrap();
+    /// let ctext2 = aes_gcm.finish(b"second message").unwrap();
+    /// ```
     pub fn start(&mut self, nonce: &[u8]) -> Result<()> {
         botan_call!(botan_cipher_start, self.obj, nonce.as_ptr(), nonce.len())
+            .map_err(self.context("start"))?;
+        self.in_message = true;
+        Ok(())
+    }
+
+    /// Start processing a message with a nonce whose length was checked
+    /// when it was created
+    pub fn start_with_nonce(&mut self, nonce: &Nonce) -> Result<()> {
+        self.start(nonce.as_bytes())
+    }
+
+    /// Discard the state of the message being processed, keeping the key
+    ///
+    /// `start` must be called before processing another message. Use
+    /// `clear` to also remove the key.
+    pub fn reset(&mut self) -> Result<()> {
+        self.end_message();
+        botan_call!(botan_cipher_reset, self.obj).map_err(self.context("reset"))
     }
 
     /// Encrypt or decrypt a message with the provided nonce. The key must
     /// incremental update
     fn _update(&mut self, msg: &[u8], end: bool) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        self._update_append(msg, end, &mut output)?;
+        Ok(output)
+    }
+
+    fn _update_append(&mut self, msg: &[u8], end: bool, output: &mut Vec<u8>) -> Result<()> {
         let flags = if end { 1 } else { 0 };
-        let mut output = vec![0; msg.len() + if end { self.tag_length() } else { 0 }];
+        let offset = output.len();
+        let expansion = if end { self.final_expansion() } else { 0 };
+        output.resize(offset + msg.len() + expansion, 0);
         let mut output_written = 0;
         let mut input_consumed = 0;
 
@@ -225,8 +857,8 @@ impl Cipher {
             botan_cipher_update,
             self.obj,
             flags,
-            output.as_mut_ptr(),
-            output.len(),
+            output[offset..].as_mut_ptr(),
+            output.len() - offset,
             &mut output_written,
             msg.as_ptr(),
             msg.len(),
@@ -234,25 +866,280 @@ impl Cipher {
         )?;
 
         assert!(input_consumed == msg.len());
-        assert!(output_written <= output.len());
+        assert!(offset + output_written <= output.len());
 
-        output.resize(output_written, 0);
+        output.truncate(offset + output_written);
 
-        Ok(output)
+        Ok(())
+    }
+
+    // How much longer than its input the final output may be: the tag for
+    // an AEAD, otherwise padding of up to a block
+    fn final_expansion(&self) -> usize {
+        if self.tag_length > 0 {
+            self.tag_length
+        } else {
+            self.update_granularity
+        }
+    }
+
+    fn _flush_pending(&mut self, pending: &mut Vec<u8>, output: &mut Vec<u8>) -> Result<()> {
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let result = self._update_append(pending, false, output);
+        crate::memutils::scrub_mem(&mut pending[..]);
+        pending.clear();
+        result
+    }
+
+    fn _update_vectored<B: core::ops::Deref<Target = [u8]>>(
+        &mut self,
+        msg: &[B],
+        end: bool,
+    ) -> Result<Vec<u8>> {
+        let total: usize = msg.iter().map(|m| m.len()).sum();
+        let granularity = core::cmp::max(self.update_granularity, 1);
+
+        // When finishing, the final call must receive the tag (when
+        // decrypting) and more than a block, since some modes need that
+        // much input to finish, such as CBC with padding or CTS
+        let reserved = match (end, self.direction) {
+            (false, _) => 0,
+            (true, CipherDirection::Encrypt) => granularity + 1,
+            (true, CipherDirection::Decrypt) => self.tag_length + granularity + 1,
+        };
+        let aligned = (total.saturating_sub(reserved) / granularity) * granularity;
+
+        if !end && aligned != total {
+            return Err(Error::with_message(
+                ErrorType::InvalidInput,
+                "Input length is not a multiple of the update granularity".to_owned(),
+            ));
+        }
+
+        // Short slices are collected into a buffer of the ideal size
+        // before being processed, rather than making many small calls
+        let ideal = core::cmp::max(self.ideal_update_granularity, granularity);
+
+        let mut output = Vec::with_capacity(total + self.tag_length);
+        let mut pending: Vec<u8> = Vec::with_capacity(ideal);
+        let mut tail: Vec<u8> = Vec::new();
+        let mut position = 0;
+
+        for m in msg {
+            let (mut head, rest) =
+                m.split_at(core::cmp::min(m.len(), aligned.saturating_sub(position)));
+            position += m.len();
+            tail.extend_from_slice(rest);
+
+            while !head.is_empty() {
+                if pending.is_empty() && head.len() >= ideal {
+                    let whole_blocks = (head.len() / granularity) * granularity;
+                    self._update_append(&head[..whole_blocks], false, &mut output)?;
+                    head = &head[whole_blocks..];
+                } else {
+                    let needed = core::cmp::min(ideal - pending.len(), head.len());
+                    pending.extend_from_slice(&head[..needed]);
+                    head = &head[needed..];
+
+                    if pending.len() == ideal {
+                        self._flush_pending(&mut pending, &mut output)?;
+                    }
+                }
+            }
+        }
+
+        // Since aligned is a multiple of the granularity, so is this
+        self._flush_pending(&mut pending, &mut output)?;
+
+        let result = if end {
+            self._update_append(&tail, true, &mut output)
+        } else {
+            Ok(())
+        };
+
+        crate::memutils::scrub_mem(&mut tail[..]);
+
+        result.map(|_| output)
     }
 
     /// incremental update
     pub fn update(&mut self, msg: &[u8]) -> Result<Vec<u8>> {
         self._update(msg, false)
+            .map_err(self.context(self.direction_name()))
+    }
+
+    /// Incremental update with the message split over several slices
+    ///
+    /// This is equivalent to calling `update` with the concatenation of
+    /// `msg`, but avoids copying the message into a single buffer. The
+    /// total length must be a multiple of `update_granularity()`; the
+    /// individual slices may have any length.
+    ///
+    /// Accepts slices of `&[u8]`, `Vec<u8>` or `std::io::IoSlice`.
+    pub fn update_vectored<B: core::ops::Deref<Target = [u8]>>(
+        &mut self,
+        msg: &[B],
+    ) -> Result<Vec<u8>> {
+        self._update_vectored(msg, false)
+            .map_err(self.context(self.direction_name()))
     }
 
     /// finish function
     pub fn finish(&mut self, msg: &[u8]) -> Result<Vec<u8>> {
-        self._update(msg, true)
+        let result = self._update(msg, true);
+        self.end_message();
+        result.map_err(self.context(self.direction_name()))
+    }
+
+    /// Finish processing with the final input split over several slices
+    ///
+    /// This is equivalent to calling `finish` with the concatenation of
+    /// `msg`.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut aes_gcm = botan::Cipher::new("AES-128/GCM", botan::CipherDirection::Encrypt).unwrap();
+    /// aes_gcm.set_key(&vec![0; 16]).unwrap();
+    /// aes_gcm.start(&vec![0; 12]).unwrap();
+    /// let ctext = aes_gcm.finish_vectored(&[&b"split "[..], &b"message"[..]]).unwrap();
+    /// assert_eq!(ctext.len(), 13 + 16);
+    /// ```
+    pub fn finish_vectored<B: core::ops::Deref<Target = [u8]>>(
+        &mut self,
+        msg: &[B],
+    ) -> Result<Vec<u8>> {
+        let result = self._update_vectored(msg, true);
+        self.end_message();
+        result.map_err(self.context(self.direction_name()))
     }
 
     /// Clear all state associated with the key
+    ///
+    /// The key must be set again before processing another message.
     pub fn clear(&mut self) -> Result<()> {
-        botan_call!(botan_cipher_clear, self.obj)
+        self.end_message();
+        botan_call!(botan_cipher_clear, self.obj).map_err(self.context("clear"))
+    }
+}
+
+// The mode part of a name such as "AES-128/GCM(16)"
+fn cipher_mode(algo_name: &str) -> &str {
+    algo_name.split('/').nth(1).unwrap_or("")
+}
+
+// The AEADs which only use the associated data when the message is
+// finished, so that it may be set after the message is started
+fn allows_late_associated_data(algo_name: &str) -> bool {
+    let mode = cipher_mode(algo_name);
+    ["SIV", "OCB", "CCM"]
+        .iter()
+        .any(|late| mode.starts_with(late))
+}
+
+impl CipherBuilder {
+    /// Set the block cipher, for example `BlockCipherAlgo::Aes256`
+    #[must_use]
+    pub fn block_cipher<N: AsRef<str>>(mut self, block_cipher: N) -> CipherBuilder {
+        self.block_cipher = Some(block_cipher.as_ref().to_owned());
+        self
+    }
+
+    /// Set the cipher mode
+    #[must_use]
+    pub fn mode(mut self, mode: CipherMode) -> CipherBuilder {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Set the length of the authentication tag in bytes
+    ///
+    /// Only GCM, OCB, EAX and CCM take a tag length.
+    #[must_use]
+    pub fn tag_len(mut self, tag_len: usize) -> CipherBuilder {
+        self.tag_len = Some(tag_len);
+        self
+    }
+
+    /// Set the padding scheme
+    ///
+    /// Only CBC and ECB take a padding scheme.
+    #[must_use]
+    pub fn padding(mut self, padding: CipherPadding) -> CipherBuilder {
+        self.padding = Some(padding);
+        self
+    }
+
+    /// Set the direction
+    #[must_use]
+    pub fn direction(mut self, direction: CipherDirection) -> CipherBuilder {
+        self.direction = Some(direction);
+        self
+    }
+
+    fn missing(field: &str) -> Error {
+        Error::with_message(
+            ErrorType::BadParameter,
+            format!("Cipher builder requires a {}", field),
+        )
+    }
+
+    /// Create the cipher
+    ///
+    /// # Errors
+    /// Fails if the block cipher, mode or direction was not set, if a
+    /// tag length or padding was given for a mode which does not take
+    /// one, or if the library does not support the resulting cipher
+    pub fn build(&self) -> Result<Cipher> {
+        let block_cipher = self
+            .block_cipher
+            .as_ref()
+            .ok_or_else(|| CipherBuilder::missing("block cipher"))?;
+        let mode = self.mode.ok_or_else(|| CipherBuilder::missing("mode"))?;
+        let direction = self
+            .direction
+            .ok_or_else(|| CipherBuilder::missing("direction"))?;
+
+        if self.tag_len.is_some() && !mode.takes_tag_length() {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                format!("{} mode does not take a tag length", mode.botan_name()),
+            ));
+        }
+
+        #[cfg(feature = "botan3")]
+        {
+            if mode == CipherMode::Ecb {
+                return Err(Error::with_message(
+                    ErrorType::NotImplemented,
+                    "ECB mode is not supported by Botan 3".to_owned(),
+                ));
+            }
+        }
+
+        if let (Some(padding), Some(padded_mode)) = (self.padding, mode.padded_mode()) {
+            return Cipher::new_padded(block_cipher, padded_mode, padding, direction);
+        }
+
+        if self.padding.is_some() {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                format!("{} mode does not take a padding", mode.botan_name()),
+            ));
+        }
+
+        if block_cipher.contains('/') {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "Expected the name of a block cipher, not a cipher mode".to_owned(),
+            ));
+        }
+
+        let name = match self.tag_len {
+            Some(tag_len) => format!("{}/{}({})", block_cipher, mode.botan_name(), tag_len),
+            None => format!("{}/{}", block_cipher, mode.botan_name()),
+        };
+        Cipher::new(name, direction)
     }
 }
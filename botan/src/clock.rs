@@ -0,0 +1,119 @@
+//! Time sources and conversions
+//!
+//! The time based APIs take `SystemTime` and `Duration`, with `u64`
+//! seconds since the Unix epoch as an alternative. `Clock`,
+//! `SystemClock` and `MockClock` are also available at the crate root.
+
+use crate::utils::*;
+
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A source of the current time
+///
+/// Code which checks TOTP codes or validates certificates "now" can take
+/// a `&dyn Clock` instead of calling `SystemTime::now` directly, so that
+/// tests can substitute a `MockClock` and get deterministic results.
+///
+/// # Examples
+///
+/// ```
+/// use botan::Clock;
+///
+/// fn current_code(totp: &botan::TOTP, clock: &dyn Clock) -> botan::Result<u32> {
+///     totp.generate_at(clock.now())
+/// }
+///
+/// let totp = botan::TOTP::new(b"12345678901234567890", "SHA-1", 8, 30).unwrap();
+/// let clock = botan::MockClock::from_unix_time(59);
+/// assert_eq!(current_code(&totp, &clock).unwrap(), 94287082);
+/// ```
+pub trait Clock: Send + Sync {
+    /// Return the current time
+    fn now(&self) -> SystemTime;
+}
+
+/// The system's real time clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock which only moves when told to, for tests
+///
+/// # Examples
+///
+/// ```
+/// use botan::Clock;
+/// use std::time::Duration;
+///
+/// let clock = botan::MockClock::from_unix_time(1000);
+/// clock.advance(Duration::from_secs(30));
+/// assert_eq!(botan::clock::unix_time(clock.now()).unwrap(), 1030);
+/// ```
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<SystemTime>,
+}
+
+impl MockClock {
+    /// Create a clock stopped at `now`
+    pub fn new(now: SystemTime) -> MockClock {
+        MockClock {
+            now: Mutex::new(now),
+        }
+    }
+
+    /// Create a clock stopped at `seconds` past the Unix epoch
+    pub fn from_unix_time(seconds: u64) -> MockClock {
+        MockClock::new(from_unix_time(seconds))
+    }
+
+    /// Set the time returned by the clock
+    pub fn set(&self, now: SystemTime) {
+        *self.lock() = now;
+    }
+
+    /// Move the clock forward by `by`
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.lock();
+        *now += by;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SystemTime> {
+        match self.now.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> SystemTime {
+        *self.lock()
+    }
+}
+
+/// Convert a time to whole seconds since the Unix epoch, as taken by the
+/// `u64` variants of the time based APIs
+///
+/// # Errors
+/// Fails with `ErrorType::InvalidInput` for times before the epoch
+pub fn unix_time(time: SystemTime) -> Result<u64> {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since_epoch) => Ok(since_epoch.as_secs()),
+        Err(_) => Err(Error::with_message(
+            ErrorType::InvalidInput,
+            "Time is before the Unix epoch".to_owned(),
+        )),
+    }
+}
+
+/// Convert seconds since the Unix epoch to a `SystemTime`
+pub fn from_unix_time(seconds: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds)
+}
@@ -0,0 +1,183 @@
+use crate::utils::*;
+use botan_sys::*;
+
+/// Hex encode some data
+pub fn hex_encode(x: &[u8]) -> Result<String> {
+    let flags = 0u32;
+
+    let mut output = vec![0u8; x.len() * 2];
+    botan_call!(
+        botan_hex_encode,
+        x.as_ptr(),
+        x.len(),
+        output.as_mut_ptr() as *mut c_char,
+        flags
+    )?;
+
+    String::from_utf8(output).map_err(Error::conversion_error)
+}
+
+/// Hex decode some data
+pub fn hex_decode(x: &str) -> Result<Vec<u8>> {
+    let mut output = vec![0u8; x.len() / 2];
+    let mut output_len = output.len();
+
+    let input = make_cstr(x)?;
+
+    botan_call!(
+        botan_hex_decode,
+        input.as_ptr(),
+        x.len(),
+        output.as_mut_ptr(),
+        &mut output_len
+    )?;
+
+    output.resize(output_len, 0);
+
+    Ok(output)
+}
+
+/// Base64 encode some data
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(botan::base64_encode(&[97,98,99,100,101,102]).unwrap(), "YWJjZGVm");
+/// assert_eq!(botan::base64_encode(&[0x5A, 0x16, 0xAD, 0x4E, 0x17, 0x87, 0x79, 0xC9]).unwrap(), "WhatTheHeck=");
+/// ```
+pub fn base64_encode(x: &[u8]) -> Result<String> {
+    let b64_len = 1 + ((x.len() + 2) / 3) * 4;
+
+    call_botan_ffi_returning_string(b64_len, &|out_buf, out_len| unsafe {
+        botan_base64_encode(x.as_ptr(), x.len(), out_buf as *mut c_char, out_len)
+    })
+}
+
+/// Base64 decode some data
+///
+/// # Examples
+///
+/// ```
+/// assert!(botan::base64_decode("ThisIsInvalid!").is_err());
+/// assert_eq!(botan::base64_decode("YWJjZGVm").unwrap(), b"abcdef");
+/// ```
+pub fn base64_decode(x: &str) -> Result<Vec<u8>> {
+    // Hard to provide a decent lower bound as it is possible x includes
+    // lots of spaces or trailing = padding chars
+    let bin_len = x.len();
+
+    let input = make_cstr(x)?;
+
+    call_botan_ffi_returning_vec_u8(bin_len, &|out_buf, out_len| unsafe {
+        botan_base64_decode(input.as_ptr(), x.len(), out_buf, out_len)
+    })
+}
+
+// Return 0xFF if a < b, else 0, without branching
+fn ct_lt(a: u8, b: u8) -> u8 {
+    ((a as u16).wrapping_sub(b as u16) >> 8) as u8
+}
+
+fn base32_char(v: u8) -> u8 {
+    let is_alpha = ct_lt(v, 26);
+    (is_alpha & b'A'.wrapping_add(v)) | (!is_alpha & b'2'.wrapping_add(v).wrapping_sub(26))
+}
+
+// Return the value of a base32 character, and 0xFF if it was valid
+fn base32_value(c: u8) -> (u8, u8) {
+    let is_alpha = !ct_lt(c, b'A') & ct_lt(c, b'Z' + 1);
+    let is_digit = !ct_lt(c, b'2') & ct_lt(c, b'7' + 1);
+
+    let v = (is_alpha & c.wrapping_sub(b'A')) | (is_digit & c.wrapping_sub(b'2').wrapping_add(26));
+    (v, is_alpha | is_digit)
+}
+
+/// Base32 encode some data (RFC 4648, with padding)
+///
+/// The encoding runs in constant time with respect to the input bytes.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(botan::base32_encode(b"foobar").unwrap(), "MZXW6YTBOI======");
+/// ```
+pub fn base32_encode(x: &[u8]) -> Result<String> {
+    let mut output = Vec::with_capacity(((x.len() + 4) / 5) * 8);
+
+    for chunk in x.chunks(5) {
+        let mut block = [0u8; 5];
+        block[..chunk.len()].copy_from_slice(chunk);
+        let bits = block.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+
+        let chars = (chunk.len() * 8 + 4) / 5;
+        for i in 0..8 {
+            if i < chars {
+                output.push(base32_char(((bits >> (35 - 5 * i)) & 0x1F) as u8));
+            } else {
+                output.push(b'=');
+            }
+        }
+    }
+
+    String::from_utf8(output).map_err(Error::conversion_error)
+}
+
+/// Base32 decode some data (RFC 4648)
+///
+/// Padding is optional, but if present must be correct. The decoding
+/// runs in constant time with respect to the encoded data.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(botan::base32_decode("MZXW6YTBOI======").unwrap(), b"foobar");
+/// assert_eq!(botan::base32_decode("MZXW6YTBOI").unwrap(), b"foobar");
+/// assert!(botan::base32_decode("MZXW6YTBOI=").is_err());
+/// ```
+pub fn base32_decode(x: &str) -> Result<Vec<u8>> {
+    let invalid = || Error::with_message(ErrorType::InvalidInput, "Invalid base32".to_owned());
+
+    let input = x.trim_end_matches('=').as_bytes();
+    let padding = x.len() - input.len();
+    let partial = input.len() % 8;
+
+    // A final block can only hold 2, 4, 5 or 7 characters
+    if partial == 1 || partial == 3 || partial == 6 {
+        return Err(invalid());
+    }
+    if padding > 0 && (partial == 0 || padding != 8 - partial) {
+        return Err(invalid());
+    }
+
+    let mut output = Vec::with_capacity(input.len() * 5 / 8);
+    let mut valid = 0xFF;
+
+    for chunk in input.chunks(8) {
+        let mut bits = 0u64;
+        for i in 0..8 {
+            let (v, ok) = if i < chunk.len() {
+                base32_value(chunk[i])
+            } else {
+                (0, 0xFF)
+            };
+            valid &= ok;
+            bits = (bits << 5) | v as u64;
+        }
+
+        let bytes = chunk.len() * 5 / 8;
+        for i in 0..bytes {
+            output.push((bits >> (32 - 8 * i)) as u8);
+        }
+
+        // Any unused trailing bits must be zero
+        let unused = bits & ((1u64 << (40 - 8 * bytes)) - 1);
+        let nonzero = ((unused | unused.wrapping_neg()) >> 63) as u8;
+        valid &= nonzero.wrapping_sub(1);
+    }
+
+    if valid != 0xFF {
+        return Err(invalid());
+    }
+
+    Ok(output)
+}
@@ -0,0 +1,521 @@
+use crate::utils::*;
+
+use crate::cipher::{Cipher, CipherDirection};
+use crate::jws::JwsAlgorithm;
+use crate::pk_ops::{Signer, Verifier};
+use crate::pubkey::{Privkey, Pubkey};
+use crate::rng::RandomNumberGenerator;
+
+const COSE_SIGN1_TAG: u64 = 18;
+const COSE_ENCRYPT0_TAG: u64 = 16;
+
+const HEADER_ALG: i64 = 1;
+const HEADER_IV: i64 = 5;
+
+const CBOR_UINT: u8 = 0;
+const CBOR_NINT: u8 = 1;
+const CBOR_BSTR: u8 = 2;
+const CBOR_TSTR: u8 = 3;
+const CBOR_ARRAY: u8 = 4;
+const CBOR_MAP: u8 = 5;
+const CBOR_TAG: u8 = 6;
+const CBOR_SIMPLE: u8 = 7;
+
+const CBOR_MAX_DEPTH: usize = 16;
+
+fn cose_error(msg: &str) -> Error {
+    Error::with_message(ErrorType::InvalidInput, msg.to_owned())
+}
+
+/// Just enough CBOR (RFC 8949) to produce and consume COSE messages.
+/// Only definite length encodings are supported.
+struct CborWriter {
+    out: Vec<u8>,
+}
+
+impl CborWriter {
+    fn new() -> CborWriter {
+        CborWriter { out: Vec::new() }
+    }
+
+    fn head(&mut self, major: u8, val: u64) {
+        let major = major << 5;
+        if val < 24 {
+            self.out.push(major | val as u8);
+        } else if val <= 0xFF {
+            self.out.push(major | 24);
+            self.out.push(val as u8);
+        } else if val <= 0xFFFF {
+            self.out.push(major | 25);
+            self.out.extend_from_slice(&(val as u16).to_be_bytes());
+        } else if val <= 0xFFFF_FFFF {
+            self.out.push(major | 26);
+            self.out.extend_from_slice(&(val as u32).to_be_bytes());
+        } else {
+            self.out.push(major | 27);
+            self.out.extend_from_slice(&val.to_be_bytes());
+        }
+    }
+
+    fn int(&mut self, val: i64) {
+        if val >= 0 {
+            self.head(CBOR_UINT, val as u64);
+        } else {
+            self.head(CBOR_NINT, !(val as u64));
+        }
+    }
+
+    fn bstr(&mut self, val: &[u8]) {
+        self.head(CBOR_BSTR, val.len() as u64);
+        self.out.extend_from_slice(val);
+    }
+
+    fn tstr(&mut self, val: &str) {
+        self.head(CBOR_TSTR, val.len() as u64);
+        self.out.extend_from_slice(val.as_bytes());
+    }
+
+    fn array(&mut self, len: usize) {
+        self.head(CBOR_ARRAY, len as u64);
+    }
+
+    fn map(&mut self, len: usize) {
+        self.head(CBOR_MAP, len as u64);
+    }
+
+    fn tag(&mut self, tag: u64) {
+        self.head(CBOR_TAG, tag);
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.out
+    }
+}
+
+struct CborReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> CborReader<'a> {
+    fn new(data: &'a [u8]) -> CborReader<'a> {
+        CborReader { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.data.len() - self.pos < len {
+            return Err(cose_error("Truncated CBOR input"));
+        }
+        let v = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(v)
+    }
+
+    fn peek_major(&self) -> Result<u8> {
+        match self.data.get(self.pos) {
+            Some(b) => Ok(b >> 5),
+            None => Err(cose_error("Truncated CBOR input")),
+        }
+    }
+
+    fn head(&mut self) -> Result<(u8, u64)> {
+        let initial = self.take(1)?[0];
+        let major = initial >> 5;
+        let info = initial & 0x1F;
+
+        let val = match info {
+            0..=23 => info as u64,
+            24 => self.take(1)?[0] as u64,
+            25 => self.take(2)?.iter().fold(0, |v, &b| (v << 8) | b as u64),
+            26 => self.take(4)?.iter().fold(0, |v, &b| (v << 8) | b as u64),
+            27 => self.take(8)?.iter().fold(0, |v, &b| (v << 8) | b as u64),
+            _ => return Err(cose_error("Unsupported CBOR encoding")),
+        };
+
+        Ok((major, val))
+    }
+
+    fn expect(&mut self, major: u8) -> Result<u64> {
+        let (m, val) = self.head()?;
+        if m != major {
+            return Err(cose_error("Unexpected CBOR type"));
+        }
+        Ok(val)
+    }
+
+    fn length(&mut self, major: u8) -> Result<usize> {
+        let len = self.expect(major)?;
+        if len > (self.data.len() - self.pos) as u64 {
+            return Err(cose_error("Truncated CBOR input"));
+        }
+        Ok(len as usize)
+    }
+
+    fn bstr(&mut self) -> Result<&'a [u8]> {
+        let len = self.length(CBOR_BSTR)?;
+        self.take(len)
+    }
+
+    fn int(&mut self) -> Result<i64> {
+        let (major, val) = self.head()?;
+        if val > i64::MAX as u64 {
+            return Err(cose_error("CBOR integer out of range"));
+        }
+        match major {
+            CBOR_UINT => Ok(val as i64),
+            CBOR_NINT => Ok(-1 - val as i64),
+            _ => Err(cose_error("Unexpected CBOR type")),
+        }
+    }
+
+    fn skip(&mut self, depth: usize) -> Result<()> {
+        if depth > CBOR_MAX_DEPTH {
+            return Err(cose_error("CBOR nesting too deep"));
+        }
+
+        let (major, val) = self.head()?;
+        match major {
+            CBOR_UINT | CBOR_NINT | CBOR_SIMPLE => Ok(()),
+            CBOR_BSTR | CBOR_TSTR => {
+                if val > (self.data.len() - self.pos) as u64 {
+                    return Err(cose_error("Truncated CBOR input"));
+                }
+                self.take(val as usize).map(|_| ())
+            }
+            CBOR_ARRAY | CBOR_MAP => {
+                let items = if major == CBOR_MAP {
+                    val.checked_mul(2)
+                        .ok_or_else(|| cose_error("CBOR map too large"))?
+                } else {
+                    val
+                };
+                for _ in 0..items {
+                    self.skip(depth + 1)?;
+                }
+                Ok(())
+            }
+            CBOR_TAG => self.skip(depth + 1),
+            _ => Err(cose_error("Unsupported CBOR encoding")),
+        }
+    }
+
+    /// Read a header map, returning the values of the requested labels
+    /// which must be integers or byte strings
+    fn header_map(&mut self, alg: &mut Option<i64>, iv: &mut Option<&'a [u8]>) -> Result<()> {
+        let entries = self.expect(CBOR_MAP)?;
+        for _ in 0..entries {
+            let label = if self.peek_major()? == CBOR_TSTR {
+                self.skip(0)?;
+                None
+            } else {
+                Some(self.int()?)
+            };
+
+            match label {
+                Some(HEADER_ALG) => {
+                    if alg.is_some() {
+                        return Err(cose_error("Duplicate COSE header"));
+                    }
+                    *alg = Some(self.int()?)
+                }
+                Some(HEADER_IV) => {
+                    if iv.is_some() {
+                        return Err(cose_error("Duplicate COSE header"));
+                    }
+                    *iv = Some(self.bstr()?)
+                }
+                _ => self.skip(0)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn tagged_array(&mut self, tag: u64, len: u64) -> Result<()> {
+        if self.peek_major()? == CBOR_TAG && self.expect(CBOR_TAG)? != tag {
+            return Err(cose_error("Unexpected COSE message type"));
+        }
+        if self.expect(CBOR_ARRAY)? != len {
+            return Err(cose_error("Malformed COSE message"));
+        }
+        Ok(())
+    }
+
+    fn finish(&self) -> Result<()> {
+        if self.pos != self.data.len() {
+            return Err(cose_error("Trailing data after COSE message"));
+        }
+        Ok(())
+    }
+}
+
+fn protected_header(alg: i64) -> Vec<u8> {
+    let mut w = CborWriter::new();
+    w.map(1);
+    w.int(HEADER_ALG);
+    w.int(alg);
+    w.finish()
+}
+
+fn check_protected_alg(protected: &[u8], expected: i64) -> Result<()> {
+    let mut alg = None;
+    let mut iv = None;
+    let mut r = CborReader::new(protected);
+    r.header_map(&mut alg, &mut iv)?;
+    r.finish()?;
+
+    if alg != Some(expected) {
+        return Err(cose_error("COSE algorithm mismatch"));
+    }
+    Ok(())
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// A COSE signature algorithm (RFC 9053)
+pub enum CoseSignAlgorithm {
+    /// ECDSA with P-256 and SHA-256
+    Es256,
+    /// ECDSA with P-384 and SHA-384
+    Es384,
+    /// ECDSA with P-521 and SHA-512
+    Es512,
+    /// EdDSA (Ed25519)
+    EdDsa,
+    /// RSASSA-PSS with SHA-256
+    Ps256,
+    /// RSASSA-PSS with SHA-384
+    Ps384,
+    /// RSASSA-PSS with SHA-512
+    Ps512,
+}
+
+impl CoseSignAlgorithm {
+    /// Return the COSE algorithm identifier
+    #[must_use]
+    pub fn id(self) -> i64 {
+        match self {
+            CoseSignAlgorithm::Es256 => -7,
+            CoseSignAlgorithm::Es384 => -35,
+            CoseSignAlgorithm::Es512 => -36,
+            CoseSignAlgorithm::EdDsa => -8,
+            CoseSignAlgorithm::Ps256 => -37,
+            CoseSignAlgorithm::Ps384 => -38,
+            CoseSignAlgorithm::Ps512 => -39,
+        }
+    }
+
+    // The JOSE algorithms use identical signature formats
+    fn jws(self) -> JwsAlgorithm {
+        match self {
+            CoseSignAlgorithm::Es256 => JwsAlgorithm::Es256,
+            CoseSignAlgorithm::Es384 => JwsAlgorithm::Es384,
+            CoseSignAlgorithm::Es512 => JwsAlgorithm::Es512,
+            CoseSignAlgorithm::EdDsa => JwsAlgorithm::EdDsa,
+            CoseSignAlgorithm::Ps256 => JwsAlgorithm::Ps256,
+            CoseSignAlgorithm::Ps384 => JwsAlgorithm::Ps384,
+            CoseSignAlgorithm::Ps512 => JwsAlgorithm::Ps512,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// A COSE content encryption algorithm (RFC 9053)
+pub enum CoseAeadAlgorithm {
+    /// AES-GCM with a 128-bit key
+    A128Gcm,
+    /// AES-GCM with a 192-bit key
+    A192Gcm,
+    /// AES-GCM with a 256-bit key
+    A256Gcm,
+    /// ChaCha20/Poly1305
+    ChaCha20Poly1305,
+    /// AES-CCM with a 128-bit key, 64-bit tag and 13 byte nonce
+    /// (AES-CCM-16-64-128)
+    AesCcm16_64_128,
+}
+
+impl CoseAeadAlgorithm {
+    /// Return the COSE algorithm identifier
+    #[must_use]
+    pub fn id(self) -> i64 {
+        match self {
+            CoseAeadAlgorithm::A128Gcm => 1,
+            CoseAeadAlgorithm::A192Gcm => 2,
+            CoseAeadAlgorithm::A256Gcm => 3,
+            CoseAeadAlgorithm::ChaCha20Poly1305 => 24,
+            CoseAeadAlgorithm::AesCcm16_64_128 => 10,
+        }
+    }
+
+    fn cipher_name(self) -> &'static str {
+        match self {
+            CoseAeadAlgorithm::A128Gcm => "AES-128/GCM",
+            CoseAeadAlgorithm::A192Gcm => "AES-192/GCM",
+            CoseAeadAlgorithm::A256Gcm => "AES-256/GCM",
+            CoseAeadAlgorithm::ChaCha20Poly1305 => "ChaCha20Poly1305",
+            CoseAeadAlgorithm::AesCcm16_64_128 => "AES-128/CCM(8,2)",
+        }
+    }
+
+    fn cipher(self, key: &[u8], direction: CipherDirection) -> Result<Cipher> {
+        let mut cipher = Cipher::new(self.cipher_name(), direction)?;
+        cipher.set_key(key)?;
+        Ok(cipher)
+    }
+}
+
+fn sig_structure(protected: &[u8], external_aad: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut w = CborWriter::new();
+    w.array(4);
+    w.tstr("Signature1");
+    w.bstr(protected);
+    w.bstr(external_aad);
+    w.bstr(payload);
+    w.finish()
+}
+
+fn enc_structure(protected: &[u8], external_aad: &[u8]) -> Vec<u8> {
+    let mut w = CborWriter::new();
+    w.array(3);
+    w.tstr("Encrypt0");
+    w.bstr(protected);
+    w.bstr(external_aad);
+    w.finish()
+}
+
+/// Create a tagged COSE_Sign1 message with an attached payload
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::Privkey::create("ECDSA", "secp256r1", &mut rng).unwrap();
+/// let alg = botan::CoseSignAlgorithm::Es256;
+/// let msg = botan::cose_sign1(&key, alg, b"payload", &[], &mut rng).unwrap();
+/// let payload = botan::cose_sign1_verify(&key.pubkey().unwrap(), alg, &msg, &[]).unwrap();
+/// assert_eq!(payload, b"payload");
+/// ```
+pub fn cose_sign1(
+    key: &Privkey,
+    alg: CoseSignAlgorithm,
+    payload: &[u8],
+    external_aad: &[u8],
+    rng: &mut RandomNumberGenerator,
+) -> Result<Vec<u8>> {
+    let jws = alg.jws();
+    jws.check_key(&key.algo_name()?, &|| key.get_field("order"))?;
+
+    let protected = protected_header(alg.id());
+
+    let mut signer = Signer::new(key, jws.padding())?;
+    signer.update(&sig_structure(&protected, external_aad, payload))?;
+    let signature = signer.finish(rng)?;
+
+    let mut w = CborWriter::new();
+    w.tag(COSE_SIGN1_TAG);
+    w.array(4);
+    w.bstr(&protected);
+    w.map(0);
+    w.bstr(payload);
+    w.bstr(&signature);
+    Ok(w.finish())
+}
+
+/// Verify a COSE_Sign1 message and return its payload
+///
+/// The algorithm in the protected header must match `alg`. Messages
+/// with a detached payload are not supported.
+pub fn cose_sign1_verify(
+    key: &Pubkey,
+    alg: CoseSignAlgorithm,
+    msg: &[u8],
+    external_aad: &[u8],
+) -> Result<Vec<u8>> {
+    let jws = alg.jws();
+    jws.check_key(&key.algo_name()?, &|| key.get_field("order"))?;
+
+    let mut r = CborReader::new(msg);
+    r.tagged_array(COSE_SIGN1_TAG, 4)?;
+    let protected = r.bstr()?;
+    r.skip(0)?;
+    let payload = r.bstr()?;
+    let signature = r.bstr()?;
+    r.finish()?;
+
+    check_protected_alg(protected, alg.id())?;
+
+    let mut verifier = Verifier::new(key, jws.padding())?;
+    verifier.update(&sig_structure(protected, external_aad, payload))?;
+
+    if !verifier.finish(signature)? {
+        return Err(Error::with_message(
+            ErrorType::InvalidVerifier,
+            "COSE signature is not valid".to_owned(),
+        ));
+    }
+
+    Ok(payload.to_vec())
+}
+
+/// Create a tagged COSE_Encrypt0 message using a random nonce
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = rng.read(16).unwrap();
+/// let alg = botan::CoseAeadAlgorithm::A128Gcm;
+/// let msg = botan::cose_encrypt0(&key, alg, b"secret", &[], &mut rng).unwrap();
+/// assert_eq!(botan::cose_decrypt0(&key, alg, &msg, &[]).unwrap(), b"secret");
+/// ```
+pub fn cose_encrypt0(
+    key: &[u8],
+    alg: CoseAeadAlgorithm,
+    ptext: &[u8],
+    external_aad: &[u8],
+    rng: &mut RandomNumberGenerator,
+) -> Result<Vec<u8>> {
+    let mut cipher = alg.cipher(key, CipherDirection::Encrypt)?;
+    let nonce = rng.read(cipher.default_nonce_length())?;
+
+    let protected = protected_header(alg.id());
+    cipher.set_associated_data(&enc_structure(&protected, external_aad))?;
+    let ctext = cipher.process(&nonce, ptext)?;
+
+    let mut w = CborWriter::new();
+    w.tag(COSE_ENCRYPT0_TAG);
+    w.array(3);
+    w.bstr(&protected);
+    w.map(1);
+    w.int(HEADER_IV);
+    w.bstr(&nonce);
+    w.bstr(&ctext);
+    Ok(w.finish())
+}
+
+/// Decrypt a COSE_Encrypt0 message
+///
+/// The algorithm in the protected header must match `alg`.
+pub fn cose_decrypt0(
+    key: &[u8],
+    alg: CoseAeadAlgorithm,
+    msg: &[u8],
+    external_aad: &[u8],
+) -> Result<Vec<u8>> {
+    let mut r = CborReader::new(msg);
+    r.tagged_array(COSE_ENCRYPT0_TAG, 3)?;
+    let protected = r.bstr()?;
+
+    let mut unprotected_alg = None;
+    let mut nonce = None;
+    r.header_map(&mut unprotected_alg, &mut nonce)?;
+    let ctext = r.bstr()?;
+    r.finish()?;
+
+    check_protected_alg(protected, alg.id())?;
+
+    let nonce = nonce.ok_or_else(|| cose_error("Missing COSE IV header"))?;
+
+    let mut cipher = alg.cipher(key, CipherDirection::Decrypt)?;
+    cipher.set_associated_data(&enc_structure(protected, external_aad))?;
+    cipher.process(nonce, ctext)
+}
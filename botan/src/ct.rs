@@ -0,0 +1,77 @@
+//! Constant time utilities
+//!
+//! These helpers avoid branches and memory accesses which depend on
+//! their (secret) inputs. Lengths are treated as public.
+
+use crate::utils::*;
+
+fn length_mismatch() -> Error {
+    Error::with_message(
+        ErrorType::BadParameter,
+        "Inputs must have the same length".to_owned(),
+    )
+}
+
+// Expand a bool into an all-ones or all-zeros mask. The volatile read
+// keeps the optimizer from turning later uses of the mask into branches.
+fn expand_mask(choice: bool) -> u8 {
+    let mask = 0u8.wrapping_sub(choice as u8);
+    unsafe { ptr::read_volatile(&mask) }
+}
+
+/// Compare two byte strings in constant time
+///
+/// # Examples
+///
+/// ```
+/// assert!(botan::ct::eq(b"tag", b"tag"));
+/// assert!(!botan::ct::eq(b"tag", b"tap"));
+/// ```
+#[must_use]
+pub fn eq(a: &[u8], b: &[u8]) -> bool {
+    crate::memutils::const_time_compare(a, b)
+}
+
+/// Return a copy of `if_true` if `choice` is set, otherwise of `if_false`
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(botan::ct::select(true, b"yes", b"no!").unwrap(), b"yes");
+/// assert_eq!(botan::ct::select(false, b"yes", b"no!").unwrap(), b"no!");
+/// ```
+pub fn select(choice: bool, if_true: &[u8], if_false: &[u8]) -> Result<Vec<u8>> {
+    if if_true.len() != if_false.len() {
+        return Err(length_mismatch());
+    }
+
+    let mask = expand_mask(choice);
+    Ok(if_true
+        .iter()
+        .zip(if_false.iter())
+        .map(|(t, f)| (mask & t) | (!mask & f))
+        .collect())
+}
+
+/// Copy `src` into `dest` if `choice` is set, otherwise leave `dest` unchanged
+///
+/// # Examples
+///
+/// ```
+/// let mut buf = vec![0; 4];
+/// botan::ct::conditional_copy(false, &mut buf, &[1, 2, 3, 4]).unwrap();
+/// assert_eq!(buf, vec![0; 4]);
+/// botan::ct::conditional_copy(true, &mut buf, &[1, 2, 3, 4]).unwrap();
+/// assert_eq!(buf, vec![1, 2, 3, 4]);
+/// ```
+pub fn conditional_copy(choice: bool, dest: &mut [u8], src: &[u8]) -> Result<()> {
+    if dest.len() != src.len() {
+        return Err(length_mismatch());
+    }
+
+    let mask = expand_mask(choice);
+    for (d, s) in dest.iter_mut().zip(src.iter()) {
+        *d = (mask & s) | (!mask & *d);
+    }
+    Ok(())
+}
@@ -0,0 +1,184 @@
+//! Tracking of live FFI objects, for debugging handle leaks
+//!
+//! With the `debug-ffi` feature, every object handle created through the
+//! FFI is recorded along with the function which created it and, if
+//! enabled with `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`, a backtrace.
+//! The record is removed when the owning Rust object is dropped, so the
+//! handles which are still recorded are exactly those which are alive.
+//!
+//! This adds a global lock to every object creation and destruction, and
+//! is intended for debugging rather than production use.
+
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+struct Record {
+    created_by: &'static str,
+    sequence: u64,
+    backtrace: Backtrace,
+}
+
+type Table = Mutex<HashMap<usize, Record>>;
+
+// Installed on first use and never freed
+static TABLE: AtomicPtr<Table> = AtomicPtr::new(core::ptr::null_mut());
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+fn table() -> MutexGuard<'static, HashMap<usize, Record>> {
+    let mut table = TABLE.load(Ordering::Acquire);
+
+    if table.is_null() {
+        let new_table = Box::into_raw(Box::new(Mutex::new(HashMap::new())));
+        table = match TABLE.compare_exchange(
+            core::ptr::null_mut(),
+            new_table,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => new_table,
+            Err(existing) => {
+                // Another thread installed a table first
+                drop(unsafe { Box::from_raw(new_table) });
+                existing
+            }
+        };
+    }
+
+    match unsafe { &*table }.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+pub(crate) fn track(handle: usize, created_by: &'static str) {
+    let record = Record {
+        created_by,
+        sequence: SEQUENCE.fetch_add(1, Ordering::Relaxed),
+        backtrace: Backtrace::capture(),
+    };
+    table().insert(handle, record);
+}
+
+pub(crate) fn untrack(handle: usize) {
+    table().remove(&handle);
+}
+
+// Count the live handles created at or after `since`, by creating function
+fn counts(since: u64) -> Vec<(&'static str, usize)> {
+    let mut counts: HashMap<&'static str, usize> = HashMap::new();
+    for record in table().values().filter(|r| r.sequence >= since) {
+        *counts.entry(record.created_by).or_insert(0) += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    counts
+}
+
+fn report<W: Write>(out: &mut W, since: u64) -> std::io::Result<()> {
+    let counts = counts(since);
+    let total: usize = counts.iter().map(|(_, n)| n).sum();
+    writeln!(out, "{} live botan object(s)", total)?;
+    for (created_by, count) in &counts {
+        writeln!(out, "  {:6} created by {}", count, created_by)?;
+    }
+
+    let table = table();
+    let mut records: Vec<&Record> = table.values().filter(|r| r.sequence >= since).collect();
+    records.sort_by_key(|r| r.sequence);
+    for record in records {
+        if record.backtrace.status() == BacktraceStatus::Captured {
+            writeln!(
+                out,
+                "\nobject #{} created by {} at:\n{}",
+                record.sequence, record.created_by, record.backtrace
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Return the number of live objects, grouped by the FFI function which
+/// created them (for example `botan_hash_init`), most common first
+///
+/// # Examples
+/// ```
+/// let before = botan::debug_ffi::live_object_count();
+/// let hash = botan::HashFunction::new("SHA-256").unwrap();
+/// assert_eq!(botan::debug_ffi::live_object_count(), before + 1);
+/// drop(hash);
+/// assert_eq!(botan::debug_ffi::live_object_count(), before);
+/// ```
+#[must_use]
+pub fn live_objects() -> Vec<(&'static str, usize)> {
+    counts(0)
+}
+
+/// Return the total number of live objects
+#[must_use]
+pub fn live_object_count() -> usize {
+    table().len()
+}
+
+/// Write a report of all live objects to `out`
+///
+/// The report gives the count for each creating function, followed by
+/// the backtrace of each object if backtraces were captured.
+pub fn write_report<W: Write>(out: &mut W) -> std::io::Result<()> {
+    report(out, 0)
+}
+
+/// Reports objects which were created during its lifetime and are still
+/// alive when it is dropped
+///
+/// Create one at the start of `main` (or of a test, or of a request
+/// handler) to find handles which are never released. When dropped, a
+/// report is written to standard error if there are any leaks. Objects
+/// created by other threads in the meantime are included.
+///
+/// # Examples
+/// ```
+/// let check = botan::debug_ffi::LeakCheck::new();
+/// let hash = botan::HashFunction::new("SHA-256").unwrap();
+/// assert_eq!(check.leaked(), vec![("botan_hash_init", 1)]);
+/// drop(hash);
+/// assert!(check.leaked().is_empty());
+/// ```
+#[derive(Debug)]
+pub struct LeakCheck {
+    since: u64,
+}
+
+impl LeakCheck {
+    /// Start checking for leaks
+    #[must_use]
+    pub fn new() -> LeakCheck {
+        LeakCheck {
+            since: SEQUENCE.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Return the objects created since this check started which are
+    /// still alive, grouped by creating function
+    #[must_use]
+    pub fn leaked(&self) -> Vec<(&'static str, usize)> {
+        counts(self.since)
+    }
+}
+
+impl Default for LeakCheck {
+    fn default() -> LeakCheck {
+        LeakCheck::new()
+    }
+}
+
+impl Drop for LeakCheck {
+    fn drop(&mut self) {
+        if !self.leaked().is_empty() {
+            let stderr = std::io::stderr();
+            let _ = report(&mut stderr.lock(), self.since);
+        }
+    }
+}
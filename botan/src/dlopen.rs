@@ -0,0 +1,69 @@
+//! Control over loading the library at runtime
+//!
+//! With the `dlopen` feature the library is not linked at build time.
+//! It is opened the first time any function of this crate calls into it,
+//! using the path in the `BOTAN_LIBRARY` environment variable if that is
+//! set, and otherwise the usual names of the library on the platform.
+//! Applications which ship the library in their own location can call
+//! `load_library` first instead.
+//!
+//! Functions which the loaded library does not have fail with
+//! `ErrorType::NotImplemented`, as do all functions if no library could
+//! be found; `library_loaded` and `has_function` allow checking for this
+//! in advance.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! if !botan::dlopen::library_loaded() {
+//!     botan::dlopen::load_library("/opt/myapp/lib/libbotan-3.so").unwrap();
+//! }
+//! if botan::dlopen::has_function("botan_pk_op_kem_encrypt_create") {
+//!     // KEMs can be used
+//! }
+//! ```
+
+use crate::utils::*;
+use botan_sys::*;
+
+/// Load the library from `path`
+///
+/// This must be called before any other function of this crate, since
+/// the library is otherwise loaded from its default location on first
+/// use and cannot be replaced afterwards.
+///
+/// # Errors
+/// Fails with `ErrorType::InvalidObjectState` if a library was already
+/// loaded, or with `ErrorType::NotImplemented` if `path` could not be
+/// opened, in which case another path may be tried.
+pub fn load_library(path: &str) -> Result<()> {
+    let cpath = make_cstr(path)?;
+    match botan_sys::dlopen::load_library(cpath.as_bytes_with_nul()) {
+        BOTAN_FFI_SUCCESS => Ok(()),
+        BOTAN_FFI_ERROR_INVALID_OBJECT_STATE => Err(Error::with_message(
+            ErrorType::InvalidObjectState,
+            "The library was already loaded".to_owned(),
+        )),
+        _ => Err(Error::with_message(
+            ErrorType::NotImplemented,
+            format!("The library could not be loaded from {}", path),
+        )),
+    }
+}
+
+/// Return true if the library is loaded, loading it from its default
+/// location if that was not yet done
+#[must_use]
+pub fn library_loaded() -> bool {
+    botan_sys::dlopen::library_loaded()
+}
+
+/// Return true if the loaded library has the FFI function `name`, for
+/// example `"botan_pk_op_kem_encrypt_create"`
+#[must_use]
+pub fn has_function(name: &str) -> bool {
+    match make_cstr(name) {
+        Ok(name) => botan_sys::dlopen::symbol_available(name.as_bytes_with_nul()),
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,711 @@
+use crate::utils::*;
+#[cfg(feature = "botan3")]
+use botan_sys::*;
+
+#[cfg(feature = "botan3")]
+use crate::capability::Capability;
+use crate::mp::MPI;
+use crate::pubkey::{Privkey, Pubkey};
+
+#[derive(Clone, Debug)]
+struct CurveParams {
+    name: String,
+    p: MPI,
+    a: MPI,
+    b: MPI,
+    order: MPI,
+    base_x: MPI,
+    base_y: MPI,
+}
+
+impl CurveParams {
+    fn load(curve: &str) -> Result<CurveParams> {
+        // A key with private value 1 has the group generator as public point
+        let key = Privkey::load_ecdsa(&MPI::new_from_u32(1)?, curve)?;
+
+        Ok(CurveParams {
+            name: curve.to_owned(),
+            p: key.get_field("p")?,
+            a: key.get_field("a")?,
+            b: key.get_field("b")?,
+            order: key.get_field("order")?,
+            base_x: key.get_field("base_x")?,
+            base_y: key.get_field("base_y")?,
+        })
+    }
+
+    fn field_len(&self) -> Result<usize> {
+        Ok((self.p.bit_count()? + 7) / 8)
+    }
+
+    fn reduce(&self, x: &MPI) -> Result<MPI> {
+        let (_, r) = x.divrem(&self.p)?;
+        if r.is_negative()? {
+            r.mp_add(&self.p)
+        } else {
+            Ok(r)
+        }
+    }
+
+    fn in_field(&self, x: &MPI) -> Result<bool> {
+        Ok(!x.is_negative()? && *x < self.p)
+    }
+
+    // Return x^3 + a*x + b
+    fn curve_rhs(&self, x: &MPI) -> Result<MPI> {
+        let x2 = MPI::mulmod(x, x, &self.p)?;
+        let x3 = MPI::mulmod(&x2, x, &self.p)?;
+        let ax = MPI::mulmod(&self.a, x, &self.p)?;
+        self.reduce(&x3.mp_add(&ax)?.mp_add(&self.b)?)
+    }
+
+    fn on_curve(&self, x: &MPI, y: &MPI) -> Result<bool> {
+        if !self.in_field(x)? || !self.in_field(y)? {
+            return Ok(false);
+        }
+        MPI::mulmod(y, y, &self.p)?.equals(&self.curve_rhs(x)?)
+    }
+
+    // Return a square root of v modulo p, if one exists
+    fn sqrt(&self, v: &MPI) -> Result<Option<MPI>> {
+        let p = &self.p;
+
+        if v.is_zero()? {
+            return Ok(Some(MPI::new()?));
+        }
+        if MPI::jacobi(v, p)? != 1 {
+            return Ok(None);
+        }
+
+        if p.get_bit(1)? {
+            // p == 3 (mod 4)
+            let e = p.mp_add_u32(1)?.mp_shr(2)?;
+            return Ok(Some(MPI::powmod(v, &e, p)?));
+        }
+
+        // Tonelli-Shanks
+        let p_minus_1 = p.mp_sub_u32(1)?;
+        let mut s = 0;
+        while !p_minus_1.get_bit(s)? {
+            s += 1;
+        }
+        let q = p_minus_1.mp_shr(s)?;
+
+        let mut z = MPI::new_from_u32(2)?;
+        while MPI::jacobi(&z, p)? != -1 {
+            z.mp_add_u32_assign(1)?;
+        }
+
+        let one = MPI::new_from_u32(1)?;
+        let mut m = s;
+        let mut c = MPI::powmod(&z, &q, p)?;
+        let mut t = MPI::powmod(v, &q, p)?;
+        let mut r = MPI::powmod(v, &q.mp_add_u32(1)?.mp_shr(1)?, p)?;
+
+        while !t.equals(&one)? {
+            let mut i = 0;
+            let mut t2i = t.clone();
+            while !t2i.equals(&one)? {
+                t2i = MPI::mulmod(&t2i, &t2i, p)?;
+                i += 1;
+                if i == m {
+                    return Ok(None);
+                }
+            }
+
+            let mut b = c.clone();
+            for _ in 0..(m - i - 1) {
+                b = MPI::mulmod(&b, &b, p)?;
+            }
+
+            m = i;
+            c = MPI::mulmod(&b, &b, p)?;
+            t = MPI::mulmod(&t, &c, p)?;
+            r = MPI::mulmod(&r, &b, p)?;
+        }
+
+        Ok(Some(r))
+    }
+}
+
+fn point_at_infinity() -> Error {
+    Error::with_message(
+        ErrorType::InvalidInput,
+        "Result is the point at infinity".to_owned(),
+    )
+}
+
+fn invalid_point() -> Error {
+    Error::with_message(ErrorType::InvalidInput, "Invalid EC point".to_owned())
+}
+
+/// A point on an elliptic curve in affine coordinates
+///
+/// Only `mul_base` uses Botan's constant time scalar multiplication.
+/// The other operations are computed in variable time in this crate, as
+/// the library has no point arithmetic interface, and their timing
+/// reveals the scalars and points they are used with. They must only be
+/// used with public values; `mul_vartime` in particular must never be
+/// given a secret scalar such as a private key.
+#[derive(Clone, Debug)]
+pub struct EcPoint {
+    params: CurveParams,
+    x: MPI,
+    y: MPI,
+}
+
+impl EcPoint {
+    /// Return the generator of the named curve
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let g = botan::EcPoint::generator("secp256r1").unwrap();
+    /// assert_eq!(g.encode(true).unwrap().len(), 33);
+    /// ```
+    pub fn generator(curve: &str) -> Result<EcPoint> {
+        let params = CurveParams::load(curve)?;
+        let x = params.base_x.clone();
+        let y = params.base_y.clone();
+        Ok(EcPoint { params, x, y })
+    }
+
+    /// Create a point from affine coordinates, checking it is on the curve
+    pub fn from_affine(curve: &str, x: &MPI, y: &MPI) -> Result<EcPoint> {
+        let params = CurveParams::load(curve)?;
+        if !params.on_curve(x, y)? {
+            return Err(invalid_point());
+        }
+        Ok(EcPoint {
+            params,
+            x: x.clone(),
+            y: y.clone(),
+        })
+    }
+
+    /// Decode a compressed or uncompressed SEC1 point
+    ///
+    /// The point is checked to be on the curve, but not that it is in
+    /// the prime order subgroup.
+    pub fn decode(curve: &str, bytes: &[u8]) -> Result<EcPoint> {
+        let params = CurveParams::load(curve)?;
+        let field_len = params.field_len()?;
+
+        match bytes.first() {
+            Some(&0x04) if bytes.len() == 1 + 2 * field_len => {
+                let x = MPI::new_from_bytes(&bytes[1..1 + field_len])?;
+                let y = MPI::new_from_bytes(&bytes[1 + field_len..])?;
+                if !params.on_curve(&x, &y)? {
+                    return Err(invalid_point());
+                }
+                Ok(EcPoint { params, x, y })
+            }
+            Some(&tag) if (tag == 0x02 || tag == 0x03) && bytes.len() == 1 + field_len => {
+                let x = MPI::new_from_bytes(&bytes[1..])?;
+                if !params.in_field(&x)? {
+                    return Err(invalid_point());
+                }
+
+                let mut y = params
+                    .sqrt(&params.curve_rhs(&x)?)?
+                    .ok_or_else(invalid_point)?;
+
+                if y.is_odd()? != (tag == 0x03) {
+                    if y.is_zero()? {
+                        return Err(invalid_point());
+                    }
+                    y = params.p.mp_sub(&y)?;
+                }
+
+                Ok(EcPoint { params, x, y })
+            }
+            _ => Err(invalid_point()),
+        }
+    }
+
+    /// Check if the affine point (x,y) is on the named curve
+    pub fn is_on_curve(curve: &str, x: &MPI, y: &MPI) -> Result<bool> {
+        CurveParams::load(curve)?.on_curve(x, y)
+    }
+
+    /// Encode the point in SEC1 format, optionally compressed
+    pub fn encode(&self, compressed: bool) -> Result<Vec<u8>> {
+        let field_len = self.params.field_len()?;
+
+        let mut out = if compressed {
+            vec![if self.y.is_odd()? { 0x03 } else { 0x02 }]
+        } else {
+            vec![0x04]
+        };
+
+        let coords = if compressed {
+            vec![&self.x]
+        } else {
+            vec![&self.x, &self.y]
+        };
+
+        for coord in coords {
+            out.extend_from_slice(&coord.to_bytes_padded(field_len)?);
+        }
+
+        Ok(out)
+    }
+
+    /// Return the name of the curve this point is on
+    pub fn curve_name(&self) -> &str {
+        &self.params.name
+    }
+
+    /// Return the affine x coordinate
+    pub fn x(&self) -> &MPI {
+        &self.x
+    }
+
+    /// Return the affine y coordinate
+    pub fn y(&self) -> &MPI {
+        &self.y
+    }
+
+    /// Return the order of the curve's prime order subgroup
+    pub fn order(&self) -> &MPI {
+        &self.params.order
+    }
+
+    /// Multiply the generator of the named curve by the scalar k
+    ///
+    /// This uses Botan's constant time (blinded) base point multiplication
+    /// and is suitable for secret scalars.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let k = botan::MPI::new_from_u32(2).unwrap();
+    /// let p = botan::EcPoint::mul_base("secp256r1", &k).unwrap();
+    /// let g = botan::EcPoint::generator("secp256r1").unwrap();
+    /// assert_eq!(p, g.add(&g).unwrap());
+    /// ```
+    pub fn mul_base(curve: &str, k: &MPI) -> Result<EcPoint> {
+        let params = CurveParams::load(curve)?;
+
+        let (_, k) = k.divrem(&params.order)?;
+        let k = if k.is_negative()? {
+            k.mp_add(&params.order)?
+        } else {
+            k
+        };
+
+        if k.is_zero()? {
+            return Err(point_at_infinity());
+        }
+
+        let key = Privkey::load_ecdsa(&k, curve)?;
+        let x = key.get_field("public_x")?;
+        let y = key.get_field("public_y")?;
+        Ok(EcPoint { params, x, y })
+    }
+
+    /// Multiply this point by the scalar k in variable time
+    ///
+    /// # Security
+    ///
+    /// The time taken depends on the bits of `k`, so it must not be a
+    /// secret. Use `mul_base`, or key agreement with a `Privkey`, for
+    /// secret scalars.
+    pub fn mul_vartime(&self, k: &MPI) -> Result<EcPoint> {
+        if k.is_negative()? {
+            return self.negate()?.mul_vartime(&-k);
+        }
+
+        let mut acc: Option<EcPoint> = None;
+
+        for i in (0..k.bit_count()?).rev() {
+            acc = match acc {
+                Some(p) => p.double_opt()?,
+                None => None,
+            };
+
+            if k.get_bit(i)? {
+                acc = match acc {
+                    Some(p) => p.add_opt(self)?,
+                    None => Some(self.clone()),
+                };
+            }
+        }
+
+        acc.ok_or_else(point_at_infinity)
+    }
+
+    /// Add two points on the same curve in variable time
+    ///
+    /// # Security
+    ///
+    /// The time taken depends on the points, so they must not be secret.
+    pub fn add(&self, other: &EcPoint) -> Result<EcPoint> {
+        self.add_opt(other)?.ok_or_else(point_at_infinity)
+    }
+
+    /// Return the negation of this point
+    pub fn negate(&self) -> Result<EcPoint> {
+        let y = if self.y.is_zero()? {
+            self.y.clone()
+        } else {
+            self.params.p.mp_sub(&self.y)?
+        };
+
+        Ok(EcPoint {
+            params: self.params.clone(),
+            x: self.x.clone(),
+            y,
+        })
+    }
+
+    fn add_opt(&self, other: &EcPoint) -> Result<Option<EcPoint>> {
+        if self.params.name != other.params.name {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "Cannot add points on different curves".to_owned(),
+            ));
+        }
+
+        if self.x.equals(&other.x)? {
+            if self.y.equals(&other.y)? {
+                return self.double_opt();
+            }
+            return Ok(None);
+        }
+
+        let params = &self.params;
+        let dy = params.reduce(&other.y.mp_sub(&self.y)?)?;
+        let dx = params.reduce(&other.x.mp_sub(&self.x)?)?;
+        let lambda = MPI::mulmod(&dy, &MPI::modular_inverse(&dx, &params.p)?, &params.p)?;
+
+        self.finish_add(&lambda, &other.x)
+    }
+
+    fn double_opt(&self) -> Result<Option<EcPoint>> {
+        if self.y.is_zero()? {
+            return Ok(None);
+        }
+
+        let params = &self.params;
+        let x2 = MPI::mulmod(&self.x, &self.x, &params.p)?;
+        let three = MPI::new_from_u32(3)?;
+        let num = params.reduce(&MPI::mulmod(&x2, &three, &params.p)?.mp_add(&params.a)?)?;
+        let denom = params.reduce(&self.y.mp_shl(1)?)?;
+        let lambda = MPI::mulmod(&num, &MPI::modular_inverse(&denom, &params.p)?, &params.p)?;
+
+        self.finish_add(&lambda, &self.x)
+    }
+
+    // Given the slope between self and a point with x coordinate x2,
+    // compute the affine sum
+    fn finish_add(&self, lambda: &MPI, x2: &MPI) -> Result<Option<EcPoint>> {
+        let params = &self.params;
+        let l2 = MPI::mulmod(lambda, lambda, &params.p)?;
+        let x3 = params.reduce(&l2.mp_sub(&self.x)?.mp_sub(x2)?)?;
+        let y3 = MPI::mulmod(lambda, &params.reduce(&self.x.mp_sub(&x3)?)?, &params.p)?;
+        let y3 = params.reduce(&y3.mp_sub(&self.y)?)?;
+
+        Ok(Some(EcPoint {
+            params: params.clone(),
+            x: x3,
+            y: y3,
+        }))
+    }
+}
+
+impl PartialEq for EcPoint {
+    fn eq(&self, other: &EcPoint) -> bool {
+        self.params.name == other.params.name && self.x == other.x && self.y == other.y
+    }
+}
+
+impl Eq for EcPoint {}
+
+/// Recover the ECDSA public key which produced a signature
+///
+/// `signature` is the fixed length `r || s` encoding which Botan
+/// produces, and `recovery_id` (0 to 3) selects which of the candidate
+/// keys is returned; it is the `v` value of protocols which transmit
+/// `(r, s, v)`, after removing any offset such as Ethereum's 27.
+/// `msg_hash` is the hash of the message that was signed, not the
+/// message itself.
+///
+/// The recovery is computed in variable time, which is fine since all
+/// of the inputs are public.
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::Privkey::create("ECDSA", "secp256k1", &mut rng).unwrap();
+/// let mut hash = botan::HashFunction::sha256().unwrap();
+/// hash.update(b"message").unwrap();
+/// let digest = hash.finish().unwrap();
+/// let sig = key.sign(&digest, "Raw", &mut rng).unwrap();
+///
+/// let expected = key.pubkey().unwrap().der_encode().unwrap();
+/// let found = (0..4).any(|v| match botan::recover_ecdsa_pubkey("secp256k1", &digest, &sig, v) {
+///     Ok(k) => k.der_encode().unwrap() == expected,
+///     Err(_) => false,
+/// });
+/// assert!(found);
+/// ```
+pub fn recover_ecdsa_pubkey(
+    curve: &str,
+    msg_hash: &[u8],
+    signature: &[u8],
+    recovery_id: u8,
+) -> Result<Pubkey> {
+    if recovery_id > 3 {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            "ECDSA recovery id must be between 0 and 3".to_owned(),
+        ));
+    }
+
+    let g = EcPoint::generator(curve)?;
+    let params = &g.params;
+    let n = &params.order;
+    let order_bits = n.bit_count()?;
+    let order_len = n.byte_count()?;
+
+    if signature.len() != 2 * order_len {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            "Unexpected ECDSA signature length".to_owned(),
+        ));
+    }
+
+    let r = MPI::new_from_bytes(&signature[..order_len])?;
+    let s = MPI::new_from_bytes(&signature[order_len..])?;
+    if r.is_zero()? || s.is_zero()? || r >= *n || s >= *n {
+        return Err(Error::with_message(
+            ErrorType::InvalidInput,
+            "Invalid ECDSA signature".to_owned(),
+        ));
+    }
+
+    // The x coordinate of the signer's nonce point, which was reduced
+    // modulo the group order to produce r
+    let x = if recovery_id & 2 == 2 {
+        r.mp_add(n)?
+    } else {
+        r.clone()
+    };
+    if !params.in_field(&x)? {
+        return Err(invalid_point());
+    }
+    let mut encoded_r = vec![0x02 | (recovery_id & 1)];
+    encoded_r.extend_from_slice(&x.to_bytes_padded(params.field_len()?)?);
+    let big_r = EcPoint::decode(curve, &encoded_r)?;
+
+    // The leftmost order_bits bits of the hash, as in signing
+    let mut e = MPI::new_from_bytes(msg_hash)?;
+    let hash_bits = 8 * msg_hash.len();
+    if hash_bits > order_bits {
+        e = e.mp_shr(hash_bits - order_bits)?;
+    }
+    let (_, e) = e.divrem(n)?;
+
+    // Q = r^-1 * (s*R - e*G)
+    let r_inv = MPI::modular_inverse(&r, n)?;
+    let u1 = MPI::mulmod(&n.mp_sub(&e)?, &r_inv, n)?;
+    let u2 = MPI::mulmod(&s, &r_inv, n)?;
+
+    let s_r = big_r.mul_vartime(&u2)?;
+    let q = if u1.is_zero()? {
+        s_r
+    } else {
+        g.mul_vartime(&u1)?.add(&s_r)?
+    };
+
+    Pubkey::load_ecdsa(&q.x, &q.y, curve)
+}
+
+#[cfg(feature = "botan3")]
+struct Oid {
+    obj: botan_asn1_oid_t,
+}
+
+#[cfg(feature = "botan3")]
+botan_impl_drop!(Oid, botan_oid_destroy);
+
+#[cfg(feature = "botan3")]
+impl Oid {
+    fn from_string(oid: &str) -> Result<Oid> {
+        let oid = make_cstr(oid)?;
+        let obj = botan_init!(botan_oid_from_string, oid.as_ptr())?;
+        Ok(Oid { obj })
+    }
+}
+
+/// An elliptic curve group
+///
+/// Besides the builtin named curves, groups can be created from explicit
+/// domain parameters and then used for key generation with
+/// `Privkey::create_ec`. This requires Botan 3.8 or later.
+#[cfg(feature = "botan3")]
+#[derive(Debug)]
+pub struct EcGroup {
+    obj: botan_ec_group_t,
+}
+
+#[cfg(feature = "botan3")]
+botan_impl_drop!(EcGroup, botan_ec_group_destroy);
+
+#[cfg(feature = "botan3")]
+unsafe impl Send for EcGroup {}
+#[cfg(feature = "botan3")]
+unsafe impl Sync for EcGroup {}
+
+#[cfg(feature = "botan3")]
+impl EcGroup {
+    pub(crate) fn handle(&self) -> botan_ec_group_t {
+        self.obj
+    }
+
+    /// Return true if groups may be created from explicit parameters
+    pub fn supports_application_specific_group() -> Result<bool> {
+        Capability::EcGroup.require()?;
+        let mut out = 0;
+        botan_call!(botan_ec_group_supports_application_specific_group, &mut out)?;
+        Ok(out == 1)
+    }
+
+    /// Return true if the named group is available
+    pub fn supports_named_group(name: &str) -> Result<bool> {
+        Capability::EcGroup.require()?;
+        let name = make_cstr(name)?;
+        let mut out = 0;
+        botan_call!(botan_ec_group_supports_named_group, name.as_ptr(), &mut out)?;
+        Ok(out == 1)
+    }
+
+    /// Load a named group, such as "secp256r1" or "brainpool256r1"
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let group = botan::EcGroup::from_name("secp256r1").unwrap();
+    /// assert_eq!(group.order().unwrap().bit_count().unwrap(), 256);
+    /// ```
+    pub fn from_name(name: &str) -> Result<EcGroup> {
+        Capability::EcGroup.require()?;
+        let name = make_cstr(name)?;
+        let obj = botan_init!(botan_ec_group_from_name, name.as_ptr())?;
+        Ok(EcGroup { obj })
+    }
+
+    /// Load a group by its object identifier, such as "1.2.840.10045.3.1.7"
+    pub fn from_oid(oid: &str) -> Result<EcGroup> {
+        Capability::EcGroup.require()?;
+        let oid = Oid::from_string(oid)?;
+        let obj = botan_init!(botan_ec_group_from_oid, oid.obj)?;
+        Ok(EcGroup { obj })
+    }
+
+    /// Load a group from its DER encoded domain parameters
+    pub fn from_der(der: &[u8]) -> Result<EcGroup> {
+        Capability::EcGroup.require()?;
+        let obj = botan_init!(botan_ec_group_from_ber, der.as_ptr(), der.len())?;
+        Ok(EcGroup { obj })
+    }
+
+    /// Load a group from its PEM encoded domain parameters
+    pub fn from_pem(pem: &str) -> Result<EcGroup> {
+        Capability::EcGroup.require()?;
+        let pem = make_cstr(pem)?;
+        let obj = botan_init!(botan_ec_group_from_pem, pem.as_ptr())?;
+        Ok(EcGroup { obj })
+    }
+
+    /// Create a group from explicit domain parameters
+    ///
+    /// The group is registered under the given object identifier, so it
+    /// can afterwards be loaded with `from_oid`. Only prime order curves
+    /// (cofactor 1) are supported.
+    pub fn from_params(
+        oid: &str,
+        p: &MPI,
+        a: &MPI,
+        b: &MPI,
+        base_x: &MPI,
+        base_y: &MPI,
+        order: &MPI,
+    ) -> Result<EcGroup> {
+        Capability::EcGroup.require()?;
+        let oid = Oid::from_string(oid)?;
+        let obj = botan_init!(
+            botan_ec_group_from_params,
+            oid.obj,
+            p.handle(),
+            a.handle(),
+            b.handle(),
+            base_x.handle(),
+            base_y.handle(),
+            order.handle()
+        )?;
+        Ok(EcGroup { obj })
+    }
+
+    /// Register `name` as an alias for the object identifier of this group
+    ///
+    /// This does not make the name usable as a curve name with
+    /// `Privkey::create`; keys on the group are created with
+    /// `Privkey::create_ec`.
+    pub fn register_name(&self, name: &str) -> Result<()> {
+        let name = make_cstr(name)?;
+        let oid = Oid {
+            obj: botan_init!(botan_ec_group_get_curve_oid, self.obj)?,
+        };
+        botan_call!(botan_oid_register, oid.obj, name.as_ptr())
+    }
+
+    /// Return the prime of the field the curve is defined over
+    pub fn p(&self) -> Result<MPI> {
+        let obj = botan_init!(botan_ec_group_get_p, self.obj)?;
+        Ok(MPI::from_handle(obj))
+    }
+
+    /// Return the curve parameter a
+    pub fn a(&self) -> Result<MPI> {
+        let obj = botan_init!(botan_ec_group_get_a, self.obj)?;
+        Ok(MPI::from_handle(obj))
+    }
+
+    /// Return the curve parameter b
+    pub fn b(&self) -> Result<MPI> {
+        let obj = botan_init!(botan_ec_group_get_b, self.obj)?;
+        Ok(MPI::from_handle(obj))
+    }
+
+    /// Return the x coordinate of the group generator
+    pub fn base_x(&self) -> Result<MPI> {
+        let obj = botan_init!(botan_ec_group_get_g_x, self.obj)?;
+        Ok(MPI::from_handle(obj))
+    }
+
+    /// Return the y coordinate of the group generator
+    pub fn base_y(&self) -> Result<MPI> {
+        let obj = botan_init!(botan_ec_group_get_g_y, self.obj)?;
+        Ok(MPI::from_handle(obj))
+    }
+
+    /// Return the order of the group generator
+    pub fn order(&self) -> Result<MPI> {
+        let obj = botan_init!(botan_ec_group_get_order, self.obj)?;
+        Ok(MPI::from_handle(obj))
+    }
+}
+
+#[cfg(feature = "botan3")]
+impl PartialEq for EcGroup {
+    fn eq(&self, other: &EcGroup) -> bool {
+        botan_bool_in_rc!(botan_ec_group_equal, self.obj, other.obj)
+            .expect("botan_ec_group_equal succeeded")
+    }
+}
+
+#[cfg(feature = "botan3")]
+impl Eq for EcGroup {}
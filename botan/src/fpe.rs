@@ -29,6 +29,12 @@ pub struct FPE {
 
 botan_impl_drop!(FPE, botan_fpe_destroy);
 
+// Not Sync: encrypt and decrypt take &self but update an internal MAC
+unsafe impl Send for FPE {}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for FPE {}
+
 impl FPE {
     /// Create a new FPE instance, FE1 scheme
     /// Rounds should be 16 or higher for best security
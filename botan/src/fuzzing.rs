@@ -0,0 +1,343 @@
+//! Inputs for fuzzing and property testing code which uses this crate
+//!
+//! With the `arbitrary` feature, the algorithm enums (such as `HashAlgo`),
+//! `KeySpec`, `MPI` and the input types of this module implement
+//! `arbitrary::Arbitrary`, for use with `cargo fuzz`. With the `proptest`
+//! feature the algorithm enums implement `proptest::arbitrary::Arbitrary`,
+//! and `fuzzing::strategies` has strategies for the other inputs.
+//!
+//! Generated algorithm enums are never `Custom`, but the library may not
+//! have been built with every algorithm, so creating an object can still
+//! fail with `ErrorType::NotImplemented`.
+
+use crate::utils::*;
+
+use crate::algo::AeadAlgo;
+use crate::cipher::{Cipher, CipherDirection};
+
+const MAX_DER_DEPTH: usize = 4;
+const MAX_DER_CHILDREN: u8 = 4;
+const MAX_NONCE_LENGTH: usize = 64;
+
+// Where the generators get their choices from: the fuzzer's input, or a
+// seed chosen by proptest. Once exhausted every choice is zero, which
+// always leads to a small output.
+trait Choices {
+    fn byte(&mut self) -> u8;
+
+    fn below(&mut self, n: u8) -> u8 {
+        self.byte() % n
+    }
+
+    fn bytes(&mut self, max_len: u8) -> Vec<u8> {
+        let len = self.below(max_len + 1);
+        (0..len).map(|_| self.byte()).collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+struct Seed<'a> {
+    seed: &'a [u8],
+}
+
+#[cfg(feature = "proptest")]
+impl Choices for Seed<'_> {
+    fn byte(&mut self) -> u8 {
+        match self.seed.split_first() {
+            Some((b, rest)) => {
+                self.seed = rest;
+                *b
+            }
+            None => 0,
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl Choices for arbitrary::Unstructured<'_> {
+    fn byte(&mut self) -> u8 {
+        self.arbitrary().unwrap_or(0)
+    }
+}
+
+fn push_der_length(out: &mut Vec<u8>, len: usize) {
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+}
+
+fn push_der(out: &mut Vec<u8>, tag: u8, contents: &[u8]) {
+    out.push(tag);
+    push_der_length(out, contents.len());
+    out.extend_from_slice(contents);
+}
+
+// Generate a single DER encoded value. Every encoding is valid DER, but
+// the structure is random, so most will be rejected by a parser which
+// expects some particular type.
+fn generate_der(c: &mut dyn Choices, depth: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    let kinds = if depth < MAX_DER_DEPTH { 10 } else { 8 };
+
+    match c.below(kinds) {
+        // BOOLEAN
+        0 => push_der(&mut out, 0x01, &[if c.byte() & 1 == 1 { 0xFF } else { 0 }]),
+        // INTEGER, with any redundant leading bytes removed
+        1 => {
+            let mut value = c.bytes(16);
+            if value.is_empty() {
+                value.push(0);
+            }
+            while value.len() > 1
+                && ((value[0] == 0 && value[1] < 0x80) || (value[0] == 0xFF && value[1] >= 0x80))
+            {
+                value.remove(0);
+            }
+            push_der(&mut out, 0x02, &value);
+        }
+        // BIT STRING with no unused bits
+        2 => {
+            let mut value = vec![0];
+            value.extend_from_slice(&c.bytes(32));
+            push_der(&mut out, 0x03, &value);
+        }
+        // OCTET STRING
+        3 => push_der(&mut out, 0x04, &c.bytes(64)),
+        // NULL
+        4 => push_der(&mut out, 0x05, &[]),
+        // OBJECT IDENTIFIER under 1.2, with small arcs
+        5 => {
+            let mut value = vec![0x2A];
+            value.extend(c.bytes(8).iter().map(|b| b & 0x7F));
+            push_der(&mut out, 0x06, &value);
+        }
+        // UTF8String
+        6 => {
+            let value: Vec<u8> = c.bytes(32).iter().map(|b| b'a' + b % 26).collect();
+            push_der(&mut out, 0x0C, &value);
+        }
+        // UTCTime
+        7 => push_der(&mut out, 0x17, b"250101000000Z"),
+        // SEQUENCE, or a constructed context specific [0] tag
+        kind => {
+            let mut contents = Vec::new();
+            for _ in 0..c.below(MAX_DER_CHILDREN + 1) {
+                contents.extend_from_slice(&generate_der(c, depth + 1));
+            }
+            push_der(&mut out, if kind == 8 { 0x30 } else { 0xA0 }, &contents);
+        }
+    }
+
+    out
+}
+
+// Break a DER encoding made by generate_der in one of several ways, each
+// of which makes it invalid
+fn malform_der(c: &mut dyn Choices, der: &[u8]) -> Vec<u8> {
+    let header_len = if der[1] < 0x80 {
+        2
+    } else {
+        2 + (der[1] & 0x7F) as usize
+    };
+    let contents = &der[header_len..];
+
+    match c.below(4) {
+        // Truncated
+        0 => der[..(c.byte() as usize % der.len())].to_vec(),
+        // Followed by trailing data
+        1 => {
+            let mut out = der.to_vec();
+            out.push(c.byte());
+            out
+        }
+        // Using BER's indefinite length form
+        2 => {
+            let mut out = vec![der[0], 0x80];
+            out.extend_from_slice(contents);
+            out.extend_from_slice(&[0, 0]);
+            out
+        }
+        // Using a length of more bytes than needed
+        _ => {
+            let mut out = vec![der[0], 0x84];
+            out.extend_from_slice(&(contents.len() as u32).to_be_bytes());
+            out.extend_from_slice(contents);
+            out
+        }
+    }
+}
+
+/// Return the nonce lengths up to 64 bytes which are accepted by an
+/// AEAD, followed by those which are not
+pub fn nonce_lengths(algo: &AeadAlgo) -> Result<(Vec<usize>, Vec<usize>)> {
+    let cipher = Cipher::new(algo, CipherDirection::Encrypt)?;
+    let mut valid = Vec::new();
+    let mut invalid = Vec::new();
+    for len in 0..=MAX_NONCE_LENGTH {
+        if cipher.valid_nonce_length(len)? {
+            valid.push(len);
+        } else {
+            invalid.push(len);
+        }
+    }
+    Ok((valid, invalid))
+}
+
+/// A valid DER encoding of a random ASN.1 structure
+///
+/// The structure is built from common universal types, `SEQUENCE` and
+/// context specific tags, so it is well-formed but usually not what a
+/// decoder expects.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DerBlob(pub Vec<u8>);
+
+/// An encoding which is almost, but not quite, valid DER
+///
+/// It is a `DerBlob` which was truncated, followed by trailing data, or
+/// given a length in BER's indefinite or non-minimal forms.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MalformedDer(pub Vec<u8>);
+
+/// An AEAD algorithm along with a nonce, which may or may not have a
+/// length the algorithm accepts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AeadNonce {
+    /// The AEAD
+    pub algo: AeadAlgo,
+    /// The nonce
+    pub nonce: Vec<u8>,
+    /// True if the nonce has a valid length for `algo`
+    pub valid: bool,
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DerBlob {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<DerBlob> {
+        Ok(DerBlob(generate_der(u, 0)))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for MalformedDer {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<MalformedDer> {
+        let der = generate_der(u, 0);
+        Ok(MalformedDer(malform_der(u, &der)))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for AeadNonce {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<AeadNonce> {
+        let algo: AeadAlgo = u.arbitrary()?;
+        let (valid_lengths, invalid_lengths) =
+            nonce_lengths(&algo).map_err(|_| arbitrary::Error::IncorrectFormat)?;
+
+        let valid = invalid_lengths.is_empty() || (!valid_lengths.is_empty() && u.arbitrary()?);
+        let len = *u.choose(if valid {
+            &valid_lengths
+        } else {
+            &invalid_lengths
+        })?;
+        let nonce = u.bytes(len)?.to_vec();
+
+        Ok(AeadNonce { algo, nonce, valid })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for KeySpec {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<KeySpec> {
+        let mod_keylen = u.int_in_range(1..=16)?;
+        let min_keylen = mod_keylen * u.int_in_range(0..=8)?;
+        let max_keylen = min_keylen + mod_keylen * u.int_in_range(0..=8)?;
+        KeySpec::new(min_keylen, max_keylen, mod_keylen)
+            .map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for crate::mp::MPI {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<crate::mp::MPI> {
+        let len = u.int_in_range(0..=64)?;
+        let negative: bool = u.arbitrary()?;
+        let mut mpi = crate::mp::MPI::new_from_bytes(u.bytes(len)?)
+            .map_err(|_| arbitrary::Error::IncorrectFormat)?;
+        if negative {
+            mpi.flip_sign()
+                .map_err(|_| arbitrary::Error::IncorrectFormat)?;
+        }
+        Ok(mpi)
+    }
+}
+
+/// `proptest` strategies for inputs which depend on parameters, or are
+/// not a type of this crate
+#[cfg(feature = "proptest")]
+pub mod strategies {
+    use super::{generate_der, malform_der, nonce_lengths, Seed};
+    use crate::algo::AeadAlgo;
+    use crate::utils::*;
+
+    use proptest::prelude::*;
+
+    const SEED_LENGTH: usize = 256;
+    const MAX_KEY_LENGTH: usize = 256;
+
+    /// Valid DER encodings of random ASN.1 structures, see `DerBlob`
+    pub fn der_blob() -> impl Strategy<Value = Vec<u8>> {
+        prop::collection::vec(any::<u8>(), 0..SEED_LENGTH)
+            .prop_map(|seed| generate_der(&mut Seed { seed: &seed }, 0))
+    }
+
+    /// Encodings which are almost, but not quite, valid DER, see
+    /// `MalformedDer`
+    pub fn malformed_der() -> impl Strategy<Value = Vec<u8>> {
+        prop::collection::vec(any::<u8>(), 0..SEED_LENGTH).prop_map(|seed| {
+            let mut seed = Seed { seed: &seed };
+            let der = generate_der(&mut seed, 0);
+            malform_der(&mut seed, &der)
+        })
+    }
+
+    /// Key specifications such as a cipher or MAC may have
+    pub fn key_spec() -> impl Strategy<Value = KeySpec> {
+        (1..=16usize, 0..=8usize, 0..=8usize).prop_map(|(m, min, extra)| {
+            KeySpec::new(m * min, m * (min + extra), m).expect("key spec is valid")
+        })
+    }
+
+    /// Key lengths of at most 256 bytes which `spec` accepts (if `valid`)
+    /// or rejects
+    ///
+    /// # Panics
+    /// Panics if there is no such key length
+    pub fn key_length(spec: &KeySpec, valid: bool) -> impl Strategy<Value = usize> {
+        let lengths: Vec<usize> = (0..=MAX_KEY_LENGTH)
+            .filter(|len| spec.is_valid_keylength(*len) == valid)
+            .collect();
+        prop::sample::select(lengths)
+    }
+
+    /// Nonces with a length `algo` accepts (if `valid`) or rejects
+    ///
+    /// # Errors
+    /// Fails if the library does not support `algo`
+    ///
+    /// # Panics
+    /// The strategy panics if there is no such nonce length up to 64 bytes
+    pub fn nonce(algo: &AeadAlgo, valid: bool) -> Result<impl Strategy<Value = Vec<u8>>> {
+        let (valid_lengths, invalid_lengths) = nonce_lengths(algo)?;
+        let lengths = if valid {
+            valid_lengths
+        } else {
+            invalid_lengths
+        };
+        Ok(prop::sample::select(lengths)
+            .prop_flat_map(|len| prop::collection::vec(any::<u8>(), len)))
+    }
+}
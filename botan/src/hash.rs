@@ -1,11 +1,22 @@
 use crate::utils::*;
 use botan_sys::*;
 
+use crate::metadata::{lookup, Metadata};
+use crate::probe::AlgorithmKind;
+
 #[derive(Debug)]
 /// A hash function object
+///
+/// The state of a hash in progress can be copied within the process with
+/// `duplicate`, but not saved: the library provides no way of exporting
+/// or importing it, so a hash cannot be checkpointed and resumed after a
+/// restart. Jobs which need that can hash fixed size chunks separately,
+/// and then hash the list of chunk digests.
 pub struct HashFunction {
     obj: botan_hash_t,
+    algo_name: String,
     output_length: usize,
+    block_size: usize,
 }
 
 impl Clone for HashFunction {
@@ -16,6 +27,16 @@ impl Clone for HashFunction {
 
 botan_impl_drop!(HashFunction, botan_hash_destroy);
 
+unsafe impl Send for HashFunction {}
+unsafe impl Sync for HashFunction {}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for HashFunction {
+    fn zeroize(&mut self) {
+        let _ = self.clear();
+    }
+}
+
 impl HashFunction {
     /// Create a new hash function
     ///
@@ -26,11 +47,68 @@ impl HashFunction {
     /// assert!(botan::HashFunction::new("SHA-256").is_ok());
     /// assert!(botan::HashFunction::new("Hash9000").is_err());
     /// ```
-    pub fn new(name: &str) -> Result<HashFunction> {
+    pub fn new<N: AsRef<str>>(name: N) -> Result<HashFunction> {
+        let name = name.as_ref();
         let obj = botan_init!(botan_hash_init, make_cstr(name)?.as_ptr(), 0u32)?;
-        let output_length = botan_usize!(botan_hash_output_length, obj)?;
+        let metadata = lookup(AlgorithmKind::Hash, 0, name, || {
+            Ok(Metadata {
+                algo_name: call_botan_ffi_returning_string(32, &|out_buf, out_len| unsafe {
+                    botan_hash_name(obj, out_buf as *mut c_char, out_len)
+                })?,
+                output_length: botan_usize!(botan_hash_output_length, obj)?,
+                block_size: botan_usize!(botan_hash_block_size, obj)?,
+                ..Default::default()
+            })
+        })?;
 
-        Ok(HashFunction { obj, output_length })
+        Ok(HashFunction {
+            obj,
+            algo_name: metadata.algo_name,
+            output_length: metadata.output_length,
+            block_size: metadata.block_size,
+        })
+    }
+
+    /// Create a new SHA-256 hash function
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let hash = botan::HashFunction::sha256().unwrap();
+    /// assert_eq!(hash.output_length().unwrap(), 32);
+    /// ```
+    pub fn sha256() -> Result<HashFunction> {
+        HashFunction::new("SHA-256")
+    }
+
+    /// Create a new SHA-384 hash function
+    pub fn sha384() -> Result<HashFunction> {
+        HashFunction::new("SHA-384")
+    }
+
+    /// Create a new SHA-512 hash function
+    pub fn sha512() -> Result<HashFunction> {
+        HashFunction::new("SHA-512")
+    }
+
+    /// Create a new SHA-512-256 hash function
+    pub fn sha512_256() -> Result<HashFunction> {
+        HashFunction::new("SHA-512-256")
+    }
+
+    /// Create a new SHA-3(256) hash function
+    pub fn sha3_256() -> Result<HashFunction> {
+        HashFunction::new("SHA-3(256)")
+    }
+
+    /// Create a new SHA-3(512) hash function
+    pub fn sha3_512() -> Result<HashFunction> {
+        HashFunction::new("SHA-3(512)")
+    }
+
+    /// Create a new BLAKE2b(512) hash function
+    pub fn blake2b_512() -> Result<HashFunction> {
+        HashFunction::new("BLAKE2b(512)")
     }
 
     /// Return the name of this algorithm which may or may not exactly
@@ -43,9 +121,7 @@ impl HashFunction {
     /// assert_eq!(hash.algo_name().unwrap(), "SHA-384");
     /// ```
     pub fn algo_name(&self) -> Result<String> {
-        call_botan_ffi_returning_string(32, &|out_buf, out_len| unsafe {
-            botan_hash_name(self.obj, out_buf as *mut c_char, out_len)
-        })
+        Ok(self.algo_name.clone())
     }
 
     /// Return the output length of the hash function, in bytes
@@ -67,7 +143,12 @@ impl HashFunction {
     /// assert_eq!(hash.block_size().unwrap(), 64);
     /// ```
     pub fn block_size(&self) -> Result<usize> {
-        botan_usize!(botan_hash_block_size, self.obj)
+        Ok(self.block_size)
+    }
+
+    // Adds the algorithm and operation to errors
+    fn context(&self, operation: &'static str) -> impl Fn(Error) -> Error + '_ {
+        move |e| e.in_context(&self.algo_name, operation)
     }
 
     /// Add data to a hash computation, may be called many times
@@ -79,7 +160,26 @@ impl HashFunction {
     /// hash.update(&[4,5,6]).unwrap();
     /// ```
     pub fn update(&mut self, data: &[u8]) -> Result<()> {
-        botan_call!(botan_hash_update, self.obj, data.as_ptr(), data.len())?;
+        botan_call!(botan_hash_update, self.obj, data.as_ptr(), data.len())
+            .map_err(self.context("update"))
+    }
+
+    /// Add data which is split over several slices to a hash computation
+    ///
+    /// Accepts slices of `&[u8]`, `Vec<u8>` or `std::io::IoSlice`.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut hash = botan::HashFunction::new("SHA-256").unwrap();
+    /// hash.update_vectored(&[&[1,2,3][..], &[4,5,6][..]]).unwrap();
+    /// ```
+    pub fn update_vectored<B: core::ops::Deref<Target = [u8]>>(
+        &mut self,
+        data: &[B],
+    ) -> Result<()> {
+        for d in data {
+            self.update(d)?;
+        }
         Ok(())
     }
 
@@ -94,7 +194,8 @@ impl HashFunction {
     /// ```
     pub fn finish(&mut self) -> Result<Vec<u8>> {
         let mut output = vec![0; self.output_length];
-        botan_call!(botan_hash_final, self.obj, output.as_mut_ptr())?;
+        botan_call!(botan_hash_final, self.obj, output.as_mut_ptr())
+            .map_err(self.context("finish"))?;
         Ok(output)
     }
 
@@ -103,8 +204,7 @@ impl HashFunction {
     /// Basically the same as calling final, but without returning a
     /// result.
     pub fn clear(&mut self) -> Result<()> {
-        botan_call!(botan_hash_clear, self.obj)?;
-        Ok(())
+        botan_call!(botan_hash_clear, self.obj).map_err(self.context("clear"))
     }
 
     /// Copy hash object state to a new object, allowing prefixes of
@@ -120,13 +220,29 @@ impl HashFunction {
     /// hash2.update(&[4,5,6]);
     /// let result1 = hash.finish().unwrap(); // hash of 1,2,3
     /// let result2 = hash2.finish().unwrap(); // hash of 1,2,3,4,5,6
+    ///
+    /// // digests of a common prefix followed by different suffixes,
+    /// // hashing the prefix only once
+    /// let mut prefix = botan::HashFunction::new("SHA-256").unwrap();
+    /// prefix.update(b"common prefix").unwrap();
+    /// let digests: Vec<Vec<u8>> = [&b"one"[..], &b"two"[..]]
+    ///     .iter()
+    ///     .map(|suffix| {
+    ///         let mut hash = prefix.duplicate().unwrap();
+    ///         hash.update(suffix).unwrap();
+    ///         hash.finish().unwrap()
+    ///     })
+    ///     .collect();
+    /// assert_ne!(digests[0], digests[1]);
     /// ```
 
     pub fn duplicate(&self) -> Result<HashFunction> {
         let obj = botan_init!(botan_hash_copy_state, self.obj)?;
         Ok(HashFunction {
             obj,
+            algo_name: self.algo_name.clone(),
             output_length: self.output_length,
+            block_size: self.block_size,
         })
     }
 }
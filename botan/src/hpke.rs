@@ -0,0 +1,919 @@
+use crate::utils::*;
+
+use crate::cipher::{Cipher, CipherDirection};
+use crate::mac::MsgAuthCode;
+use crate::mp::MPI;
+use crate::pk_ops::KeyAgreement;
+use crate::pubkey::Privkey;
+use crate::rng::RandomNumberGenerator;
+
+// HPKE (RFC 9180) is not exposed by the Botan FFI, so it is built here
+// out of the DH, HMAC and AEAD primitives that are.
+
+const HPKE_VERSION_LABEL: &[u8] = b"HPKE-v1";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// The key encapsulation mechanisms available for HPKE
+pub enum HpkeKem {
+    /// DHKEM(P-256, HKDF-SHA256)
+    DhkemP256HkdfSha256,
+    /// DHKEM(P-384, HKDF-SHA384)
+    DhkemP384HkdfSha384,
+    /// DHKEM(P-521, HKDF-SHA512)
+    DhkemP521HkdfSha512,
+    /// DHKEM(X25519, HKDF-SHA256)
+    DhkemX25519HkdfSha256,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// The key derivation functions available for HPKE
+pub enum HpkeKdf {
+    /// HKDF-SHA256
+    HkdfSha256,
+    /// HKDF-SHA384
+    HkdfSha384,
+    /// HKDF-SHA512
+    HkdfSha512,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// The AEADs available for HPKE
+pub enum HpkeAead {
+    /// AES-128-GCM
+    Aes128Gcm,
+    /// AES-256-GCM
+    Aes256Gcm,
+    /// ChaCha20Poly1305
+    ChaCha20Poly1305,
+    /// No AEAD; the context can only be used to export secrets
+    ExportOnly,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum HpkeMode {
+    Base,
+    Psk,
+    Auth,
+    AuthPsk,
+}
+
+impl HpkeMode {
+    fn id(self) -> u8 {
+        match self {
+            HpkeMode::Base => 0,
+            HpkeMode::Psk => 1,
+            HpkeMode::Auth => 2,
+            HpkeMode::AuthPsk => 3,
+        }
+    }
+}
+
+impl HpkeKem {
+    /// Return the IANA identifier of this KEM
+    #[must_use]
+    pub fn id(self) -> u16 {
+        match self {
+            HpkeKem::DhkemP256HkdfSha256 => 0x0010,
+            HpkeKem::DhkemP384HkdfSha384 => 0x0011,
+            HpkeKem::DhkemP521HkdfSha512 => 0x0012,
+            HpkeKem::DhkemX25519HkdfSha256 => 0x0020,
+        }
+    }
+
+    pub(crate) fn from_id(id: u16) -> Option<HpkeKem> {
+        match id {
+            0x0010 => Some(HpkeKem::DhkemP256HkdfSha256),
+            0x0011 => Some(HpkeKem::DhkemP384HkdfSha384),
+            0x0012 => Some(HpkeKem::DhkemP521HkdfSha512),
+            0x0020 => Some(HpkeKem::DhkemX25519HkdfSha256),
+            _ => None,
+        }
+    }
+
+    /// Identify the KEM matching a key, given its algorithm name and a
+    /// way of retrieving the group order (only needed for ECDH)
+    pub(crate) fn for_key(algo: &str, order: &dyn Fn() -> Result<MPI>) -> Result<HpkeKem> {
+        if algo == "Curve25519" || algo == "X25519" {
+            return Ok(HpkeKem::DhkemX25519HkdfSha256);
+        }
+
+        if algo == "ECDH" {
+            let order = order()?.to_bin()?;
+            let kems = [
+                HpkeKem::DhkemP256HkdfSha256,
+                HpkeKem::DhkemP384HkdfSha384,
+                HpkeKem::DhkemP521HkdfSha512,
+            ];
+            for kem in kems.iter() {
+                if crate::codec::hex_decode(kem.group_order())? == order {
+                    return Ok(*kem);
+                }
+            }
+        }
+
+        Err(Error::with_message(
+            ErrorType::NotImplemented,
+            format!("No HPKE KEM available for {} key", algo),
+        ))
+    }
+
+    /// Serialize an ECDH public point given its affine coordinates
+    pub(crate) fn encode_point(self, x: &MPI, y: &MPI) -> Result<Vec<u8>> {
+        let field_len = self.private_key_length();
+        let mut out = vec![0x04];
+        for coord in [x, y].iter() {
+            out.extend_from_slice(&coord.to_bytes_padded(field_len).map_err(|_| {
+                Error::with_message(ErrorType::InvalidInput, "Invalid public point".to_owned())
+            })?);
+        }
+        Ok(out)
+    }
+
+    pub(crate) fn kdf(self) -> HpkeKdf {
+        match self {
+            HpkeKem::DhkemP256HkdfSha256 => HpkeKdf::HkdfSha256,
+            HpkeKem::DhkemP384HkdfSha384 => HpkeKdf::HkdfSha384,
+            HpkeKem::DhkemP521HkdfSha512 => HpkeKdf::HkdfSha512,
+            HpkeKem::DhkemX25519HkdfSha256 => HpkeKdf::HkdfSha256,
+        }
+    }
+
+    fn curve(self) -> Option<&'static str> {
+        match self {
+            HpkeKem::DhkemP256HkdfSha256 => Some("secp256r1"),
+            HpkeKem::DhkemP384HkdfSha384 => Some("secp384r1"),
+            HpkeKem::DhkemP521HkdfSha512 => Some("secp521r1"),
+            HpkeKem::DhkemX25519HkdfSha256 => None,
+        }
+    }
+
+    fn secret_length(self) -> usize {
+        self.kdf().hash_length()
+    }
+
+    fn private_key_length(self) -> usize {
+        match self {
+            HpkeKem::DhkemP256HkdfSha256 => 32,
+            HpkeKem::DhkemP384HkdfSha384 => 48,
+            HpkeKem::DhkemP521HkdfSha512 => 66,
+            HpkeKem::DhkemX25519HkdfSha256 => 32,
+        }
+    }
+
+    fn group_order(self) -> &'static str {
+        match self {
+            HpkeKem::DhkemP256HkdfSha256 => {
+                "FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551"
+            }
+            HpkeKem::DhkemP384HkdfSha384 => concat!(
+                "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFC7634D81F4372DDF",
+                "581A0DB248B0A77AECEC196ACCC52973"
+            ),
+            HpkeKem::DhkemP521HkdfSha512 => concat!(
+                "01FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+                "FA51868783BF2F966B7FCC0148F709A5D03BB5C9B8899C47AEBB6FB71E91386409"
+            ),
+            HpkeKem::DhkemX25519HkdfSha256 => "",
+        }
+    }
+
+    fn suite_id(self) -> Vec<u8> {
+        let mut suite_id = b"KEM".to_vec();
+        suite_id.extend_from_slice(&self.id().to_be_bytes());
+        suite_id
+    }
+
+    fn generate_key(self, rng: &mut RandomNumberGenerator) -> Result<Privkey> {
+        match self.curve() {
+            Some(curve) => Privkey::create("ECDH", curve, rng),
+            None => Privkey::create("X25519", "", rng),
+        }
+    }
+
+    fn derive_key(self, ikm: &[u8]) -> Result<Privkey> {
+        let kdf = self.kdf();
+        let suite_id = self.suite_id();
+        let dkp_prk = kdf.labeled_extract(&suite_id, &[], b"dkp_prk", ikm)?;
+
+        let curve = match self.curve() {
+            Some(curve) => curve,
+            None => {
+                let sk = kdf.labeled_expand(&suite_id, &dkp_prk, b"sk", &[], 32)?;
+                return Privkey::load_x25519(&sk);
+            }
+        };
+
+        let order = crate::codec::hex_decode(self.group_order())?;
+        let bitmask = if self == HpkeKem::DhkemP521HkdfSha512 {
+            0x01
+        } else {
+            0xFF
+        };
+
+        for counter in 0..=255u8 {
+            let mut sk = kdf.labeled_expand(
+                &suite_id,
+                &dkp_prk,
+                b"candidate",
+                &[counter],
+                self.private_key_length(),
+            )?;
+            sk[0] &= bitmask;
+
+            if sk.iter().any(|&b| b != 0) && sk < order {
+                return Privkey::load_ecdh(&MPI::new_from_bytes(&sk)?, curve);
+            }
+        }
+
+        Err(Error::with_message(
+            ErrorType::InternalError,
+            "HPKE key derivation failed".to_owned(),
+        ))
+    }
+
+    fn dh(self, sk: &Privkey, pk: &[u8]) -> Result<Vec<u8>> {
+        let mut ka = KeyAgreement::new(sk, "Raw")?;
+        let dh = ka.agree(0, pk, &[])?;
+
+        if dh.iter().all(|&b| b == 0) {
+            return Err(Error::with_message(
+                ErrorType::InvalidInput,
+                "HPKE key agreement produced an all-zero output".to_owned(),
+            ));
+        }
+
+        Ok(dh)
+    }
+
+    fn extract_and_expand(self, dh: &[u8], kem_context: &[u8]) -> Result<Vec<u8>> {
+        let kdf = self.kdf();
+        let suite_id = self.suite_id();
+        let eae_prk = kdf.labeled_extract(&suite_id, &[], b"eae_prk", dh)?;
+        kdf.labeled_expand(
+            &suite_id,
+            &eae_prk,
+            b"shared_secret",
+            kem_context,
+            self.secret_length(),
+        )
+    }
+
+    fn encap(
+        self,
+        pk_r: &[u8],
+        sk_s: Option<&Privkey>,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let sk_e = self.generate_key(rng)?;
+        let enc = sk_e.key_agreement_key()?;
+
+        let mut dh = self.dh(&sk_e, pk_r)?;
+        let mut kem_context = enc.clone();
+        kem_context.extend_from_slice(pk_r);
+
+        if let Some(sk_s) = sk_s {
+            dh.extend_from_slice(&self.dh(sk_s, pk_r)?);
+            kem_context.extend_from_slice(&sk_s.key_agreement_key()?);
+        }
+
+        let shared_secret = self.extract_and_expand(&dh, &kem_context)?;
+        Ok((shared_secret, enc))
+    }
+
+    fn decap(self, enc: &[u8], sk_r: &Privkey, pk_s: Option<&[u8]>) -> Result<Vec<u8>> {
+        let mut dh = self.dh(sk_r, enc)?;
+        let mut kem_context = enc.to_vec();
+        kem_context.extend_from_slice(&sk_r.key_agreement_key()?);
+
+        if let Some(pk_s) = pk_s {
+            dh.extend_from_slice(&self.dh(sk_r, pk_s)?);
+            kem_context.extend_from_slice(pk_s);
+        }
+
+        self.extract_and_expand(&dh, &kem_context)
+    }
+}
+
+impl HpkeKdf {
+    /// Return the IANA identifier of this KDF
+    #[must_use]
+    pub fn id(self) -> u16 {
+        match self {
+            HpkeKdf::HkdfSha256 => 0x0001,
+            HpkeKdf::HkdfSha384 => 0x0002,
+            HpkeKdf::HkdfSha512 => 0x0003,
+        }
+    }
+
+    fn hmac_name(self) -> &'static str {
+        match self {
+            HpkeKdf::HkdfSha256 => "HMAC(SHA-256)",
+            HpkeKdf::HkdfSha384 => "HMAC(SHA-384)",
+            HpkeKdf::HkdfSha512 => "HMAC(SHA-512)",
+        }
+    }
+
+    fn hash_length(self) -> usize {
+        match self {
+            HpkeKdf::HkdfSha256 => 32,
+            HpkeKdf::HkdfSha384 => 48,
+            HpkeKdf::HkdfSha512 => 64,
+        }
+    }
+
+    fn extract(self, salt: &[u8], ikm: &[&[u8]]) -> Result<Vec<u8>> {
+        let hmac = MsgAuthCode::new(self.hmac_name())?;
+        hmac.set_key(salt)?;
+        for input in ikm {
+            hmac.update(input)?;
+        }
+        hmac.finish()
+    }
+
+    fn expand(self, prk: &[u8], info: &[&[u8]], output_len: usize) -> Result<Vec<u8>> {
+        if output_len > 255 * self.hash_length() {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "HKDF output length too large".to_owned(),
+            ));
+        }
+
+        let hmac = MsgAuthCode::new(self.hmac_name())?;
+        hmac.set_key(prk)?;
+
+        let mut output = Vec::with_capacity(output_len);
+        let mut block = Vec::new();
+        let mut counter = 1u8;
+
+        while output.len() < output_len {
+            hmac.update(&block)?;
+            for input in info {
+                hmac.update(input)?;
+            }
+            hmac.update(&[counter])?;
+            block = hmac.finish()?;
+
+            let take = core::cmp::min(block.len(), output_len - output.len());
+            output.extend_from_slice(&block[..take]);
+            counter = counter.wrapping_add(1);
+        }
+
+        Ok(output)
+    }
+
+    fn labeled_extract(
+        self,
+        suite_id: &[u8],
+        salt: &[u8],
+        label: &[u8],
+        ikm: &[u8],
+    ) -> Result<Vec<u8>> {
+        self.extract(salt, &[HPKE_VERSION_LABEL, suite_id, label, ikm])
+    }
+
+    fn labeled_expand(
+        self,
+        suite_id: &[u8],
+        prk: &[u8],
+        label: &[u8],
+        info: &[u8],
+        output_len: usize,
+    ) -> Result<Vec<u8>> {
+        if output_len > 0xFFFF {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "HPKE output length too large".to_owned(),
+            ));
+        }
+
+        let length = (output_len as u16).to_be_bytes();
+        self.expand(
+            prk,
+            &[&length, HPKE_VERSION_LABEL, suite_id, label, info],
+            output_len,
+        )
+    }
+}
+
+impl HpkeAead {
+    /// Return the IANA identifier of this AEAD
+    #[must_use]
+    pub fn id(self) -> u16 {
+        match self {
+            HpkeAead::Aes128Gcm => 0x0001,
+            HpkeAead::Aes256Gcm => 0x0002,
+            HpkeAead::ChaCha20Poly1305 => 0x0003,
+            HpkeAead::ExportOnly => 0xFFFF,
+        }
+    }
+
+    fn cipher_name(self) -> Option<&'static str> {
+        match self {
+            HpkeAead::Aes128Gcm => Some("AES-128/GCM"),
+            HpkeAead::Aes256Gcm => Some("AES-256/GCM"),
+            HpkeAead::ChaCha20Poly1305 => Some("ChaCha20Poly1305"),
+            HpkeAead::ExportOnly => None,
+        }
+    }
+
+    fn key_length(self) -> usize {
+        match self {
+            HpkeAead::Aes128Gcm => 16,
+            HpkeAead::Aes256Gcm => 32,
+            HpkeAead::ChaCha20Poly1305 => 32,
+            HpkeAead::ExportOnly => 0,
+        }
+    }
+
+    fn nonce_length(self) -> usize {
+        match self {
+            HpkeAead::ExportOnly => 0,
+            _ => 12,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// An HPKE ciphersuite (RFC 9180)
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let hpke = botan::Hpke::new(
+///     botan::HpkeKem::DhkemX25519HkdfSha256,
+///     botan::HpkeKdf::HkdfSha256,
+///     botan::HpkeAead::ChaCha20Poly1305,
+/// );
+/// let (sk_r, pk_r) = hpke.generate_key_pair(&mut rng).unwrap();
+/// let (enc, ctext) = hpke.seal_base(&pk_r, b"info", b"aad", b"message", &mut rng).unwrap();
+/// let ptext = hpke.open_base(&enc, &sk_r, b"info", b"aad", &ctext).unwrap();
+/// assert_eq!(ptext, b"message");
+/// ```
+pub struct Hpke {
+    kem: HpkeKem,
+    kdf: HpkeKdf,
+    aead: HpkeAead,
+}
+
+impl Hpke {
+    /// Create a new HPKE ciphersuite
+    #[must_use]
+    pub fn new(kem: HpkeKem, kdf: HpkeKdf, aead: HpkeAead) -> Hpke {
+        Hpke { kem, kdf, aead }
+    }
+
+    /// Return the KEM of this ciphersuite
+    #[must_use]
+    pub fn kem(&self) -> HpkeKem {
+        self.kem
+    }
+
+    /// Return the KDF of this ciphersuite
+    #[must_use]
+    pub fn kdf(&self) -> HpkeKdf {
+        self.kdf
+    }
+
+    /// Return the AEAD of this ciphersuite
+    #[must_use]
+    pub fn aead(&self) -> HpkeAead {
+        self.aead
+    }
+
+    fn suite_id(&self) -> Vec<u8> {
+        let mut suite_id = b"HPKE".to_vec();
+        suite_id.extend_from_slice(&self.kem.id().to_be_bytes());
+        suite_id.extend_from_slice(&self.kdf.id().to_be_bytes());
+        suite_id.extend_from_slice(&self.aead.id().to_be_bytes());
+        suite_id
+    }
+
+    /// Generate a new key pair for the KEM, returning the private key
+    /// and the serialized public key
+    pub fn generate_key_pair(&self, rng: &mut RandomNumberGenerator) -> Result<(Privkey, Vec<u8>)> {
+        let sk = self.kem.generate_key(rng)?;
+        let pk = sk.key_agreement_key()?;
+        Ok((sk, pk))
+    }
+
+    /// Deterministically derive a key pair for the KEM from the input
+    /// keying material, returning the private key and the serialized
+    /// public key
+    pub fn derive_key_pair(&self, ikm: &[u8]) -> Result<(Privkey, Vec<u8>)> {
+        if ikm.len() < self.kem.private_key_length() {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "HPKE key derivation input too short".to_owned(),
+            ));
+        }
+        let sk = self.kem.derive_key(ikm)?;
+        let pk = sk.key_agreement_key()?;
+        Ok((sk, pk))
+    }
+
+    fn key_schedule(
+        &self,
+        mode: HpkeMode,
+        direction: CipherDirection,
+        shared_secret: &[u8],
+        info: &[u8],
+        (psk, psk_id): (&[u8], &[u8]),
+    ) -> Result<HpkeContext> {
+        let uses_psk = mode == HpkeMode::Psk || mode == HpkeMode::AuthPsk;
+        if psk.is_empty() != psk_id.is_empty() || psk.is_empty() == uses_psk {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "Inconsistent HPKE PSK inputs".to_owned(),
+            ));
+        }
+
+        let suite_id = self.suite_id();
+        let kdf = self.kdf;
+
+        let psk_id_hash = kdf.labeled_extract(&suite_id, &[], b"psk_id_hash", psk_id)?;
+        let info_hash = kdf.labeled_extract(&suite_id, &[], b"info_hash", info)?;
+
+        let mut context = vec![mode.id()];
+        context.extend_from_slice(&psk_id_hash);
+        context.extend_from_slice(&info_hash);
+
+        let secret = kdf.labeled_extract(&suite_id, shared_secret, b"secret", psk)?;
+
+        let cipher = match self.aead.cipher_name() {
+            Some(name) => {
+                let key = kdf.labeled_expand(
+                    &suite_id,
+                    &secret,
+                    b"key",
+                    &context,
+                    self.aead.key_length(),
+                )?;
+                let mut cipher = Cipher::new(name, direction)?;
+                cipher.set_key(&key)?;
+                Some(cipher)
+            }
+            None => None,
+        };
+
+        let base_nonce = kdf.labeled_expand(
+            &suite_id,
+            &secret,
+            b"base_nonce",
+            &context,
+            self.aead.nonce_length(),
+        )?;
+        let exporter_secret =
+            kdf.labeled_expand(&suite_id, &secret, b"exp", &context, kdf.hash_length())?;
+
+        Ok(HpkeContext {
+            cipher,
+            direction,
+            kdf,
+            suite_id,
+            base_nonce,
+            exporter_secret,
+            seq: 0,
+        })
+    }
+
+    fn setup_sender(
+        &self,
+        mode: HpkeMode,
+        pk_r: &[u8],
+        info: &[u8],
+        psk: (&[u8], &[u8]),
+        sk_s: Option<&Privkey>,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<(Vec<u8>, HpkeContext)> {
+        let (shared_secret, enc) = self.kem.encap(pk_r, sk_s, rng)?;
+        let ctx = self.key_schedule(mode, CipherDirection::Encrypt, &shared_secret, info, psk)?;
+        Ok((enc, ctx))
+    }
+
+    fn setup_receiver(
+        &self,
+        mode: HpkeMode,
+        enc: &[u8],
+        sk_r: &Privkey,
+        info: &[u8],
+        psk: (&[u8], &[u8]),
+        pk_s: Option<&[u8]>,
+    ) -> Result<HpkeContext> {
+        let shared_secret = self.kem.decap(enc, sk_r, pk_s)?;
+        self.key_schedule(mode, CipherDirection::Decrypt, &shared_secret, info, psk)
+    }
+
+    /// Set up a sender context in base mode, returning the
+    /// encapsulated key and the context
+    pub fn setup_base_sender(
+        &self,
+        pk_r: &[u8],
+        info: &[u8],
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<(Vec<u8>, HpkeContext)> {
+        self.setup_sender(HpkeMode::Base, pk_r, info, (&[], &[]), None, rng)
+    }
+
+    /// Set up a receiver context in base mode
+    pub fn setup_base_receiver(
+        &self,
+        enc: &[u8],
+        sk_r: &Privkey,
+        info: &[u8],
+    ) -> Result<HpkeContext> {
+        self.setup_receiver(HpkeMode::Base, enc, sk_r, info, (&[], &[]), None)
+    }
+
+    /// Set up a sender context authenticated with a pre-shared key
+    pub fn setup_psk_sender(
+        &self,
+        pk_r: &[u8],
+        info: &[u8],
+        psk: &[u8],
+        psk_id: &[u8],
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<(Vec<u8>, HpkeContext)> {
+        self.setup_sender(HpkeMode::Psk, pk_r, info, (psk, psk_id), None, rng)
+    }
+
+    /// Set up a receiver context authenticated with a pre-shared key
+    pub fn setup_psk_receiver(
+        &self,
+        enc: &[u8],
+        sk_r: &Privkey,
+        info: &[u8],
+        psk: &[u8],
+        psk_id: &[u8],
+    ) -> Result<HpkeContext> {
+        self.setup_receiver(HpkeMode::Psk, enc, sk_r, info, (psk, psk_id), None)
+    }
+
+    /// Set up a sender context authenticated with the sender's private key
+    pub fn setup_auth_sender(
+        &self,
+        pk_r: &[u8],
+        info: &[u8],
+        sk_s: &Privkey,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<(Vec<u8>, HpkeContext)> {
+        self.setup_sender(HpkeMode::Auth, pk_r, info, (&[], &[]), Some(sk_s), rng)
+    }
+
+    /// Set up a receiver context authenticated with the sender's public key
+    pub fn setup_auth_receiver(
+        &self,
+        enc: &[u8],
+        sk_r: &Privkey,
+        info: &[u8],
+        pk_s: &[u8],
+    ) -> Result<HpkeContext> {
+        self.setup_receiver(HpkeMode::Auth, enc, sk_r, info, (&[], &[]), Some(pk_s))
+    }
+
+    /// Set up a sender context authenticated with both a pre-shared key
+    /// and the sender's private key
+    pub fn setup_auth_psk_sender(
+        &self,
+        pk_r: &[u8],
+        info: &[u8],
+        psk: &[u8],
+        psk_id: &[u8],
+        sk_s: &Privkey,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<(Vec<u8>, HpkeContext)> {
+        self.setup_sender(
+            HpkeMode::AuthPsk,
+            pk_r,
+            info,
+            (psk, psk_id),
+            Some(sk_s),
+            rng,
+        )
+    }
+
+    /// Set up a receiver context authenticated with both a pre-shared key
+    /// and the sender's public key
+    pub fn setup_auth_psk_receiver(
+        &self,
+        enc: &[u8],
+        sk_r: &Privkey,
+        info: &[u8],
+        psk: &[u8],
+        psk_id: &[u8],
+        pk_s: &[u8],
+    ) -> Result<HpkeContext> {
+        self.setup_receiver(
+            HpkeMode::AuthPsk,
+            enc,
+            sk_r,
+            info,
+            (psk, psk_id),
+            Some(pk_s),
+        )
+    }
+
+    /// Encrypt a single message in base mode, returning the
+    /// encapsulated key and the ciphertext
+    pub fn seal_base(
+        &self,
+        pk_r: &[u8],
+        info: &[u8],
+        aad: &[u8],
+        ptext: &[u8],
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let (enc, mut ctx) = self.setup_base_sender(pk_r, info, rng)?;
+        let ctext = ctx.seal(aad, ptext)?;
+        Ok((enc, ctext))
+    }
+
+    /// Decrypt a single message in base mode
+    pub fn open_base(
+        &self,
+        enc: &[u8],
+        sk_r: &Privkey,
+        info: &[u8],
+        aad: &[u8],
+        ctext: &[u8],
+    ) -> Result<Vec<u8>> {
+        let mut ctx = self.setup_base_receiver(enc, sk_r, info)?;
+        ctx.open(aad, ctext)
+    }
+
+    /// Derive an exported secret in base mode, returning the
+    /// encapsulated key and the secret
+    pub fn send_export_base(
+        &self,
+        pk_r: &[u8],
+        info: &[u8],
+        exporter_context: &[u8],
+        output_len: usize,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let (enc, ctx) = self.setup_base_sender(pk_r, info, rng)?;
+        let secret = ctx.export(exporter_context, output_len)?;
+        Ok((enc, secret))
+    }
+
+    /// Derive an exported secret in base mode on the receiving side
+    pub fn receive_export_base(
+        &self,
+        enc: &[u8],
+        sk_r: &Privkey,
+        info: &[u8],
+        exporter_context: &[u8],
+        output_len: usize,
+    ) -> Result<Vec<u8>> {
+        let ctx = self.setup_base_receiver(enc, sk_r, info)?;
+        ctx.export(exporter_context, output_len)
+    }
+}
+
+/// An HPKE encryption context, as produced by one of the setup
+/// functions of `Hpke`
+///
+/// A sender context can only seal messages, and a receiver context
+/// can only open them; both can export secrets.
+pub struct HpkeContext {
+    cipher: Option<Cipher>,
+    direction: CipherDirection,
+    kdf: HpkeKdf,
+    suite_id: Vec<u8>,
+    base_nonce: Vec<u8>,
+    exporter_secret: Vec<u8>,
+    seq: u64,
+}
+
+impl Drop for HpkeContext {
+    fn drop(&mut self) {
+        crate::memutils::scrub_mem(&mut self.exporter_secret);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for HpkeContext {
+    fn zeroize(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for HpkeContext {}
+
+impl HpkeContext {
+    fn check_not_cleared(&self) -> Result<()> {
+        if self.exporter_secret.is_empty() {
+            return Err(Error::with_message(
+                ErrorType::InvalidObjectState,
+                "HPKE context has been cleared".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn next_nonce(&mut self) -> Result<Vec<u8>> {
+        // An export only context has no nonce to derive from
+        if self.cipher.is_none() || self.base_nonce.len() < 8 {
+            return Err(Error::with_message(
+                ErrorType::InvalidObjectState,
+                "HPKE context is export only".to_owned(),
+            ));
+        }
+
+        if self.seq == u64::MAX {
+            return Err(Error::with_message(
+                ErrorType::InvalidObjectState,
+                "HPKE message limit reached".to_owned(),
+            ));
+        }
+
+        let mut nonce = self.base_nonce.clone();
+        let offset = nonce.len() - 8;
+        for (n, s) in nonce[offset..]
+            .iter_mut()
+            .zip(self.seq.to_be_bytes().iter())
+        {
+            *n ^= s;
+        }
+        Ok(nonce)
+    }
+
+    fn process(&mut self, direction: CipherDirection, aad: &[u8], input: &[u8]) -> Result<Vec<u8>> {
+        if self.direction != direction {
+            return Err(Error::with_message(
+                ErrorType::InvalidObjectState,
+                "HPKE context used in the wrong direction".to_owned(),
+            ));
+        }
+
+        self.check_not_cleared()?;
+        let nonce = self.next_nonce()?;
+
+        let cipher = match self.cipher.as_mut() {
+            Some(cipher) => cipher,
+            None => {
+                return Err(Error::with_message(
+                    ErrorType::InvalidObjectState,
+                    "HPKE context is export only".to_owned(),
+                ))
+            }
+        };
+
+        cipher.set_associated_data(aad)?;
+        let output = cipher.process(&nonce, input)?;
+        self.seq += 1;
+        Ok(output)
+    }
+
+    /// Encrypt and authenticate a message (sender only)
+    pub fn seal(&mut self, aad: &[u8], ptext: &[u8]) -> Result<Vec<u8>> {
+        self.process(CipherDirection::Encrypt, aad, ptext)
+    }
+
+    /// Decrypt and verify a message (receiver only)
+    pub fn open(&mut self, aad: &[u8], ctext: &[u8]) -> Result<Vec<u8>> {
+        self.process(CipherDirection::Decrypt, aad, ctext)
+    }
+
+    /// Derive a secret of the requested length from the exporter secret
+    pub fn export(&self, exporter_context: &[u8], output_len: usize) -> Result<Vec<u8>> {
+        self.check_not_cleared()?;
+        self.kdf.labeled_expand(
+            &self.suite_id,
+            &self.exporter_secret,
+            b"sec",
+            exporter_context,
+            output_len,
+        )
+    }
+
+    /// Return the sequence number of the next message
+    #[must_use]
+    pub fn sequence_number(&self) -> u64 {
+        self.seq
+    }
+
+    /// Erase the keys held by this context
+    ///
+    /// Afterwards the context can no longer seal, open or export.
+    pub fn clear(&mut self) {
+        if let Some(mut cipher) = self.cipher.take() {
+            let _ = cipher.clear();
+        }
+        crate::memutils::scrub_mem(&mut self.exporter_secret);
+        crate::memutils::scrub_mem(&mut self.base_nonce);
+        self.exporter_secret.clear();
+        self.base_nonce.clear();
+    }
+}
+
+impl core::fmt::Debug for HpkeContext {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("HpkeContext")
+            .field("direction", &self.direction)
+            .field("kdf", &self.kdf)
+            .field("seq", &self.seq)
+            .finish()
+    }
+}
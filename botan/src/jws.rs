@@ -0,0 +1,513 @@
+use crate::utils::*;
+
+use core::str::FromStr;
+
+use crate::codec::{base64_decode, base64_encode};
+use crate::pk_ops::{Signer, Verifier};
+use crate::pubkey::{Privkey, Pubkey};
+use crate::rng::RandomNumberGenerator;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// A JWS signature algorithm (RFC 7518, RFC 8037)
+pub enum JwsAlgorithm {
+    /// RSASSA-PKCS1-v1_5 with SHA-256
+    Rs256,
+    /// RSASSA-PKCS1-v1_5 with SHA-384
+    Rs384,
+    /// RSASSA-PKCS1-v1_5 with SHA-512
+    Rs512,
+    /// RSASSA-PSS with SHA-256
+    Ps256,
+    /// RSASSA-PSS with SHA-384
+    Ps384,
+    /// RSASSA-PSS with SHA-512
+    Ps512,
+    /// ECDSA with P-256 and SHA-256
+    Es256,
+    /// ECDSA with P-384 and SHA-384
+    Es384,
+    /// ECDSA with P-521 and SHA-512
+    Es512,
+    /// EdDSA (Ed25519)
+    EdDsa,
+}
+
+impl JwsAlgorithm {
+    /// Return the name of the algorithm as used in the JWS "alg" header
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            JwsAlgorithm::Rs256 => "RS256",
+            JwsAlgorithm::Rs384 => "RS384",
+            JwsAlgorithm::Rs512 => "RS512",
+            JwsAlgorithm::Ps256 => "PS256",
+            JwsAlgorithm::Ps384 => "PS384",
+            JwsAlgorithm::Ps512 => "PS512",
+            JwsAlgorithm::Es256 => "ES256",
+            JwsAlgorithm::Es384 => "ES384",
+            JwsAlgorithm::Es512 => "ES512",
+            JwsAlgorithm::EdDsa => "EdDSA",
+        }
+    }
+
+    pub(crate) fn padding(self) -> &'static str {
+        match self {
+            JwsAlgorithm::Rs256 => "PKCS1v15(SHA-256)",
+            JwsAlgorithm::Rs384 => "PKCS1v15(SHA-384)",
+            JwsAlgorithm::Rs512 => "PKCS1v15(SHA-512)",
+            JwsAlgorithm::Ps256 => "PSSR(SHA-256,MGF1,32)",
+            JwsAlgorithm::Ps384 => "PSSR(SHA-384,MGF1,48)",
+            JwsAlgorithm::Ps512 => "PSSR(SHA-512,MGF1,64)",
+            JwsAlgorithm::Es256 => "EMSA1(SHA-256)",
+            JwsAlgorithm::Es384 => "EMSA1(SHA-384)",
+            JwsAlgorithm::Es512 => "EMSA1(SHA-512)",
+            JwsAlgorithm::EdDsa => "Pure",
+        }
+    }
+
+    fn key_algo(self) -> &'static str {
+        match self {
+            JwsAlgorithm::Rs256
+            | JwsAlgorithm::Rs384
+            | JwsAlgorithm::Rs512
+            | JwsAlgorithm::Ps256
+            | JwsAlgorithm::Ps384
+            | JwsAlgorithm::Ps512 => "RSA",
+            JwsAlgorithm::Es256 | JwsAlgorithm::Es384 | JwsAlgorithm::Es512 => "ECDSA",
+            JwsAlgorithm::EdDsa => "Ed25519",
+        }
+    }
+
+    fn curve_bits(self) -> Option<usize> {
+        match self {
+            JwsAlgorithm::Es256 => Some(256),
+            JwsAlgorithm::Es384 => Some(384),
+            JwsAlgorithm::Es512 => Some(521),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn check_key(
+        self,
+        algo: &str,
+        order: &dyn Fn() -> Result<crate::mp::MPI>,
+    ) -> Result<()> {
+        let matches = algo == self.key_algo()
+            && match self.curve_bits() {
+                Some(bits) => order()?.bit_count()? == bits,
+                None => true,
+            };
+
+        if matches {
+            Ok(())
+        } else {
+            Err(Error::with_message(
+                ErrorType::BadParameter,
+                format!("{} key cannot be used with {}", algo, self.name()),
+            ))
+        }
+    }
+}
+
+impl FromStr for JwsAlgorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<JwsAlgorithm> {
+        match s {
+            "RS256" => Ok(JwsAlgorithm::Rs256),
+            "RS384" => Ok(JwsAlgorithm::Rs384),
+            "RS512" => Ok(JwsAlgorithm::Rs512),
+            "PS256" => Ok(JwsAlgorithm::Ps256),
+            "PS384" => Ok(JwsAlgorithm::Ps384),
+            "PS512" => Ok(JwsAlgorithm::Ps512),
+            "ES256" => Ok(JwsAlgorithm::Es256),
+            "ES384" => Ok(JwsAlgorithm::Es384),
+            "ES512" => Ok(JwsAlgorithm::Es512),
+            "EdDSA" => Ok(JwsAlgorithm::EdDsa),
+            _ => Err(Error::with_message(
+                ErrorType::NotImplemented,
+                format!("Unknown JWS algorithm {}", s),
+            )),
+        }
+    }
+}
+
+fn jws_error(msg: &str) -> Error {
+    Error::with_message(ErrorType::InvalidInput, msg.to_owned())
+}
+
+pub(crate) fn base64url_encode(x: &[u8]) -> Result<String> {
+    let b64 = base64_encode(x)?;
+    Ok(b64
+        .trim_end_matches('=')
+        .chars()
+        .map(|c| match c {
+            '+' => '-',
+            '/' => '_',
+            c => c,
+        })
+        .collect())
+}
+
+pub(crate) fn base64url_decode(x: &str) -> Result<Vec<u8>> {
+    if x.len() % 4 == 1
+        || !x
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_')
+    {
+        return Err(jws_error("Invalid base64url encoding"));
+    }
+
+    let mut b64: String = x
+        .chars()
+        .map(|c| match c {
+            '-' => '+',
+            '_' => '/',
+            c => c,
+        })
+        .collect();
+
+    while b64.len() % 4 != 0 {
+        b64.push('=');
+    }
+
+    base64_decode(&b64)
+}
+
+// The members of a JSON object, as names and the undecoded text of
+// their values. This is just enough JSON to read a JWS protected
+// header: member names are checked to be unique, and the values are
+// only checked for being well formed.
+struct JsonObject<'a> {
+    members: Vec<(String, &'a str)>,
+}
+
+// JOSE headers are flat, so little nesting is needed
+const MAX_JSON_DEPTH: usize = 16;
+
+impl<'a> JsonObject<'a> {
+    fn parse(json: &'a str) -> Result<JsonObject<'a>> {
+        let mut parser = JsonParser { json, pos: 0 };
+        let mut members: Vec<(String, &'a str)> = Vec::new();
+
+        parser.expect(b'{')?;
+        if !parser.consume(b'}') {
+            loop {
+                let name = parser.string()?;
+                if members.iter().any(|(n, _)| *n == name) {
+                    return Err(jws_error("Duplicate member in JWS header"));
+                }
+                parser.expect(b':')?;
+                parser.skip_ws();
+                let start = parser.pos;
+                parser.value(0)?;
+                members.push((name, &json[start..parser.pos]));
+
+                if parser.consume(b'}') {
+                    break;
+                }
+                parser.expect(b',')?;
+            }
+        }
+
+        parser.skip_ws();
+        if parser.pos != json.len() {
+            return Err(jws_error("Malformed JWS header"));
+        }
+        Ok(JsonObject { members })
+    }
+
+    fn get(&self, name: &str) -> Option<&'a str> {
+        self.members
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| *value)
+    }
+
+    fn get_string(&self, name: &str) -> Result<Option<String>> {
+        match self.get(name) {
+            Some(value) => Ok(Some(
+                JsonParser {
+                    json: value,
+                    pos: 0,
+                }
+                .string()?,
+            )),
+            None => Ok(None),
+        }
+    }
+}
+
+struct JsonParser<'a> {
+    json: &'a str,
+    pos: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.json.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn consume(&mut self, c: u8) -> bool {
+        self.skip_ws();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Result<()> {
+        if self.consume(c) {
+            Ok(())
+        } else {
+            Err(jws_error("Malformed JWS header"))
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32> {
+        let digits = self
+            .json
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| jws_error("Malformed JWS header"))?;
+        if !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(jws_error("Malformed JWS header"));
+        }
+        self.pos += 4;
+        u32::from_str_radix(digits, 16).map_err(|_| jws_error("Malformed JWS header"))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+
+        loop {
+            let rest = &self.json[self.pos..];
+            let c = rest
+                .chars()
+                .next()
+                .ok_or_else(|| jws_error("Malformed JWS header"))?;
+            self.pos += c.len_utf8();
+
+            match c {
+                '"' => return Ok(out),
+                '\\' => {
+                    let escape = self
+                        .peek()
+                        .ok_or_else(|| jws_error("Malformed JWS header"))?;
+                    self.pos += 1;
+                    match escape {
+                        b'"' => out.push('"'),
+                        b'\\' => out.push('\\'),
+                        b'/' => out.push('/'),
+                        b'b' => out.push('\u{8}'),
+                        b'f' => out.push('\u{c}'),
+                        b'n' => out.push('\n'),
+                        b'r' => out.push('\r'),
+                        b't' => out.push('\t'),
+                        b'u' => {
+                            let mut code = self.hex4()?;
+                            if (0xD800..0xDC00).contains(&code) {
+                                if !self.json[self.pos..].starts_with("\\u") {
+                                    return Err(jws_error("Malformed JWS header"));
+                                }
+                                self.pos += 2;
+                                let low = self.hex4()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(jws_error("Malformed JWS header"));
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            out.push(
+                                core::char::from_u32(code)
+                                    .ok_or_else(|| jws_error("Malformed JWS header"))?,
+                            );
+                        }
+                        _ => return Err(jws_error("Malformed JWS header")),
+                    }
+                }
+                c if (c as u32) < 0x20 => return Err(jws_error("Malformed JWS header")),
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<()> {
+        if depth > MAX_JSON_DEPTH {
+            return Err(jws_error("JWS header is nested too deeply"));
+        }
+
+        self.skip_ws();
+        match self.peek() {
+            Some(b'"') => self.string().map(|_| ()),
+            Some(b'{') => {
+                self.pos += 1;
+                if self.consume(b'}') {
+                    return Ok(());
+                }
+                loop {
+                    self.string()?;
+                    self.expect(b':')?;
+                    self.value(depth + 1)?;
+                    if self.consume(b'}') {
+                        return Ok(());
+                    }
+                    self.expect(b',')?;
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                if self.consume(b']') {
+                    return Ok(());
+                }
+                loop {
+                    self.value(depth + 1)?;
+                    if self.consume(b']') {
+                        return Ok(());
+                    }
+                    self.expect(b',')?;
+                }
+            }
+            _ => {
+                let rest = &self.json[self.pos..];
+                let len = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric() && !"+-.".contains(c))
+                    .unwrap_or(rest.len());
+                let token = &rest[..len];
+                let is_number = token.starts_with(|c: char| c == '-' || c.is_ascii_digit())
+                    && token.bytes().all(|c| b"0123456789+-.eE".contains(&c))
+                    && token.parse::<f64>().is_ok();
+                if token == "true" || token == "false" || token == "null" || is_number {
+                    self.pos += len;
+                    Ok(())
+                } else {
+                    Err(jws_error("Malformed JWS header"))
+                }
+            }
+        }
+    }
+}
+
+fn jws_sign_with_header(
+    key: &Privkey,
+    alg: JwsAlgorithm,
+    header: &str,
+    payload: &[u8],
+    rng: &mut RandomNumberGenerator,
+) -> Result<String> {
+    alg.check_key(&key.algo_name()?, &|| key.get_field("order"))?;
+
+    let mut token = base64url_encode(header.as_bytes())?;
+    token.push('.');
+    token.push_str(&base64url_encode(payload)?);
+
+    let mut signer = Signer::new(key, alg.padding())?;
+    signer.update(token.as_bytes())?;
+    let signature = signer.finish(rng)?;
+
+    token.push('.');
+    token.push_str(&base64url_encode(&signature)?);
+    Ok(token)
+}
+
+/// Create a compact JWS over `payload`
+///
+/// The protected header contains only the "alg" member. The key
+/// type must match the requested algorithm.
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::Privkey::create("Ed25519", "", &mut rng).unwrap();
+/// let jws = botan::jws_sign(&key, botan::JwsAlgorithm::EdDsa, b"payload", &mut rng).unwrap();
+/// let payload = botan::jws_verify(&key.pubkey().unwrap(), botan::JwsAlgorithm::EdDsa, &jws).unwrap();
+/// assert_eq!(payload, b"payload");
+/// ```
+pub fn jws_sign(
+    key: &Privkey,
+    alg: JwsAlgorithm,
+    payload: &[u8],
+    rng: &mut RandomNumberGenerator,
+) -> Result<String> {
+    let header = format!("{{\"alg\":\"{}\"}}", alg.name());
+    jws_sign_with_header(key, alg, &header, payload, rng)
+}
+
+/// Verify a compact JWS and return its payload
+///
+/// The "alg" member of the protected header must match `alg`, and the
+/// key type must match the algorithm. Headers which are not a JSON
+/// object, repeat a member name, or contain a "crit" member are
+/// rejected.
+pub fn jws_verify(key: &Pubkey, alg: JwsAlgorithm, jws: &str) -> Result<Vec<u8>> {
+    alg.check_key(&key.algo_name()?, &|| key.get_field("order"))?;
+
+    let mut parts = jws.split('.');
+    let (header_b64, payload_b64, signature_b64) =
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(h), Some(p), Some(s), None) => (h, p, s),
+            _ => return Err(jws_error("Malformed compact JWS")),
+        };
+
+    let header = base64url_decode(header_b64)?;
+    let header = core::str::from_utf8(&header).map_err(Error::conversion_error)?;
+
+    let header = JsonObject::parse(header)?;
+
+    match header.get_string("alg")? {
+        Some(ref name) if name == alg.name() => {}
+        _ => return Err(jws_error("JWS algorithm mismatch")),
+    }
+
+    // No header parameter extensions are understood, so any which are
+    // marked critical cannot be processed (RFC 7515 section 4.1.11)
+    if header.get("crit").is_some() {
+        return Err(jws_error("Unsupported critical JWS header"));
+    }
+
+    let signature = base64url_decode(signature_b64)?;
+    let signed_len = header_b64.len() + 1 + payload_b64.len();
+
+    let mut verifier = Verifier::new(key, alg.padding())?;
+    verifier.update(&jws.as_bytes()[..signed_len])?;
+
+    if !verifier.finish(&signature)? {
+        return Err(Error::with_message(
+            ErrorType::InvalidVerifier,
+            "JWS signature is not valid".to_owned(),
+        ));
+    }
+
+    base64url_decode(payload_b64)
+}
+
+/// Create a JWT, signing the provided JSON claims set
+///
+/// The protected header contains the "alg" member and "typ" set to "JWT".
+/// The claims are not inspected.
+pub fn jwt_sign(
+    key: &Privkey,
+    alg: JwsAlgorithm,
+    claims: &str,
+    rng: &mut RandomNumberGenerator,
+) -> Result<String> {
+    let header = format!("{{\"alg\":\"{}\",\"typ\":\"JWT\"}}", alg.name());
+    jws_sign_with_header(key, alg, &header, claims.as_bytes(), rng)
+}
+
+/// Verify a JWT and return its claims set
+///
+/// Only the signature is checked; validating the claims (expiry,
+/// audience, ...) is left to the application.
+pub fn jwt_verify(key: &Pubkey, alg: JwsAlgorithm, jwt: &str) -> Result<String> {
+    let claims = jws_verify(key, alg, jwt)?;
+    String::from_utf8(claims).map_err(Error::conversion_error)
+}
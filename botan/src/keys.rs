@@ -0,0 +1,998 @@
+use crate::utils::*;
+
+#[cfg(feature = "botan3")]
+use crate::capability::Capability;
+use crate::ec::EcPoint;
+use crate::mp::MPI;
+use crate::pubkey::{Privkey, Pubkey};
+use crate::rng::RandomNumberGenerator;
+
+use core::convert::TryFrom;
+
+// Curves checked when converting a generic Privkey to an EcdsaKeyPair,
+// since the FFI cannot report the name of a key's curve
+const KNOWN_CURVES: &[&str] = &[
+    "secp256r1",
+    "secp384r1",
+    "secp521r1",
+    "secp256k1",
+    "brainpool256r1",
+    "brainpool384r1",
+    "brainpool512r1",
+    "secp224r1",
+    "secp192r1",
+    "frp256v1",
+    "sm2p256v1",
+    "gost_256A",
+    "gost_512A",
+];
+
+// Some algorithms have more than one name, depending on the version
+// of the library
+fn check_algo(algo: String, expected: &[&str]) -> Result<()> {
+    if !expected.contains(&algo.as_str()) {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            format!("Expected a {} key but got {}", expected[0], algo),
+        ));
+    }
+    Ok(())
+}
+
+fn to_array(v: &[u8]) -> Result<[u8; 32]> {
+    if v.len() != 32 {
+        return Err(Error::with_message(
+            ErrorType::InternalError,
+            format!("Unexpected key length {}", v.len()),
+        ));
+    }
+    let mut out = [0u8; 32];
+    out.copy_from_slice(v);
+    Ok(out)
+}
+
+macro_rules! typed_privkey {
+    ($name:ident) => {
+        impl $name {
+            /// Return the generic private key
+            #[must_use]
+            pub fn as_privkey(&self) -> &Privkey {
+                &self.key
+            }
+
+            /// Return the public key
+            pub fn public_key(&self) -> Result<Pubkey> {
+                self.key.pubkey()
+            }
+        }
+
+        impl AsRef<Privkey> for $name {
+            fn as_ref(&self) -> &Privkey {
+                &self.key
+            }
+        }
+
+        impl From<$name> for Privkey {
+            fn from(key: $name) -> Privkey {
+                key.key
+            }
+        }
+
+        #[cfg(feature = "pkcs8")]
+        impl<'a> TryFrom<pkcs8::PrivateKeyInfo<'a>> for $name {
+            type Error = pkcs8::Error;
+
+            fn try_from(info: pkcs8::PrivateKeyInfo<'a>) -> pkcs8::Result<$name> {
+                $name::try_from(Privkey::try_from(info)?).map_err(|_| pkcs8::Error::KeyMalformed)
+            }
+        }
+
+        #[cfg(feature = "pkcs8")]
+        impl pkcs8::EncodePrivateKey for $name {
+            fn to_pkcs8_der(&self) -> pkcs8::Result<pkcs8::SecretDocument> {
+                pkcs8::EncodePrivateKey::to_pkcs8_der(&self.key)
+            }
+        }
+    };
+}
+
+macro_rules! typed_pubkey {
+    ($name:ident, $load:ident, $get:ident, $algos:expr) => {
+        impl $name {
+            /// Load a public key from its 32 byte encoding
+            pub fn from_bytes(bytes: [u8; 32]) -> Result<$name> {
+                Ok($name {
+                    key: Pubkey::$load(&bytes)?,
+                    bytes,
+                })
+            }
+
+            /// Return the 32 byte encoding of the public key
+            #[must_use]
+            pub fn to_bytes(&self) -> [u8; 32] {
+                self.bytes
+            }
+
+            /// Return the generic public key
+            #[must_use]
+            pub fn as_pubkey(&self) -> &Pubkey {
+                &self.key
+            }
+        }
+
+        impl AsRef<Pubkey> for $name {
+            fn as_ref(&self) -> &Pubkey {
+                &self.key
+            }
+        }
+
+        impl From<$name> for Pubkey {
+            fn from(key: $name) -> Pubkey {
+                key.key
+            }
+        }
+
+        impl TryFrom<Pubkey> for $name {
+            type Error = Error;
+
+            fn try_from(key: Pubkey) -> Result<$name> {
+                check_algo(key.algo_name()?, $algos)?;
+                let bytes = to_array(&key.$get()?)?;
+                Ok($name { key, bytes })
+            }
+        }
+    };
+}
+
+/// An RSA private key
+///
+/// # Examples
+/// ```
+/// use std::convert::TryFrom;
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::RsaPrivateKey::generate(1024, &mut rng).unwrap();
+/// assert_eq!(key.bits().unwrap(), 1024);
+///
+/// let generic: botan::Privkey = key.into();
+/// let key = botan::RsaPrivateKey::try_from(generic).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct RsaPrivateKey {
+    key: Privkey,
+}
+
+typed_privkey!(RsaPrivateKey);
+
+impl RsaPrivateKey {
+    /// Generate a new RSA key with a modulus of `bits` bits
+    pub fn generate(bits: usize, rng: &mut RandomNumberGenerator) -> Result<RsaPrivateKey> {
+        Ok(RsaPrivateKey {
+            key: Privkey::create("RSA", &bits.to_string(), rng)?,
+        })
+    }
+
+    /// Load an RSA key from its primes and public exponent
+    pub fn load(p: &MPI, q: &MPI, e: &MPI) -> Result<RsaPrivateKey> {
+        Ok(RsaPrivateKey {
+            key: Privkey::load_rsa(p, q, e)?,
+        })
+    }
+
+    /// Return the public modulus
+    pub fn modulus(&self) -> Result<MPI> {
+        self.key.get_field("n")
+    }
+
+    /// Return the public exponent
+    pub fn public_exponent(&self) -> Result<MPI> {
+        self.key.get_field("e")
+    }
+
+    /// Return the size of the modulus in bits
+    pub fn bits(&self) -> Result<usize> {
+        self.modulus()?.bit_count()
+    }
+
+    /// Return the first prime factor of the modulus
+    pub fn p(&self) -> Result<MPI> {
+        self.key.get_field("p")
+    }
+
+    /// Return the second prime factor of the modulus
+    pub fn q(&self) -> Result<MPI> {
+        self.key.get_field("q")
+    }
+
+    /// Return the private exponent
+    pub fn private_exponent(&self) -> Result<MPI> {
+        self.key.get_field("d")
+    }
+}
+
+impl TryFrom<Privkey> for RsaPrivateKey {
+    type Error = Error;
+
+    fn try_from(key: Privkey) -> Result<RsaPrivateKey> {
+        check_algo(key.algo_name()?, &["RSA"])?;
+        Ok(RsaPrivateKey { key })
+    }
+}
+
+// Identify the named curve of an elliptic curve key by comparing its
+// domain parameters (read with `field`) with those of each known curve
+pub(crate) fn find_curve(field: &dyn Fn(&str) -> Result<MPI>) -> Result<String> {
+    let p = field("p")?;
+    let order = field("order")?;
+    let base_x = field("base_x")?;
+
+    for curve in KNOWN_CURVES {
+        // A key with private value 1 has the group generator as public point
+        let candidate = match Privkey::load_ecdsa(&MPI::new_from_u32(1)?, curve) {
+            Ok(candidate) => candidate,
+            Err(_) => continue,
+        };
+
+        if candidate.get_field("p")? == p
+            && candidate.get_field("order")? == order
+            && candidate.get_field("base_x")? == base_x
+        {
+            return Ok((*curve).to_owned());
+        }
+    }
+
+    Err(Error::with_message(
+        ErrorType::NotImplemented,
+        "The curve of this key is not known".to_owned(),
+    ))
+}
+
+macro_rules! typed_ec_privkey {
+    ($name:ident, $algo:expr) => {
+        typed_privkey!($name);
+
+        impl $name {
+            /// Generate a new key on the named curve
+            pub fn generate(curve: &str, rng: &mut RandomNumberGenerator) -> Result<$name> {
+                Ok($name {
+                    key: Privkey::create($algo, curve, rng)?,
+                    curve: curve.to_owned(),
+                })
+            }
+
+            /// Return the name of the curve
+            #[must_use]
+            pub fn curve(&self) -> &str {
+                &self.curve
+            }
+
+            /// Return the public point
+            pub fn public_point(&self) -> Result<EcPoint> {
+                EcPoint::from_affine(
+                    &self.curve,
+                    &self.key.get_field("public_x")?,
+                    &self.key.get_field("public_y")?,
+                )
+            }
+
+            /// Return the private scalar
+            pub fn private_scalar(&self) -> Result<MPI> {
+                self.key.get_field("x")
+            }
+        }
+
+        /// Converts a generic key, identifying its curve among the commonly
+        /// used named curves
+        impl TryFrom<Privkey> for $name {
+            type Error = Error;
+
+            fn try_from(key: Privkey) -> Result<$name> {
+                check_algo(key.algo_name()?, &[$algo])?;
+                let curve = find_curve(&|f| key.get_field(f))?;
+                Ok($name { key, curve })
+            }
+        }
+    };
+}
+
+/// A DSA private key
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::DsaKeyPair::generate_with_group("dsa/botan/2048", &mut rng).unwrap();
+/// let sig = key.sign(b"message", "SHA-256", &mut rng).unwrap();
+/// assert!(key.verify(b"message", &sig, "SHA-256").unwrap());
+/// ```
+#[derive(Debug)]
+pub struct DsaKeyPair {
+    key: Privkey,
+}
+
+typed_privkey!(DsaKeyPair);
+
+impl DsaKeyPair {
+    /// Generate a new key, along with new parameters of the given sizes
+    /// (for example 2048 and 256)
+    ///
+    /// Parameter generation can take quite some time.
+    pub fn generate(
+        pbits: usize,
+        qbits: usize,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<DsaKeyPair> {
+        Ok(DsaKeyPair {
+            key: Privkey::create_dsa(pbits, qbits, rng)?,
+        })
+    }
+
+    /// Generate a new key in a named group, such as `dsa/botan/2048`
+    /// or `dsa/jce/1024`
+    pub fn generate_with_group(group: &str, rng: &mut RandomNumberGenerator) -> Result<DsaKeyPair> {
+        Ok(DsaKeyPair {
+            key: Privkey::create("DSA", group, rng)?,
+        })
+    }
+
+    /// Generate a new key using existing parameters (p,q,g)
+    pub fn generate_with_params(
+        p: &MPI,
+        q: &MPI,
+        g: &MPI,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<DsaKeyPair> {
+        let mut x = MPI::new()?;
+        x.random_range(rng, &MPI::new_from_u32(1)?, q)?;
+        DsaKeyPair::load(p, q, g, &x)
+    }
+
+    /// Load a DSA key from its parameters and private value
+    pub fn load(p: &MPI, q: &MPI, g: &MPI, x: &MPI) -> Result<DsaKeyPair> {
+        Ok(DsaKeyPair {
+            key: Privkey::load_dsa(p, q, g, x)?,
+        })
+    }
+
+    /// Return the prime p
+    pub fn p(&self) -> Result<MPI> {
+        self.key.get_field("p")
+    }
+
+    /// Return the subgroup order q
+    pub fn q(&self) -> Result<MPI> {
+        self.key.get_field("q")
+    }
+
+    /// Return the generator g
+    pub fn g(&self) -> Result<MPI> {
+        self.key.get_field("g")
+    }
+
+    /// Return the public value y
+    pub fn y(&self) -> Result<MPI> {
+        self.key.get_field("y")
+    }
+
+    /// Return the private value x
+    pub fn x(&self) -> Result<MPI> {
+        self.key.get_field("x")
+    }
+
+    /// Sign a message, hashing it with `hash` (for example `"SHA-256"`)
+    pub fn sign<H: AsRef<str>>(
+        &self,
+        message: &[u8],
+        hash: H,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<Vec<u8>> {
+        self.key.sign(message, &dsa_padding(hash.as_ref()), rng)
+    }
+
+    /// Verify a signature of a message made with this key
+    pub fn verify<H: AsRef<str>>(&self, message: &[u8], signature: &[u8], hash: H) -> Result<bool> {
+        self.key
+            .pubkey()?
+            .verify(message, signature, &dsa_padding(hash.as_ref()))
+    }
+}
+
+// DSA signatures, like ECDSA, truncate the hash to the size of the group
+fn dsa_padding(hash: &str) -> String {
+    format!("EMSA1({})", hash)
+}
+
+impl TryFrom<Privkey> for DsaKeyPair {
+    type Error = Error;
+
+    fn try_from(key: Privkey) -> Result<DsaKeyPair> {
+        check_algo(key.algo_name()?, &["DSA"])?;
+        Ok(DsaKeyPair { key })
+    }
+}
+
+/// An ElGamal private key
+///
+/// Messages are encrypted with one of the padding methods used for RSA
+/// encryption, such as `"OAEP(SHA-256)"` or `"PKCS1v15"`.
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::ElGamalKeyPair::generate_with_group("modp/ietf/2048", &mut rng).unwrap();
+/// let ctext = key.encrypt(b"message", "OAEP(SHA-256)", &mut rng).unwrap();
+/// assert_eq!(key.decrypt(&ctext, "OAEP(SHA-256)").unwrap(), b"message");
+/// ```
+#[derive(Debug)]
+pub struct ElGamalKeyPair {
+    key: Privkey,
+}
+
+typed_privkey!(ElGamalKeyPair);
+
+impl ElGamalKeyPair {
+    /// Generate a new key, along with a new group of the given sizes
+    ///
+    /// Parameter generation can take quite some time.
+    pub fn generate(
+        pbits: usize,
+        qbits: usize,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<ElGamalKeyPair> {
+        Ok(ElGamalKeyPair {
+            key: Privkey::create_elgamal(pbits, qbits, rng)?,
+        })
+    }
+
+    /// Generate a new key in a named group, such as `modp/ietf/2048`
+    pub fn generate_with_group(
+        group: &str,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<ElGamalKeyPair> {
+        Ok(ElGamalKeyPair {
+            key: Privkey::create("ElGamal", group, rng)?,
+        })
+    }
+
+    /// Load an ElGamal key from its group (p,g) and private value
+    pub fn load(p: &MPI, g: &MPI, x: &MPI) -> Result<ElGamalKeyPair> {
+        Ok(ElGamalKeyPair {
+            key: Privkey::load_elgamal(p, g, x)?,
+        })
+    }
+
+    /// Return the prime p
+    pub fn p(&self) -> Result<MPI> {
+        self.key.get_field("p")
+    }
+
+    /// Return the generator g
+    pub fn g(&self) -> Result<MPI> {
+        self.key.get_field("g")
+    }
+
+    /// Return the public value y
+    pub fn y(&self) -> Result<MPI> {
+        self.key.get_field("y")
+    }
+
+    /// Return the private value x
+    pub fn x(&self) -> Result<MPI> {
+        self.key.get_field("x")
+    }
+
+    /// Encrypt a message to this key using the specified padding method
+    pub fn encrypt(
+        &self,
+        message: &[u8],
+        padding: &str,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<Vec<u8>> {
+        self.key.pubkey()?.encrypt(message, padding, rng)
+    }
+
+    /// Decrypt a message that was encrypted using the specified padding method
+    pub fn decrypt(&self, ctext: &[u8], padding: &str) -> Result<Vec<u8>> {
+        self.key.decrypt(ctext, padding)
+    }
+}
+
+impl TryFrom<Privkey> for ElGamalKeyPair {
+    type Error = Error;
+
+    fn try_from(key: Privkey) -> Result<ElGamalKeyPair> {
+        check_algo(key.algo_name()?, &["ElGamal"])?;
+        Ok(ElGamalKeyPair { key })
+    }
+}
+
+/// An ECDSA private key, along with the name of its curve
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::EcdsaKeyPair::generate("secp256r1", &mut rng).unwrap();
+/// assert_eq!(key.curve(), "secp256r1");
+/// let point = key.public_point().unwrap();
+/// assert_eq!(point.curve_name(), "secp256r1");
+/// ```
+#[derive(Debug)]
+pub struct EcdsaKeyPair {
+    key: Privkey,
+    curve: String,
+}
+
+typed_ec_privkey!(EcdsaKeyPair, "ECDSA");
+
+impl EcdsaKeyPair {
+    /// Load an ECDSA key from its private scalar
+    pub fn load(s: &MPI, curve: &str) -> Result<EcdsaKeyPair> {
+        Ok(EcdsaKeyPair {
+            key: Privkey::load_ecdsa(s, curve)?,
+            curve: curve.to_owned(),
+        })
+    }
+}
+
+/// An ECGDSA (German elliptic curve DSA, as used by BSI TR-03111) private
+/// key, along with the name of its curve
+///
+/// Signatures use the same padding strings as ECDSA, for example
+/// `"EMSA1(SHA-256)"`.
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::EcgdsaKeyPair::generate("brainpool256r1", &mut rng).unwrap();
+/// let sig = key.as_privkey().sign(b"message", "EMSA1(SHA-256)", &mut rng).unwrap();
+/// assert!(key.public_key().unwrap().verify(b"message", &sig, "EMSA1(SHA-256)").unwrap());
+/// ```
+#[derive(Debug)]
+pub struct EcgdsaKeyPair {
+    key: Privkey,
+    curve: String,
+}
+
+typed_ec_privkey!(EcgdsaKeyPair, "ECGDSA");
+
+/// An ECKCDSA (Korean certificate based DSA, as in TTAK.KO-12.0015) private
+/// key, along with the name of its curve
+///
+/// Signatures use the same padding strings as ECDSA, for example
+/// `"EMSA1(SHA-256)"`.
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::EckcdsaKeyPair::generate("secp256r1", &mut rng).unwrap();
+/// let sig = key.as_privkey().sign(b"message", "EMSA1(SHA-256)", &mut rng).unwrap();
+/// assert!(key.public_key().unwrap().verify(b"message", &sig, "EMSA1(SHA-256)").unwrap());
+/// ```
+#[derive(Debug)]
+pub struct EckcdsaKeyPair {
+    key: Privkey,
+    curve: String,
+}
+
+typed_ec_privkey!(EckcdsaKeyPair, "ECKCDSA");
+
+/// The parameter sets of GOST R 34.10-2012 signatures
+///
+/// Each fixes the curve, the key algorithm and the Streebog hash used
+/// for signing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GostParameterSet {
+    /// 256 bit keys on curve `gost_256A`, signing with Streebog-256
+    Gost256A,
+    /// 512 bit keys on curve `gost_512A`, signing with Streebog-512
+    Gost512A,
+}
+
+impl GostParameterSet {
+    fn algo_name(self) -> &'static str {
+        match self {
+            GostParameterSet::Gost256A => "GOST-34.10-2012-256",
+            GostParameterSet::Gost512A => "GOST-34.10-2012-512",
+        }
+    }
+
+    /// Return the name of the curve
+    #[must_use]
+    pub fn curve(self) -> &'static str {
+        match self {
+            GostParameterSet::Gost256A => "gost_256A",
+            GostParameterSet::Gost512A => "gost_512A",
+        }
+    }
+
+    /// Return the padding to use with `Signer` and `Verifier`
+    #[must_use]
+    pub fn padding(self) -> &'static str {
+        match self {
+            GostParameterSet::Gost256A => "EMSA1(Streebog-256)",
+            GostParameterSet::Gost512A => "EMSA1(Streebog-512)",
+        }
+    }
+
+    fn from_curve(curve: &str) -> Option<GostParameterSet> {
+        match curve {
+            "gost_256A" => Some(GostParameterSet::Gost256A),
+            "gost_512A" => Some(GostParameterSet::Gost512A),
+            _ => None,
+        }
+    }
+}
+
+/// A GOST R 34.10-2012 private key
+///
+/// # Examples
+/// ```
+/// use botan::{GostKeyPair, GostParameterSet};
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = GostKeyPair::generate(GostParameterSet::Gost256A, &mut rng).unwrap();
+/// let sig = key.sign(b"message", &mut rng).unwrap();
+/// assert!(key.verify(b"message", &sig).unwrap());
+/// ```
+#[derive(Debug)]
+pub struct GostKeyPair {
+    key: Privkey,
+    params: GostParameterSet,
+}
+
+typed_privkey!(GostKeyPair);
+
+impl GostKeyPair {
+    /// Generate a new key with the given parameter set
+    pub fn generate(
+        params: GostParameterSet,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<GostKeyPair> {
+        Ok(GostKeyPair {
+            key: Privkey::create(params.algo_name(), params.curve(), rng)?,
+            params,
+        })
+    }
+
+    /// Return the parameter set of this key
+    #[must_use]
+    pub fn parameter_set(&self) -> GostParameterSet {
+        self.params
+    }
+
+    /// Return the public point
+    pub fn public_point(&self) -> Result<EcPoint> {
+        EcPoint::from_affine(
+            self.params.curve(),
+            &self.key.get_field("public_x")?,
+            &self.key.get_field("public_y")?,
+        )
+    }
+
+    /// Return the private scalar
+    pub fn private_scalar(&self) -> Result<MPI> {
+        self.key.get_field("x")
+    }
+
+    /// Sign a message, using the hash of the key's parameter set
+    pub fn sign(&self, message: &[u8], rng: &mut RandomNumberGenerator) -> Result<Vec<u8>> {
+        self.key.sign(message, self.params.padding(), rng)
+    }
+
+    /// Verify a signature of a message made with this key
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<bool> {
+        self.key
+            .pubkey()?
+            .verify(message, signature, self.params.padding())
+    }
+}
+
+/// Converts a generic GOST 34.10-2012 key on one of the curves of
+/// `GostParameterSet`
+impl TryFrom<Privkey> for GostKeyPair {
+    type Error = Error;
+
+    fn try_from(key: Privkey) -> Result<GostKeyPair> {
+        check_algo(
+            key.algo_name()?,
+            &["GOST-34.10-2012-256", "GOST-34.10-2012-512"],
+        )?;
+        let params =
+            GostParameterSet::from_curve(&find_curve(&|f| key.get_field(f))?).ok_or_else(|| {
+                Error::with_message(
+                    ErrorType::NotImplemented,
+                    "Unsupported GOST 34.10 parameter set".to_owned(),
+                )
+            })?;
+        if params.algo_name() != key.algo_name()? {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "GOST 34.10 key size does not match its curve".to_owned(),
+            ));
+        }
+        Ok(GostKeyPair { key, params })
+    }
+}
+
+/// An HSS/LMS private key (RFC 8554), a stateful hash based signature
+/// scheme
+///
+/// Keys are created from a parameter string giving the hash and the
+/// height and Winternitz parameter of each tree level, for example
+/// `"SHA-256,HW(10,4)"` (1024 signatures) or
+/// `"SHA-256,HW(10,4),HW(10,4)"` (about a million).
+///
+/// Every signature consumes a one time key, so the private key changes
+/// each time it is used. `sign` returns the updated key along with the
+/// signature; it must be stored, replacing the old key, before the
+/// signature is released. Signing again with an old copy of the key
+/// reuses one time keys and allows forgeries.
+///
+/// This requires Botan 3.8 or later
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let mut key = botan::HssLmsKeyPair::generate("SHA-256,HW(5,8)", &mut rng).unwrap();
+/// assert_eq!(key.remaining_signatures().unwrap(), 32);
+/// let (sig, state) = key.sign(b"firmware image", &mut rng).unwrap();
+/// // ... persist `state` before using `sig` ...
+/// assert!(key.verify(b"firmware image", &sig).unwrap());
+/// assert_eq!(key.remaining_signatures().unwrap(), 31);
+///
+/// let key = botan::HssLmsKeyPair::load(&state).unwrap();
+/// assert_eq!(key.remaining_signatures().unwrap(), 31);
+/// ```
+#[cfg(feature = "botan3")]
+#[derive(Debug)]
+pub struct HssLmsKeyPair {
+    key: Privkey,
+}
+
+#[cfg(feature = "botan3")]
+typed_privkey!(HssLmsKeyPair);
+
+#[cfg(feature = "botan3")]
+impl HssLmsKeyPair {
+    /// Generate a new key with the given parameters
+    pub fn generate(params: &str, rng: &mut RandomNumberGenerator) -> Result<HssLmsKeyPair> {
+        Capability::StatefulKeys.require()?;
+        Ok(HssLmsKeyPair {
+            key: Privkey::create("HSS-LMS", params, rng)?,
+        })
+    }
+
+    /// Load a key (in its current state) from its DER encoding, as
+    /// returned by `sign`
+    pub fn load(der: &[u8]) -> Result<HssLmsKeyPair> {
+        HssLmsKeyPair::try_from(Privkey::load_der(der)?)
+    }
+
+    /// Return how many more signatures this key can create
+    pub fn remaining_signatures(&self) -> Result<u64> {
+        self.key.remaining_operations()
+    }
+
+    /// Sign a message
+    ///
+    /// Returns the signature and the DER encoding of the updated
+    /// private key, which must be persisted in place of the previous
+    /// state before the signature is used. Fails if the key has no
+    /// remaining signatures.
+    pub fn sign(
+        &mut self,
+        message: &[u8],
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        if self.remaining_signatures()? == 0 {
+            return Err(Error::with_message(
+                ErrorType::InvalidObjectState,
+                "HSS/LMS key has no remaining signatures".to_owned(),
+            ));
+        }
+        let signature = self.key.sign(message, "", rng)?;
+        let state = self.key.der_encode()?;
+        Ok((signature, state))
+    }
+
+    /// Verify a signature of a message made with this key
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<bool> {
+        self.key.pubkey()?.verify(message, signature, "")
+    }
+}
+
+#[cfg(feature = "botan3")]
+impl TryFrom<Privkey> for HssLmsKeyPair {
+    type Error = Error;
+
+    fn try_from(key: Privkey) -> Result<HssLmsKeyPair> {
+        Capability::StatefulKeys.require()?;
+        check_algo(key.algo_name()?, &["HSS-LMS"])?;
+        Ok(HssLmsKeyPair { key })
+    }
+}
+
+/// An Ed25519 private key
+///
+/// # Examples
+/// ```
+/// let key = botan::Ed25519KeyPair::load(&[0x42; 32]).unwrap();
+/// let public = key.public_key_bytes().unwrap();
+/// assert_eq!(key.public_key().unwrap().get_ed25519_key().unwrap(), public);
+/// ```
+#[derive(Debug)]
+pub struct Ed25519KeyPair {
+    key: Privkey,
+}
+
+typed_privkey!(Ed25519KeyPair);
+
+impl Ed25519KeyPair {
+    /// Generate a new Ed25519 key
+    pub fn generate(rng: &mut RandomNumberGenerator) -> Result<Ed25519KeyPair> {
+        Ok(Ed25519KeyPair {
+            key: Privkey::create("Ed25519", "", rng)?,
+        })
+    }
+
+    /// Load an Ed25519 key from its 32 byte seed
+    pub fn load(seed: &[u8; 32]) -> Result<Ed25519KeyPair> {
+        Ok(Ed25519KeyPair {
+            key: Privkey::load_ed25519(seed)?,
+        })
+    }
+
+    /// Return the 32 byte public key
+    pub fn public_key_bytes(&self) -> Result<[u8; 32]> {
+        to_array(&self.key.get_ed25519_key()?.0)
+    }
+
+    /// Return the 32 byte seed
+    pub fn private_key_bytes(&self) -> Result<Vec<u8>> {
+        Ok(self.key.get_ed25519_key()?.1)
+    }
+
+    /// Load an Ed25519 key from its 32 byte seed
+    ///
+    /// The same as `load`, for symmetry with `to_bytes`
+    pub fn from_bytes(seed: [u8; 32]) -> Result<Ed25519KeyPair> {
+        Ed25519KeyPair::load(&seed)
+    }
+
+    /// Return the 32 byte seed
+    ///
+    /// The same as `private_key_bytes`
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        self.private_key_bytes()
+    }
+
+    /// Return the public key as an `Ed25519PublicKey`
+    pub fn public(&self) -> Result<Ed25519PublicKey> {
+        Ed25519PublicKey::from_bytes(self.public_key_bytes()?)
+    }
+}
+
+impl TryFrom<Privkey> for Ed25519KeyPair {
+    type Error = Error;
+
+    fn try_from(key: Privkey) -> Result<Ed25519KeyPair> {
+        check_algo(key.algo_name()?, &["Ed25519"])?;
+        Ok(Ed25519KeyPair { key })
+    }
+}
+
+/// An Ed25519 public key
+///
+/// # Examples
+/// ```
+/// let key = botan::Ed25519KeyPair::from_bytes([0x42; 32]).unwrap();
+/// let public = botan::Ed25519PublicKey::from_bytes(key.public_key_bytes().unwrap()).unwrap();
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let sig = key.as_privkey().sign(b"message", "Pure", &mut rng).unwrap();
+/// assert!(public.as_pubkey().verify(b"message", &sig, "Pure").unwrap());
+/// ```
+#[derive(Debug)]
+pub struct Ed25519PublicKey {
+    key: Pubkey,
+    bytes: [u8; 32],
+}
+
+typed_pubkey!(
+    Ed25519PublicKey,
+    load_ed25519,
+    get_ed25519_key,
+    &["Ed25519"]
+);
+
+/// An X25519 private key
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let alice = botan::X25519KeyPair::generate(&mut rng).unwrap();
+/// let bob = botan::X25519KeyPair::generate(&mut rng).unwrap();
+///
+/// let k1 = alice.as_privkey().agree(&bob.public_key_bytes().unwrap(), 32, b"", "Raw").unwrap();
+/// let k2 = bob.as_privkey().agree(&alice.public_key_bytes().unwrap(), 32, b"", "Raw").unwrap();
+/// assert_eq!(k1, k2);
+/// ```
+#[derive(Debug)]
+pub struct X25519KeyPair {
+    key: Privkey,
+}
+
+typed_privkey!(X25519KeyPair);
+
+impl X25519KeyPair {
+    /// Generate a new X25519 key
+    pub fn generate(rng: &mut RandomNumberGenerator) -> Result<X25519KeyPair> {
+        Ok(X25519KeyPair {
+            key: Privkey::create("X25519", "", rng)?,
+        })
+    }
+
+    /// Load an X25519 key from its 32 byte private value
+    pub fn load(key: &[u8; 32]) -> Result<X25519KeyPair> {
+        Ok(X25519KeyPair {
+            key: Privkey::load_x25519(key)?,
+        })
+    }
+
+    /// Return the 32 byte public key
+    pub fn public_key_bytes(&self) -> Result<[u8; 32]> {
+        to_array(&self.key.pubkey()?.get_x25519_key()?)
+    }
+
+    /// Return the 32 byte private value
+    pub fn private_key_bytes(&self) -> Result<Vec<u8>> {
+        self.key.get_x25519_key()
+    }
+
+    /// Load an X25519 key from its 32 byte private value
+    ///
+    /// The same as `load`, for symmetry with `to_bytes`
+    pub fn from_bytes(key: [u8; 32]) -> Result<X25519KeyPair> {
+        X25519KeyPair::load(&key)
+    }
+
+    /// Return the 32 byte private value
+    ///
+    /// The same as `private_key_bytes`
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        self.private_key_bytes()
+    }
+
+    /// Return the public key as an `X25519PublicKey`
+    pub fn public(&self) -> Result<X25519PublicKey> {
+        X25519PublicKey::from_bytes(self.public_key_bytes()?)
+    }
+}
+
+impl TryFrom<Privkey> for X25519KeyPair {
+    type Error = Error;
+
+    fn try_from(key: Privkey) -> Result<X25519KeyPair> {
+        check_algo(key.algo_name()?, &["X25519", "Curve25519"])?;
+        Ok(X25519KeyPair { key })
+    }
+}
+
+/// An X25519 public key
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::X25519KeyPair::generate(&mut rng).unwrap();
+/// let public = key.public().unwrap();
+/// let copy = botan::X25519PublicKey::from_bytes(public.to_bytes()).unwrap();
+/// assert_eq!(copy.to_bytes(), key.public_key_bytes().unwrap());
+/// ```
+#[derive(Debug)]
+pub struct X25519PublicKey {
+    key: Pubkey,
+    bytes: [u8; 32],
+}
+
+typed_pubkey!(
+    X25519PublicKey,
+    load_x25519,
+    get_x25519_key,
+    &["X25519", "Curve25519"]
+);
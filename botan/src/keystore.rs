@@ -0,0 +1,665 @@
+use crate::utils::*;
+
+use core::convert::TryFrom;
+
+use crate::aead::{AeadDecryptor, AeadEncryptor};
+use crate::algo::AeadAlgo;
+use crate::memutils::{scrub_mem, SecretVec};
+use crate::pbkdf::derive_key_from_password;
+use crate::pubkey::{Privkey, Pubkey};
+use crate::rng::RandomNumberGenerator;
+use crate::x509::Certificate;
+
+// A keystore file is a fixed size header followed by the AEAD encrypted
+// entries. The whole header is authenticated as associated data.
+//
+//   magic          8 bytes, "botan-ks"
+//   version        u16
+//   kdf            u8, 1 = Argon2id
+//   memory (KiB)   u32
+//   iterations     u32
+//   parallelism    u32
+//   salt           16 bytes
+//   nonce          12 bytes
+//
+// The plaintext is a u32 entry count, then for each entry a u16 length
+// prefixed name, a kind byte and a u32 count of versions, each being a
+// u32 version number and u32 length prefixed data. All integers are big
+// endian.
+
+const KEYSTORE_MAGIC: &[u8] = b"botan-ks";
+const KEYSTORE_VERSION: u16 = 1;
+const KDF_ARGON2ID: u8 = 1;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+const HEADER_LENGTH: usize = 8 + 2 + 1 + 3 * 4 + SALT_LENGTH + NONCE_LENGTH;
+
+// The defaults for new files, recommended by RFC 9106
+pub(crate) const ARGON2_MEMORY_KIB: u32 = 64 * 1024;
+pub(crate) const ARGON2_ITERATIONS: u32 = 3;
+pub(crate) const ARGON2_PARALLELISM: u32 = 4;
+
+// Files asking for more than this are rejected by default, so that
+// loading an untrusted file cannot exhaust memory or time
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 10;
+const MAX_PARALLELISM: u32 = 255;
+
+/// The largest Argon2id parameters accepted when loading a file
+///
+/// Since the parameters are read from the file, a file from an untrusted
+/// source could otherwise make loading it use any amount of memory and
+/// time. The default allows 1 GiB of memory, 10 iterations and a
+/// parallelism of 255, which covers the RFC 9106 recommendations.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Argon2Limits {
+    /// The most memory, in KiB
+    pub max_memory_kib: u32,
+    /// The most iterations
+    pub max_iterations: u32,
+    /// The highest parallelism
+    pub max_parallelism: u32,
+}
+
+impl Default for Argon2Limits {
+    fn default() -> Argon2Limits {
+        Argon2Limits {
+            max_memory_kib: MAX_MEMORY_KIB,
+            max_iterations: MAX_ITERATIONS,
+            max_parallelism: MAX_PARALLELISM,
+        }
+    }
+}
+
+impl Argon2Limits {
+    // Reject Argon2id parameters read from a file which exceed the limits
+    pub(crate) fn check(&self, memory_kib: u32, iterations: u32, parallelism: u32) -> Result<()> {
+        if memory_kib > self.max_memory_kib
+            || iterations > self.max_iterations
+            || parallelism > self.max_parallelism
+        {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "Argon2id parameters are too large".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn argon2id_key(
+    password: &str,
+    salt: &[u8],
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+) -> Result<Vec<u8>> {
+    derive_key_from_password(
+        "Argon2id",
+        32,
+        password,
+        salt,
+        memory_kib as usize,
+        iterations as usize,
+        parallelism as usize,
+    )
+}
+
+fn keystore_error(msg: &str) -> Error {
+    Error::with_message(ErrorType::InvalidInput, msg.to_owned())
+}
+
+/// The kind of item held in a `Keystore` entry
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeystoreEntryKind {
+    /// A private key, stored as PKCS #8
+    PrivateKey,
+    /// A public key, stored as SubjectPublicKeyInfo
+    PublicKey,
+    /// An X.509 certificate, stored as given
+    Certificate,
+    /// Arbitrary secret bytes, such as a symmetric key
+    Secret,
+}
+
+impl KeystoreEntryKind {
+    fn code(self) -> u8 {
+        match self {
+            Self::PrivateKey => 1,
+            Self::PublicKey => 2,
+            Self::Certificate => 3,
+            Self::Secret => 4,
+        }
+    }
+
+    fn from_code(code: u8) -> Result<KeystoreEntryKind> {
+        match code {
+            1 => Ok(Self::PrivateKey),
+            2 => Ok(Self::PublicKey),
+            3 => Ok(Self::Certificate),
+            4 => Ok(Self::Secret),
+            _ => Err(keystore_error("Unknown keystore entry kind")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct KeystoreEntry {
+    name: String,
+    kind: KeystoreEntryKind,
+    // Oldest first, so the last one is current
+    versions: Vec<(u32, SecretVec)>,
+}
+
+/// A password protected container of named keys, certificates and
+/// secrets
+///
+/// Each entry may hold several versions of its item: `rotate_*` adds a
+/// new version which becomes current, while older ones stay available
+/// (for example to decrypt or verify old data) until removed with
+/// `prune`. Versions are numbered from 1.
+///
+/// The contents are encrypted with AES-256/GCM using a key derived from
+/// the password with Argon2id. To change the password, load the keystore
+/// and save it again with the new one.
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let mut keystore = botan::Keystore::new();
+/// keystore.set_kdf_params(1024, 1, 1);
+///
+/// let key = botan::Privkey::create("Ed25519", "", &mut rng).unwrap();
+/// keystore.insert_private_key("signing", &key).unwrap();
+/// keystore.insert_secret("api-token", b"hunter2").unwrap();
+///
+/// let new_key = botan::Privkey::create("Ed25519", "", &mut rng).unwrap();
+/// assert_eq!(keystore.rotate_private_key("signing", &new_key).unwrap(), 2);
+///
+/// let file = keystore.save("password", &mut rng).unwrap();
+/// let loaded = botan::Keystore::load(&file, "password").unwrap();
+/// assert_eq!(loaded.versions("signing").unwrap(), vec![1, 2]);
+/// assert_eq!(loaded.secret("api-token").unwrap(), b"hunter2".to_vec());
+/// assert!(botan::Keystore::load(&file, "wrong").is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Keystore {
+    entries: Vec<KeystoreEntry>,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl Default for Keystore {
+    fn default() -> Keystore {
+        Keystore::new()
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(keystore_error("Truncated keystore"));
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+fn encoded_len<T: TryFrom<usize>>(len: usize, what: &str) -> Result<T> {
+    T::try_from(len).map_err(|_| {
+        Error::with_message(
+            ErrorType::BadParameter,
+            format!("Keystore {} is too long", what),
+        )
+    })
+}
+
+impl Keystore {
+    /// Create an empty keystore
+    ///
+    /// The KDF parameters default to 64 MiB of memory, 3 iterations and
+    /// a parallelism of 4, as recommended by RFC 9106.
+    #[must_use]
+    pub fn new() -> Keystore {
+        Keystore {
+            entries: Vec::new(),
+            memory_kib: ARGON2_MEMORY_KIB,
+            iterations: ARGON2_ITERATIONS,
+            parallelism: ARGON2_PARALLELISM,
+        }
+    }
+
+    /// Set the Argon2id parameters used by `save`
+    ///
+    /// A loaded keystore keeps the parameters it was saved with.
+    pub fn set_kdf_params(&mut self, memory_kib: u32, iterations: u32, parallelism: u32) {
+        self.memory_kib = memory_kib;
+        self.iterations = iterations;
+        self.parallelism = parallelism;
+    }
+
+    /// Return the Argon2id parameters as (memory in KiB, iterations,
+    /// parallelism)
+    #[must_use]
+    pub fn kdf_params(&self) -> (u32, u32, u32) {
+        (self.memory_kib, self.iterations, self.parallelism)
+    }
+
+    fn derive_key(&self, password: &str, salt: &[u8]) -> Result<Vec<u8>> {
+        argon2id_key(
+            password,
+            salt,
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+        )
+    }
+
+    fn header(&self, salt: &[u8], nonce: &[u8]) -> Vec<u8> {
+        let mut header = Vec::with_capacity(HEADER_LENGTH);
+        header.extend_from_slice(KEYSTORE_MAGIC);
+        header.extend_from_slice(&KEYSTORE_VERSION.to_be_bytes());
+        header.push(KDF_ARGON2ID);
+        header.extend_from_slice(&self.memory_kib.to_be_bytes());
+        header.extend_from_slice(&self.iterations.to_be_bytes());
+        header.extend_from_slice(&self.parallelism.to_be_bytes());
+        header.extend_from_slice(salt);
+        header.extend_from_slice(nonce);
+        header
+    }
+
+    fn encode_entries(&self) -> Result<SecretVec> {
+        let mut out = SecretVec::default();
+        out.extend_from_slice(
+            &encoded_len::<u32>(self.entries.len(), "entry count")?.to_be_bytes(),
+        );
+
+        for entry in &self.entries {
+            out.extend_from_slice(&encoded_len::<u16>(entry.name.len(), "name")?.to_be_bytes());
+            out.extend_from_slice(entry.name.as_bytes());
+            out.extend_from_slice(&[entry.kind.code()]);
+            out.extend_from_slice(
+                &encoded_len::<u32>(entry.versions.len(), "version count")?.to_be_bytes(),
+            );
+            for (version, data) in &entry.versions {
+                out.extend_from_slice(&version.to_be_bytes());
+                out.extend_from_slice(&encoded_len::<u32>(data.len(), "entry")?.to_be_bytes());
+                out.extend_from_slice(data);
+            }
+        }
+
+        Ok(out)
+    }
+
+    fn decode_entries(ptext: &[u8]) -> Result<Vec<KeystoreEntry>> {
+        let mut reader = Reader { data: ptext };
+        let count = reader.u32()?;
+        let mut entries: Vec<KeystoreEntry> = Vec::new();
+
+        for _ in 0..count {
+            let name_len = reader.u16()? as usize;
+            let name = core::str::from_utf8(reader.take(name_len)?)
+                .map_err(|_| keystore_error("Keystore entry name is not UTF-8"))?
+                .to_owned();
+            if name.is_empty() || entries.iter().any(|e| e.name == name) {
+                return Err(keystore_error("Invalid keystore entry name"));
+            }
+
+            let kind = KeystoreEntryKind::from_code(reader.u8()?)?;
+            let version_count = reader.u32()?;
+            if version_count == 0 {
+                return Err(keystore_error("Keystore entry has no versions"));
+            }
+
+            let mut versions: Vec<(u32, SecretVec)> = Vec::new();
+            for _ in 0..version_count {
+                let version = reader.u32()?;
+                if matches!(versions.last(), Some((v, _)) if *v >= version) {
+                    return Err(keystore_error("Keystore entry versions out of order"));
+                }
+                let len = reader.u32()? as usize;
+                versions.push((version, SecretVec::from(reader.take(len)?)));
+            }
+
+            entries.push(KeystoreEntry {
+                name,
+                kind,
+                versions,
+            });
+        }
+
+        if !reader.data.is_empty() {
+            return Err(keystore_error("Trailing data in keystore"));
+        }
+
+        Ok(entries)
+    }
+
+    /// Encrypt the keystore with `password`
+    pub fn save(&self, password: &str, rng: &mut RandomNumberGenerator) -> Result<Vec<u8>> {
+        let salt = rng.read(SALT_LENGTH)?;
+        let nonce = rng.read(NONCE_LENGTH)?;
+        let header = self.header(&salt, &nonce);
+
+        let key = self.derive_key(password, &salt)?;
+        let mut aead = AeadEncryptor::new(AeadAlgo::Aes256Gcm)?;
+        aead.set_key(&key)?;
+        aead.set_associated_data(&header)?;
+
+        let ctext = aead.encrypt(&nonce, &self.encode_entries()?)?;
+
+        let mut out = header;
+        out.extend_from_slice(&ctext);
+        Ok(out)
+    }
+
+    /// Decrypt a keystore created by `save`
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::BadAuthCode` if the password is wrong or the
+    /// file was modified, and with `ErrorType::InvalidInput` if it is not
+    /// a keystore of a supported version. Files whose KDF parameters
+    /// exceed the default `Argon2Limits` (1 GiB of memory, 10 iterations
+    /// or a parallelism of 255) are rejected with
+    /// `ErrorType::BadParameter`.
+    pub fn load(data: &[u8], password: &str) -> Result<Keystore> {
+        Keystore::load_with_limits(data, password, &Argon2Limits::default())
+    }
+
+    /// Decrypt a keystore created by `save`, accepting KDF parameters up
+    /// to `limits`
+    ///
+    /// # Errors
+    /// As for `load`, but files whose KDF parameters exceed `limits` are
+    /// rejected with `ErrorType::BadParameter`.
+    pub fn load_with_limits(
+        data: &[u8],
+        password: &str,
+        limits: &Argon2Limits,
+    ) -> Result<Keystore> {
+        let mut reader = Reader { data };
+
+        if reader.take(KEYSTORE_MAGIC.len())? != KEYSTORE_MAGIC {
+            return Err(keystore_error("Not a keystore"));
+        }
+        if reader.u16()? != KEYSTORE_VERSION {
+            return Err(keystore_error("Unsupported keystore version"));
+        }
+        if reader.u8()? != KDF_ARGON2ID {
+            return Err(keystore_error("Unsupported keystore KDF"));
+        }
+
+        let mut keystore = Keystore::new();
+        keystore.set_kdf_params(reader.u32()?, reader.u32()?, reader.u32()?);
+        limits.check(
+            keystore.memory_kib,
+            keystore.iterations,
+            keystore.parallelism,
+        )?;
+
+        let salt = reader.take(SALT_LENGTH)?;
+        let nonce = reader.take(NONCE_LENGTH)?;
+
+        let key = keystore.derive_key(password, salt)?;
+        let mut aead = AeadDecryptor::new(AeadAlgo::Aes256Gcm)?;
+        aead.set_key(&key)?;
+        aead.set_associated_data(&data[..HEADER_LENGTH])?;
+
+        let mut ptext = aead.decrypt(nonce, reader.data)?;
+        let entries = Keystore::decode_entries(&ptext);
+        scrub_mem(&mut ptext);
+
+        keystore.entries = entries?;
+        Ok(keystore)
+    }
+
+    /// Read and decrypt a keystore from a file
+    #[cfg(not(feature = "no-std"))]
+    pub fn load_file<P: AsRef<std::path::Path>>(path: P, password: &str) -> Result<Keystore> {
+        Keystore::load(&std::fs::read(path)?, password)
+    }
+
+    /// Encrypt the keystore with `password` and write it to a file
+    ///
+    /// An existing file is replaced atomically. The file is only ever
+    /// accessible by its owner: it has mode 0600 on Unix, and an ACL
+    /// granting access only to the owner on Windows.
+    #[cfg(not(feature = "no-std"))]
+    pub fn save_file<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        password: &str,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<()> {
+        write_file(path.as_ref(), &self.save(password, rng)?, true)
+    }
+
+    /// Return the names of all entries, in the order they were added
+    #[must_use]
+    pub fn names(&self) -> Vec<&str> {
+        self.entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    /// Return the kind of the entry `name`, if there is one
+    #[must_use]
+    pub fn kind(&self, name: &str) -> Option<KeystoreEntryKind> {
+        self.entries.iter().find(|e| e.name == name).map(|e| e.kind)
+    }
+
+    /// Remove the entry `name` with all its versions
+    ///
+    /// Returns false if there was no such entry.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|e| e.name != name);
+        self.entries.len() != len
+    }
+
+    fn entry(&self, name: &str) -> Result<&KeystoreEntry> {
+        self.entries.iter().find(|e| e.name == name).ok_or_else(|| {
+            Error::with_message(
+                ErrorType::NoValue,
+                format!("No keystore entry named '{}'", name),
+            )
+        })
+    }
+
+    fn entry_mut(&mut self, name: &str) -> Result<&mut KeystoreEntry> {
+        self.entries
+            .iter_mut()
+            .find(|e| e.name == name)
+            .ok_or_else(|| {
+                Error::with_message(
+                    ErrorType::NoValue,
+                    format!("No keystore entry named '{}'", name),
+                )
+            })
+    }
+
+    fn insert(&mut self, name: &str, kind: KeystoreEntryKind, data: SecretVec) -> Result<()> {
+        if name.is_empty() {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "Keystore entry names must not be empty".to_owned(),
+            ));
+        }
+        encoded_len::<u16>(name.len(), "name")?;
+
+        let entry = KeystoreEntry {
+            name: name.to_owned(),
+            kind,
+            versions: vec![(1, data)],
+        };
+
+        match self.entries.iter_mut().find(|e| e.name == name) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+        Ok(())
+    }
+
+    fn rotate(&mut self, name: &str, kind: KeystoreEntryKind, data: SecretVec) -> Result<u32> {
+        let entry = self.entry_mut(name)?;
+        if entry.kind != kind {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                format!("Keystore entry '{}' holds a {:?}", name, entry.kind),
+            ));
+        }
+
+        let version = entry
+            .versions
+            .last()
+            .map_or(Some(1), |(v, _)| v.checked_add(1))
+            .ok_or_else(|| {
+                Error::with_message(
+                    ErrorType::InvalidObjectState,
+                    "Keystore entry version overflow".to_owned(),
+                )
+            })?;
+        entry.versions.push((version, data));
+        Ok(version)
+    }
+
+    fn data(&self, name: &str, kind: KeystoreEntryKind, version: Option<u32>) -> Result<&[u8]> {
+        let entry = self.entry(name)?;
+        if entry.kind != kind {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                format!("Keystore entry '{}' holds a {:?}", name, entry.kind),
+            ));
+        }
+
+        let found = match version {
+            None => entry.versions.last(),
+            Some(version) => entry.versions.iter().find(|(v, _)| *v == version),
+        };
+
+        found.map(|(_, data)| &data[..]).ok_or_else(|| {
+            Error::with_message(
+                ErrorType::NoValue,
+                format!("Keystore entry '{}' has no such version", name),
+            )
+        })
+    }
+
+    /// Store a private key as `name`, replacing any existing entry
+    pub fn insert_private_key(&mut self, name: &str, key: &Privkey) -> Result<()> {
+        let der = key.der_encode_secret()?;
+        self.insert(name, KeystoreEntryKind::PrivateKey, der)
+    }
+
+    /// Store a public key as `name`, replacing any existing entry
+    pub fn insert_public_key(&mut self, name: &str, key: &Pubkey) -> Result<()> {
+        let der = SecretVec::new(key.der_encode()?);
+        self.insert(name, KeystoreEntryKind::PublicKey, der)
+    }
+
+    /// Store a PEM or DER encoded certificate as `name`, replacing any
+    /// existing entry
+    ///
+    /// The encoding is checked by parsing it, and stored as given.
+    pub fn insert_certificate(&mut self, name: &str, cert: &[u8]) -> Result<()> {
+        Certificate::load(cert)?;
+        self.insert(name, KeystoreEntryKind::Certificate, SecretVec::from(cert))
+    }
+
+    /// Store secret bytes as `name`, replacing any existing entry
+    pub fn insert_secret(&mut self, name: &str, secret: &[u8]) -> Result<()> {
+        self.insert(name, KeystoreEntryKind::Secret, SecretVec::from(secret))
+    }
+
+    /// Add `key` as the new current version of the private key `name`,
+    /// returning its version number
+    pub fn rotate_private_key(&mut self, name: &str, key: &Privkey) -> Result<u32> {
+        let der = key.der_encode_secret()?;
+        self.rotate(name, KeystoreEntryKind::PrivateKey, der)
+    }
+
+    /// Add a new current version of the certificate `name`, returning its
+    /// version number
+    pub fn rotate_certificate(&mut self, name: &str, cert: &[u8]) -> Result<u32> {
+        Certificate::load(cert)?;
+        self.rotate(name, KeystoreEntryKind::Certificate, SecretVec::from(cert))
+    }
+
+    /// Add a new current version of the secret `name`, returning its
+    /// version number
+    pub fn rotate_secret(&mut self, name: &str, secret: &[u8]) -> Result<u32> {
+        self.rotate(name, KeystoreEntryKind::Secret, SecretVec::from(secret))
+    }
+
+    /// Return the version numbers of the entry `name`, oldest first
+    pub fn versions(&self, name: &str) -> Result<Vec<u32>> {
+        Ok(self.entry(name)?.versions.iter().map(|(v, _)| *v).collect())
+    }
+
+    /// Remove all but the `keep` most recent versions of the entry `name`
+    ///
+    /// The current version is always kept. Returns the number of versions
+    /// removed.
+    pub fn prune(&mut self, name: &str, keep: usize) -> Result<usize> {
+        let entry = self.entry_mut(name)?;
+        let keep = keep.max(1);
+        let removed = entry.versions.len().saturating_sub(keep);
+        entry.versions.drain(..removed);
+        Ok(removed)
+    }
+
+    /// Return the current version of the private key `name`
+    pub fn private_key(&self, name: &str) -> Result<Privkey> {
+        Privkey::load_der(self.data(name, KeystoreEntryKind::PrivateKey, None)?)
+    }
+
+    /// Return the given version of the private key `name`
+    pub fn private_key_version(&self, name: &str, version: u32) -> Result<Privkey> {
+        Privkey::load_der(self.data(name, KeystoreEntryKind::PrivateKey, Some(version))?)
+    }
+
+    /// Return the public key `name`
+    pub fn public_key(&self, name: &str) -> Result<Pubkey> {
+        Pubkey::load_der(self.data(name, KeystoreEntryKind::PublicKey, None)?)
+    }
+
+    /// Return the current version of the certificate `name`
+    pub fn certificate(&self, name: &str) -> Result<Certificate> {
+        Certificate::load(self.data(name, KeystoreEntryKind::Certificate, None)?)
+    }
+
+    /// Return the given version of the certificate `name`
+    pub fn certificate_version(&self, name: &str, version: u32) -> Result<Certificate> {
+        Certificate::load(self.data(name, KeystoreEntryKind::Certificate, Some(version))?)
+    }
+
+    /// Return the current version of the secret `name`
+    pub fn secret(&self, name: &str) -> Result<Vec<u8>> {
+        let data = self.data(name, KeystoreEntryKind::Secret, None)?;
+        Ok(data.to_vec())
+    }
+
+    /// Return the given version of the secret `name`
+    pub fn secret_version(&self, name: &str, version: u32) -> Result<Vec<u8>> {
+        let data = self.data(name, KeystoreEntryKind::Secret, Some(version))?;
+        Ok(data.to_vec())
+    }
+}
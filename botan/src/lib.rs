@@ -2,6 +2,11 @@
 #![deny(missing_docs)]
 
 //! A wrapper for the Botan cryptography library
+//!
+//! Types which hold secrets, such as `Privkey`, `Cipher` and `SecretVec`,
+//! implement `Debug` without showing them: keys, passphrases and secret
+//! bytes are printed as `[REDACTED]`, while the algorithm and other
+//! public parameters are shown.
 
 #![cfg_attr(feature = "no-std", no_std)]
 
@@ -9,9 +14,45 @@
 #[macro_use]
 extern crate alloc;
 
+#[cfg(feature = "arbitrary")]
+extern crate arbitrary;
+
 #[cfg(feature = "no-std")]
 extern crate cstr_core;
 
+#[cfg(feature = "getrandom")]
+extern crate getrandom;
+
+#[cfg(feature = "num-bigint")]
+extern crate num_bigint;
+
+#[cfg(feature = "openssl")]
+extern crate openssl;
+
+#[cfg(feature = "pkcs8")]
+extern crate pkcs8;
+
+#[cfg(feature = "proptest")]
+extern crate proptest;
+
+#[cfg(feature = "rand_core")]
+extern crate rand_core;
+
+#[cfg(feature = "rustls")]
+extern crate rustls;
+
+#[cfg(feature = "serde")]
+extern crate serde;
+
+#[cfg(feature = "spki")]
+extern crate spki;
+
+#[cfg(feature = "tracing")]
+extern crate tracing;
+
+#[cfg(feature = "zeroize")]
+extern crate zeroize;
+
 extern crate botan_sys;
 extern crate cty;
 
@@ -21,7 +62,7 @@ macro_rules! botan_call {
         if rc == 0 {
             Ok(())
         } else {
-            Err(Error::from_rc(rc))
+            Err(Error::from_rc_in(rc, core::stringify!($fn)))
         }
     }};
 }
@@ -31,18 +72,22 @@ macro_rules! botan_init {
         let mut obj = ptr::null_mut();
         let rc = unsafe { $fn(&mut obj) };
         if rc == 0 {
+            #[cfg(all(feature = "debug-ffi", not(feature = "no-std")))]
+            crate::debug_ffi::track(obj as usize, core::stringify!($fn));
             Ok(obj)
         } else {
-            Err(Error::from_rc(rc))
+            Err(Error::from_rc_in(rc, core::stringify!($fn)))
         }
     }};
     ($fn:path, $($args:expr),*) => {{
         let mut obj = ptr::null_mut();
         let rc = unsafe { $fn(&mut obj, $($args),*) };
         if rc == 0 {
+            #[cfg(all(feature = "debug-ffi", not(feature = "no-std")))]
+            crate::debug_ffi::track(obj as usize, core::stringify!($fn));
             Ok(obj)
         } else {
-            Err(Error::from_rc(rc))
+            Err(Error::from_rc_in(rc, core::stringify!($fn)))
         }
     }};
 }
@@ -51,6 +96,8 @@ macro_rules! botan_impl_drop {
     ($typ:ty, $fn:path) => {
         impl Drop for $typ {
             fn drop(&mut self) {
+                #[cfg(all(feature = "debug-ffi", not(feature = "no-std")))]
+                crate::debug_ffi::untrack(self.obj as usize);
                 let rc = unsafe { $fn(self.obj) };
                 if rc != 0 {
                     let err = Error::from_rc(rc);
@@ -61,12 +108,30 @@ macro_rules! botan_impl_drop {
     };
 }
 
+// Enter a `tracing` span lasting until the end of the enclosing block.
+// Without the `tracing` feature this expands to nothing, so the fields
+// are not evaluated. Fields must never include key material.
+macro_rules! botan_span {
+    ($name:expr $(, $($fields:tt)*)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($name $(, $($fields)*)?).entered();
+    };
+}
+
+// Emit a `tracing` event, if the `tracing` feature is enabled
+macro_rules! botan_event {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($args)*);
+    };
+}
+
 macro_rules! botan_usize {
     ($fn:path, $obj:expr) => {{
         let mut val = 0;
         let rc = unsafe { $fn($obj, &mut val) };
         if rc != 0 {
-            Err(Error::from_rc(rc))
+            Err(Error::from_rc_in(rc, core::stringify!($fn)))
         } else {
             Ok(val)
         }
@@ -80,7 +145,7 @@ macro_rules! botan_usize3 {
         let mut val3 = 0;
         let rc = unsafe { $fn($obj, &mut val1, &mut val2, &mut val3) };
         if rc != 0 {
-            Err(Error::from_rc(rc))
+            Err(Error::from_rc_in(rc, core::stringify!($fn)))
         } else {
             Ok((val1, val2, val3))
         }
@@ -94,26 +159,72 @@ macro_rules! botan_bool_in_rc {
         match rc {
             0 => Ok(false),
             1 => Ok(true),
-            e => Err(Error::from_rc(e)),
+            e => Err(Error::from_rc_in(e, core::stringify!($fn))),
         }
     }};
 }
 
+mod aead;
+#[cfg(feature = "age")]
+mod age;
+mod algo;
+#[cfg(not(feature = "no-std"))]
+mod batch;
 mod bcrypt;
+#[cfg(all(feature = "bench", not(feature = "no-std")))]
+pub mod bench;
+mod blake2;
+mod blind_rsa;
 mod block;
+mod capability;
 mod cipher;
+#[cfg(not(feature = "no-std"))]
+pub mod clock;
+mod codec;
+#[cfg(feature = "cose")]
+mod cose;
+pub mod ct;
+#[cfg(all(feature = "debug-ffi", not(feature = "no-std")))]
+pub mod debug_ffi;
+#[cfg(feature = "dlopen")]
+pub mod dlopen;
+mod ec;
 mod fpe;
+#[cfg(all(
+    any(feature = "arbitrary", feature = "proptest"),
+    not(feature = "no-std")
+))]
+pub mod fuzzing;
 mod hash;
+mod hpke;
+mod jws;
 mod kdf;
+mod keys;
+mod keystore;
 mod keywrap;
 mod mac;
 mod memutils;
+mod metadata;
+#[cfg(not(feature = "no-std"))]
+mod mlock;
 mod mp;
+mod nonce_mac;
+mod one_pass_dh;
 mod otp;
+mod paseto;
 mod pbkdf;
 mod pk_ops;
+#[cfg(not(feature = "no-std"))]
+mod pool;
+mod probe;
 mod pubkey;
 mod rng;
+#[cfg(feature = "rustls")]
+pub mod rustls_provider;
+pub mod simple;
+mod symmetric;
+#[cfg(feature = "tpm2")]
+mod tpm2;
 mod utils;
 mod version;
 mod x509;
@@ -121,18 +232,50 @@ mod x509;
 pub use crate::mp::*;
 pub use crate::rng::*;
 pub use crate::utils::*;
+pub use aead::*;
+#[cfg(feature = "age")]
+pub use age::*;
+pub use algo::*;
+#[cfg(not(feature = "no-std"))]
+pub use batch::*;
 pub use bcrypt::*;
+pub use blake2::*;
+pub use blind_rsa::*;
 pub use block::*;
+pub use capability::*;
 pub use cipher::*;
+#[cfg(not(feature = "no-std"))]
+pub use clock::{Clock, MockClock, SystemClock};
+pub use codec::{
+    base32_decode, base32_encode, base64_decode, base64_encode, hex_decode, hex_encode,
+};
+#[cfg(feature = "cose")]
+pub use cose::*;
+pub use ec::*;
 pub use fpe::*;
 pub use hash::*;
+pub use hpke::*;
+pub use jws::*;
 pub use kdf::*;
+pub use keys::*;
+pub use keystore::*;
 pub use keywrap::*;
 pub use mac::*;
 pub use memutils::*;
+#[cfg(not(feature = "no-std"))]
+pub use mlock::*;
+pub use nonce_mac::*;
+pub use one_pass_dh::*;
 pub use otp::*;
+pub use paseto::*;
 pub use pbkdf::*;
 pub use pk_ops::*;
+#[cfg(not(feature = "no-std"))]
+pub use pool::*;
+pub use probe::*;
 pub use pubkey::*;
+pub use symmetric::*;
+#[cfg(feature = "tpm2")]
+pub use tpm2::*;
 pub use version::*;
 pub use x509::*;
@@ -1,10 +1,21 @@
 use crate::utils::*;
 use botan_sys::*;
 
-#[derive(Debug)]
+use crate::metadata::{lookup, Metadata};
+use crate::probe::AlgorithmKind;
+use crate::symmetric::{AuthTag, SymmetricKey};
+
 /// Message authentication code
+///
+/// Unlike `HashFunction` this is not `Clone`: the FFI cannot copy the
+/// state of a MAC, so each thread needs its own object with the key set.
+/// Nor can the state be saved to resume a computation later.
+///
+/// GMAC and Poly1305 need a new nonce or key for every message; `Gmac`
+/// and `ChaChaPoly1305Mac` check that this is done.
 pub struct MsgAuthCode {
     obj: botan_mac_t,
+    algo_name: String,
     output_length: usize,
     min_keylen: usize,
     max_keylen: usize,
@@ -13,6 +24,29 @@ pub struct MsgAuthCode {
 
 botan_impl_drop!(MsgAuthCode, botan_mac_destroy);
 
+impl core::fmt::Debug for MsgAuthCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("MsgAuthCode")
+            .field("algo_name", &self.algo_name)
+            .field("output_length", &self.output_length)
+            .field("key", &format_args!("[REDACTED]"))
+            .finish()
+    }
+}
+
+// Not Sync, since update and finish modify the object through &self
+unsafe impl Send for MsgAuthCode {}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for MsgAuthCode {
+    fn zeroize(&mut self) {
+        let _ = self.clear();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for MsgAuthCode {}
+
 impl MsgAuthCode {
     /// Create a new message authentication code
     ///
@@ -23,21 +57,66 @@ impl MsgAuthCode {
     /// ```
     /// let poly1305 = botan::MsgAuthCode::new("Poly1305").unwrap();
     /// ```
-    pub fn new(name: &str) -> Result<MsgAuthCode> {
+    pub fn new<N: AsRef<str>>(name: N) -> Result<MsgAuthCode> {
+        let name = name.as_ref();
         let obj = botan_init!(botan_mac_init, make_cstr(name)?.as_ptr(), 0u32)?;
-        let output_length = botan_usize!(botan_mac_output_length, obj)?;
+        let metadata = lookup(AlgorithmKind::Mac, 0, name, || {
+            Ok(Metadata {
+                algo_name: call_botan_ffi_returning_string(32, &|out_buf, out_len| unsafe {
+                    botan_mac_name(obj, out_buf as *mut c_char, out_len)
+                })?,
+                output_length: botan_usize!(botan_mac_output_length, obj)?,
+                keyspec: botan_usize3!(botan_mac_get_keyspec, obj)?,
+                ..Default::default()
+            })
+        })?;
 
-        let (min_keylen, max_keylen, mod_keylen) = botan_usize3!(botan_mac_get_keyspec, obj)?;
+        let (min_keylen, max_keylen, mod_keylen) = metadata.keyspec;
 
         Ok(MsgAuthCode {
             obj,
-            output_length,
+            algo_name: metadata.algo_name,
+            output_length: metadata.output_length,
             min_keylen,
             max_keylen,
             mod_keylen,
         })
     }
 
+    /// Create a new HMAC with SHA-256
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut mac = botan::MsgAuthCode::hmac_sha256().unwrap();
+    /// mac.set_key(&[0u8; 32]).unwrap();
+    /// mac.update(b"message").unwrap();
+    /// assert_eq!(mac.finish().unwrap().len(), 32);
+    /// ```
+    pub fn hmac_sha256() -> Result<MsgAuthCode> {
+        MsgAuthCode::new("HMAC(SHA-256)")
+    }
+
+    /// Create a new HMAC with SHA-384
+    pub fn hmac_sha384() -> Result<MsgAuthCode> {
+        MsgAuthCode::new("HMAC(SHA-384)")
+    }
+
+    /// Create a new HMAC with SHA-512
+    pub fn hmac_sha512() -> Result<MsgAuthCode> {
+        MsgAuthCode::new("HMAC(SHA-512)")
+    }
+
+    /// Create a new CMAC with AES-128
+    pub fn cmac_aes128() -> Result<MsgAuthCode> {
+        MsgAuthCode::new("CMAC(AES-128)")
+    }
+
+    /// Create a new CMAC with AES-256
+    pub fn cmac_aes256() -> Result<MsgAuthCode> {
+        MsgAuthCode::new("CMAC(AES-256)")
+    }
+
     /// Return the name of this algorithm which may or may not exactly
     /// match what was provided to new()
     ///
@@ -48,9 +127,7 @@ impl MsgAuthCode {
     /// assert_eq!(mac.algo_name().unwrap(), "HMAC(SHA-384)");
     /// ```
     pub fn algo_name(&self) -> Result<String> {
-        call_botan_ffi_returning_string(32, &|out_buf, out_len| unsafe {
-            botan_mac_name(self.obj, out_buf as *mut c_char, out_len)
-        })
+        Ok(self.algo_name.clone())
     }
 
     /// Return information about the key lengths supported by this object
@@ -68,6 +145,11 @@ impl MsgAuthCode {
         Ok(self.output_length)
     }
 
+    // Adds the algorithm and operation to errors
+    fn context(&self, operation: &'static str) -> impl Fn(Error) -> Error + '_ {
+        move |e| e.in_context(&self.algo_name, operation)
+    }
+
     /// Set the key for the authentication code object
     /// # Examples
     /// ```
@@ -76,6 +158,30 @@ impl MsgAuthCode {
     /// ```
     pub fn set_key(&self, key: &[u8]) -> Result<()> {
         botan_call!(botan_mac_set_key, self.obj, key.as_ptr(), key.len())
+            .map_err(self.context("set key"))
+    }
+
+    /// Set the key for the authentication code object
+    pub fn set_symmetric_key(&self, key: &SymmetricKey) -> Result<()> {
+        self.set_key(key.as_bytes())
+    }
+
+    // Used by Gmac, which checks that a nonce is set for each message
+    pub(crate) fn set_nonce(&self, nonce: &[u8]) -> Result<()> {
+        #[cfg(feature = "botan3")]
+        {
+            botan_call!(botan_mac_set_nonce, self.obj, nonce.as_ptr(), nonce.len())
+                .map_err(self.context("set nonce"))
+        }
+
+        #[cfg(not(feature = "botan3"))]
+        {
+            let _ = nonce;
+            Err(Error::with_message(
+                ErrorType::NotImplemented,
+                "Setting a MAC nonce requires Botan 3.x".to_owned(),
+            ))
+        }
     }
 
     /// Add data to a MAC computation, may be called many times
@@ -90,6 +196,7 @@ impl MsgAuthCode {
     /// ```
     pub fn update(&self, data: &[u8]) -> Result<()> {
         botan_call!(botan_mac_update, self.obj, data.as_ptr(), data.len())
+            .map_err(self.context("update"))
     }
 
     /// Complete a MAC computation, after which the object is reset to
@@ -106,10 +213,32 @@ impl MsgAuthCode {
     /// ```
     pub fn finish(&self) -> Result<Vec<u8>> {
         let mut output = vec![0; self.output_length];
-        botan_call!(botan_mac_final, self.obj, output.as_mut_ptr())?;
+        botan_call!(botan_mac_final, self.obj, output.as_mut_ptr())
+            .map_err(self.context("finish"))?;
         Ok(output)
     }
 
+    /// Complete a MAC computation, returning the tag as an `AuthTag`
+    pub fn finish_tag(&self) -> Result<AuthTag> {
+        Ok(AuthTag::from_vec(self.finish()?))
+    }
+
+    /// Complete a MAC computation and compare the result with `tag` in
+    /// constant time
+    ///
+    /// # Examples
+    /// ```
+    /// let hmac = botan::MsgAuthCode::new("HMAC(SHA-256)").unwrap();
+    /// hmac.set_key(&vec![0; 16]).unwrap();
+    /// hmac.update(b"message").unwrap();
+    /// let tag = hmac.finish_tag().unwrap();
+    /// hmac.update(b"massage").unwrap();
+    /// assert!(!hmac.verify_tag(&tag).unwrap());
+    /// ```
+    pub fn verify_tag(&self, tag: &AuthTag) -> Result<bool> {
+        Ok(self.finish_tag()? == *tag)
+    }
+
     /// Clear the MAC key
     ///
     /// # Examples
@@ -121,6 +250,6 @@ impl MsgAuthCode {
     /// assert!(hmac.update(&[23]).is_err()); // key not set anymore
     /// ```
     pub fn clear(&self) -> Result<()> {
-        botan_call!(botan_mac_clear, self.obj)
+        botan_call!(botan_mac_clear, self.obj).map_err(self.context("clear"))
     }
 }
@@ -27,74 +27,191 @@ pub fn scrub_mem<T: Copy>(a: &mut [T]) {
     unsafe { botan_scrub_mem(a.as_mut_ptr() as *mut c_void, bytes) };
 }
 
-/// Hex encode some data
-pub fn hex_encode(x: &[u8]) -> Result<String> {
-    let flags = 0u32;
+// Lock the allocation of a buffer into memory, so that it is not
+// written to swap. Botan's own locked pool is not available through the
+// FFI, so this uses mlock directly. It is best effort: locking fails
+// silently once RLIMIT_MEMLOCK is reached, and since locks apply to whole
+// pages, releasing one buffer unlocks any other sharing a page with it.
+#[cfg(unix)]
+mod lock {
+    extern "C" {
+        fn mlock(addr: *const cty::c_void, len: usize) -> cty::c_int;
+        fn munlock(addr: *const cty::c_void, len: usize) -> cty::c_int;
+    }
 
-    let mut output = vec![0u8; x.len() * 2];
-    botan_call!(
-        botan_hex_encode,
-        x.as_ptr(),
-        x.len(),
-        output.as_mut_ptr() as *mut c_char,
-        flags
-    )?;
+    pub(super) fn lock(addr: *const u8, len: usize) {
+        if len > 0 {
+            unsafe { mlock(addr as *const cty::c_void, len) };
+        }
+    }
 
-    String::from_utf8(output).map_err(Error::conversion_error)
+    pub(super) fn unlock(addr: *const u8, len: usize) {
+        if len > 0 {
+            unsafe { munlock(addr as *const cty::c_void, len) };
+        }
+    }
 }
 
-/// Hex decode some data
-pub fn hex_decode(x: &str) -> Result<Vec<u8>> {
-    let mut output = vec![0u8; x.len() / 2];
-    let mut output_len = output.len();
+#[cfg(not(unix))]
+mod lock {
+    pub(super) fn lock(_addr: *const u8, _len: usize) {}
 
-    let input = make_cstr(x)?;
+    pub(super) fn unlock(_addr: *const u8, _len: usize) {}
+}
 
-    botan_call!(
-        botan_hex_decode,
-        input.as_ptr(),
-        x.len(),
-        output.as_mut_ptr(),
-        &mut output_len
-    )?;
+/// A byte buffer holding secret data, such as a key
+///
+/// The contents are zeroized when the buffer is dropped, comparisons
+/// run in constant time, and the contents are not shown by `Debug`.
+/// On Unix the buffer is also locked with `mlock` where the process
+/// limits allow, which keeps it out of swap.
+///
+/// Functions returning key material have variants returning a
+/// `SecretVec`, such as `Privkey::der_encode_secret`. Any other
+/// `Vec<u8>` can be converted with `SecretVec::from`, which takes
+/// ownership of its allocation without copying it.
+#[derive(Default)]
+pub struct SecretVec {
+    data: Vec<u8>,
+}
 
-    output.resize(output_len, 0);
+impl SecretVec {
+    /// Create a secret buffer, taking ownership of `data`
+    pub fn new(data: Vec<u8>) -> SecretVec {
+        lock::lock(data.as_ptr(), data.capacity());
+        SecretVec { data }
+    }
 
-    Ok(output)
+    // Replace the buffer, releasing the previous allocation
+    fn replace(&mut self, data: Vec<u8>) {
+        let old = core::mem::replace(self, SecretVec::new(data));
+        drop(old);
+    }
+
+    /// Return the length of the buffer
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Return true if the buffer is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Append `other` to the buffer
+    ///
+    /// Unlike `Vec::extend_from_slice`, the previous allocation is
+    /// zeroized if the buffer has to grow.
+    pub fn extend_from_slice(&mut self, other: &[u8]) {
+        if self.data.capacity() - self.data.len() < other.len() {
+            let mut data = Vec::with_capacity(self.data.len() + other.len());
+            data.extend_from_slice(&self.data);
+            self.replace(data);
+        }
+        self.data.extend_from_slice(other);
+    }
+
+    /// Zeroize the contents and truncate the buffer to zero length
+    pub fn clear(&mut self) {
+        scrub_mem(&mut self.data);
+        self.data.clear();
+    }
+
+    /// Remove the bytes starting at `at` into a new buffer
+    ///
+    /// The bytes are zeroized in this buffer, which keeps its allocation.
+    ///
+    /// # Panics
+    /// Panics if `at` is larger than the length, as `Vec::split_off` does
+    pub fn split_off(&mut self, at: usize) -> SecretVec {
+        let tail = SecretVec::from(&self.data[at..]);
+        scrub_mem(&mut self.data[at..]);
+        self.data.truncate(at);
+        tail
+    }
 }
 
-/// Base64 encode some data
-///
-/// # Examples
-///
-/// ```
-/// assert_eq!(botan::base64_encode(&[97,98,99,100,101,102]).unwrap(), "YWJjZGVm");
-/// assert_eq!(botan::base64_encode(&[0x5A, 0x16, 0xAD, 0x4E, 0x17, 0x87, 0x79, 0xC9]).unwrap(), "WhatTheHeck=");
-/// ```
-pub fn base64_encode(x: &[u8]) -> Result<String> {
-    let b64_len = 1 + ((x.len() + 2) / 3) * 4;
+impl Clone for SecretVec {
+    fn clone(&self) -> SecretVec {
+        SecretVec::from(&self.data[..])
+    }
+}
 
-    call_botan_ffi_returning_string(b64_len, &|out_buf, out_len| unsafe {
-        botan_base64_encode(x.as_ptr(), x.len(), out_buf as *mut c_char, out_len)
-    })
+impl Drop for SecretVec {
+    fn drop(&mut self) {
+        // The spare capacity may also hold secrets, for example after
+        // split_off, so the whole allocation is zeroized
+        unsafe { botan_scrub_mem(self.data.as_mut_ptr() as *mut c_void, self.data.capacity()) };
+        lock::unlock(self.data.as_ptr(), self.data.capacity());
+    }
 }
 
-/// Base64 decode some data
-///
-/// # Examples
-///
-/// ```
-/// assert!(botan::base64_decode("ThisIsInvalid!").is_err());
-/// assert_eq!(botan::base64_decode("YWJjZGVm").unwrap(), b"abcdef");
-/// ```
-pub fn base64_decode(x: &str) -> Result<Vec<u8>> {
-    // Hard to provide a decent lower bound as it is possible x includes
-    // lots of spaces or trailing = padding chars
-    let bin_len = x.len();
-
-    let input = make_cstr(x)?;
-
-    call_botan_ffi_returning_vec_u8(bin_len, &|out_buf, out_len| unsafe {
-        botan_base64_decode(input.as_ptr(), x.len(), out_buf, out_len)
-    })
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for SecretVec {
+    fn zeroize(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for SecretVec {}
+
+impl From<Vec<u8>> for SecretVec {
+    fn from(data: Vec<u8>) -> SecretVec {
+        SecretVec::new(data)
+    }
+}
+
+impl From<&[u8]> for SecretVec {
+    fn from(data: &[u8]) -> SecretVec {
+        SecretVec::new(data.to_vec())
+    }
+}
+
+impl core::ops::Deref for SecretVec {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl core::ops::DerefMut for SecretVec {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+}
+
+impl AsRef<[u8]> for SecretVec {
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl core::fmt::Debug for SecretVec {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "SecretVec([REDACTED; {}])", self.data.len())
+    }
+}
+
+impl PartialEq for SecretVec {
+    fn eq(&self, other: &SecretVec) -> bool {
+        const_time_compare(&self.data, &other.data)
+    }
+}
+
+impl Eq for SecretVec {}
+
+impl PartialEq<[u8]> for SecretVec {
+    fn eq(&self, other: &[u8]) -> bool {
+        const_time_compare(&self.data, other)
+    }
+}
+
+impl PartialEq<Vec<u8>> for SecretVec {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        const_time_compare(&self.data, other)
+    }
 }
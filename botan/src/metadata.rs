@@ -0,0 +1,104 @@
+use crate::utils::*;
+
+use crate::probe::AlgorithmKind;
+
+// Properties of an algorithm which do not change once an object has
+// been created. Each type only fills in the fields relevant to it.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Metadata {
+    pub(crate) algo_name: String,
+    pub(crate) output_length: usize,
+    pub(crate) block_size: usize,
+    pub(crate) tag_length: usize,
+    pub(crate) update_granularity: usize,
+    pub(crate) ideal_update_granularity: usize,
+    pub(crate) default_nonce_length: usize,
+    pub(crate) keyspec: (usize, usize, usize),
+}
+
+#[cfg(not(feature = "no-std"))]
+mod cache {
+    use super::Metadata;
+    use crate::probe::AlgorithmKind;
+
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicPtr, Ordering};
+    use std::sync::RwLock;
+
+    type Table = RwLock<HashMap<(AlgorithmKind, u32), HashMap<String, Metadata>>>;
+
+    // Installed on first use and never freed
+    static TABLE: AtomicPtr<Table> = AtomicPtr::new(core::ptr::null_mut());
+
+    fn table() -> &'static Table {
+        let mut table = TABLE.load(Ordering::Acquire);
+
+        if table.is_null() {
+            let new_table = Box::into_raw(Box::new(RwLock::new(HashMap::new())));
+            table = match TABLE.compare_exchange(
+                core::ptr::null_mut(),
+                new_table,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => new_table,
+                Err(existing) => {
+                    // Another thread installed a table first
+                    drop(unsafe { Box::from_raw(new_table) });
+                    existing
+                }
+            };
+        }
+
+        unsafe { &*table }
+    }
+
+    pub(super) fn get(kind: AlgorithmKind, flags: u32, name: &str) -> Option<Metadata> {
+        let table = match table().read() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        table.get(&(kind, flags)).and_then(|t| t.get(name)).cloned()
+    }
+
+    pub(super) fn insert(kind: AlgorithmKind, flags: u32, name: &str, metadata: &Metadata) {
+        let mut table = match table().write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        table
+            .entry((kind, flags))
+            .or_default()
+            .insert(name.to_owned(), metadata.clone());
+    }
+}
+
+// Return the metadata of the named algorithm, calling `query` only the
+// first time it is requested in this process. `flags` distinguishes
+// objects of the same name which may have different properties, such
+// as the two directions of a cipher.
+//
+// In no-std builds there is no cache and `query` is always called.
+#[cfg_attr(feature = "no-std", allow(unused_variables))]
+pub(crate) fn lookup(
+    kind: AlgorithmKind,
+    flags: u32,
+    name: &str,
+    query: impl FnOnce() -> Result<Metadata>,
+) -> Result<Metadata> {
+    #[cfg(not(feature = "no-std"))]
+    {
+        if let Some(metadata) = cache::get(kind, flags, name) {
+            return Ok(metadata);
+        }
+
+        let metadata = query()?;
+        cache::insert(kind, flags, name, &metadata);
+        Ok(metadata)
+    }
+
+    #[cfg(feature = "no-std")]
+    {
+        query()
+    }
+}
@@ -0,0 +1,253 @@
+use crate::utils::*;
+
+// Botan's locked memory pool is not configurable through the FFI, and
+// its size is not reported. When the pool is first used, the library
+// raises the soft RLIMIT_MEMLOCK to the hard limit where possible, and
+// then locks at most that limit, so the limits are what an application
+// can inspect and tune.
+
+/// A limit on the amount of memory a process may lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemlockLimit {
+    /// At most this many bytes
+    Bytes(u64),
+    /// No limit
+    Unlimited,
+    /// The limit could not be queried on this platform
+    Unknown,
+}
+
+/// The limits on Botan's locked memory pool
+///
+/// Secrets held by the library itself (keys inside cipher and private key
+/// objects, for example) are allocated from a pool of memory locked with
+/// `mlock`, which keeps them out of swap. The pool can be no larger than
+/// the hard `RLIMIT_MEMLOCK`. If the pool is exhausted or could not be
+/// locked, allocations fall back to ordinary heap memory; the library
+/// does not report this. An allocation which fails outright is reported
+/// as `ErrorType::OutOfMemory`.
+///
+/// # Examples
+///
+/// ```
+/// let info = botan::LockedPoolInfo::current();
+/// if let botan::MemlockLimit::Bytes(limit) = info.memlock_hard_limit() {
+///     println!("At most {} bytes of secrets are kept out of swap", limit);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockedPoolInfo {
+    memlock_soft_limit: MemlockLimit,
+    memlock_hard_limit: MemlockLimit,
+}
+
+impl LockedPoolInfo {
+    /// Read the current process limits
+    #[must_use]
+    pub fn current() -> LockedPoolInfo {
+        let (soft, hard) =
+            memlock_limits().unwrap_or((MemlockLimit::Unknown, MemlockLimit::Unknown));
+        LockedPoolInfo {
+            memlock_soft_limit: soft,
+            memlock_hard_limit: hard,
+        }
+    }
+
+    /// Return the current soft `RLIMIT_MEMLOCK`
+    #[must_use]
+    pub fn memlock_soft_limit(&self) -> MemlockLimit {
+        self.memlock_soft_limit
+    }
+
+    /// Return the hard `RLIMIT_MEMLOCK`, which is the most the library
+    /// can raise the soft limit to
+    #[must_use]
+    pub fn memlock_hard_limit(&self) -> MemlockLimit {
+        self.memlock_hard_limit
+    }
+}
+
+/// Limit the size of Botan's locked memory pool to `bytes`
+///
+/// This sets both the soft and hard `RLIMIT_MEMLOCK` of the process, so
+/// it also limits any other use of `mlock`. The pool is sized when the
+/// library first allocates memory for a secret, so this must be called
+/// before then, in practice at the start of `main`. Raising the hard
+/// limit requires privileges (`CAP_SYS_RESOURCE` on Linux), so once
+/// lowered it usually cannot be raised again.
+///
+/// # Errors
+/// Fails with `ErrorType::SystemError` if the limit cannot be set, with
+/// `ErrorType::BadParameter` if `bytes` is too large to represent, and
+/// with `ErrorType::NotImplemented` on platforms without `RLIMIT_MEMLOCK`
+pub fn set_memlock_limit(bytes: u64) -> Result<()> {
+    set_memlock_limits(bytes)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod sys {
+    #[cfg(target_env = "musl")]
+    pub(super) type RlimT = u64;
+    #[cfg(not(target_env = "musl"))]
+    pub(super) type RlimT = cty::c_ulong;
+
+    #[cfg(any(
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "mips32r6",
+        target_arch = "mips64r6"
+    ))]
+    pub(super) const RLIMIT_MEMLOCK: cty::c_int = 9;
+    #[cfg(not(any(
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "mips32r6",
+        target_arch = "mips64r6"
+    )))]
+    pub(super) const RLIMIT_MEMLOCK: cty::c_int = 8;
+
+    // 32-bit MIPS and SPARC kernels use a different infinity from other
+    // targets; depending on the C library it may or may not be translated
+    pub(super) fn is_infinity(value: RlimT) -> bool {
+        value == !0
+            || (cfg!(any(
+                target_arch = "mips",
+                target_arch = "mips32r6",
+                target_arch = "sparc"
+            )) && value == 0x7fff_ffff)
+    }
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+mod sys {
+    pub(super) type RlimT = u64;
+    pub(super) const RLIMIT_MEMLOCK: cty::c_int = 6;
+
+    pub(super) fn is_infinity(value: RlimT) -> bool {
+        value == (1 << 63) - 1
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+mod rlimit {
+    use super::{sys, MemlockLimit};
+    use crate::utils::*;
+    use core::convert::TryFrom;
+
+    #[repr(C)]
+    struct Rlimit {
+        rlim_cur: sys::RlimT,
+        rlim_max: sys::RlimT,
+    }
+
+    extern "C" {
+        fn getrlimit(resource: c_int, rlim: *mut Rlimit) -> c_int;
+        fn setrlimit(resource: c_int, rlim: *const Rlimit) -> c_int;
+    }
+
+    // The soft and hard RLIMIT_MEMLOCK, or None if they cannot be read
+    pub(super) fn memlock_limits() -> Option<(MemlockLimit, MemlockLimit)> {
+        let mut limits = Rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        if unsafe { getrlimit(sys::RLIMIT_MEMLOCK, &mut limits) } != 0 {
+            return None;
+        }
+
+        // rlim_t is only 32 bits on some targets
+        #[allow(clippy::useless_conversion)]
+        let limit = |value: sys::RlimT| {
+            if sys::is_infinity(value) {
+                MemlockLimit::Unlimited
+            } else {
+                MemlockLimit::Bytes(u64::from(value))
+            }
+        };
+        Some((limit(limits.rlim_cur), limit(limits.rlim_max)))
+    }
+
+    pub(super) fn set_memlock_limits(bytes: u64) -> Result<()> {
+        #[allow(clippy::useless_conversion)]
+        let value = match sys::RlimT::try_from(bytes) {
+            Ok(value) if !sys::is_infinity(value) => value,
+            _ => {
+                return Err(Error::with_message(
+                    ErrorType::BadParameter,
+                    format!("A memory lock limit of {} bytes is too large", bytes),
+                ))
+            }
+        };
+
+        let limits = Rlimit {
+            rlim_cur: value,
+            rlim_max: value,
+        };
+        if unsafe { setrlimit(sys::RLIMIT_MEMLOCK, &limits) } != 0 {
+            return Err(Error::with_message(
+                ErrorType::SystemError,
+                format!("Setting RLIMIT_MEMLOCK to {} bytes failed", bytes),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+use rlimit::{memlock_limits, set_memlock_limits};
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+)))]
+fn memlock_limits() -> Option<(MemlockLimit, MemlockLimit)> {
+    None
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+)))]
+fn set_memlock_limits(_bytes: u64) -> Result<()> {
+    Err(Error::with_message(
+        ErrorType::NotImplemented,
+        "RLIMIT_MEMLOCK is not available on this platform".to_owned(),
+    ))
+}
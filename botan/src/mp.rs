@@ -13,6 +13,12 @@ use core::ops::{
 };
 
 /// A big integer type
+///
+/// The value is stored in Botan's secure memory, which is allocated from
+/// the locked (non-swappable) memory pool when one is available, and
+/// which is zeroed when the MPI is cleared or dropped. Values exported
+/// with functions such as `to_bin` or `to_string` are ordinary heap
+/// allocations and are not protected.
 #[allow(clippy::upper_case_acronyms)]
 pub struct MPI {
     obj: botan_mp_t,
@@ -20,6 +26,26 @@ pub struct MPI {
 
 botan_impl_drop!(MPI, botan_mp_destroy);
 
+unsafe impl Send for MPI {}
+unsafe impl Sync for MPI {}
+
+// Botan stores the value of an MPI in memory which is zeroized when freed
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for MPI {}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for MPI {
+    fn zeroize(&mut self) {
+        let _ = self.clear();
+    }
+}
+
+const PRIME_TEST_PROB: usize = 128;
+
+const SMALL_PRIMES: [u32; 24] = [
+    3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
 impl Clone for MPI {
     fn clone(&self) -> MPI {
         self.duplicate().expect("copying MPI object failed")
@@ -31,6 +57,11 @@ impl MPI {
         self.obj
     }
 
+    #[cfg(feature = "botan3")]
+    pub(crate) fn from_handle(obj: botan_mp_t) -> MPI {
+        MPI { obj }
+    }
+
     /// Crate a new (zero-valued) MPI
     pub fn new() -> Result<MPI> {
         let obj = botan_init!(botan_mp_init)?;
@@ -44,6 +75,25 @@ impl MPI {
         Ok(mpi)
     }
 
+    /// Create a new MPI from a big-endian encoding of exactly `width` bytes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let n = botan::MPI::from_bytes_be(&[0, 0, 1, 0], 4).unwrap();
+    /// assert_eq!(n.to_u32().unwrap(), 256);
+    /// assert!(botan::MPI::from_bytes_be(&[1, 0], 4).is_err());
+    /// ```
+    pub fn from_bytes_be(val: &[u8], width: usize) -> Result<MPI> {
+        if val.len() != width {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                format!("Expected {} bytes but got {}", width, val.len()),
+            ));
+        }
+        MPI::new_from_bytes(val)
+    }
+
     /// Crate a new MPI setting value from a i32
     pub fn new_from_i32(val: i32) -> Result<MPI> {
         let mut mpi = MPI::new()?;
@@ -58,6 +108,22 @@ impl MPI {
         Ok(mpi)
     }
 
+    /// Create a new MPI setting value from a u128
+    pub fn new_from_u128(val: u128) -> Result<MPI> {
+        MPI::new_from_bytes(&val.to_be_bytes())
+    }
+
+    /// Create a new MPI setting value from an i128
+    pub fn new_from_i128(val: i128) -> Result<MPI> {
+        if val >= 0 {
+            return MPI::new_from_u128(val as u128);
+        }
+
+        let mut mpi = MPI::new_from_u128((val as u128).wrapping_neg())?;
+        mpi.flip_sign()?;
+        Ok(mpi)
+    }
+
     /// Crate a new MPI duplicating the value of self
     pub fn duplicate(&self) -> Result<MPI> {
         let mpi = MPI::new()?;
@@ -81,7 +147,7 @@ impl MPI {
         botan_call!(botan_mp_from_bin, self.obj, val.as_ptr(), val.len())
     }
 
-    /// Set self to zero
+    /// Set self to zero, overwriting the previous value in memory
     pub fn clear(&mut self) -> Result<()> {
         botan_call!(botan_mp_clear, self.obj)
     }
@@ -122,6 +188,31 @@ impl MPI {
         )
     }
 
+    /// Return a uniformly random integer in the range [0, modulus)
+    ///
+    /// The value is generated by rejection sampling, so it is unbiased.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let n = botan::MPI::new_from_u32(1000).unwrap();
+    /// let r = botan::MPI::random_mod(&mut rng, &n).unwrap();
+    /// assert!(r < n);
+    /// ```
+    pub fn random_mod(rng: &mut RandomNumberGenerator, modulus: &MPI) -> Result<MPI> {
+        if !modulus.is_positive()? || modulus.is_zero()? {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "Modulus must be positive".to_owned(),
+            ));
+        }
+
+        let mut r = MPI::new()?;
+        r.random_range(rng, &MPI::new()?, modulus)?;
+        Ok(r)
+    }
+
     /// Return value of self as decimal string
     pub fn to_string(&self) -> Result<String> {
         let bit_count = self.bit_count()? as f64;
@@ -157,6 +248,43 @@ impl MPI {
         Ok(output)
     }
 
+    /// Return value of self as a big-endian byte array of exactly `len` bytes
+    ///
+    /// The value is left padded with zeros. An error is returned if self is
+    /// negative or does not fit in `len` bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let n = botan::MPI::new_from_u32(256).unwrap();
+    /// assert_eq!(n.to_bytes_padded(4).unwrap(), vec![0, 0, 1, 0]);
+    /// assert!(n.to_bytes_padded(1).is_err());
+    /// ```
+    pub fn to_bytes_padded(&self, len: usize) -> Result<Vec<u8>> {
+        if self.is_negative()? {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "Cannot encode a negative MPI".to_owned(),
+            ));
+        }
+
+        let bytes = self.byte_count()?;
+        if bytes > len {
+            return Err(Error::with_message(
+                ErrorType::InsufficientBufferSpace,
+                format!("MPI requires {} bytes but only {} available", bytes, len),
+            ));
+        }
+
+        let mut output = vec![0; len];
+        botan_call!(
+            botan_mp_to_bin,
+            self.obj,
+            output[len - bytes..].as_mut_ptr()
+        )?;
+        Ok(output)
+    }
+
     /// Return number of significant bits
     pub fn bit_count(&self) -> Result<usize> {
         let mut bits = 0;
@@ -178,6 +306,72 @@ impl MPI {
         Ok(val)
     }
 
+    /// Return self as a u128, if it fits
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let n = botan::MPI::new_from_u128(u128::MAX).unwrap();
+    /// assert_eq!(n.to_u128().unwrap(), u128::MAX);
+    /// assert!((n + 1).to_u128().is_err());
+    /// ```
+    pub fn to_u128(&self) -> Result<u128> {
+        if self.is_negative()? {
+            return Err(Error::with_message(
+                ErrorType::ConversionError,
+                "Cannot convert negative MPI to u128".to_owned(),
+            ));
+        }
+        self.magnitude_u128()
+    }
+
+    /// Return self as an i128, if it fits
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let n = botan::MPI::new_from_i128(i128::MIN).unwrap();
+    /// assert_eq!(n.to_i128().unwrap(), i128::MIN);
+    /// assert!((n - 1).to_i128().is_err());
+    /// ```
+    pub fn to_i128(&self) -> Result<i128> {
+        let magnitude = self.magnitude_u128()?;
+
+        let limit = if self.is_negative()? {
+            1u128 << 127
+        } else {
+            (1u128 << 127) - 1
+        };
+
+        if magnitude > limit {
+            return Err(Error::with_message(
+                ErrorType::ConversionError,
+                "MPI is out of range for i128".to_owned(),
+            ));
+        }
+
+        if self.is_negative()? {
+            Ok((magnitude as i128).wrapping_neg())
+        } else {
+            Ok(magnitude as i128)
+        }
+    }
+
+    fn magnitude_u128(&self) -> Result<u128> {
+        let bin = self.to_bin()?;
+
+        if bin.len() > 16 {
+            return Err(Error::with_message(
+                ErrorType::ConversionError,
+                "MPI is too large for a 128-bit integer".to_owned(),
+            ));
+        }
+
+        let mut bytes = [0u8; 16];
+        bytes[16 - bin.len()..].copy_from_slice(&bin);
+        Ok(u128::from_be_bytes(bytes))
+    }
+
     /// Return true if self is an integer >= 0
     pub fn is_positive(&self) -> Result<bool> {
         botan_bool_in_rc!(botan_mp_is_positive, self.obj)
@@ -331,6 +525,9 @@ impl MPI {
 
     /// Perform a primality test on self
     ///
+    /// `test_prob` sets the number of Miller-Rabin rounds, such that a
+    /// composite is accepted with probability at most 2^-test_prob
+    ///
     /// # Examples
     ///
     /// ```
@@ -343,6 +540,99 @@ impl MPI {
         botan_bool_in_rc!(botan_mp_is_prime, self.obj, rng.handle(), test_prob)
     }
 
+    /// Return a random prime of exactly `bits` bits
+    ///
+    /// The two high bits are set, so the product of two such primes has
+    /// exactly `2*bits` bits
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let p = botan::MPI::random_prime(&mut rng, 128).unwrap();
+    /// assert_eq!(p.bit_count().unwrap(), 128);
+    /// assert!(p.is_prime(&mut rng, 128).unwrap());
+    /// ```
+    pub fn random_prime(rng: &mut RandomNumberGenerator, bits: usize) -> Result<MPI> {
+        if bits < 2 {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "Random primes must be at least 2 bits".to_owned(),
+            ));
+        }
+
+        let mut p = MPI::new()?;
+        loop {
+            p.random_candidate(rng, bits)?;
+            p.set_bit(bits - 2)?;
+            if p.small_factor(|_, r| r == 0)? {
+                continue;
+            }
+            if p.is_prime(rng, PRIME_TEST_PROB)? {
+                return Ok(p);
+            }
+        }
+    }
+
+    /// Return a random safe prime of exactly `bits` bits
+    ///
+    /// A safe prime is a prime p such that (p-1)/2 is also prime. This is
+    /// much slower than `random_prime`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let p = botan::MPI::random_safe_prime(&mut rng, 64).unwrap();
+    /// let q = &p >> 1;
+    /// assert!(p.is_prime(&mut rng, 128).unwrap());
+    /// assert!(q.is_prime(&mut rng, 128).unwrap());
+    /// ```
+    pub fn random_safe_prime(rng: &mut RandomNumberGenerator, bits: usize) -> Result<MPI> {
+        if bits < 3 {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "Random safe primes must be at least 3 bits".to_owned(),
+            ));
+        }
+
+        let mut q = MPI::new()?;
+        loop {
+            q.random_candidate(rng, bits - 1)?;
+
+            // Reject q if either q or 2q+1 is divisible by a small prime
+            if q.small_factor(|f, r| r == 0 || r == f / 2)? {
+                continue;
+            }
+
+            let p = q.mp_shl(1)?.mp_add_u32(1)?;
+            if q.is_prime(rng, PRIME_TEST_PROB)? && p.is_prime(rng, PRIME_TEST_PROB)? {
+                return Ok(p);
+            }
+        }
+    }
+
+    fn random_candidate(&mut self, rng: &mut RandomNumberGenerator, bits: usize) -> Result<()> {
+        self.randomize(rng, bits)?;
+        self.set_bit(bits - 1)?;
+        self.set_bit(0)
+    }
+
+    // Return true if reject(f, self mod f) holds for any small odd prime f
+    // which is less than self
+    fn small_factor<F: Fn(u32, u32) -> bool>(&self, reject: F) -> Result<bool> {
+        for &f in SMALL_PRIMES.iter() {
+            let fm = MPI::new_from_u32(f)?;
+            if self.compare(&fm)? != Ordering::Greater {
+                break;
+            }
+            if reject(f, self.divrem(&fm)?.1.to_u32()?) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     /// Return the greatest common divisor of x and y
     /// # Examples
     ///
@@ -371,6 +661,97 @@ impl MPI {
         botan_call!(botan_mp_powmod, r.obj, x.obj, e.obj, m.obj)?;
         Ok(r)
     }
+
+    /// Return (x*y) mod m
+    ///
+    /// x and y may be larger than m, as they are reduced modulo m first
+    /// (the library requires both to be smaller than the modulus).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let x = botan::MPI::new_from_u32(1000).unwrap();
+    /// let y = botan::MPI::new_from_u32(2000).unwrap();
+    /// let m = botan::MPI::new_from_u32(17).unwrap();
+    /// assert_eq!(botan::MPI::mulmod(&x, &y, &m).unwrap().to_u32().unwrap(), 2000000 % 17);
+    /// ```
+    pub fn mulmod(x: &MPI, y: &MPI, m: &MPI) -> Result<MPI> {
+        let x = x.divrem(m)?.1;
+        let y = y.divrem(m)?.1;
+        let r = MPI::new()?;
+        botan_call!(botan_mp_mod_mul, r.obj, x.obj, y.obj, m.obj)?;
+        Ok(r)
+    }
+
+    /// Return the Jacobi symbol (a/n), which is one of -1, 0, or 1
+    ///
+    /// n must be odd and positive
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let a = botan::MPI::new_from_u32(2).unwrap();
+    /// let n = botan::MPI::new_from_u32(7).unwrap();
+    /// assert_eq!(botan::MPI::jacobi(&a, &n).unwrap(), 1);
+    /// ```
+    pub fn jacobi(a: &MPI, n: &MPI) -> Result<i32> {
+        if !n.is_positive()? || n.is_zero()? || n.is_even()? {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "Jacobi symbol requires an odd positive modulus".to_owned(),
+            ));
+        }
+
+        let mut x = a.divrem(n)?.1;
+        if x.is_negative()? {
+            x.mp_add_assign(n)?;
+        }
+        let mut y = n.duplicate()?;
+        let mut result = 1;
+
+        while !x.is_zero()? {
+            let zeros = x.low_zero_bits()?;
+            x.mp_shr_assign(zeros)?;
+
+            // (2/y) = -1 iff y = 3 or 5 (mod 8)
+            let y_mod_8 = y.low_bits(3)?;
+            if zeros % 2 == 1 && (y_mod_8 == 3 || y_mod_8 == 5) {
+                result = -result;
+            }
+
+            // quadratic reciprocity
+            if x.low_bits(2)? == 3 && y.low_bits(2)? == 3 {
+                result = -result;
+            }
+
+            x.swap(&mut y)?;
+            x = x.divrem(&y)?.1;
+        }
+
+        if y.equals(&MPI::new_from_u32(1)?)? {
+            Ok(result)
+        } else {
+            Ok(0)
+        }
+    }
+
+    fn low_bits(&self, bits: usize) -> Result<u32> {
+        let mut v = 0;
+        for i in 0..bits {
+            if self.get_bit(i)? {
+                v |= 1 << i;
+            }
+        }
+        Ok(v)
+    }
+
+    fn low_zero_bits(&self) -> Result<usize> {
+        let mut zeros = 0;
+        while !self.get_bit(zeros)? {
+            zeros += 1;
+        }
+        Ok(zeros)
+    }
 }
 
 impl PartialOrd for MPI {
@@ -403,6 +784,17 @@ impl FromStr for MPI {
     }
 }
 
+/// Create an MPI from an unsigned big-endian encoding
+///
+/// The same as `MPI::new_from_bytes`.
+impl<'a> core::convert::TryFrom<&'a [u8]> for MPI {
+    type Error = Error;
+
+    fn try_from(val: &'a [u8]) -> Result<MPI> {
+        MPI::new_from_bytes(val)
+    }
+}
+
 impl fmt::Debug for MPI {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         let s = self.to_string().map_err(|_| fmt::Error)?;
@@ -439,7 +831,7 @@ impl fmt::LowerHex for MPI {
     }
 }
 
-impl<'a> Add<&'a MPI> for MPI {
+impl Add<&MPI> for MPI {
     type Output = MPI;
 
     fn add(mut self, other: &MPI) -> MPI {
@@ -449,7 +841,7 @@ impl<'a> Add<&'a MPI> for MPI {
     }
 }
 
-impl<'a, 'b> Add<&'a MPI> for &'b MPI {
+impl Add<&MPI> for &MPI {
     type Output = MPI;
 
     fn add(self, other: &MPI) -> MPI {
@@ -467,7 +859,7 @@ impl Add<u32> for MPI {
     }
 }
 
-impl<'a> Add<u32> for &'a MPI {
+impl Add<u32> for &MPI {
     type Output = MPI;
 
     fn add(self, other: u32) -> MPI {
@@ -475,7 +867,7 @@ impl<'a> Add<u32> for &'a MPI {
     }
 }
 
-impl<'a> AddAssign<&'a MPI> for MPI {
+impl AddAssign<&MPI> for MPI {
     fn add_assign(&mut self, other: &MPI) {
         self.mp_add_assign(other)
             .expect("MPI::mp_add_assign succeeded");
@@ -489,7 +881,7 @@ impl AddAssign<u32> for MPI {
     }
 }
 
-impl<'a> Sub<&'a MPI> for MPI {
+impl Sub<&MPI> for MPI {
     type Output = MPI;
 
     fn sub(mut self, other: &MPI) -> MPI {
@@ -499,7 +891,7 @@ impl<'a> Sub<&'a MPI> for MPI {
     }
 }
 
-impl<'a, 'b> Sub<&'a MPI> for &'b MPI {
+impl Sub<&MPI> for &MPI {
     type Output = MPI;
 
     fn sub(self, other: &MPI) -> MPI {
@@ -517,7 +909,7 @@ impl Sub<u32> for MPI {
     }
 }
 
-impl<'a> Sub<u32> for &'a MPI {
+impl Sub<u32> for &MPI {
     type Output = MPI;
 
     fn sub(self, other: u32) -> MPI {
@@ -525,7 +917,7 @@ impl<'a> Sub<u32> for &'a MPI {
     }
 }
 
-impl<'a> SubAssign<&'a MPI> for MPI {
+impl SubAssign<&MPI> for MPI {
     fn sub_assign(&mut self, other: &MPI) {
         self.mp_sub_assign(other)
             .expect("MPI::mp_sub_assign succeeded");
@@ -539,7 +931,7 @@ impl SubAssign<u32> for MPI {
     }
 }
 
-impl<'a> Mul<&'a MPI> for MPI {
+impl Mul<&MPI> for MPI {
     type Output = MPI;
 
     fn mul(mut self, other: &MPI) -> MPI {
@@ -549,7 +941,7 @@ impl<'a> Mul<&'a MPI> for MPI {
     }
 }
 
-impl<'a, 'b> Mul<&'a MPI> for &'b MPI {
+impl Mul<&MPI> for &MPI {
     type Output = MPI;
 
     fn mul(self, other: &MPI) -> MPI {
@@ -557,14 +949,14 @@ impl<'a, 'b> Mul<&'a MPI> for &'b MPI {
     }
 }
 
-impl<'a> MulAssign<&'a MPI> for MPI {
+impl MulAssign<&MPI> for MPI {
     fn mul_assign(&mut self, other: &MPI) {
         self.mp_mul_assign(other)
             .expect("MPI::mp_mul_assign succeeded");
     }
 }
 
-impl<'a, 'b> Div<&'b MPI> for &'a MPI {
+impl Div<&MPI> for &MPI {
     type Output = MPI;
 
     #[inline]
@@ -574,13 +966,21 @@ impl<'a, 'b> Div<&'b MPI> for &'a MPI {
     }
 }
 
-impl<'a> DivAssign<&'a MPI> for MPI {
-    fn div_assign(&mut self, other: &'a MPI) {
+impl Div<&MPI> for MPI {
+    type Output = MPI;
+
+    fn div(self, other: &MPI) -> MPI {
+        &self / other
+    }
+}
+
+impl DivAssign<&MPI> for MPI {
+    fn div_assign(&mut self, other: &MPI) {
         *self = &*self / other;
     }
 }
 
-impl<'a, 'b> Rem<&'b MPI> for &'a MPI {
+impl Rem<&MPI> for &MPI {
     type Output = MPI;
 
     fn rem(self, other: &MPI) -> MPI {
@@ -589,13 +989,21 @@ impl<'a, 'b> Rem<&'b MPI> for &'a MPI {
     }
 }
 
-impl<'a> RemAssign<&'a MPI> for MPI {
+impl Rem<&MPI> for MPI {
+    type Output = MPI;
+
+    fn rem(self, other: &MPI) -> MPI {
+        &self % other
+    }
+}
+
+impl RemAssign<&MPI> for MPI {
     fn rem_assign(&mut self, other: &MPI) {
         *self = &*self % other;
     }
 }
 
-impl<'a> Shl<usize> for &'a MPI {
+impl Shl<usize> for &MPI {
     type Output = MPI;
 
     fn shl(self, shift: usize) -> MPI {
@@ -603,6 +1011,16 @@ impl<'a> Shl<usize> for &'a MPI {
     }
 }
 
+impl Shl<usize> for MPI {
+    type Output = MPI;
+
+    fn shl(mut self, shift: usize) -> MPI {
+        self.mp_shl_assign(shift)
+            .expect("MPI::mp_shl_assign succeeded");
+        self
+    }
+}
+
 impl ShlAssign<usize> for MPI {
     fn shl_assign(&mut self, shift: usize) {
         self.mp_shl_assign(shift)
@@ -610,7 +1028,7 @@ impl ShlAssign<usize> for MPI {
     }
 }
 
-impl<'a> Shr<usize> for &'a MPI {
+impl Shr<usize> for &MPI {
     type Output = MPI;
 
     fn shr(self, shift: usize) -> MPI {
@@ -618,6 +1036,16 @@ impl<'a> Shr<usize> for &'a MPI {
     }
 }
 
+impl Shr<usize> for MPI {
+    type Output = MPI;
+
+    fn shr(mut self, shift: usize) -> MPI {
+        self.mp_shr_assign(shift)
+            .expect("MPI::mp_shr_assign succeeded");
+        self
+    }
+}
+
 impl ShrAssign<usize> for MPI {
     fn shr_assign(&mut self, shift: usize) {
         self.mp_shr_assign(shift)
@@ -633,3 +1061,167 @@ impl Neg for MPI {
         self
     }
 }
+
+impl Neg for &MPI {
+    type Output = MPI;
+
+    fn neg(self) -> MPI {
+        let mut r = self.duplicate().expect("MPI::duplicate succeeded");
+        r.flip_sign().expect("MPI::flip_sign succeeded");
+        r
+    }
+}
+
+// Forward operations taking the right hand side by value to the
+// reference implementations above
+macro_rules! mpi_forward_by_value {
+    ($op:ident, $fn:ident, $op_assign:ident, $fn_assign:ident) => {
+        impl $op<MPI> for MPI {
+            type Output = MPI;
+
+            fn $fn(self, other: MPI) -> MPI {
+                $op::$fn(self, &other)
+            }
+        }
+
+        impl<'a> $op<MPI> for &'a MPI {
+            type Output = MPI;
+
+            fn $fn(self, other: MPI) -> MPI {
+                $op::$fn(self, &other)
+            }
+        }
+
+        impl $op_assign<MPI> for MPI {
+            fn $fn_assign(&mut self, other: MPI) {
+                $op_assign::$fn_assign(self, &other)
+            }
+        }
+    };
+}
+
+mpi_forward_by_value!(Add, add, AddAssign, add_assign);
+mpi_forward_by_value!(Sub, sub, SubAssign, sub_assign);
+mpi_forward_by_value!(Mul, mul, MulAssign, mul_assign);
+mpi_forward_by_value!(Div, div, DivAssign, div_assign);
+mpi_forward_by_value!(Rem, rem, RemAssign, rem_assign);
+
+#[cfg(feature = "num-bigint")]
+impl<'a> From<&'a num_bigint::BigUint> for MPI {
+    fn from(n: &num_bigint::BigUint) -> MPI {
+        MPI::new_from_bytes(&n.to_bytes_be()).expect("MPI::new_from_bytes succeeded")
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl<'a> From<&'a num_bigint::BigInt> for MPI {
+    fn from(n: &num_bigint::BigInt) -> MPI {
+        let (sign, bytes) = n.to_bytes_be();
+        let mut r = MPI::new_from_bytes(&bytes).expect("MPI::new_from_bytes succeeded");
+        if sign == num_bigint::Sign::Minus {
+            r.flip_sign().expect("MPI::flip_sign succeeded");
+        }
+        r
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl<'a> core::convert::TryFrom<&'a MPI> for num_bigint::BigUint {
+    type Error = Error;
+
+    fn try_from(n: &MPI) -> Result<num_bigint::BigUint> {
+        if n.is_negative()? {
+            return Err(Error::with_message(
+                ErrorType::ConversionError,
+                "Cannot convert negative MPI to BigUint".to_owned(),
+            ));
+        }
+        Ok(num_bigint::BigUint::from_bytes_be(&n.to_bin()?))
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl<'a> core::convert::TryFrom<&'a MPI> for num_bigint::BigInt {
+    type Error = Error;
+
+    fn try_from(n: &MPI) -> Result<num_bigint::BigInt> {
+        let sign = if n.is_zero()? {
+            num_bigint::Sign::NoSign
+        } else if n.is_negative()? {
+            num_bigint::Sign::Minus
+        } else {
+            num_bigint::Sign::Plus
+        };
+        Ok(num_bigint::BigInt::from_bytes_be(sign, &n.to_bin()?))
+    }
+}
+
+// Human readable formats use a (possibly negative) hex string such as
+// "-0x1F"; binary formats use a sign byte (0 or 1) followed by the
+// big-endian magnitude.
+#[cfg(feature = "serde")]
+impl serde::Serialize for MPI {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        use serde::ser::Error as _;
+
+        let negative = self.is_negative().map_err(S::Error::custom)?;
+        let abs = if negative { -self } else { self.clone() };
+
+        if serializer.is_human_readable() {
+            let mut hex = abs.to_hex().map_err(S::Error::custom)?;
+            if hex.is_empty() {
+                hex.push('0');
+            }
+            let sign = if negative { "-" } else { "" };
+            serializer.serialize_str(&format!("{}0x{}", sign, hex))
+        } else {
+            let mut bytes = vec![negative as u8];
+            bytes.extend_from_slice(&abs.to_bin().map_err(S::Error::custom)?);
+            serializer.serialize_bytes(&bytes)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MPI {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<MPI, D::Error> {
+        struct MPIVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for MPIVisitor {
+            type Value = MPI;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an integer as a hex string or sign prefixed bytes")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> core::result::Result<MPI, E> {
+                MPI::from_str(v).map_err(E::custom)
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> core::result::Result<MPI, E> {
+                let (negative, magnitude) = match v.split_first() {
+                    Some((&0, rest)) => (false, rest),
+                    Some((&1, rest)) => (true, rest),
+                    _ => return Err(E::custom("invalid MPI sign byte")),
+                };
+
+                let mut r = MPI::new_from_bytes(magnitude).map_err(E::custom)?;
+                if negative {
+                    r.flip_sign().map_err(E::custom)?;
+                }
+                Ok(r)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(MPIVisitor)
+        } else {
+            deserializer.deserialize_bytes(MPIVisitor)
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use crate::utils::*;
+
+use crate::cipher::{Cipher, CipherDirection};
+use crate::mac::MsgAuthCode;
+use crate::memutils::scrub_mem;
+use crate::symmetric::AuthTag;
+
+// Shared by both types: a nonce must be set for each message, and using
+// the MAC without one is reported rather than left to the library
+fn nonce_required(algo: &str) -> Error {
+    Error::with_message(
+        ErrorType::InvalidObjectState,
+        format!(
+            "{} requires set_nonce to be called before each message",
+            algo
+        ),
+    )
+}
+
+/// GMAC, the MAC of GCM mode, with a nonce which must be set for each
+/// message
+///
+/// With `MsgAuthCode` it is easy to forget the nonce, or to reuse it.
+/// Here `update` and `finish` fail with `ErrorType::InvalidObjectState`
+/// unless `set_nonce` was called since the last message was finished.
+/// As with GCM, a nonce must never be used twice with the same key.
+/// This requires Botan 3.x.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "botan3")]
+/// # {
+/// let mut gmac = botan::Gmac::new("AES-128").unwrap();
+/// gmac.set_key(&[0; 16]).unwrap();
+/// assert!(gmac.update(b"message").is_err());
+///
+/// gmac.set_nonce(&[1; 12]).unwrap();
+/// gmac.update(b"message").unwrap();
+/// let tag = gmac.finish().unwrap();
+/// assert_eq!(tag.len(), 16);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Gmac {
+    mac: MsgAuthCode,
+    nonce_set: bool,
+}
+
+impl Gmac {
+    /// Create GMAC using the named block cipher, which must have a 16
+    /// byte block, for example `BlockCipherAlgo::Aes256`
+    pub fn new<N: AsRef<str>>(block_cipher: N) -> Result<Gmac> {
+        Ok(Gmac {
+            mac: MsgAuthCode::new(format!("GMAC({})", block_cipher.as_ref()))?,
+            nonce_set: false,
+        })
+    }
+
+    /// Create GMAC using AES-256
+    pub fn aes256() -> Result<Gmac> {
+        Gmac::new("AES-256")
+    }
+
+    /// Return the name of this algorithm
+    pub fn algo_name(&self) -> Result<String> {
+        self.mac.algo_name()
+    }
+
+    /// Return information about the key lengths supported
+    pub fn key_spec(&self) -> Result<KeySpec> {
+        self.mac.key_spec()
+    }
+
+    /// Return the length of the tag, in bytes
+    pub fn output_length(&self) -> Result<usize> {
+        self.mac.output_length()
+    }
+
+    /// Set the key, after which a nonce must be set again
+    pub fn set_key(&mut self, key: &[u8]) -> Result<()> {
+        self.nonce_set = false;
+        self.mac.set_key(key)
+    }
+
+    /// Set the nonce for the next message
+    ///
+    /// Any nonempty length is accepted, but 12 bytes is recommended.
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::BadParameter` for an empty nonce, or with
+    /// `ErrorType::NotImplemented` with Botan 2.x
+    pub fn set_nonce(&mut self, nonce: &[u8]) -> Result<()> {
+        if nonce.is_empty() {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "GMAC requires a nonempty nonce".to_owned(),
+            ));
+        }
+        self.mac.set_nonce(nonce)?;
+        self.nonce_set = true;
+        Ok(())
+    }
+
+    /// Add data to the message
+    pub fn update(&mut self, data: &[u8]) -> Result<()> {
+        if !self.nonce_set {
+            return Err(nonce_required("GMAC"));
+        }
+        self.mac.update(data)
+    }
+
+    /// Return the tag of the message, after which a new nonce must be set
+    pub fn finish(&mut self) -> Result<Vec<u8>> {
+        if !self.nonce_set {
+            return Err(nonce_required("GMAC"));
+        }
+        self.nonce_set = false;
+        self.mac.finish()
+    }
+
+    /// Compare the tag of the message with `tag` in constant time
+    pub fn verify_tag(&mut self, tag: &AuthTag) -> Result<bool> {
+        Ok(AuthTag::from_vec(self.finish()?) == *tag)
+    }
+
+    /// Clear the key
+    pub fn clear(&mut self) -> Result<()> {
+        self.nonce_set = false;
+        self.mac.clear()
+    }
+}
+
+/// Poly1305 with a one-time key derived by ChaCha20 from a long term key
+/// and a nonce
+///
+/// Poly1305 itself takes no nonce, and its key must never be used for
+/// more than one message. This derives each message's key from ChaCha20
+/// with the long term key and `set_nonce`, as RFC 8439 section 2.6 does
+/// for ChaCha20Poly1305. As with `Gmac`, `update` and `finish` fail with
+/// `ErrorType::InvalidObjectState` unless a nonce was set since the last
+/// message was finished, and a nonce must never be used twice with the
+/// same key.
+///
+/// # Examples
+/// ```
+/// let mut mac = botan::ChaChaPoly1305Mac::new(&[0x42; 32]).unwrap();
+/// mac.set_nonce(&[0; 12]).unwrap();
+/// mac.update(b"message").unwrap();
+/// let tag = mac.finish().unwrap();
+/// assert_eq!(tag.len(), 16);
+/// assert!(mac.finish().is_err());
+/// ```
+#[derive(Debug)]
+pub struct ChaChaPoly1305Mac {
+    mac: MsgAuthCode,
+    chacha: Cipher,
+    nonce_set: bool,
+}
+
+impl ChaChaPoly1305Mac {
+    /// Create the MAC with the 32 byte long term `key`
+    pub fn new(key: &[u8; 32]) -> Result<ChaChaPoly1305Mac> {
+        let mut chacha = Cipher::new("ChaCha(20)", CipherDirection::Encrypt)?;
+        chacha.set_key(key)?;
+        Ok(ChaChaPoly1305Mac {
+            mac: MsgAuthCode::new("Poly1305")?,
+            chacha,
+            nonce_set: false,
+        })
+    }
+
+    /// Set the nonce for the next message, which may be 8, 12 or 24 bytes
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::BadParameter` if the nonce is another length
+    pub fn set_nonce(&mut self, nonce: &[u8]) -> Result<()> {
+        if !self.chacha.valid_nonce_length(nonce.len())? {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                format!(
+                    "A {} byte nonce is not valid for Poly1305 with ChaCha20",
+                    nonce.len()
+                ),
+            ));
+        }
+
+        // The first 32 bytes of the ChaCha20 keystream
+        let mut one_time_key = self.chacha.process(nonce, &[0u8; 32])?;
+        let result = self.mac.set_key(&one_time_key);
+        scrub_mem(&mut one_time_key);
+        result?;
+
+        self.nonce_set = true;
+        Ok(())
+    }
+
+    /// Add data to the message
+    pub fn update(&mut self, data: &[u8]) -> Result<()> {
+        if !self.nonce_set {
+            return Err(nonce_required("Poly1305"));
+        }
+        self.mac.update(data)
+    }
+
+    /// Return the 16 byte tag of the message, after which a new nonce
+    /// must be set
+    pub fn finish(&mut self) -> Result<Vec<u8>> {
+        if !self.nonce_set {
+            return Err(nonce_required("Poly1305"));
+        }
+        self.nonce_set = false;
+        let tag = self.mac.finish();
+        // Remove the one-time key so it cannot be used again
+        self.mac.clear()?;
+        tag
+    }
+
+    /// Compare the tag of the message with `tag` in constant time
+    pub fn verify_tag(&mut self, tag: &AuthTag) -> Result<bool> {
+        Ok(AuthTag::from_vec(self.finish()?) == *tag)
+    }
+
+    /// Clear the keys
+    pub fn clear(&mut self) -> Result<()> {
+        self.nonce_set = false;
+        self.mac.clear()?;
+        self.chacha.clear()
+    }
+}
@@ -0,0 +1,323 @@
+use crate::utils::*;
+
+use crate::ec::EcPoint;
+use crate::hash::HashFunction;
+use crate::kdf::kdf;
+use crate::keys::find_curve;
+use crate::mac::MsgAuthCode;
+use crate::memutils::const_time_compare;
+use crate::mp::MPI;
+use crate::pk_ops::KeyAgreement;
+use crate::pubkey::{Privkey, Pubkey};
+use crate::rng::RandomNumberGenerator;
+
+use core::convert::TryFrom;
+
+// One-pass Diffie-Hellman, C(1e, 1s) in NIST SP 800-56A: the initiator
+// (party U) combines a fresh ephemeral key with the static key of the
+// recipient (party V), who can optionally confirm the derived key.
+
+const KEY_CONFIRMATION_LABEL: &[u8] = b"KC_1_V";
+
+/// One-pass static-ephemeral (EC)DH with the SP 800-56A concatenation KDF
+///
+/// The initiator calls `initiate` with the recipient's static public key
+/// and sends the resulting ephemeral public key; the recipient passes it
+/// to `respond` along with its static private key. Both sides must use
+/// the same options.
+///
+/// The KDF's FixedInfo is `AlgorithmID || PartyUInfo || PartyVInfo`,
+/// each prefixed with its length as a 32-bit big-endian integer, followed
+/// by `SuppPubInfo` as is. This is also the layout used by JOSE ECDH-ES.
+///
+/// With key confirmation enabled, the recipient also returns a MAC tag
+/// (`KC_1_V` of SP 800-56A section 5.9) which the initiator must check
+/// with `OnePassDhInitiation::confirm` before using the key.
+///
+/// ECDH, X25519 and DH keys are supported.
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let recipient = botan::Privkey::create("ECDH", "secp256r1", &mut rng).unwrap();
+///
+/// let dh = botan::OnePassDh::new("SHA-256")
+///     .algorithm_id(b"A256GCM")
+///     .initiator_id(b"alice")
+///     .recipient_id(b"bob")
+///     .key_confirmation(true);
+///
+/// // Initiator
+/// let initiation = dh.initiate(&recipient.pubkey().unwrap(), 32, &mut rng).unwrap();
+/// let ephemeral = initiation.ephemeral_public_key().to_vec();
+/// // Recipient
+/// let (key, tag) = dh.respond(&recipient, &ephemeral, 32).unwrap();
+/// // Initiator
+/// let confirmed = initiation.confirm(&tag.unwrap()).unwrap();
+/// assert_eq!(key, confirmed);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnePassDh {
+    hash: String,
+    algorithm_id: Vec<u8>,
+    initiator_id: Vec<u8>,
+    recipient_id: Vec<u8>,
+    supp_pub_info: Vec<u8>,
+    key_confirmation: bool,
+}
+
+/// The initiator's side of a `OnePassDh` exchange
+///
+/// `Debug` never shows the derived key.
+pub struct OnePassDhInitiation {
+    ephemeral_public_key: Vec<u8>,
+    key: Vec<u8>,
+    expected_tag: Option<Vec<u8>>,
+}
+
+impl core::fmt::Debug for OnePassDhInitiation {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("OnePassDhInitiation")
+            .field("ephemeral_public_key", &self.ephemeral_public_key)
+            .field("key_confirmation", &self.expected_tag.is_some())
+            .field("key", &format_args!("[REDACTED]"))
+            .finish()
+    }
+}
+
+fn length_prefixed(out: &mut Vec<u8>, data: &[u8]) -> Result<()> {
+    let len = u32::try_from(data.len()).map_err(|_| {
+        Error::with_message(
+            ErrorType::BadParameter,
+            "One-pass DH info field is too long".to_owned(),
+        )
+    })?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+// The encoding of a public key as used by KeyAgreement
+fn public_value(key: &Pubkey) -> Result<Vec<u8>> {
+    match key.algo_name()?.as_str() {
+        "ECDH" => {
+            let curve = find_curve(&|f| key.get_field(f))?;
+            EcPoint::from_affine(
+                &curve,
+                &key.get_field("public_x")?,
+                &key.get_field("public_y")?,
+            )?
+            .encode(false)
+        }
+        "X25519" | "Curve25519" => key.get_x25519_key(),
+        "DH" => key.get_field("y")?.to_bin(),
+        algo => Err(Error::with_message(
+            ErrorType::NotImplemented,
+            format!("One-pass DH is not supported for {} keys", algo),
+        )),
+    }
+}
+
+// Create an ephemeral key in the same group as `key`
+fn ephemeral_key(key: &Pubkey, rng: &mut RandomNumberGenerator) -> Result<Privkey> {
+    let algo = key.algo_name()?;
+    match algo.as_str() {
+        "ECDH" => Privkey::create("ECDH", &find_curve(&|f| key.get_field(f))?, rng),
+        "X25519" | "Curve25519" => Privkey::create(&algo, "", rng),
+        "DH" => {
+            let p = key.get_field("p")?;
+            let g = key.get_field("g")?;
+            let mut x = MPI::new()?;
+            x.random_range(rng, &MPI::new_from_u32(2)?, &(&p - 1))?;
+            Privkey::load_dh(&p, &g, &x)
+        }
+        _ => Err(Error::with_message(
+            ErrorType::NotImplemented,
+            format!("One-pass DH is not supported for {} keys", algo),
+        )),
+    }
+}
+
+impl OnePassDh {
+    /// Start configuring a one-pass DH which derives keys using the
+    /// SP 800-56A KDF with `hash`, for example `HashAlgo::Sha256`
+    ///
+    /// Key confirmation, if enabled, uses HMAC with the same hash.
+    #[must_use]
+    pub fn new<H: AsRef<str>>(hash: H) -> OnePassDh {
+        OnePassDh {
+            hash: hash.as_ref().to_owned(),
+            algorithm_id: Vec::new(),
+            initiator_id: Vec::new(),
+            recipient_id: Vec::new(),
+            supp_pub_info: Vec::new(),
+            key_confirmation: false,
+        }
+    }
+
+    /// Set the AlgorithmID, identifying what the derived key is for
+    #[must_use]
+    pub fn algorithm_id(mut self, algorithm_id: &[u8]) -> OnePassDh {
+        self.algorithm_id = algorithm_id.to_vec();
+        self
+    }
+
+    /// Set the identifier of the initiator (PartyUInfo)
+    #[must_use]
+    pub fn initiator_id(mut self, id: &[u8]) -> OnePassDh {
+        self.initiator_id = id.to_vec();
+        self
+    }
+
+    /// Set the identifier of the recipient (PartyVInfo)
+    #[must_use]
+    pub fn recipient_id(mut self, id: &[u8]) -> OnePassDh {
+        self.recipient_id = id.to_vec();
+        self
+    }
+
+    /// Set the SuppPubInfo, which is appended to the FixedInfo without a
+    /// length prefix
+    #[must_use]
+    pub fn supp_pub_info(mut self, info: &[u8]) -> OnePassDh {
+        self.supp_pub_info = info.to_vec();
+        self
+    }
+
+    /// Enable or disable key confirmation by the recipient
+    #[must_use]
+    pub fn key_confirmation(mut self, enabled: bool) -> OnePassDh {
+        self.key_confirmation = enabled;
+        self
+    }
+
+    fn fixed_info(&self) -> Result<Vec<u8>> {
+        let mut info = Vec::new();
+        length_prefixed(&mut info, &self.algorithm_id)?;
+        length_prefixed(&mut info, &self.initiator_id)?;
+        length_prefixed(&mut info, &self.recipient_id)?;
+        info.extend_from_slice(&self.supp_pub_info);
+        Ok(info)
+    }
+
+    // Derive the key, and the MAC tag if key confirmation is enabled
+    fn derive(
+        &self,
+        z: &[u8],
+        ephemeral_public_key: &[u8],
+        key_len: usize,
+    ) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+        let mac_key_len = if self.key_confirmation {
+            HashFunction::new(&self.hash)?.output_length()?
+        } else {
+            0
+        };
+
+        let dkm = kdf(
+            &format!("SP800-56A({})", self.hash),
+            mac_key_len + key_len,
+            z,
+            &[],
+            &self.fixed_info()?,
+        )?;
+        let key = dkm[mac_key_len..].to_vec();
+
+        if !self.key_confirmation {
+            return Ok((key, None));
+        }
+
+        let mac = MsgAuthCode::new(format!("HMAC({})", self.hash))?;
+        mac.set_key(&dkm[..mac_key_len])?;
+        mac.update(KEY_CONFIRMATION_LABEL)?;
+        mac.update(&self.recipient_id)?;
+        mac.update(&self.initiator_id)?;
+        mac.update(ephemeral_public_key)?;
+        Ok((key, Some(mac.finish()?)))
+    }
+
+    /// Derive a `key_len` byte key for the holder of `recipient`
+    ///
+    /// The returned value holds the ephemeral public key to send to the
+    /// recipient, and gives access to the key.
+    pub fn initiate(
+        &self,
+        recipient: &Pubkey,
+        key_len: usize,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<OnePassDhInitiation> {
+        let recipient_public = public_value(recipient)?;
+        let ephemeral = ephemeral_key(recipient, rng)?;
+        let ephemeral_public_key = ephemeral.key_agreement_key()?;
+
+        let z = KeyAgreement::new_raw(&ephemeral)?.agree(0, &recipient_public, &[])?;
+        let (key, expected_tag) = self.derive(&z, &ephemeral_public_key, key_len)?;
+
+        Ok(OnePassDhInitiation {
+            ephemeral_public_key,
+            key,
+            expected_tag,
+        })
+    }
+
+    /// Derive the `key_len` byte key from the initiator's ephemeral
+    /// public key, using the recipient's static key
+    ///
+    /// Returns the key, and the MAC tag to send back to the initiator
+    /// if key confirmation is enabled.
+    pub fn respond(
+        &self,
+        key: &Privkey,
+        ephemeral_public_key: &[u8],
+        key_len: usize,
+    ) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+        let z = KeyAgreement::new_raw(key)?.agree(0, ephemeral_public_key, &[])?;
+        self.derive(&z, ephemeral_public_key, key_len)
+    }
+}
+
+impl OnePassDhInitiation {
+    /// Return the ephemeral public key, which must be sent to the recipient
+    #[must_use]
+    pub fn ephemeral_public_key(&self) -> &[u8] {
+        &self.ephemeral_public_key
+    }
+
+    /// Check the recipient's key confirmation tag, returning the key if
+    /// it is correct
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::BadAuthCode` if the tag is wrong, or with
+    /// `ErrorType::InvalidObjectState` if key confirmation is not enabled
+    pub fn confirm(self, tag: &[u8]) -> Result<Vec<u8>> {
+        let expected = self.expected_tag.as_ref().ok_or_else(|| {
+            Error::with_message(
+                ErrorType::InvalidObjectState,
+                "Key confirmation is not enabled".to_owned(),
+            )
+        })?;
+
+        if !const_time_compare(expected, tag) {
+            return Err(Error::with_message(
+                ErrorType::BadAuthCode,
+                "Key confirmation tag is not valid".to_owned(),
+            ));
+        }
+
+        Ok(self.key)
+    }
+
+    /// Return the key, when key confirmation is not enabled
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::InvalidObjectState` if key confirmation is
+    /// enabled, in which case `confirm` must be used instead
+    pub fn into_key(self) -> Result<Vec<u8>> {
+        if self.expected_tag.is_some() {
+            return Err(Error::with_message(
+                ErrorType::InvalidObjectState,
+                "The key must be confirmed before it is used".to_owned(),
+            ));
+        }
+        Ok(self.key)
+    }
+}
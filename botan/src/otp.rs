@@ -1,6 +1,12 @@
 use crate::utils::*;
 use botan_sys::*;
 
+use core::convert::TryFrom;
+use core::time::Duration;
+
+#[cfg(not(feature = "no-std"))]
+use std::time::SystemTime;
+
 /// Generate or check HOTP tokens
 #[derive(Debug)]
 #[allow(clippy::upper_case_acronyms)]
@@ -10,6 +16,9 @@ pub struct HOTP {
 
 botan_impl_drop!(HOTP, botan_hotp_destroy);
 
+// Not Sync: generating a code updates the underlying MAC
+unsafe impl Send for HOTP {}
+
 /// Generate or check TOTP tokens
 #[derive(Debug)]
 #[allow(clippy::upper_case_acronyms)]
@@ -19,6 +28,8 @@ pub struct TOTP {
 
 botan_impl_drop!(TOTP, botan_totp_destroy);
 
+unsafe impl Send for TOTP {}
+
 impl HOTP {
     /// Instantiate a new HOTP instance with the given parameters
     ///
@@ -103,7 +114,60 @@ impl TOTP {
         Ok(TOTP { obj })
     }
 
+    /// Instantiate a new TOTP instance, with the time step as a `Duration`
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::InvalidInput` unless `time_step` is a
+    /// positive whole number of seconds
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// let totp = botan::TOTP::with_time_step(&[1,2,3,4], "SHA-1", 6, Duration::from_secs(30));
+    /// assert!(totp.is_ok());
+    /// ```
+    pub fn with_time_step(
+        key: &[u8],
+        hash_algo: &str,
+        digits: usize,
+        time_step: Duration,
+    ) -> Result<TOTP> {
+        if time_step.as_secs() == 0 || time_step.subsec_nanos() != 0 {
+            return Err(Error::with_message(
+                ErrorType::InvalidInput,
+                "TOTP time step must be a whole number of seconds".to_owned(),
+            ));
+        }
+        let time_step = usize::try_from(time_step.as_secs()).map_err(Error::conversion_error)?;
+        TOTP::new(key, hash_algo, digits, time_step)
+    }
+
+    /// Generate a TOTP code for the given time
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let totp = botan::TOTP::new(b"12345678901234567890", "SHA-1", 8, 30).unwrap();
+    /// let code = totp.generate_at(std::time::SystemTime::now()).unwrap();
+    /// ```
+    #[cfg(not(feature = "no-std"))]
+    pub fn generate_at(&self, time: SystemTime) -> Result<u32> {
+        self.generate(crate::clock::unix_time(time)?)
+    }
+
+    /// Check a TOTP code against the given time
+    ///
+    /// Codes from up to `allowed_drift` time steps before or after `time`
+    /// are accepted.
+    #[cfg(not(feature = "no-std"))]
+    pub fn check_at(&self, code: u32, time: SystemTime, allowed_drift: usize) -> Result<bool> {
+        self.check(code, crate::clock::unix_time(time)?, allowed_drift)
+    }
+
     /// Generate an TOTP code
+    ///
+    /// `timestamp` is in seconds since the Unix epoch; see also `generate_at`
     pub fn generate(&self, timestamp: u64) -> Result<u32> {
         let mut code = 0;
         botan_call!(botan_totp_generate, self.obj, &mut code, timestamp)?;
@@ -111,6 +175,8 @@ impl TOTP {
     }
 
     /// Check an TOTP code
+    ///
+    /// `timestamp` is in seconds since the Unix epoch; see also `check_at`
     pub fn check(&self, code: u32, timestamp: u64, allowed_drift: usize) -> Result<bool> {
         // Return value is inverted
         Ok(!botan_bool_in_rc!(
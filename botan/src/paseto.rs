@@ -0,0 +1,250 @@
+use crate::utils::*;
+
+use crate::cipher::{Cipher, CipherDirection};
+use crate::jws::{base64url_decode, base64url_encode};
+use crate::mac::MsgAuthCode;
+use crate::memutils::const_time_compare;
+use crate::pk_ops::{Signer, Verifier};
+use crate::pubkey::{Privkey, Pubkey};
+use crate::rng::RandomNumberGenerator;
+
+const V4_LOCAL_HEADER: &str = "v4.local.";
+const V4_PUBLIC_HEADER: &str = "v4.public.";
+
+const V4_NONCE_LENGTH: usize = 32;
+const V4_TAG_LENGTH: usize = 32;
+const V4_SIGNATURE_LENGTH: usize = 64;
+
+fn paseto_error(msg: &str) -> Error {
+    Error::with_message(ErrorType::InvalidInput, msg.to_owned())
+}
+
+// Pre-Authentication Encoding
+fn pae(pieces: &[&[u8]]) -> Vec<u8> {
+    fn le64(out: &mut Vec<u8>, n: usize) {
+        out.extend_from_slice(&((n as u64) & 0x7FFF_FFFF_FFFF_FFFF).to_le_bytes());
+    }
+
+    let mut out = Vec::new();
+    le64(&mut out, pieces.len());
+    for piece in pieces {
+        le64(&mut out, piece.len());
+        out.extend_from_slice(piece);
+    }
+    out
+}
+
+fn blake2b(key: &[u8], output_bits: usize, inputs: &[&[u8]]) -> Result<Vec<u8>> {
+    let mac = MsgAuthCode::new(format!("BLAKE2b({})", output_bits))?;
+    mac.set_key(key)?;
+    for input in inputs {
+        mac.update(input)?;
+    }
+    mac.finish()
+}
+
+fn make_token(header: &str, body: &[u8], footer: &[u8]) -> Result<String> {
+    let mut token = header.to_owned();
+    token.push_str(&base64url_encode(body)?);
+    if !footer.is_empty() {
+        token.push('.');
+        token.push_str(&base64url_encode(footer)?);
+    }
+    Ok(token)
+}
+
+fn split_token(token: &str, header: &str, footer: &[u8]) -> Result<Vec<u8>> {
+    if !token.starts_with(header) {
+        return Err(paseto_error("Unexpected PASETO header"));
+    }
+
+    let mut parts = token[header.len()..].split('.');
+    let body = parts.next().unwrap_or("");
+    let token_footer = match parts.next() {
+        Some(f) => base64url_decode(f)?,
+        None => Vec::new(),
+    };
+
+    if parts.next().is_some() {
+        return Err(paseto_error("Malformed PASETO token"));
+    }
+
+    if !const_time_compare(&token_footer, footer) {
+        return Err(paseto_error("PASETO footer mismatch"));
+    }
+
+    base64url_decode(body)
+}
+
+fn v4_local_keys(key: &[u8], nonce: &[u8]) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    if key.len() != 32 {
+        return Err(Error::with_message(
+            ErrorType::InvalidKeyLength,
+            "PASETO v4.local keys must be 32 bytes".to_owned(),
+        ));
+    }
+
+    let mut tmp = blake2b(key, 448, &[b"paseto-encryption-key", nonce])?;
+    let counter_nonce = tmp.split_off(32);
+    let auth_key = blake2b(key, 256, &[b"paseto-auth-key-for-aead", nonce])?;
+    Ok((tmp, counter_nonce, auth_key))
+}
+
+fn xchacha20(key: &[u8], nonce: &[u8], input: &[u8]) -> Result<Vec<u8>> {
+    let mut cipher = Cipher::new("ChaCha(20)", CipherDirection::Encrypt)?;
+    cipher.set_key(key)?;
+    cipher.process(nonce, input)
+}
+
+/// Create a PASETO v4.local token (XChaCha20 and keyed BLAKE2b)
+///
+/// The footer is authenticated but not encrypted, and the implicit
+/// assertion is authenticated without being included in the token.
+/// This requires keyed BLAKE2b, which is only available in Botan 3.x.
+pub fn paseto_v4_local_encrypt(
+    key: &[u8],
+    message: &[u8],
+    footer: &[u8],
+    implicit: &[u8],
+    rng: &mut RandomNumberGenerator,
+) -> Result<String> {
+    let nonce = rng.read(V4_NONCE_LENGTH)?;
+    let (mut enc_key, counter_nonce, mut auth_key) = v4_local_keys(key, &nonce)?;
+
+    let ctext = xchacha20(&enc_key, &counter_nonce, message);
+    crate::memutils::scrub_mem(&mut enc_key);
+    let ctext = ctext?;
+
+    let pre_auth = pae(&[V4_LOCAL_HEADER.as_bytes(), &nonce, &ctext, footer, implicit]);
+    let tag = blake2b(&auth_key, 256, &[&pre_auth]);
+    crate::memutils::scrub_mem(&mut auth_key);
+
+    let mut body = nonce;
+    body.extend_from_slice(&ctext);
+    body.extend_from_slice(&tag?);
+
+    make_token(V4_LOCAL_HEADER, &body, footer)
+}
+
+/// Decrypt a PASETO v4.local token
+///
+/// The token's footer must equal `footer` (empty if the token has no
+/// footer) and the same implicit assertion used during encryption
+/// must be provided.
+pub fn paseto_v4_local_decrypt(
+    key: &[u8],
+    token: &str,
+    footer: &[u8],
+    implicit: &[u8],
+) -> Result<Vec<u8>> {
+    let body = split_token(token, V4_LOCAL_HEADER, footer)?;
+
+    if body.len() < V4_NONCE_LENGTH + V4_TAG_LENGTH {
+        return Err(paseto_error("PASETO token too short"));
+    }
+
+    let nonce = &body[..V4_NONCE_LENGTH];
+    let ctext = &body[V4_NONCE_LENGTH..body.len() - V4_TAG_LENGTH];
+    let tag = &body[body.len() - V4_TAG_LENGTH..];
+
+    let (mut enc_key, counter_nonce, mut auth_key) = v4_local_keys(key, nonce)?;
+
+    let pre_auth = pae(&[V4_LOCAL_HEADER.as_bytes(), nonce, ctext, footer, implicit]);
+    let expected_tag = blake2b(&auth_key, 256, &[&pre_auth]);
+    crate::memutils::scrub_mem(&mut auth_key);
+
+    if !const_time_compare(&expected_tag?, tag) {
+        crate::memutils::scrub_mem(&mut enc_key);
+        return Err(Error::with_message(
+            ErrorType::BadAuthCode,
+            "PASETO authentication tag is not valid".to_owned(),
+        ));
+    }
+
+    let ptext = xchacha20(&enc_key, &counter_nonce, ctext);
+    crate::memutils::scrub_mem(&mut enc_key);
+    ptext
+}
+
+fn check_ed25519(algo: &str) -> Result<()> {
+    if algo != "Ed25519" {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            format!("{} key cannot be used with PASETO v4.public", algo),
+        ));
+    }
+    Ok(())
+}
+
+/// Create a PASETO v4.public token (Ed25519)
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::Privkey::create("Ed25519", "", &mut rng).unwrap();
+/// let token = botan::paseto_v4_public_sign(&key, b"{}", &[], &[], &mut rng).unwrap();
+/// let msg = botan::paseto_v4_public_verify(&key.pubkey().unwrap(), &token, &[], &[]).unwrap();
+/// assert_eq!(msg, b"{}");
+/// ```
+pub fn paseto_v4_public_sign(
+    key: &Privkey,
+    message: &[u8],
+    footer: &[u8],
+    implicit: &[u8],
+    rng: &mut RandomNumberGenerator,
+) -> Result<String> {
+    check_ed25519(&key.algo_name()?)?;
+
+    let mut signer = Signer::new(key, "Pure")?;
+    signer.update(&pae(&[
+        V4_PUBLIC_HEADER.as_bytes(),
+        message,
+        footer,
+        implicit,
+    ]))?;
+    let signature = signer.finish(rng)?;
+
+    let mut body = message.to_vec();
+    body.extend_from_slice(&signature);
+    make_token(V4_PUBLIC_HEADER, &body, footer)
+}
+
+/// Verify a PASETO v4.public token and return its message
+///
+/// The token's footer must equal `footer` (empty if the token has no
+/// footer) and the same implicit assertion used during signing must
+/// be provided.
+pub fn paseto_v4_public_verify(
+    key: &Pubkey,
+    token: &str,
+    footer: &[u8],
+    implicit: &[u8],
+) -> Result<Vec<u8>> {
+    check_ed25519(&key.algo_name()?)?;
+
+    let mut body = split_token(token, V4_PUBLIC_HEADER, footer)?;
+
+    if body.len() < V4_SIGNATURE_LENGTH {
+        return Err(paseto_error("PASETO token too short"));
+    }
+
+    let signature = body.split_off(body.len() - V4_SIGNATURE_LENGTH);
+
+    let mut verifier = Verifier::new(key, "Pure")?;
+    verifier.update(&pae(&[
+        V4_PUBLIC_HEADER.as_bytes(),
+        &body,
+        footer,
+        implicit,
+    ]))?;
+
+    if !verifier.finish(&signature)? {
+        return Err(Error::with_message(
+            ErrorType::InvalidVerifier,
+            "PASETO signature is not valid".to_owned(),
+        ));
+    }
+
+    Ok(body)
+}
@@ -60,6 +60,7 @@ pub fn derive_key_from_password_timed(
     salt: &[u8],
     msec: u32,
 ) -> Result<(Vec<u8>, usize, usize, usize)> {
+    botan_span!("pbkdf_tune", algo, msec);
     let algo = make_cstr(algo)?;
     let passphrase = make_cstr(passphrase)?;
 
@@ -83,6 +84,7 @@ pub fn derive_key_from_password_timed(
         salt.len()
     )?;
 
+    botan_event!(param1, param2, param3, "tuned password hash parameters");
     Ok((output, param1, param2, param3))
 }
 
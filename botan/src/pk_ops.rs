@@ -1,9 +1,121 @@
 use crate::utils::*;
 use botan_sys::*;
 
+use crate::hash::HashFunction;
+use crate::memutils::SecretVec;
 use crate::pubkey::{Privkey, Pubkey};
 use crate::rng::RandomNumberGenerator;
 
+/// The variants of Ed25519 signatures defined in RFC 8032
+///
+/// Ed25519ctx, and Ed25519ph with a context string, are not available
+/// since the library does not support context strings.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Ed25519Variant {
+    /// Ed25519, which signs the message itself
+    Pure,
+    /// Ed25519ph, which signs the SHA-512 hash of the message
+    Prehashed,
+}
+
+// Return the padding string for an Ed25519 variant
+fn ed25519_padding(variant: Ed25519Variant) -> &'static str {
+    match variant {
+        Ed25519Variant::Pure => "Pure",
+        Ed25519Variant::Prehashed => "Ed25519ph",
+    }
+}
+
+/// A builder for RSA-PSS signature padding strings, for use with
+/// `Signer::new` and `Verifier::new`
+///
+/// The salt length is always given explicitly, so verification rejects
+/// signatures using any other salt length. It defaults to the output
+/// length of the hash, as required by JOSE `PS256` and friends.
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::Privkey::create("RSA", "2048", &mut rng).unwrap();
+///
+/// let padding = botan::PssPadding::new("SHA-256").salt_len(20).build().unwrap();
+/// assert_eq!(padding, "PSSR(SHA-256,MGF1,20)");
+/// let sig = key.sign(b"message", &padding, &mut rng).unwrap();
+/// assert!(key.pubkey().unwrap().verify(b"message", &sig, &padding).unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PssPadding {
+    hash: String,
+    mgf_hash: Option<String>,
+    salt_len: Option<usize>,
+}
+
+impl PssPadding {
+    /// Start building a PSS padding which hashes the message with `hash`,
+    /// for example `HashAlgo::Sha256`
+    #[must_use]
+    pub fn new<H: AsRef<str>>(hash: H) -> PssPadding {
+        PssPadding {
+            hash: hash.as_ref().to_owned(),
+            mgf_hash: None,
+            salt_len: None,
+        }
+    }
+
+    /// Set the length of the salt in bytes
+    #[must_use]
+    pub fn salt_len(mut self, salt_len: usize) -> PssPadding {
+        self.salt_len = Some(salt_len);
+        self
+    }
+
+    /// Set the hash used by MGF1
+    ///
+    /// Botan only implements MGF1 with the message hash, so `build`
+    /// fails if this is set to anything else.
+    #[must_use]
+    pub fn mgf_hash<H: AsRef<str>>(mut self, mgf_hash: H) -> PssPadding {
+        self.mgf_hash = Some(mgf_hash.as_ref().to_owned());
+        self
+    }
+
+    /// Return the padding string
+    ///
+    /// # Errors
+    /// Fails if the hash is not supported, or if a different MGF1 hash
+    /// was requested
+    pub fn build(&self) -> Result<String> {
+        let hash_len = HashFunction::new(&self.hash)?.output_length()?;
+
+        if let Some(mgf_hash) = &self.mgf_hash {
+            if *mgf_hash != self.hash {
+                return Err(Error::with_message(
+                    ErrorType::NotImplemented,
+                    format!(
+                        "PSS with MGF1({}) and message hash {} is not supported",
+                        mgf_hash, self.hash
+                    ),
+                ));
+            }
+        }
+
+        let salt_len = self.salt_len.unwrap_or(hash_len);
+        Ok(format!("PSSR({},MGF1,{})", self.hash, salt_len))
+    }
+}
+
+// Unpadded operations are only offered for RSA, where Botan's "Raw"
+// padding means textbook RSA
+fn check_raw_rsa(algo: String) -> Result<()> {
+    if algo != "RSA" {
+        return Err(Error::with_message(
+            ErrorType::BadParameter,
+            format!("Raw operations are only supported for RSA, not {}", algo),
+        ));
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 /// An object that can generate signatures
 ///
@@ -16,17 +128,79 @@ use crate::rng::RandomNumberGenerator;
 /// signer.update(&[1,2,3]).unwrap();
 /// let signature = signer.finish(&mut rng).unwrap();
 /// ```
-pub struct Signer {
+///
+/// A signer can be used for any number of messages: `finish` returns
+/// the signature of everything passed to `update` since the previous
+/// `finish`, and leaves the signer ready for the next message.
+pub struct Signer<'a> {
     obj: botan_pk_op_sign_t,
     sig_len: usize,
+    key: &'a Privkey,
+    padding: CString,
+    in_progress: bool,
+    // Used by sign_message while a streamed message is in progress
+    oneshot: Option<Box<Signer<'a>>>,
 }
 
-botan_impl_drop!(Signer, botan_pk_op_sign_destroy);
+botan_impl_drop!(Signer<'_>, botan_pk_op_sign_destroy);
+
+unsafe impl Send for Signer<'_> {}
 
-impl Signer {
+impl<'a> Signer<'a> {
     /// Create a new signature operator
-    pub fn new(key: &Privkey, padding: &str) -> Result<Signer> {
-        let padding = make_cstr(padding)?;
+    pub fn new(key: &'a Privkey, padding: &str) -> Result<Signer<'a>> {
+        Signer::create(key, make_cstr(padding)?)
+    }
+
+    /// Create a signature operator which applies the RSA private key
+    /// operation directly to the message, without hashing or padding
+    ///
+    /// This is textbook RSA, which is insecure unless the message is
+    /// encoded by the caller (for example by a blind signature protocol).
+    /// The message must be smaller than the modulus.
+    ///
+    /// # Errors
+    /// Fails if `key` is not an RSA key
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let rsa = botan::Privkey::create("RSA", "1024", &mut rng).unwrap();
+    /// let mut signer = botan::Signer::raw_unsafe(&rsa).unwrap();
+    /// let sig = signer.sign_message(b"pre-encoded message", &mut rng).unwrap();
+    ///
+    /// let mut verifier = botan::Verifier::raw_unsafe(&rsa.pubkey().unwrap()).unwrap();
+    /// verifier.update(b"pre-encoded message").unwrap();
+    /// assert!(verifier.finish(&sig).unwrap());
+    /// ```
+    pub fn raw_unsafe(key: &'a Privkey) -> Result<Signer<'a>> {
+        check_raw_rsa(key.algo_name()?)?;
+        Signer::new(key, "Raw")
+    }
+
+    /// Create a signature operator for one of the RFC 8032 variants
+    /// of Ed25519, with an empty context string
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use botan::Ed25519Variant;
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let key = botan::Privkey::create("Ed25519", "", &mut rng).unwrap();
+    /// let mut signer = botan::Signer::new_ed25519(&key, Ed25519Variant::Prehashed).unwrap();
+    /// let sig = signer.sign_message(b"message", &mut rng).unwrap();
+    ///
+    /// let pubkey = key.pubkey().unwrap();
+    /// let mut verifier = botan::Verifier::new_ed25519(&pubkey, Ed25519Variant::Prehashed).unwrap();
+    /// verifier.update(b"message").unwrap();
+    /// assert!(verifier.finish(&sig).unwrap());
+    /// ```
+    pub fn new_ed25519(key: &'a Privkey, variant: Ed25519Variant) -> Result<Signer<'a>> {
+        Signer::new(key, ed25519_padding(variant))
+    }
+
+    fn create(key: &'a Privkey, padding: CString) -> Result<Signer<'a>> {
         let obj = botan_init!(
             botan_pk_op_sign_create,
             key.handle(),
@@ -34,20 +208,110 @@ impl Signer {
             0u32
         )?;
         let sig_len = botan_usize!(botan_pk_op_sign_output_length, obj)?;
-        Ok(Signer { obj, sig_len })
+        Ok(Signer {
+            obj,
+            sig_len,
+            key,
+            padding,
+            in_progress: false,
+            oneshot: None,
+        })
     }
 
     /// Add more bytes of the message that will be signed
     pub fn update(&mut self, data: &[u8]) -> Result<()> {
+        self.in_progress = true;
         botan_call!(botan_pk_op_sign_update, self.obj, data.as_ptr(), data.len())
     }
 
     /// Complete and return the signature
+    ///
+    /// The signer is then reset, ready to sign another message.
     pub fn finish(&mut self, rng: &mut RandomNumberGenerator) -> Result<Vec<u8>> {
+        self.in_progress = false;
         call_botan_ffi_returning_vec_u8(self.sig_len, &|out_buf, out_len| unsafe {
             botan_pk_op_sign_finish(self.obj, rng.handle(), out_buf, out_len)
         })
     }
+
+    /// Sign a complete message
+    ///
+    /// This does not affect a message which is being passed to `update`;
+    /// if one is in progress, a second signing operation is created
+    /// (once) and used for this message instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let key = botan::Privkey::create("Ed25519", "", &mut rng).unwrap();
+    /// let mut signer = botan::Signer::new(&key, "Pure").unwrap();
+    /// signer.update(b"streamed ").unwrap();
+    /// let sig = signer.sign_message(b"other message", &mut rng).unwrap();
+    /// signer.update(b"message").unwrap();
+    /// let streamed_sig = signer.finish(&mut rng).unwrap();
+    ///
+    /// let pubkey = key.pubkey().unwrap();
+    /// assert!(pubkey.verify(b"other message", &sig, "Pure").unwrap());
+    /// assert!(pubkey.verify(b"streamed message", &streamed_sig, "Pure").unwrap());
+    /// ```
+    pub fn sign_message(
+        &mut self,
+        message: &[u8],
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<Vec<u8>> {
+        if !self.in_progress {
+            self.update(message)?;
+            return self.finish(rng);
+        }
+
+        if self.oneshot.is_none() {
+            let signer = Signer::create(self.key, self.padding.clone())?;
+            self.oneshot = Some(Box::new(signer));
+        }
+
+        self.oneshot
+            .as_mut()
+            .expect("one shot signer was created")
+            .sign_message(message, rng)
+    }
+
+    /// Return the maximum length of a signature
+    ///
+    /// Some signatures (for example DER encoded ECDSA) may be shorter.
+    #[must_use]
+    pub fn max_output_length(&self) -> usize {
+        self.sig_len
+    }
+
+    /// Complete the signature, writing it to `out`
+    ///
+    /// Returns the length of the signature. `out` must be at least
+    /// `max_output_length()` bytes long.
+    pub fn finish_into(
+        &mut self,
+        rng: &mut RandomNumberGenerator,
+        out: &mut [u8],
+    ) -> Result<usize> {
+        // Checked before finishing, since the message is consumed either way
+        if out.len() < self.sig_len {
+            return Err(Error::with_message(
+                ErrorType::InsufficientBufferSpace,
+                "Output buffer is smaller than the maximum signature length".to_owned(),
+            ));
+        }
+
+        self.in_progress = false;
+        let mut out_len = out.len();
+        botan_call!(
+            botan_pk_op_sign_finish,
+            self.obj,
+            rng.handle(),
+            out.as_mut_ptr(),
+            &mut out_len
+        )?;
+        Ok(out_len)
+    }
 }
 
 #[derive(Debug)]
@@ -58,6 +322,8 @@ pub struct Decryptor {
 
 botan_impl_drop!(Decryptor, botan_pk_op_decrypt_destroy);
 
+unsafe impl Send for Decryptor {}
+
 impl Decryptor {
     /// Create a new decryption object
     pub fn new(key: &Privkey, padding: &str) -> Result<Decryptor> {
@@ -71,20 +337,60 @@ impl Decryptor {
         Ok(Decryptor { obj })
     }
 
+    /// Create a decryptor which applies the RSA private key operation
+    /// without removing any padding
+    ///
+    /// This is textbook RSA; see `Signer::raw_unsafe`. Leading zero bytes
+    /// of the result are removed.
+    ///
+    /// # Errors
+    /// Fails if `key` is not an RSA key
+    pub fn raw_unsafe(key: &Privkey) -> Result<Decryptor> {
+        check_raw_rsa(key.algo_name()?)?;
+        Decryptor::new(key, "Raw")
+    }
+
     /// Decrypt a message
     pub fn decrypt(&mut self, ctext: &[u8]) -> Result<Vec<u8>> {
-        let mut ptext_len = 0;
+        let ptext_len = self.max_output_length(ctext.len())?;
 
+        call_botan_ffi_returning_vec_u8(ptext_len, &|out_buf, out_len| unsafe {
+            botan_pk_op_decrypt(self.obj, out_buf, out_len, ctext.as_ptr(), ctext.len())
+        })
+    }
+
+    /// Decrypt a message, returning the plaintext as a `SecretVec`
+    pub fn decrypt_secret(&mut self, ctext: &[u8]) -> Result<SecretVec> {
+        self.decrypt(ctext).map(SecretVec::new)
+    }
+
+    /// Return the maximum length of the plaintext of a ciphertext of
+    /// the specified length
+    pub fn max_output_length(&self, ctext_len: usize) -> Result<usize> {
+        let mut ptext_len = 0;
         botan_call!(
             botan_pk_op_decrypt_output_length,
             self.obj,
-            ctext.len(),
+            ctext_len,
             &mut ptext_len
         )?;
+        Ok(ptext_len)
+    }
 
-        call_botan_ffi_returning_vec_u8(ptext_len, &|out_buf, out_len| unsafe {
-            botan_pk_op_decrypt(self.obj, out_buf, out_len, ctext.as_ptr(), ctext.len())
-        })
+    /// Decrypt a message, writing the plaintext to `out`
+    ///
+    /// Returns the length of the plaintext.
+    pub fn decrypt_into(&mut self, ctext: &[u8], out: &mut [u8]) -> Result<usize> {
+        let mut out_len = out.len();
+        botan_call!(
+            botan_pk_op_decrypt,
+            self.obj,
+            out.as_mut_ptr(),
+            &mut out_len,
+            ctext.as_ptr(),
+            ctext.len()
+        )?;
+        Ok(out_len)
     }
 }
 
@@ -96,10 +402,30 @@ pub struct Verifier {
 
 botan_impl_drop!(Verifier, botan_pk_op_verify_destroy);
 
+unsafe impl Send for Verifier {}
+
 impl Verifier {
     /// Create a new verifier object
     pub fn new(key: &Pubkey, padding: &str) -> Result<Verifier> {
-        let padding = make_cstr(padding)?;
+        Verifier::create(key, make_cstr(padding)?)
+    }
+
+    /// Create a verifier for signatures made with `Signer::raw_unsafe`
+    ///
+    /// # Errors
+    /// Fails if `key` is not an RSA key
+    pub fn raw_unsafe(key: &Pubkey) -> Result<Verifier> {
+        check_raw_rsa(key.algo_name()?)?;
+        Verifier::new(key, "Raw")
+    }
+
+    /// Create a verifier for one of the RFC 8032 variants of Ed25519,
+    /// with an empty context string
+    pub fn new_ed25519(key: &Pubkey, variant: Ed25519Variant) -> Result<Verifier> {
+        Verifier::new(key, ed25519_padding(variant))
+    }
+
+    fn create(key: &Pubkey, padding: CString) -> Result<Verifier> {
         let obj = botan_init!(
             botan_pk_op_verify_create,
             key.handle(),
@@ -120,6 +446,9 @@ impl Verifier {
     }
 
     /// Verify the provided signature and return true if valid
+    ///
+    /// Afterwards the verifier is reset, and can be used to verify
+    /// another message with the same key.
     pub fn finish(&mut self, signature: &[u8]) -> Result<bool> {
         match unsafe { botan_pk_op_verify_finish(self.obj, signature.as_ptr(), signature.len()) } {
             0 => Ok(true),
@@ -127,6 +456,157 @@ impl Verifier {
             e => Err(Error::from_rc(e)),
         }
     }
+
+    /// Verify a detached signature over everything read from `reader`
+    ///
+    /// The message is passed to `update` in chunks, so a large download
+    /// can be checked without holding it in memory. If reading fails the
+    /// error is returned and the verifier is reset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let key = botan::Privkey::create("ECDSA", "secp256r1", &mut rng).unwrap();
+    /// let sig = key.sign(&[7u8; 100000], "EMSA1(SHA-256)", &mut rng).unwrap();
+    ///
+    /// let pubkey = key.pubkey().unwrap();
+    /// let mut verifier = botan::Verifier::new(&pubkey, "EMSA1(SHA-256)").unwrap();
+    /// let download = std::io::Cursor::new(vec![7u8; 100000]);
+    /// assert!(verifier.verify_reader(download, &sig).unwrap());
+    /// ```
+    #[cfg(not(feature = "no-std"))]
+    pub fn verify_reader<R: std::io::Read>(&mut self, reader: R, signature: &[u8]) -> Result<bool> {
+        if let Err(e) = read_chunks(reader, |chunk| self.update(chunk)) {
+            // Checking any signature discards the partial message
+            let _ = self.finish(&[]);
+            return Err(e);
+        }
+        self.finish(signature)
+    }
+}
+
+/// A signature verifier which can be shared between threads
+///
+/// Creating a `Verifier` involves some setup work for each key, and a
+/// `Verifier` can only check one message at a time. A `SharedVerifier`
+/// owns a public key and keeps the verifiers it has created for reuse,
+/// creating another only when all existing ones are busy in other
+/// threads.
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::Privkey::create("Ed25519", "", &mut rng).unwrap();
+/// let sig = key.sign(b"message", "Pure", &mut rng).unwrap();
+///
+/// let verifier = botan::SharedVerifier::new(key.pubkey().unwrap(), "Pure").unwrap();
+/// assert!(verifier.verify(b"message", &sig).unwrap());
+/// assert!(!verifier.verify(b"forgery", &sig).unwrap());
+/// ```
+#[cfg(not(feature = "no-std"))]
+#[derive(Debug)]
+pub struct SharedVerifier {
+    // Declared before key so that the verifiers are destroyed first
+    idle: std::sync::Mutex<Vec<Verifier>>,
+    key: Pubkey,
+    padding: String,
+}
+
+#[cfg(not(feature = "no-std"))]
+impl SharedVerifier {
+    /// Create a verifier for signatures made by `key` with `padding`
+    pub fn new(key: Pubkey, padding: &str) -> Result<SharedVerifier> {
+        // Create the first verifier now, so that an invalid padding is
+        // reported here rather than by verify
+        let verifier = Verifier::new(&key, padding)?;
+        Ok(SharedVerifier {
+            idle: std::sync::Mutex::new(vec![verifier]),
+            key,
+            padding: padding.to_owned(),
+        })
+    }
+
+    /// Return the public key signatures are verified with
+    #[must_use]
+    pub fn key(&self) -> &Pubkey {
+        &self.key
+    }
+
+    fn take(&self) -> Option<Verifier> {
+        match self.idle.lock() {
+            Ok(mut idle) => idle.pop(),
+            Err(poisoned) => poisoned.into_inner().pop(),
+        }
+    }
+
+    fn put(&self, verifier: Verifier) {
+        match self.idle.lock() {
+            Ok(mut idle) => idle.push(verifier),
+            Err(poisoned) => poisoned.into_inner().push(verifier),
+        }
+    }
+
+    /// Verify the signature of a message, returning true if valid
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<bool> {
+        let mut verifier = match self.take() {
+            Some(v) => v,
+            None => Verifier::new(&self.key, &self.padding)?,
+        };
+
+        verifier.update(message)?;
+        let valid = verifier.finish(signature)?;
+
+        // Only reused if every call succeeded, so its state is known
+        // to have been reset
+        self.put(verifier);
+        Ok(valid)
+    }
+}
+
+/// Sign everything read from `reader`
+///
+/// The message is processed in chunks, so it never needs to be held in
+/// memory all at once. Note that some schemes, such as Ed25519 with
+/// `"Pure"` padding, buffer the entire message internally; use a
+/// prehashed variant (for example `"Ed25519ph"`) for very large inputs.
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::Privkey::create("ECDSA", "secp256r1", &mut rng).unwrap();
+/// let file = std::io::Cursor::new(vec![0u8; 100000]);
+/// let sig = botan::sign_reader(&key, "EMSA1(SHA-256)", file, &mut rng).unwrap();
+/// let file = std::io::Cursor::new(vec![0u8; 100000]);
+/// let pubkey = key.pubkey().unwrap();
+/// assert!(botan::verify_reader(&pubkey, "EMSA1(SHA-256)", file, &sig).unwrap());
+/// ```
+#[cfg(not(feature = "no-std"))]
+pub fn sign_reader<R: std::io::Read>(
+    key: &Privkey,
+    padding: &str,
+    reader: R,
+    rng: &mut RandomNumberGenerator,
+) -> Result<Vec<u8>> {
+    let mut signer = Signer::new(key, padding)?;
+    read_chunks(reader, |chunk| signer.update(chunk))?;
+    signer.finish(rng)
+}
+
+/// Verify a signature over everything read from `reader`
+///
+/// Returns true if the signature is valid. The message is processed in
+/// chunks as with `sign_reader`.
+#[cfg(not(feature = "no-std"))]
+pub fn verify_reader<R: std::io::Read>(
+    key: &Pubkey,
+    padding: &str,
+    reader: R,
+    signature: &[u8],
+) -> Result<bool> {
+    Verifier::new(key, padding)?.verify_reader(reader, signature)
 }
 
 #[derive(Debug)]
@@ -147,6 +627,8 @@ pub struct Encryptor {
 
 botan_impl_drop!(Encryptor, botan_pk_op_encrypt_destroy);
 
+unsafe impl Send for Encryptor {}
+
 impl Encryptor {
     /// Create a new public key encryptor object
     pub fn new(key: &Pubkey, padding: &str) -> Result<Encryptor> {
@@ -160,15 +642,35 @@ impl Encryptor {
         Ok(Encryptor { obj })
     }
 
+    /// Create an encryptor which applies the RSA public key operation
+    /// without any padding
+    ///
+    /// This is textbook RSA, which is deterministic and malleable; see
+    /// `Signer::raw_unsafe`. The message must be smaller than the modulus.
+    ///
+    /// # Errors
+    /// Fails if `key` is not an RSA key
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let rsa = botan::Privkey::create("RSA", "1024", &mut rng).unwrap();
+    /// let mut enc = botan::Encryptor::raw_unsafe(&rsa.pubkey().unwrap()).unwrap();
+    /// let ctext = enc.encrypt(&[1, 2, 3], &mut rng).unwrap();
+    /// assert_eq!(ctext.len(), 128);
+    ///
+    /// let mut dec = botan::Decryptor::raw_unsafe(&rsa).unwrap();
+    /// assert_eq!(dec.decrypt(&ctext).unwrap(), vec![1, 2, 3]);
+    /// ```
+    pub fn raw_unsafe(key: &Pubkey) -> Result<Encryptor> {
+        check_raw_rsa(key.algo_name()?)?;
+        Encryptor::new(key, "Raw")
+    }
+
     /// Encrypt a message using the provided public key
     pub fn encrypt(&mut self, ptext: &[u8], rng: &mut RandomNumberGenerator) -> Result<Vec<u8>> {
-        let mut ctext_len = 0;
-        botan_call!(
-            botan_pk_op_encrypt_output_length,
-            self.obj,
-            ptext.len(),
-            &mut ctext_len
-        )?;
+        let ctext_len = self.max_output_length(ptext.len())?;
 
         call_botan_ffi_returning_vec_u8(ctext_len, &|out_buf, out_len| unsafe {
             botan_pk_op_encrypt(
@@ -181,16 +683,91 @@ impl Encryptor {
             )
         })
     }
+
+    /// Return the maximum length of the ciphertext of a message of
+    /// the specified length
+    pub fn max_output_length(&self, ptext_len: usize) -> Result<usize> {
+        let mut ctext_len = 0;
+        botan_call!(
+            botan_pk_op_encrypt_output_length,
+            self.obj,
+            ptext_len,
+            &mut ctext_len
+        )?;
+        Ok(ctext_len)
+    }
+
+    /// Encrypt a message, writing the ciphertext to `out`
+    ///
+    /// Returns the length of the ciphertext.
+    pub fn encrypt_into(
+        &mut self,
+        ptext: &[u8],
+        rng: &mut RandomNumberGenerator,
+        out: &mut [u8],
+    ) -> Result<usize> {
+        let mut out_len = out.len();
+        botan_call!(
+            botan_pk_op_encrypt,
+            self.obj,
+            rng.handle(),
+            out.as_mut_ptr(),
+            &mut out_len,
+            ptext.as_ptr(),
+            ptext.len()
+        )?;
+        Ok(out_len)
+    }
 }
 
 #[derive(Debug)]
 /// An object that performs key agreement
+///
+/// A key agreement object holds no per-message state, so a single
+/// object can be used to agree keys with any number of peers in turn.
+/// `agree_into` avoids allocating a new vector for each shared secret.
+///
+/// The underlying operation uses internal scratch space, so it is
+/// `Send` but not `Sync`; a server using many threads should create one
+/// `KeyAgreement` per thread.
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::Privkey::create("ECDH", "secp256r1", &mut rng).unwrap();
+/// let mut ka = botan::KeyAgreement::new(&key, "HKDF(SHA-256)").unwrap();
+///
+/// let mut secret = [0u8; 32];
+/// for _ in 0..3 {
+///     let peer = botan::Privkey::create("ECDH", "secp256r1", &mut rng).unwrap();
+///     let peer_public = peer.key_agreement_key().unwrap();
+///     ka.agree_into(&peer_public, b"salt", &mut secret).unwrap();
+/// }
+/// ```
 pub struct KeyAgreement {
     obj: botan_pk_op_ka_t,
+    ec: bool,
+    peer_point_format: PeerPointFormat,
+}
+
+/// Which encodings of the peer's public point a `KeyAgreement` accepts
+///
+/// This only applies to ECDH; other algorithms have a single encoding.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PeerPointFormat {
+    /// Accept both compressed and uncompressed points (the default)
+    Any,
+    /// Accept only compressed points
+    Compressed,
+    /// Accept only uncompressed points
+    Uncompressed,
 }
 
 botan_impl_drop!(KeyAgreement, botan_pk_op_key_agreement_destroy);
 
+unsafe impl Send for KeyAgreement {}
+
 impl KeyAgreement {
     /// Create a new key agreement operator
     pub fn new(key: &Privkey, kdf: &str) -> Result<KeyAgreement> {
@@ -201,7 +778,71 @@ impl KeyAgreement {
             kdf.as_ptr(),
             0u32
         )?;
-        Ok(KeyAgreement { obj })
+        Ok(KeyAgreement {
+            obj,
+            ec: key.algo_name()? == "ECDH",
+            peer_point_format: PeerPointFormat::Any,
+        })
+    }
+
+    /// Create a new key agreement operator which returns the raw shared
+    /// secret, without applying a KDF
+    ///
+    /// For ECDH this is the x coordinate of the shared point, as used by
+    /// some protocols which apply their own key derivation. It is not
+    /// uniformly random, and should not be used directly as a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let key = botan::Privkey::create("ECDH", "secp384r1", &mut rng).unwrap();
+    /// let peer = botan::Privkey::create("ECDH", "secp384r1", &mut rng).unwrap();
+    /// let mut ka = botan::KeyAgreement::new_raw(&key).unwrap();
+    /// let z = ka.agree(0, &peer.key_agreement_key().unwrap(), &[]).unwrap();
+    /// assert_eq!(z.len(), 48);
+    /// ```
+    pub fn new_raw(key: &Privkey) -> Result<KeyAgreement> {
+        KeyAgreement::new(key, "Raw")
+    }
+
+    /// Restrict the encodings of the peer's public point which are accepted
+    ///
+    /// Points in any other encoding are rejected with
+    /// `ErrorType::BadParameter`. This has no effect for algorithms
+    /// other than ECDH.
+    pub fn set_peer_point_format(&mut self, format: PeerPointFormat) {
+        self.peer_point_format = format;
+    }
+
+    /// Return the accepted encodings of the peer's public point
+    #[must_use]
+    pub fn peer_point_format(&self) -> PeerPointFormat {
+        self.peer_point_format
+    }
+
+    fn check_peer_point(&self, counterparty_key: &[u8]) -> Result<()> {
+        if !self.ec {
+            return Ok(());
+        }
+
+        let accepted = match (self.peer_point_format, counterparty_key.first()) {
+            (PeerPointFormat::Any, _) => true,
+            (PeerPointFormat::Compressed, Some(&tag)) => tag == 0x02 || tag == 0x03,
+            (PeerPointFormat::Uncompressed, Some(&tag)) => tag == 0x04,
+            (_, None) => false,
+        };
+
+        if !accepted {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                format!(
+                    "Peer point is not in the accepted format ({:?})",
+                    self.peer_point_format
+                ),
+            ));
+        }
+        Ok(())
     }
 
     /// Perform key agreement operation
@@ -211,10 +852,11 @@ impl KeyAgreement {
         counterparty_key: &[u8],
         salt: &[u8],
     ) -> Result<Vec<u8>> {
+        self.check_peer_point(counterparty_key)?;
         let mut ka_len = requested_output;
 
         if ka_len == 0 {
-            ka_len = botan_usize!(botan_pk_op_key_agreement_size, self.obj)?;
+            ka_len = self.max_output_length()?;
         }
 
         call_botan_ffi_returning_vec_u8(ka_len, &|out_buf, out_len| unsafe {
@@ -229,4 +871,221 @@ impl KeyAgreement {
             )
         })
     }
+
+    /// Perform key agreement, returning the output as a `SecretVec`
+    pub fn agree_secret(
+        &mut self,
+        requested_output: usize,
+        counterparty_key: &[u8],
+        salt: &[u8],
+    ) -> Result<SecretVec> {
+        self.agree(requested_output, counterparty_key, salt)
+            .map(SecretVec::new)
+    }
+
+    /// Return the length of the raw shared secret
+    ///
+    /// This is the output length when no KDF is used.
+    pub fn max_output_length(&self) -> Result<usize> {
+        botan_usize!(botan_pk_op_key_agreement_size, self.obj)
+    }
+
+    /// Perform key agreement, writing the output to `out`
+    ///
+    /// When a KDF is used, `out.len()` bytes are requested from it.
+    /// Returns the length of the output.
+    pub fn agree_into(
+        &mut self,
+        counterparty_key: &[u8],
+        salt: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize> {
+        self.check_peer_point(counterparty_key)?;
+        let mut out_len = out.len();
+        botan_call!(
+            botan_pk_op_key_agreement,
+            self.obj,
+            out.as_mut_ptr(),
+            &mut out_len,
+            counterparty_key.as_ptr(),
+            counterparty_key.len(),
+            salt.as_ptr(),
+            salt.len()
+        )?;
+        Ok(out_len)
+    }
+}
+
+#[cfg(feature = "botan3")]
+#[derive(Debug)]
+/// An object that creates shared keys encapsulated to a KEM public key
+///
+/// This works the same way for any key encapsulation mechanism the
+/// library supports, such as RSA-KEM, ML-KEM, FrodoKEM and Classic
+/// McEliece. The shared key is passed through the given KDF, along with
+/// a salt; with the KDF `"Raw"` the mechanism's own shared secret is
+/// returned, and the desired length is ignored.
+///
+/// This requires Botan 3.x
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::Privkey::create("ML-KEM", "ML-KEM-768", &mut rng).unwrap();
+///
+/// let mut enc = botan::KemEncryptor::new(&key.pubkey().unwrap(), "HKDF(SHA-256)").unwrap();
+/// let (shared_key, encapsulated) = enc.create_shared_key(&mut rng, b"salt", 32).unwrap();
+///
+/// let mut dec = botan::KemDecryptor::new(&key, "HKDF(SHA-256)").unwrap();
+/// assert_eq!(dec.decrypt_shared_key(b"salt", &encapsulated, 32).unwrap(), shared_key);
+/// ```
+pub struct KemEncryptor {
+    obj: botan_pk_op_kem_encrypt_t,
+}
+
+#[cfg(feature = "botan3")]
+botan_impl_drop!(KemEncryptor, botan_pk_op_kem_encrypt_destroy);
+
+#[cfg(feature = "botan3")]
+unsafe impl Send for KemEncryptor {}
+
+#[cfg(feature = "botan3")]
+impl KemEncryptor {
+    /// Create a new KEM encryptor, using `kdf` to derive the shared key
+    pub fn new(key: &Pubkey, kdf: &str) -> Result<KemEncryptor> {
+        let kdf = make_cstr(kdf)?;
+        let obj = botan_init!(botan_pk_op_kem_encrypt_create, key.handle(), kdf.as_ptr())?;
+        Ok(KemEncryptor { obj })
+    }
+
+    /// Return the length of the shared key produced when `desired_len`
+    /// bytes are requested
+    pub fn shared_key_length(&self, desired_len: usize) -> Result<usize> {
+        let mut len = 0;
+        botan_call!(
+            botan_pk_op_kem_encrypt_shared_key_length,
+            self.obj,
+            desired_len,
+            &mut len
+        )?;
+        Ok(len)
+    }
+
+    /// Return the length of the encapsulated key
+    pub fn encapsulated_key_length(&self) -> Result<usize> {
+        botan_usize!(botan_pk_op_kem_encrypt_encapsulated_key_length, self.obj)
+    }
+
+    /// Create a new shared key of `desired_len` bytes
+    ///
+    /// Returns the shared key and the encapsulated key to send to the
+    /// holder of the private key.
+    pub fn create_shared_key(
+        &mut self,
+        rng: &mut RandomNumberGenerator,
+        salt: &[u8],
+        desired_len: usize,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let mut shared_key = vec![0; self.shared_key_length(desired_len)?];
+        let mut shared_key_len = shared_key.len();
+        let mut encapsulated = vec![0; self.encapsulated_key_length()?];
+        let mut encapsulated_len = encapsulated.len();
+
+        botan_call!(
+            botan_pk_op_kem_encrypt_create_shared_key,
+            self.obj,
+            rng.handle(),
+            salt.as_ptr(),
+            salt.len(),
+            desired_len,
+            shared_key.as_mut_ptr(),
+            &mut shared_key_len,
+            encapsulated.as_mut_ptr(),
+            &mut encapsulated_len
+        )?;
+
+        shared_key.truncate(shared_key_len);
+        encapsulated.truncate(encapsulated_len);
+        Ok((shared_key, encapsulated))
+    }
+}
+
+#[cfg(feature = "botan3")]
+#[derive(Debug)]
+/// An object that recovers shared keys created by a `KemEncryptor`
+///
+/// The KDF, salt and desired length must match those used to create
+/// the shared key.
+///
+/// This requires Botan 3.x
+pub struct KemDecryptor {
+    obj: botan_pk_op_kem_decrypt_t,
+}
+
+#[cfg(feature = "botan3")]
+botan_impl_drop!(KemDecryptor, botan_pk_op_kem_decrypt_destroy);
+
+#[cfg(feature = "botan3")]
+unsafe impl Send for KemDecryptor {}
+
+#[cfg(feature = "botan3")]
+impl KemDecryptor {
+    /// Create a new KEM decryptor, using `kdf` to derive the shared key
+    pub fn new(key: &Privkey, kdf: &str) -> Result<KemDecryptor> {
+        let kdf = make_cstr(kdf)?;
+        let obj = botan_init!(botan_pk_op_kem_decrypt_create, key.handle(), kdf.as_ptr())?;
+        Ok(KemDecryptor { obj })
+    }
+
+    /// Return the length of the shared key produced when `desired_len`
+    /// bytes are requested
+    pub fn shared_key_length(&self, desired_len: usize) -> Result<usize> {
+        let mut len = 0;
+        botan_call!(
+            botan_pk_op_kem_decrypt_shared_key_length,
+            self.obj,
+            desired_len,
+            &mut len
+        )?;
+        Ok(len)
+    }
+
+    /// Recover the shared key from an encapsulated key
+    pub fn decrypt_shared_key(
+        &mut self,
+        salt: &[u8],
+        encapsulated: &[u8],
+        desired_len: usize,
+    ) -> Result<Vec<u8>> {
+        let mut shared_key = vec![0; self.shared_key_length(desired_len)?];
+        let mut shared_key_len = shared_key.len();
+
+        botan_call!(
+            botan_pk_op_kem_decrypt_shared_key,
+            self.obj,
+            salt.as_ptr(),
+            salt.len(),
+            encapsulated.as_ptr(),
+            encapsulated.len(),
+            desired_len,
+            shared_key.as_mut_ptr(),
+            &mut shared_key_len
+        )?;
+
+        shared_key.truncate(shared_key_len);
+        Ok(shared_key)
+    }
+
+    /// Recover the shared key from an encapsulated key, returning it as
+    /// a `SecretVec`
+    pub fn decrypt_shared_key_secret(
+        &mut self,
+        salt: &[u8],
+        encapsulated: &[u8],
+        desired_len: usize,
+    ) -> Result<SecretVec> {
+        self.decrypt_shared_key(salt, encapsulated, desired_len)
+            .map(SecretVec::new)
+    }
 }
@@ -0,0 +1,207 @@
+use crate::utils::*;
+
+use crate::block::BlockCipher;
+use crate::cipher::{Cipher, CipherDirection};
+use crate::hash::HashFunction;
+use crate::mac::MsgAuthCode;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Mutex, MutexGuard};
+
+/// An object which can be kept in a `Pool`
+pub trait Poolable: Sized {
+    /// The key identifying which objects are interchangeable, usually
+    /// the algorithm name
+    type Key: Clone + Eq + Hash;
+
+    /// Create a new object
+    fn create(key: &Self::Key) -> Result<Self>;
+
+    /// Reset the object before it is returned to the pool
+    ///
+    /// Any keys or message state must be cleared, so that nothing
+    /// leaks between users of the pool.
+    fn reset(&mut self) -> Result<()>;
+}
+
+impl Poolable for HashFunction {
+    type Key = String;
+
+    fn create(key: &String) -> Result<HashFunction> {
+        HashFunction::new(key)
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.clear()
+    }
+}
+
+impl Poolable for MsgAuthCode {
+    type Key = String;
+
+    fn create(key: &String) -> Result<MsgAuthCode> {
+        MsgAuthCode::new(key)
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.clear()
+    }
+}
+
+impl Poolable for BlockCipher {
+    type Key = String;
+
+    fn create(key: &String) -> Result<BlockCipher> {
+        BlockCipher::new(key)
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.clear()
+    }
+}
+
+impl Poolable for Cipher {
+    type Key = (String, CipherDirection);
+
+    fn create(key: &(String, CipherDirection)) -> Result<Cipher> {
+        Cipher::new(&key.0, key.1)
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.clear()
+    }
+}
+
+/// A pool of reusable objects, keyed by algorithm name
+///
+/// Creating a new hash, MAC or cipher object is fairly expensive
+/// compared to processing a short message. A pool keeps objects which
+/// are no longer in use so that later requests can reuse them.
+///
+/// Objects are reset when returned to the pool, so MACs and ciphers
+/// obtained from a pool never have a key set.
+///
+/// # Examples
+///
+/// ```
+/// let pool = botan::Pool::<botan::HashFunction>::new(8);
+/// let mut hash = pool.get("SHA-256").unwrap();
+/// hash.update(b"abc").unwrap();
+/// let digest = hash.finish().unwrap();
+/// assert_eq!(digest.len(), 32);
+/// ```
+#[derive(Debug)]
+pub struct Pool<T: Poolable> {
+    max_idle: usize,
+    idle: Mutex<HashMap<T::Key, Vec<T>>>,
+}
+
+impl<T: Poolable> Pool<T> {
+    /// Create a new pool which keeps up to `max_idle` unused objects
+    /// for each key
+    pub fn new(max_idle: usize) -> Pool<T> {
+        Pool {
+            max_idle,
+            idle: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<T::Key, Vec<T>>> {
+        // The map is consistent even if another thread panicked
+        match self.idle.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Take an object from the pool, creating a new one if none is idle
+    ///
+    /// The object is returned to the pool when the `Pooled` is dropped.
+    pub fn get<K: Into<T::Key>>(&self, key: K) -> Result<Pooled<'_, T>> {
+        let key = key.into();
+        let cached = self.lock().get_mut(&key).and_then(|objs| objs.pop());
+
+        let obj = match cached {
+            Some(obj) => obj,
+            None => T::create(&key)?,
+        };
+
+        Ok(Pooled {
+            pool: self,
+            key,
+            obj: Some(obj),
+        })
+    }
+
+    /// Return the number of idle objects held for `key`
+    #[must_use]
+    pub fn idle_count<K: Into<T::Key>>(&self, key: K) -> usize {
+        self.lock().get(&key.into()).map_or(0, |objs| objs.len())
+    }
+
+    /// Drop all idle objects
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn put(&self, key: T::Key, mut obj: T) {
+        if obj.reset().is_err() {
+            return;
+        }
+
+        let mut idle = self.lock();
+        let objs = idle.entry(key).or_default();
+        if objs.len() < self.max_idle {
+            objs.push(obj);
+        }
+    }
+}
+
+/// An object borrowed from a `Pool`
+///
+/// Dereferences to the underlying object, which is reset and returned
+/// to the pool when this is dropped.
+#[derive(Debug)]
+pub struct Pooled<'a, T: Poolable> {
+    pool: &'a Pool<T>,
+    key: T::Key,
+    obj: Option<T>,
+}
+
+impl<'a, T: Poolable> Pooled<'a, T> {
+    /// Remove the object from the pool's control
+    ///
+    /// It will not be reset or returned to the pool.
+    pub fn detach(mut self) -> T {
+        self.obj
+            .take()
+            .expect("Pooled object is present until dropped")
+    }
+}
+
+impl<'a, T: Poolable> core::ops::Deref for Pooled<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.obj
+            .as_ref()
+            .expect("Pooled object is present until dropped")
+    }
+}
+
+impl<'a, T: Poolable> core::ops::DerefMut for Pooled<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.obj
+            .as_mut()
+            .expect("Pooled object is present until dropped")
+    }
+}
+
+impl<'a, T: Poolable> Drop for Pooled<'a, T> {
+    fn drop(&mut self) {
+        if let Some(obj) = self.obj.take() {
+            self.pool.put(self.key.clone(), obj);
+        }
+    }
+}
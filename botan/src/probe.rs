@@ -0,0 +1,422 @@
+use crate::block::BlockCipher;
+use crate::cipher::{Cipher, CipherDirection};
+use crate::hash::HashFunction;
+use crate::kdf::kdf;
+use crate::mac::MsgAuthCode;
+use crate::pbkdf::derive_key_from_password_timed;
+use crate::pk_ops::{KeyAgreement, Signer};
+use crate::pubkey::Privkey;
+use crate::rng::RandomNumberGenerator;
+
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+/// The kinds of algorithm whose availability can be probed
+pub enum AlgorithmKind {
+    /// Hash functions, as used with `HashFunction`
+    Hash,
+    /// Message authentication codes, as used with `MsgAuthCode`
+    Mac,
+    /// Block ciphers, as used with `BlockCipher`
+    BlockCipher,
+    /// Cipher modes and stream ciphers, as used with `Cipher`
+    Cipher,
+    /// Key derivation functions, as used with `kdf`
+    Kdf,
+    /// Password hashes, as used with `derive_key_from_password`
+    PasswordHash,
+    /// Public key signature algorithms, as used with `Signer`
+    Signature,
+    /// Public key agreement algorithms, as used with `KeyAgreement`
+    KeyAgreement,
+}
+
+const HASHES: &[&str] = &[
+    "SHA-1",
+    "SHA-224",
+    "SHA-256",
+    "SHA-384",
+    "SHA-512",
+    "SHA-512-256",
+    "SHA-3(224)",
+    "SHA-3(256)",
+    "SHA-3(384)",
+    "SHA-3(512)",
+    "SHAKE-128(256)",
+    "SHAKE-256(512)",
+    "Keccak-1600",
+    "BLAKE2b",
+    "BLAKE2s",
+    "RIPEMD-160",
+    "Whirlpool",
+    "Skein-512",
+    "Streebog-256",
+    "Streebog-512",
+    "SM3",
+    "GOST-34.11",
+    "Tiger",
+    "MD5",
+    "MD4",
+    "Adler32",
+    "CRC24",
+    "CRC32",
+];
+
+const MACS: &[&str] = &[
+    "HMAC(SHA-256)",
+    "HMAC(SHA-384)",
+    "HMAC(SHA-512)",
+    "CMAC(AES-128)",
+    "CMAC(AES-256)",
+    "GMAC(AES-128)",
+    "GMAC(AES-256)",
+    "KMAC-128(256)",
+    "KMAC-256(512)",
+    "BLAKE2b(256)",
+    "Poly1305",
+    "SipHash(2,4)",
+    "X9.19-MAC",
+];
+
+const BLOCK_CIPHERS: &[&str] = &[
+    "AES-128",
+    "AES-192",
+    "AES-256",
+    "ARIA-128",
+    "ARIA-256",
+    "Blowfish",
+    "Camellia-128",
+    "Camellia-256",
+    "CAST-128",
+    "DES",
+    "TripleDES",
+    "GOST-28147-89",
+    "IDEA",
+    "Noekeon",
+    "SEED",
+    "Serpent",
+    "SHACAL2",
+    "SM4",
+    "Threefish-512",
+    "Twofish",
+];
+
+const CIPHERS: &[&str] = &[
+    "AES-128/GCM",
+    "AES-256/GCM",
+    "AES-128/OCB",
+    "AES-256/OCB",
+    "AES-128/EAX",
+    "AES-256/EAX",
+    "AES-128/CCM",
+    "AES-256/CCM",
+    "AES-128/SIV",
+    "AES-256/SIV",
+    "AES-128/CBC/PKCS7",
+    "AES-256/CBC/PKCS7",
+    "AES-128/CFB",
+    "AES-256/CFB",
+    "AES-256/XTS",
+    "ChaCha20Poly1305",
+    "CTR(AES-128)",
+    "CTR(AES-256)",
+    "ChaCha(20)",
+    "Salsa20",
+];
+
+const KDFS: &[&str] = &[
+    "HKDF(SHA-256)",
+    "HKDF(SHA-512)",
+    "HKDF-Extract(SHA-256)",
+    "HKDF-Expand(SHA-256)",
+    "KDF2(SHA-256)",
+    "KDF1-18033(SHA-256)",
+    "SP800-56A(SHA-256)",
+    "SP800-108-Counter(HMAC(SHA-256))",
+    "SP800-108-Feedback(HMAC(SHA-256))",
+    "SP800-108-Pipeline(HMAC(SHA-256))",
+    "TLS-12-PRF(SHA-256)",
+];
+
+const PASSWORD_HASHES: &[&str] = &[
+    "PBKDF2(SHA-256)",
+    "PBKDF2(SHA-512)",
+    "Scrypt",
+    "Argon2d",
+    "Argon2i",
+    "Argon2id",
+    "Bcrypt-PBKDF",
+    "OpenPGP-S2K(SHA-256)",
+];
+
+const SIGNATURES: &[&str] = &[
+    "RSA",
+    "ECDSA",
+    "Ed25519",
+    "Ed448",
+    "DSA",
+    "ECGDSA",
+    "ECKCDSA",
+    "SM2",
+    "GOST-34.10",
+    "ML-DSA",
+    "Dilithium",
+    "SPHINCS+",
+];
+
+const KEY_AGREEMENTS: &[&str] = &["X25519", "X448", "ECDH", "DH"];
+
+// Parameters used to create a key when probing a public key algorithm,
+// chosen to make key generation as quick as possible
+fn probe_params(name: &str) -> &'static str {
+    match name {
+        "RSA" => "1024",
+        "DSA" => "dsa/botan/2048",
+        "DH" => "modp/ietf/2048",
+        "ECDSA" | "ECGDSA" | "ECKCDSA" | "ECDH" => "secp256r1",
+        "SM2" => "sm2p256v1",
+        "GOST-34.10" => "gost_256A",
+        "ML-DSA" => "ML-DSA-4x4",
+        "Dilithium" => "Dilithium-4x4-r3",
+        "SPHINCS+" => "SphincsPlus-sha2-128f-r3.1",
+        _ => "",
+    }
+}
+
+// Check that a key can be created and used for the operations of
+// `kind`, so that (say) X25519 is not reported as a signature scheme
+fn supports_operation(kind: AlgorithmKind, name: &str) -> bool {
+    let key = match RandomNumberGenerator::new_system()
+        .and_then(|mut rng| Privkey::create(name, probe_params(name), &mut rng))
+    {
+        Ok(key) => key,
+        Err(_) => return false,
+    };
+
+    match kind {
+        // Not every algorithm accepts an empty padding, but all that
+        // take a padding accept "Raw"
+        AlgorithmKind::Signature => ["", "Raw"]
+            .iter()
+            .any(|padding| Signer::new(&key, padding).is_ok()),
+        AlgorithmKind::KeyAgreement => KeyAgreement::new(&key, "Raw").is_ok(),
+        _ => false,
+    }
+}
+
+// Results of probing, kept since probing a public key algorithm
+// generates a key
+#[cfg(not(feature = "no-std"))]
+mod cache {
+    use super::AlgorithmKind;
+
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicPtr, Ordering};
+    use std::sync::RwLock;
+
+    type Table = RwLock<HashMap<(AlgorithmKind, String), bool>>;
+
+    // Installed on first use and never freed
+    static TABLE: AtomicPtr<Table> = AtomicPtr::new(core::ptr::null_mut());
+
+    fn table() -> &'static Table {
+        let mut table = TABLE.load(Ordering::Acquire);
+
+        if table.is_null() {
+            let new_table = Box::into_raw(Box::new(RwLock::new(HashMap::new())));
+            table = match TABLE.compare_exchange(
+                core::ptr::null_mut(),
+                new_table,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => new_table,
+                Err(existing) => {
+                    // Another thread installed a table first
+                    drop(unsafe { Box::from_raw(new_table) });
+                    existing
+                }
+            };
+        }
+
+        unsafe { &*table }
+    }
+
+    pub(super) fn get(kind: AlgorithmKind, name: &str) -> Option<bool> {
+        let table = match table().read() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        table.get(&(kind, name.to_owned())).cloned()
+    }
+
+    pub(super) fn insert(kind: AlgorithmKind, name: &str, available: bool) {
+        let mut table = match table().write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        table.insert((kind, name.to_owned()), available);
+    }
+}
+
+impl AlgorithmKind {
+    fn candidates(self) -> &'static [&'static str] {
+        match self {
+            AlgorithmKind::Hash => HASHES,
+            AlgorithmKind::Mac => MACS,
+            AlgorithmKind::BlockCipher => BLOCK_CIPHERS,
+            AlgorithmKind::Cipher => CIPHERS,
+            AlgorithmKind::Kdf => KDFS,
+            AlgorithmKind::PasswordHash => PASSWORD_HASHES,
+            AlgorithmKind::Signature => SIGNATURES,
+            AlgorithmKind::KeyAgreement => KEY_AGREEMENTS,
+        }
+    }
+}
+
+/// Return true if the linked library supports the named algorithm
+///
+/// The library is probed by creating (and then discarding) an object of
+/// the given kind; for password hashes this runs the hash for about a
+/// millisecond. For public key algorithms a key is generated, using
+/// small parameters where the algorithm has them (such as 1024 bit RSA),
+/// and checked to support the operation, so `X25519` is not available
+/// as a `Signature`. The result is kept, so later calls with the same
+/// arguments are fast (except in no-std builds).
+///
+/// # Examples
+///
+/// ```
+/// assert!(botan::is_available(botan::AlgorithmKind::Hash, "SHA-256"));
+/// assert!(!botan::is_available(botan::AlgorithmKind::Hash, "BunnyHash9000"));
+/// ```
+#[must_use]
+pub fn is_available(kind: AlgorithmKind, name: &str) -> bool {
+    #[cfg(not(feature = "no-std"))]
+    {
+        if let Some(available) = cache::get(kind, name) {
+            return available;
+        }
+
+        let available = probe(kind, name);
+        cache::insert(kind, name, available);
+        available
+    }
+
+    #[cfg(feature = "no-std")]
+    {
+        probe(kind, name)
+    }
+}
+
+fn probe(kind: AlgorithmKind, name: &str) -> bool {
+    match kind {
+        AlgorithmKind::Hash => HashFunction::new(name).is_ok(),
+        AlgorithmKind::Mac => MsgAuthCode::new(name).is_ok(),
+        AlgorithmKind::BlockCipher => BlockCipher::new(name).is_ok(),
+        AlgorithmKind::Cipher => Cipher::new(name, CipherDirection::Encrypt).is_ok(),
+        AlgorithmKind::Kdf => kdf(name, 16, &[0; 16], &[], &[]).is_ok(),
+        AlgorithmKind::PasswordHash => {
+            derive_key_from_password_timed(name, 16, "", &[0; 16], 1).is_ok()
+        }
+        AlgorithmKind::Signature | AlgorithmKind::KeyAgreement => supports_operation(kind, name),
+    }
+}
+
+/// List the commonly used algorithms of a kind which the linked library
+/// supports
+///
+/// The FFI provides no way of enumerating algorithms, so this checks a
+/// fixed list of well known names with `is_available`. Algorithms not
+/// on that list (or other parameterizations, such as `HMAC(SHA-3(256))`)
+/// may still be supported.
+///
+/// # Examples
+///
+/// ```
+/// let hashes: Vec<&str> = botan::list_algorithms(botan::AlgorithmKind::Hash).collect();
+/// assert!(hashes.contains(&"SHA-256"));
+/// ```
+pub fn list_algorithms(kind: AlgorithmKind) -> impl Iterator<Item = &'static str> {
+    kind.candidates()
+        .iter()
+        .cloned()
+        .filter(move |name| is_available(kind, name))
+}
+
+/// List the supported hash functions
+///
+/// The same as `list_algorithms(AlgorithmKind::Hash)`.
+///
+/// # Examples
+///
+/// ```
+/// assert!(botan::hash_algorithms().any(|h| h == "SHA-256"));
+/// ```
+pub fn hash_algorithms() -> impl Iterator<Item = &'static str> {
+    list_algorithms(AlgorithmKind::Hash)
+}
+
+/// List the supported message authentication codes
+///
+/// The same as `list_algorithms(AlgorithmKind::Mac)`.
+pub fn mac_algorithms() -> impl Iterator<Item = &'static str> {
+    list_algorithms(AlgorithmKind::Mac)
+}
+
+/// List the supported block ciphers
+///
+/// The same as `list_algorithms(AlgorithmKind::BlockCipher)`.
+pub fn block_ciphers() -> impl Iterator<Item = &'static str> {
+    list_algorithms(AlgorithmKind::BlockCipher)
+}
+
+/// List the supported cipher modes and stream ciphers
+///
+/// The same as `list_algorithms(AlgorithmKind::Cipher)`.
+///
+/// # Examples
+///
+/// ```
+/// for mode in botan::cipher_modes() {
+///     assert!(botan::Cipher::new(mode, botan::CipherDirection::Encrypt).is_ok());
+/// }
+/// ```
+pub fn cipher_modes() -> impl Iterator<Item = &'static str> {
+    list_algorithms(AlgorithmKind::Cipher)
+}
+
+/// List the supported key derivation functions
+///
+/// The same as `list_algorithms(AlgorithmKind::Kdf)`.
+pub fn kdf_algorithms() -> impl Iterator<Item = &'static str> {
+    list_algorithms(AlgorithmKind::Kdf)
+}
+
+/// List the supported password hashes
+///
+/// The same as `list_algorithms(AlgorithmKind::PasswordHash)`.
+pub fn password_hashes() -> impl Iterator<Item = &'static str> {
+    list_algorithms(AlgorithmKind::PasswordHash)
+}
+
+/// List the supported public key signature algorithms
+///
+/// The same as `list_algorithms(AlgorithmKind::Signature)`. The first
+/// call generates a key of each algorithm, so it takes some time (mostly
+/// for RSA).
+///
+/// # Examples
+///
+/// ```
+/// let schemes: Vec<&str> = botan::signature_schemes().collect();
+/// assert!(schemes.contains(&"Ed25519"));
+/// ```
+pub fn signature_schemes() -> impl Iterator<Item = &'static str> {
+    list_algorithms(AlgorithmKind::Signature)
+}
+
+/// List the supported public key agreement algorithms
+///
+/// The same as `list_algorithms(AlgorithmKind::KeyAgreement)`.
+pub fn key_agreement_algorithms() -> impl Iterator<Item = &'static str> {
+    list_algorithms(AlgorithmKind::KeyAgreement)
+}
@@ -1,6 +1,9 @@
 use crate::utils::*;
 use botan_sys::*;
 
+use crate::cipher::{Cipher, CipherDirection};
+use crate::hpke::{Hpke, HpkeAead, HpkeKem};
+use crate::memutils::SecretVec;
 use crate::mp::MPI;
 use crate::pk_ops::*;
 use crate::rng::RandomNumberGenerator;
@@ -13,7 +16,11 @@ pub struct Pubkey {
 
 botan_impl_drop!(Pubkey, botan_pubkey_destroy);
 
-#[derive(Debug)]
+// Keys are immutable once created; operations using them create their
+// own objects
+unsafe impl Send for Pubkey {}
+unsafe impl Sync for Pubkey {}
+
 /// A private key object
 pub struct Privkey {
     obj: botan_privkey_t,
@@ -21,6 +28,135 @@ pub struct Privkey {
 
 botan_impl_drop!(Privkey, botan_privkey_destroy);
 
+impl core::fmt::Debug for Privkey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let algo_name = self.algo_name();
+        let mut d = f.debug_struct("Privkey");
+        match &algo_name {
+            Ok(name) => d.field("algo_name", name),
+            Err(_) => d.field("algo_name", &format_args!("<unknown>")),
+        };
+        d.field("key", &format_args!("[REDACTED]")).finish()
+    }
+}
+
+unsafe impl Send for Privkey {}
+unsafe impl Sync for Privkey {}
+
+// Botan zeroizes private key material when the key object is destroyed
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for Privkey {}
+
+/// How thoroughly `Privkey::check` and `Pubkey::check` validate a key
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Strength {
+    /// Cheap structural checks, such as values being in range
+    Basic,
+    /// Also run the expensive checks, such as primality tests of RSA
+    /// and DSA parameters and pairwise consistency of private keys
+    Strong,
+}
+
+impl Strength {
+    fn flags(self) -> u32 {
+        match self {
+            Strength::Basic => 0,
+            Strength::Strong => 1,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Strength::Basic => "basic",
+            Strength::Strong => "strong",
+        }
+    }
+}
+
+/// The encoding produced by `Privkey::export` and `Pubkey::export`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// Binary DER
+    Der,
+    /// PEM, the base64 encoding of the DER with a header and footer
+    Pem,
+}
+
+// Used by der_encode_encrypted and Encryption::password
+const DEFAULT_PBE_CIPHER: &str = "AES-256/CBC";
+const DEFAULT_PBE_HASH: &str = "SHA-512";
+const DEFAULT_PBE_ITERATIONS: usize = 150_000;
+
+/// How `Privkey::export` protects the exported key
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Encryption<'a> {
+    /// Unencrypted PKCS #8
+    None,
+    /// PKCS #8 encrypted with a key derived from a passphrase
+    Password {
+        /// The passphrase
+        passphrase: &'a str,
+        /// The cipher to encrypt with, such as `AES-256/CBC`
+        cipher: &'a str,
+        /// The hash used with PBKDF2, such as `SHA-512`
+        pbkdf: &'a str,
+        /// The number of PBKDF2 iterations
+        iterations: usize,
+    },
+}
+
+impl core::fmt::Debug for Encryption<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Encryption::None => f.write_str("None"),
+            Encryption::Password {
+                cipher,
+                pbkdf,
+                iterations,
+                ..
+            } => f
+                .debug_struct("Password")
+                .field("passphrase", &format_args!("[REDACTED]"))
+                .field("cipher", cipher)
+                .field("pbkdf", pbkdf)
+                .field("iterations", iterations)
+                .finish(),
+        }
+    }
+}
+
+impl<'a> Encryption<'a> {
+    /// Encrypt with the passphrase, using AES-256/CBC and 150000
+    /// iterations of PBKDF2 with SHA-512
+    #[must_use]
+    pub fn password(passphrase: &'a str) -> Encryption<'a> {
+        Encryption::Password {
+            passphrase,
+            cipher: DEFAULT_PBE_CIPHER,
+            pbkdf: DEFAULT_PBE_HASH,
+            iterations: DEFAULT_PBE_ITERATIONS,
+        }
+    }
+}
+
+fn key_check_result(rc: c_int, what: &str, algo: &str, strength: Strength) -> Result<()> {
+    if rc == 0 {
+        Ok(())
+    } else if rc == BOTAN_FFI_ERROR_INVALID_INPUT {
+        Err(Error::with_message(
+            ErrorType::InvalidInput,
+            format!(
+                "{} {} failed {} key validation",
+                algo,
+                what,
+                strength.describe()
+            ),
+        ))
+    } else {
+        Err(Error::from_rc(rc))
+    }
+}
+
 impl Privkey {
     pub(crate) fn handle(&self) -> botan_privkey_t {
         self.obj
@@ -29,6 +165,10 @@ impl Privkey {
     /// Create a new private key
     ///
     pub fn create(alg: &str, params: &str, rng: &mut RandomNumberGenerator) -> Result<Privkey> {
+        botan_span!("privkey_create", algo = alg, params = params);
+        if let Some(capability) = crate::capability::Capability::for_algorithm(alg) {
+            capability.require()?;
+        }
         let obj = botan_init!(
             botan_privkey_create,
             make_cstr(alg)?.as_ptr(),
@@ -39,6 +179,28 @@ impl Privkey {
         Ok(Privkey { obj })
     }
 
+    /// Create a new elliptic curve private key (ECDSA, ECDH, ...) over the
+    /// provided group
+    ///
+    /// This requires Botan 3.8 or later
+    #[cfg(feature = "botan3")]
+    pub fn create_ec(
+        alg: &str,
+        group: &crate::ec::EcGroup,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<Privkey> {
+        crate::capability::Capability::EcGroup.require()?;
+        botan_span!("privkey_create", algo = alg, params = "EC group");
+        let obj = botan_init!(
+            botan_ec_privkey_create,
+            make_cstr(alg)?.as_ptr(),
+            group.handle(),
+            rng.handle()
+        )?;
+
+        Ok(Privkey { obj })
+    }
+
     /// Load an RSA private key (p,q,e)
     ///
     /// # Examples
@@ -93,6 +255,60 @@ impl Privkey {
         Ok(Privkey { obj })
     }
 
+    /// Create a new DSA private key, generating new parameters with a
+    /// `pbits` bit prime p and `qbits` bit subgroup order q
+    ///
+    /// Generating parameters is slow; `create("DSA", "dsa/botan/2048", rng)`
+    /// uses a named group instead.
+    pub fn create_dsa(
+        pbits: usize,
+        qbits: usize,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<Privkey> {
+        botan_span!("privkey_create", algo = "DSA", pbits, qbits);
+        let obj = botan_init!(botan_privkey_create_dsa, rng.handle(), pbits, qbits)?;
+        Ok(Privkey { obj })
+    }
+
+    /// Load a DSA private key (p,q,g,x)
+    pub fn load_dsa(p: &MPI, q: &MPI, g: &MPI, x: &MPI) -> Result<Privkey> {
+        let obj = botan_init!(
+            botan_privkey_load_dsa,
+            p.handle(),
+            q.handle(),
+            g.handle(),
+            x.handle()
+        )?;
+        Ok(Privkey { obj })
+    }
+
+    /// Create a new ElGamal private key, generating a new `pbits` bit
+    /// prime p whose multiplicative group has a `qbits` bit prime order
+    /// subgroup
+    ///
+    /// Generating parameters is slow; `create("ElGamal", "modp/ietf/2048", rng)`
+    /// uses a named group instead.
+    pub fn create_elgamal(
+        pbits: usize,
+        qbits: usize,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<Privkey> {
+        botan_span!("privkey_create", algo = "ElGamal", pbits, qbits);
+        let obj = botan_init!(botan_privkey_create_elgamal, rng.handle(), pbits, qbits)?;
+        Ok(Privkey { obj })
+    }
+
+    /// Load an ElGamal private key (p,g,x)
+    pub fn load_elgamal(p: &MPI, g: &MPI, x: &MPI) -> Result<Privkey> {
+        let obj = botan_init!(
+            botan_privkey_load_elgamal,
+            p.handle(),
+            g.handle(),
+            x.handle()
+        )?;
+        Ok(Privkey { obj })
+    }
+
     /// Load an ECDSA private key with specified curve and secret scalar
     pub fn load_ecdsa(s: &MPI, curve_name: &str) -> Result<Privkey> {
         let curve_name = make_cstr(curve_name)?;
@@ -107,6 +323,40 @@ impl Privkey {
         Ok(Privkey { obj })
     }
 
+    /// Load a FrodoKEM private key in the given mode, such as
+    /// `FrodoKEM-640-SHAKE` or `eFrodoKEM-976-AES`
+    ///
+    /// This requires Botan 3.6 or later
+    #[cfg(feature = "botan3")]
+    pub fn load_frodokem(key: &[u8], mode: &str) -> Result<Privkey> {
+        crate::capability::Capability::FrodoKem.require()?;
+        let mode = make_cstr(mode)?;
+        let obj = botan_init!(
+            botan_privkey_load_frodokem,
+            key.as_ptr(),
+            key.len(),
+            mode.as_ptr()
+        )?;
+        Ok(Privkey { obj })
+    }
+
+    /// Load a Classic McEliece private key with the given parameters,
+    /// such as `348864f` or `6960119pcf`
+    ///
+    /// This requires Botan 3.6 or later
+    #[cfg(feature = "botan3")]
+    pub fn load_classic_mceliece(key: &[u8], params: &str) -> Result<Privkey> {
+        crate::capability::Capability::ClassicMcEliece.require()?;
+        let params = make_cstr(params)?;
+        let obj = botan_init!(
+            botan_privkey_load_classic_mceliece,
+            key.as_ptr(),
+            key.len(),
+            params.as_ptr()
+        )?;
+        Ok(Privkey { obj })
+    }
+
     /// Load DER bytes as an unencrypted PKCS#8 private key
     pub fn load_der(der: &[u8]) -> Result<Privkey> {
         let obj = botan_init!(
@@ -161,6 +411,94 @@ impl Privkey {
         Ok(Privkey { obj })
     }
 
+    /// Load an unencrypted PKCS#8 private key from a file
+    ///
+    /// The file may be either PEM or DER; the format is detected from
+    /// its contents.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let key = botan::Privkey::create("Ed25519", "", &mut rng).unwrap();
+    /// let path = std::env::temp_dir().join("botan-doc-privkey.pem");
+    /// key.save_pem_file(&path).unwrap();
+    /// let loaded = botan::Privkey::load_file(&path).unwrap();
+    /// assert_eq!(loaded.get_ed25519_key().unwrap(), key.get_ed25519_key().unwrap());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    #[cfg(not(feature = "no-std"))]
+    pub fn load_file<P: AsRef<std::path::Path>>(path: P) -> Result<Privkey> {
+        let data = SecretVec::new(std::fs::read(path)?);
+        Privkey::load_der(&data)
+    }
+
+    /// Load an encrypted PKCS#8 private key from a file
+    ///
+    /// The file may be either PEM or DER; the format is detected from
+    /// its contents.
+    #[cfg(not(feature = "no-std"))]
+    pub fn load_encrypted_file<P: AsRef<std::path::Path>>(
+        path: P,
+        passphrase: &str,
+    ) -> Result<Privkey> {
+        let data = SecretVec::new(std::fs::read(path)?);
+        Privkey::load_encrypted_der(&data, passphrase)
+    }
+
+    /// Write the private key to a file as unencrypted PEM
+    ///
+    /// An existing file is replaced atomically. The file is only ever
+    /// accessible by its owner: it has mode 0600 on Unix, and an ACL
+    /// granting access only to the owner on Windows.
+    #[cfg(not(feature = "no-std"))]
+    pub fn save_pem_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<()> {
+        write_file(path.as_ref(), self.pem_encode()?.as_bytes(), true)
+    }
+
+    /// Write the private key to a file as unencrypted DER
+    ///
+    /// An existing file is replaced atomically. The file is only ever
+    /// accessible by its owner: it has mode 0600 on Unix, and an ACL
+    /// granting access only to the owner on Windows.
+    #[cfg(not(feature = "no-std"))]
+    pub fn save_der_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<()> {
+        write_file(path.as_ref(), &self.der_encode()?, true)
+    }
+
+    /// Write the private key to a file as PEM, encrypted with a passphrase
+    ///
+    /// The same encryption as `pem_encode_encrypted` is used. An existing
+    /// file is replaced atomically. The file is only ever accessible by
+    /// its owner: it has mode 0600 on Unix, and an ACL granting access
+    /// only to the owner on Windows.
+    #[cfg(not(feature = "no-std"))]
+    pub fn save_encrypted_pem_file<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        passphrase: &str,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<()> {
+        let pem = self.pem_encode_encrypted(passphrase, rng)?;
+        write_file(path.as_ref(), pem.as_bytes(), true)
+    }
+
+    /// Write the private key to a file as DER, encrypted with a passphrase
+    ///
+    /// The same encryption as `der_encode_encrypted` is used. An existing
+    /// file is replaced atomically. The file is only ever accessible by
+    /// its owner: it has mode 0600 on Unix, and an ACL granting access
+    /// only to the owner on Windows.
+    #[cfg(not(feature = "no-std"))]
+    pub fn save_encrypted_der_file<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        passphrase: &str,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<()> {
+        let der = self.der_encode_encrypted(passphrase, rng)?;
+        write_file(path.as_ref(), &der, true)
+    }
+
     /// Check if the key seems to be valid
     pub fn check_key(&self, rng: &mut RandomNumberGenerator) -> Result<bool> {
         let flags = 1u32;
@@ -175,6 +513,48 @@ impl Privkey {
         }
     }
 
+    /// Return true if using this key changes it, as is the case for
+    /// the hash based signature schemes HSS/LMS and XMSS
+    ///
+    /// After each use the key must be saved again, or some one time
+    /// keys will be reused, which breaks the security of the scheme.
+    ///
+    /// This requires Botan 3.8 or later
+    #[cfg(feature = "botan3")]
+    pub fn is_stateful(&self) -> Result<bool> {
+        crate::capability::Capability::StatefulKeys.require()?;
+        let mut stateful = 0;
+        botan_call!(botan_privkey_stateful_operation, self.obj, &mut stateful)?;
+        Ok(stateful != 0)
+    }
+
+    /// Return how many more times a stateful key can be used
+    ///
+    /// Fails if the key is not stateful.
+    ///
+    /// This requires Botan 3.8 or later
+    #[cfg(feature = "botan3")]
+    pub fn remaining_operations(&self) -> Result<u64> {
+        crate::capability::Capability::StatefulKeys.require()?;
+        let mut remaining = 0;
+        botan_call!(botan_privkey_remaining_operations, self.obj, &mut remaining)?;
+        Ok(remaining)
+    }
+
+    /// Validate the key, failing with an `InvalidInput` error naming the
+    /// algorithm and check if it is malformed or weak
+    ///
+    /// # Examples
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let key = botan::Privkey::create("ECDSA", "secp256r1", &mut rng).unwrap();
+    /// assert!(key.check(&mut rng, botan::Strength::Strong).is_ok());
+    /// ```
+    pub fn check(&self, rng: &mut RandomNumberGenerator, strength: Strength) -> Result<()> {
+        let rc = unsafe { botan_privkey_check_key(self.obj, rng.handle(), strength.flags()) };
+        key_check_result(rc, "private key", &self.algo_name()?, strength)
+    }
+
     /// Return the public key associated with this private key
     pub fn pubkey(&self) -> Result<Pubkey> {
         let obj = botan_init!(botan_privkey_export_pubkey, self.obj)?;
@@ -197,18 +577,57 @@ impl Privkey {
         })
     }
 
+    /// DER encode the key (unencrypted), returning it as a `SecretVec`
+    pub fn der_encode_secret(&self) -> Result<SecretVec> {
+        self.der_encode().map(SecretVec::new)
+    }
+
+    /// Return the raw encoding of the key, as defined by its algorithm
+    ///
+    /// This is the format expected by (for example) `load_frodokem`.
+    /// The key is read in a single pass, which matters for algorithms
+    /// such as Classic McEliece whose keys are very large.
+    ///
+    /// This requires Botan 3.6 or later
+    #[cfg(feature = "botan3")]
+    pub fn raw_bytes(&self) -> Result<Vec<u8>> {
+        crate::capability::Capability::RawKeyEncoding.require()?;
+        let mut out = Vec::new();
+        call_botan_ffi_viewing(
+            &mut |data| {
+                out.extend_from_slice(data);
+                Ok(())
+            },
+            &|ctx, view| unsafe { botan_privkey_view_raw(self.obj, ctx, view) },
+        )?;
+        Ok(out)
+    }
+
+    /// Write the DER encoding of the key (as unencrypted PKCS#8) to `out`
+    ///
+    /// The encoding is streamed to the writer rather than first being
+    /// collected into a buffer of guessed size.
+    ///
+    /// This requires Botan 3.x
+    #[cfg(all(feature = "botan3", not(feature = "no-std")))]
+    pub fn write_der<W: std::io::Write>(&self, out: &mut W) -> Result<()> {
+        call_botan_ffi_viewing(
+            &mut |data| out.write_all(data).map_err(Error::from),
+            &|ctx, view| unsafe { botan_privkey_view_der(self.obj, ctx, view) },
+        )
+    }
+
     /// DER encode the key (encrypted)
     pub fn der_encode_encrypted(
         &self,
         passphrase: &str,
         rng: &mut RandomNumberGenerator,
     ) -> Result<Vec<u8>> {
-        let iterations = 150_000;
         self.der_encode_encrypted_with_options(
             passphrase,
-            "AES-256/CBC",
-            "SHA-512",
-            iterations,
+            DEFAULT_PBE_CIPHER,
+            DEFAULT_PBE_HASH,
+            DEFAULT_PBE_ITERATIONS,
             rng,
         )
     }
@@ -248,12 +667,11 @@ impl Privkey {
         passphrase: &str,
         rng: &mut RandomNumberGenerator,
     ) -> Result<String> {
-        let iterations = 150_000;
         self.pem_encode_encrypted_with_options(
             passphrase,
-            "AES-256/CBC",
-            "SHA-512",
-            iterations,
+            DEFAULT_PBE_CIPHER,
+            DEFAULT_PBE_HASH,
+            DEFAULT_PBE_ITERATIONS,
             rng,
         )
     }
@@ -296,6 +714,49 @@ impl Privkey {
         })
     }
 
+    /// Export the key in the given format, optionally encrypted
+    ///
+    /// This covers all of `der_encode`, `pem_encode` and the
+    /// `*_encode_encrypted*` functions. A PEM encoding is returned as
+    /// its (ASCII) bytes. Encryption uses a fresh system RNG.
+    ///
+    /// # Examples
+    /// ```
+    /// use botan::{Encryption, Format};
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let key = botan::Privkey::create("Ed25519", "", &mut rng).unwrap();
+    /// let der = key.export(Format::Der, Encryption::password("hunter2")).unwrap();
+    /// let loaded = botan::Privkey::load_encrypted_der(&der, "hunter2").unwrap();
+    /// assert_eq!(loaded.der_encode().unwrap(), key.der_encode().unwrap());
+    /// ```
+    pub fn export(&self, format: Format, encryption: Encryption) -> Result<Vec<u8>> {
+        match encryption {
+            Encryption::None => match format {
+                Format::Der => self.der_encode(),
+                Format::Pem => Ok(self.pem_encode()?.into_bytes()),
+            },
+            Encryption::Password {
+                passphrase,
+                cipher,
+                pbkdf,
+                iterations,
+            } => {
+                let mut rng = RandomNumberGenerator::new_system()?;
+                let encoded = match format {
+                    Format::Der => self.der_encode_encrypted_with_options(
+                        passphrase, cipher, pbkdf, iterations, &mut rng,
+                    )?,
+                    Format::Pem => self
+                        .pem_encode_encrypted_with_options(
+                            passphrase, cipher, pbkdf, iterations, &mut rng,
+                        )?
+                        .into_bytes(),
+                };
+                Ok(encoded)
+            }
+        }
+    }
+
     /// Return the key agrement key, only valid for DH/ECDH
     pub fn key_agreement_key(&self) -> Result<Vec<u8>> {
         let ka_key_len = 512; // fixme
@@ -306,6 +767,20 @@ impl Privkey {
 
     /// Get a value for the private key
     /// The which parameter selects a field which is algorithm specific
+    ///
+    /// Along with the fields of the public key, these include `p`, `q`
+    /// and `d` for RSA, and the private value `x` for DSA, ElGamal, DH
+    /// and the elliptic curve algorithms. Fails with an error if the
+    /// key has no such field.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let key = botan::Privkey::create("RSA", "1024", &mut rng).unwrap();
+    /// let n = key.get_field("n").unwrap();
+    /// assert_eq!(n, &key.get_field("p").unwrap() * &key.get_field("q").unwrap());
+    /// assert!(key.get_field("bogus").is_err());
+    /// ```
     pub fn get_field(&self, which: &str) -> Result<MPI> {
         let which = make_cstr(which)?;
 
@@ -370,6 +845,40 @@ impl Privkey {
     }
 }
 
+/// Load an unencrypted PKCS#8 private key, either DER or PEM
+///
+/// # Examples
+/// ```
+/// use std::convert::TryFrom;
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::Privkey::create("Ed25519", "", &mut rng).unwrap();
+/// let der = key.der_encode().unwrap();
+/// let loaded = botan::Privkey::try_from(&der[..]).unwrap();
+/// ```
+impl<'a> core::convert::TryFrom<&'a [u8]> for Privkey {
+    type Error = Error;
+
+    fn try_from(data: &'a [u8]) -> Result<Privkey> {
+        Privkey::load_der(data)
+    }
+}
+
+/// Load an unencrypted PEM encoded PKCS#8 private key
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::Privkey::create("Ed25519", "", &mut rng).unwrap();
+/// let loaded: botan::Privkey = key.pem_encode().unwrap().parse().unwrap();
+/// ```
+impl core::str::FromStr for Privkey {
+    type Err = Error;
+
+    fn from_str(pem: &str) -> Result<Privkey> {
+        Privkey::load_pem(pem)
+    }
+}
+
 impl Pubkey {
     pub(crate) fn from_handle(obj: botan_pubkey_t) -> Pubkey {
         Pubkey { obj }
@@ -407,6 +916,29 @@ impl Pubkey {
         Ok(Pubkey { obj })
     }
 
+    /// Load a DSA public key (p,q,g,y)
+    pub fn load_dsa(p: &MPI, q: &MPI, g: &MPI, y: &MPI) -> Result<Pubkey> {
+        let obj = botan_init!(
+            botan_pubkey_load_dsa,
+            p.handle(),
+            q.handle(),
+            g.handle(),
+            y.handle()
+        )?;
+        Ok(Pubkey { obj })
+    }
+
+    /// Load an ElGamal public key (p,g,y)
+    pub fn load_elgamal(p: &MPI, g: &MPI, y: &MPI) -> Result<Pubkey> {
+        let obj = botan_init!(
+            botan_pubkey_load_elgamal,
+            p.handle(),
+            g.handle(),
+            y.handle()
+        )?;
+        Ok(Pubkey { obj })
+    }
+
     /// Load an ECDSA public key (x,y) for the specified curve
     pub fn load_ecdsa(pub_x: &MPI, pub_y: &MPI, curve_name: &str) -> Result<Pubkey> {
         let curve_name = make_cstr(curve_name)?;
@@ -431,6 +963,38 @@ impl Pubkey {
         Ok(Pubkey { obj })
     }
 
+    /// Load a FrodoKEM public key in the given mode
+    ///
+    /// This requires Botan 3.6 or later
+    #[cfg(feature = "botan3")]
+    pub fn load_frodokem(key: &[u8], mode: &str) -> Result<Pubkey> {
+        crate::capability::Capability::FrodoKem.require()?;
+        let mode = make_cstr(mode)?;
+        let obj = botan_init!(
+            botan_pubkey_load_frodokem,
+            key.as_ptr(),
+            key.len(),
+            mode.as_ptr()
+        )?;
+        Ok(Pubkey { obj })
+    }
+
+    /// Load a Classic McEliece public key with the given parameters
+    ///
+    /// This requires Botan 3.6 or later
+    #[cfg(feature = "botan3")]
+    pub fn load_classic_mceliece(key: &[u8], params: &str) -> Result<Pubkey> {
+        crate::capability::Capability::ClassicMcEliece.require()?;
+        let params = make_cstr(params)?;
+        let obj = botan_init!(
+            botan_pubkey_load_classic_mceliece,
+            key.as_ptr(),
+            key.len(),
+            params.as_ptr()
+        )?;
+        Ok(Pubkey { obj })
+    }
+
     /// Load an Ed25519 public key
     pub fn load_ed25519(key: &[u8]) -> Result<Pubkey> {
         let obj = botan_init!(botan_pubkey_load_ed25519, key.as_ptr())?;
@@ -448,6 +1012,15 @@ impl Pubkey {
         botan_usize!(botan_pubkey_estimated_strength, self.obj)
     }
 
+    /// Validate the key, failing with an `InvalidInput` error naming the
+    /// algorithm and check if it is malformed or weak
+    ///
+    /// Keys received from untrusted sources should be checked before use.
+    pub fn check(&self, rng: &mut RandomNumberGenerator, strength: Strength) -> Result<()> {
+        let rc = unsafe { botan_pubkey_check_key(self.obj, rng.handle(), strength.flags()) };
+        key_check_result(rc, "public key", &self.algo_name()?, strength)
+    }
+
     /// Check key for problems
     pub fn check_key(&self, rng: &mut RandomNumberGenerator) -> Result<bool> {
         let flags = 1u32;
@@ -479,6 +1052,38 @@ impl Pubkey {
         })
     }
 
+    /// Return the raw encoding of the key, as defined by its algorithm
+    ///
+    /// This is the format expected by (for example) `load_frodokem`, and
+    /// is read in a single pass.
+    ///
+    /// This requires Botan 3.6 or later
+    #[cfg(feature = "botan3")]
+    pub fn raw_bytes(&self) -> Result<Vec<u8>> {
+        crate::capability::Capability::RawKeyEncoding.require()?;
+        let mut out = Vec::new();
+        call_botan_ffi_viewing(
+            &mut |data| {
+                out.extend_from_slice(data);
+                Ok(())
+            },
+            &|ctx, view| unsafe { botan_pubkey_view_raw(self.obj, ctx, view) },
+        )?;
+        Ok(out)
+    }
+
+    /// Write the DER encoding of the key (as X.509 SubjectPublicKeyInfo)
+    /// to `out`
+    ///
+    /// This requires Botan 3.x
+    #[cfg(all(feature = "botan3", not(feature = "no-std")))]
+    pub fn write_der<W: std::io::Write>(&self, out: &mut W) -> Result<()> {
+        call_botan_ffi_viewing(
+            &mut |data| out.write_all(data).map_err(Error::from),
+            &|ctx, view| unsafe { botan_pubkey_view_der(self.obj, ctx, view) },
+        )
+    }
+
     /// PEM encode this public key
     pub fn pem_encode(&self) -> Result<String> {
         let pem_len = 4096; // fixme
@@ -487,6 +1092,41 @@ impl Pubkey {
         })
     }
 
+    /// Export the key in the given format
+    ///
+    /// A PEM encoding is returned as its (ASCII) bytes.
+    pub fn export(&self, format: Format) -> Result<Vec<u8>> {
+        match format {
+            Format::Der => self.der_encode(),
+            Format::Pem => Ok(self.pem_encode()?.into_bytes()),
+        }
+    }
+
+    /// Load a public key from a file
+    ///
+    /// The file may be either PEM or DER; the format is detected from
+    /// its contents.
+    #[cfg(not(feature = "no-std"))]
+    pub fn load_file<P: AsRef<std::path::Path>>(path: P) -> Result<Pubkey> {
+        Pubkey::load_der(&std::fs::read(path)?)
+    }
+
+    /// Write the public key to a file as PEM
+    ///
+    /// An existing file is replaced.
+    #[cfg(not(feature = "no-std"))]
+    pub fn save_pem_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<()> {
+        write_file(path.as_ref(), self.pem_encode()?.as_bytes(), false)
+    }
+
+    /// Write the public key to a file as DER
+    ///
+    /// An existing file is replaced.
+    #[cfg(not(feature = "no-std"))]
+    pub fn save_der_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<()> {
+        write_file(path.as_ref(), &self.der_encode()?, false)
+    }
+
     /// Return the name of the algorithm
     pub fn algo_name(&self) -> Result<String> {
         let name_len = 32;
@@ -497,6 +1137,10 @@ impl Pubkey {
 
     /// Get a value for the public key
     /// The which parameter selects a field which is algorithm specific
+    ///
+    /// These include `n` and `e` for RSA, `p`, `q`, `g` and `y` for DSA,
+    /// and `public_x`, `public_y` and the domain parameters (`p`, `a`,
+    /// `b`, `base_x`, `base_y` and `order`) for elliptic curve keys.
     pub fn get_field(&self, which: &str) -> Result<MPI> {
         let which = make_cstr(which)?;
 
@@ -540,6 +1184,24 @@ impl Pubkey {
     }
 }
 
+/// Load a public key, either DER or PEM
+impl<'a> core::convert::TryFrom<&'a [u8]> for Pubkey {
+    type Error = Error;
+
+    fn try_from(data: &'a [u8]) -> Result<Pubkey> {
+        Pubkey::load_der(data)
+    }
+}
+
+/// Load a PEM encoded public key
+impl core::str::FromStr for Pubkey {
+    type Err = Error;
+
+    fn from_str(pem: &str) -> Result<Pubkey> {
+        Pubkey::load_pem(pem)
+    }
+}
+
 /// Return the identifier used for PKCS1 v1.5 signatures for the specified hash
 pub fn pkcs_hash_id(hash_algo: &str) -> Result<Vec<u8>> {
     let hash_algo = make_cstr(hash_algo)?;
@@ -548,3 +1210,287 @@ pub fn pkcs_hash_id(hash_algo: &str) -> Result<Vec<u8>> {
         botan_pkcs_hash_id(hash_algo.as_ptr(), out_buf, out_len)
     })
 }
+
+const ENVELOPE_VERSION: u8 = 1;
+const ENVELOPE_HPKE: u8 = 1;
+const ENVELOPE_RSA_OAEP: u8 = 2;
+const ENVELOPE_INFO: &[u8] = b"botan-rs envelope v1";
+const ENVELOPE_RSA_PADDING: &str = "OAEP(SHA-256)";
+const ENVELOPE_DEK_LENGTH: usize = 32;
+const ENVELOPE_NONCE_LENGTH: usize = 12;
+
+fn envelope_error(msg: &str) -> Error {
+    Error::with_message(ErrorType::InvalidInput, msg.to_owned())
+}
+
+fn envelope_push(out: &mut Vec<u8>, data: &[u8]) -> Result<()> {
+    if data.len() > 0xFFFF {
+        return Err(envelope_error("Envelope field too long"));
+    }
+    out.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+fn envelope_read_u16(blob: &[u8], offset: &mut usize) -> Result<usize> {
+    if blob.len() < *offset + 2 {
+        return Err(envelope_error("Truncated envelope"));
+    }
+    let v = ((blob[*offset] as usize) << 8) | (blob[*offset + 1] as usize);
+    *offset += 2;
+    Ok(v)
+}
+
+fn envelope_read<'a>(blob: &'a [u8], offset: &mut usize, len: usize) -> Result<&'a [u8]> {
+    if blob.len() < *offset + len {
+        return Err(envelope_error("Truncated envelope"));
+    }
+    let v = &blob[*offset..*offset + len];
+    *offset += len;
+    Ok(v)
+}
+
+/// Encrypt a message to the holder of the private key for `recipient`
+///
+/// The output is self-contained and can be decrypted using [`open`].
+/// For X25519 and ECDH (P-256, P-384, P-521) keys the message is
+/// encrypted with HPKE using AES-256/GCM; for RSA keys a random data
+/// encryption key is wrapped with OAEP and the message is encrypted
+/// using AES-256/GCM.
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let key = botan::Privkey::create("X25519", "", &mut rng).unwrap();
+/// let blob = botan::seal(&key.pubkey().unwrap(), b"secret", &mut rng).unwrap();
+/// assert_eq!(botan::open(&key, &blob).unwrap(), b"secret");
+/// ```
+pub fn seal(recipient: &Pubkey, ptext: &[u8], rng: &mut RandomNumberGenerator) -> Result<Vec<u8>> {
+    let algo = recipient.algo_name()?;
+    let mut out = vec![ENVELOPE_VERSION];
+
+    if algo == "RSA" {
+        let mut dek = rng.read(ENVELOPE_DEK_LENGTH)?;
+        let wrapped = recipient.encrypt(&dek, ENVELOPE_RSA_PADDING, rng)?;
+        out.push(ENVELOPE_RSA_OAEP);
+        envelope_push(&mut out, &wrapped)?;
+
+        let nonce = rng.read(ENVELOPE_NONCE_LENGTH)?;
+        out.extend_from_slice(&nonce);
+
+        let mut cipher = Cipher::new("AES-256/GCM", CipherDirection::Encrypt)?;
+        cipher.set_key(&dek)?;
+        crate::memutils::scrub_mem(&mut dek);
+        cipher.set_associated_data(&out)?;
+        let ctext = cipher.process(&nonce, ptext)?;
+        out.extend_from_slice(&ctext);
+    } else {
+        let kem = HpkeKem::for_key(&algo, &|| recipient.get_field("order"))?;
+        let pk_r = match kem {
+            HpkeKem::DhkemX25519HkdfSha256 => recipient.get_x25519_key()?,
+            _ => kem.encode_point(
+                &recipient.get_field("public_x")?,
+                &recipient.get_field("public_y")?,
+            )?,
+        };
+
+        let hpke = Hpke::new(kem, kem.kdf(), HpkeAead::Aes256Gcm);
+        let (enc, mut ctx) = hpke.setup_base_sender(&pk_r, ENVELOPE_INFO, rng)?;
+
+        out.push(ENVELOPE_HPKE);
+        out.extend_from_slice(&kem.id().to_be_bytes());
+        envelope_push(&mut out, &enc)?;
+
+        let ctext = ctx.seal(&out, ptext)?;
+        out.extend_from_slice(&ctext);
+    }
+
+    Ok(out)
+}
+
+/// Decrypt a message produced by [`seal`]
+pub fn open(key: &Privkey, blob: &[u8]) -> Result<Vec<u8>> {
+    let mut offset = 0;
+    let header = envelope_read(blob, &mut offset, 2)?;
+
+    if header[0] != ENVELOPE_VERSION {
+        return Err(envelope_error("Unknown envelope version"));
+    }
+
+    match header[1] {
+        ENVELOPE_RSA_OAEP => {
+            let wrapped_len = envelope_read_u16(blob, &mut offset)?;
+            let wrapped = envelope_read(blob, &mut offset, wrapped_len)?;
+            let nonce = envelope_read(blob, &mut offset, ENVELOPE_NONCE_LENGTH)?;
+
+            let mut dek = key.decrypt(wrapped, ENVELOPE_RSA_PADDING)?;
+            let mut cipher = Cipher::new("AES-256/GCM", CipherDirection::Decrypt)?;
+            let rc = cipher.set_key(&dek);
+            crate::memutils::scrub_mem(&mut dek);
+            rc?;
+            cipher.set_associated_data(&blob[..offset])?;
+            cipher.process(nonce, &blob[offset..])
+        }
+        ENVELOPE_HPKE => {
+            let kem_id = envelope_read_u16(blob, &mut offset)?;
+            let kem = HpkeKem::from_id(kem_id as u16)
+                .ok_or_else(|| envelope_error("Unknown envelope KEM"))?;
+
+            let algo = key.algo_name()?;
+            if HpkeKem::for_key(&algo, &|| key.get_field("order"))? != kem {
+                return Err(envelope_error(
+                    "Envelope was not encrypted to this key type",
+                ));
+            }
+
+            let enc_len = envelope_read_u16(blob, &mut offset)?;
+            let enc = envelope_read(blob, &mut offset, enc_len)?;
+
+            let hpke = Hpke::new(kem, kem.kdf(), HpkeAead::Aes256Gcm);
+            let mut ctx = hpke.setup_base_receiver(enc, key, ENVELOPE_INFO)?;
+            ctx.open(&blob[..offset], &blob[offset..])
+        }
+        _ => Err(envelope_error("Unknown envelope type")),
+    }
+}
+
+// Human readable formats use PEM; binary formats use DER
+#[cfg(feature = "serde")]
+impl serde::Serialize for Pubkey {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        use serde::ser::Error as _;
+
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.pem_encode().map_err(S::Error::custom)?)
+        } else {
+            serializer.serialize_bytes(&self.der_encode().map_err(S::Error::custom)?)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Pubkey {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Pubkey, D::Error> {
+        struct PubkeyVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for PubkeyVisitor {
+            type Value = Pubkey;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("a PEM or DER encoded public key")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> core::result::Result<Pubkey, E> {
+                Pubkey::load_pem(v).map_err(E::custom)
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> core::result::Result<Pubkey, E> {
+                Pubkey::load_der(v).map_err(E::custom)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(PubkeyVisitor)
+        } else {
+            deserializer.deserialize_bytes(PubkeyVisitor)
+        }
+    }
+}
+
+// Allows Privkey::from_pkcs8_der and related functions from
+// pkcs8::DecodePrivateKey
+#[cfg(feature = "pkcs8")]
+impl<'a> core::convert::TryFrom<pkcs8::PrivateKeyInfo<'a>> for Privkey {
+    type Error = pkcs8::Error;
+
+    fn try_from(info: pkcs8::PrivateKeyInfo<'a>) -> pkcs8::Result<Privkey> {
+        use pkcs8::der::Encode;
+
+        let der = SecretVec::new(info.to_der()?);
+        Privkey::load_der(&der).map_err(|_| pkcs8::Error::KeyMalformed)
+    }
+}
+
+#[cfg(feature = "pkcs8")]
+impl pkcs8::EncodePrivateKey for Privkey {
+    fn to_pkcs8_der(&self) -> pkcs8::Result<pkcs8::SecretDocument> {
+        use core::convert::TryFrom;
+
+        let der = self.der_encode().map_err(|_| pkcs8::Error::KeyMalformed)?;
+        Ok(pkcs8::SecretDocument::try_from(&der[..])?)
+    }
+}
+
+// Allows Pubkey::from_public_key_der and related functions from
+// spki::DecodePublicKey
+#[cfg(feature = "spki")]
+impl<'a> core::convert::TryFrom<spki::SubjectPublicKeyInfoRef<'a>> for Pubkey {
+    type Error = spki::Error;
+
+    fn try_from(info: spki::SubjectPublicKeyInfoRef<'a>) -> spki::Result<Pubkey> {
+        use spki::der::Encode;
+
+        Pubkey::load_der(&info.to_der()?).map_err(|_| spki::Error::KeyMalformed)
+    }
+}
+
+#[cfg(feature = "spki")]
+impl spki::EncodePublicKey for Pubkey {
+    fn to_public_key_der(&self) -> spki::Result<spki::Document> {
+        use core::convert::TryFrom;
+
+        let der = self.der_encode().map_err(|_| spki::Error::KeyMalformed)?;
+        Ok(spki::Document::try_from(der)?)
+    }
+}
+
+// Conversions to and from the `openssl` crate's key types, by way of
+// SubjectPublicKeyInfo and PKCS #8 DER
+#[cfg(feature = "openssl")]
+impl core::convert::TryFrom<&openssl::pkey::PKey<openssl::pkey::Public>> for Pubkey {
+    type Error = Error;
+
+    fn try_from(key: &openssl::pkey::PKey<openssl::pkey::Public>) -> Result<Pubkey> {
+        let der = key.public_key_to_der().map_err(Error::conversion_error)?;
+        Pubkey::load_der(&der)
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl core::convert::TryFrom<&Pubkey> for openssl::pkey::PKey<openssl::pkey::Public> {
+    type Error = Error;
+
+    fn try_from(key: &Pubkey) -> Result<openssl::pkey::PKey<openssl::pkey::Public>> {
+        openssl::pkey::PKey::public_key_from_der(&key.der_encode()?)
+            .map_err(Error::conversion_error)
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl core::convert::TryFrom<&openssl::pkey::PKey<openssl::pkey::Private>> for Privkey {
+    type Error = Error;
+
+    fn try_from(key: &openssl::pkey::PKey<openssl::pkey::Private>) -> Result<Privkey> {
+        let der = SecretVec::new(
+            key.private_key_to_pkcs8()
+                .map_err(Error::conversion_error)?,
+        );
+        Privkey::load_der(&der)
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl core::convert::TryFrom<&Privkey> for openssl::pkey::PKey<openssl::pkey::Private> {
+    type Error = Error;
+
+    fn try_from(key: &Privkey) -> Result<openssl::pkey::PKey<openssl::pkey::Private>> {
+        openssl::pkey::PKey::private_key_from_pkcs8(&key.der_encode()?)
+            .map_err(Error::conversion_error)
+    }
+}
@@ -5,15 +5,25 @@ use botan_sys::*;
 /// A cryptographic random number generator
 pub struct RandomNumberGenerator {
     obj: botan_rng_t,
+    // A TPM RNG keeps the crypto backend RNG of its context alive
+    #[cfg(feature = "tpm2")]
+    _crypto_backend_rng: Option<crate::tpm2::CryptoBackendRng>,
 }
 
 botan_impl_drop!(RandomNumberGenerator, botan_rng_destroy);
 
+// Not Sync, as the userspace RNG is not safe for concurrent use
+unsafe impl Send for RandomNumberGenerator {}
+
 impl RandomNumberGenerator {
     fn new_of_type(typ: &str) -> Result<RandomNumberGenerator> {
         let typ = make_cstr(typ)?;
         let obj = botan_init!(botan_rng_init, typ.as_ptr())?;
-        Ok(RandomNumberGenerator { obj })
+        Ok(RandomNumberGenerator {
+            obj,
+            #[cfg(feature = "tpm2")]
+            _crypto_backend_rng: None,
+        })
     }
 
     pub(crate) fn handle(&self) -> botan_rng_t {
@@ -50,6 +60,31 @@ impl RandomNumberGenerator {
         RandomNumberGenerator::new_userspace()
     }
 
+    /// Create an RNG which reads from a TPM 2.0
+    ///
+    /// If a session is given, the random bytes are encrypted in transit.
+    ///
+    /// This requires Botan 3.x built with TPM2 support, and the `tpm2`
+    /// feature
+    #[cfg(feature = "tpm2")]
+    pub fn new_tpm2(
+        ctx: &crate::tpm2::Tpm2Context,
+        session: Option<&crate::tpm2::Tpm2Session>,
+    ) -> Result<RandomNumberGenerator> {
+        let session = session.map_or(ptr::null_mut(), |s| s.handle());
+        let obj = botan_init!(
+            botan_tpm2_rng_init,
+            ctx.handle(),
+            session,
+            ptr::null_mut(),
+            ptr::null_mut()
+        )?;
+        Ok(RandomNumberGenerator {
+            obj,
+            _crypto_backend_rng: Some(ctx.crypto_backend_rng()),
+        })
+    }
+
     /// Read bytes from an RNG
     ///
     /// # Examples
@@ -115,3 +150,63 @@ impl RandomNumberGenerator {
         botan_call!(botan_rng_add_entropy, self.obj, seed.as_ptr(), seed.len())
     }
 }
+
+/// Uses the system RNG; failures (which should not happen) panic, as
+/// `RngCore` has no way of reporting them
+///
+/// This allows, for example, seeding the generators of the `rand`
+/// ecosystem from Botan with `SeedableRng::from_rng`.
+///
+/// ```
+/// use rand_core::RngCore;
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let mut seed = [0u8; 32];
+/// rng.fill_bytes(&mut seed);
+/// ```
+#[cfg(feature = "rand_core")]
+impl rand_core::RngCore for RandomNumberGenerator {
+    fn next_u32(&mut self) -> u32 {
+        let mut buf = [0u8; 4];
+        self.fill_bytes(&mut buf);
+        u32::from_le_bytes(buf)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut buf = [0u8; 8];
+        self.fill_bytes(&mut buf);
+        u64::from_le_bytes(buf)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.fill(dest).expect("Botan RNG failed");
+    }
+}
+
+#[cfg(feature = "rand_core")]
+impl rand_core::CryptoRng for RandomNumberGenerator {}
+
+// The getrandom custom backend, filling `dest` from the system RNG
+//
+// getrandom (and so everything built on it) calls this when the final
+// binary is built with RUSTFLAGS='--cfg getrandom_backend="custom"';
+// otherwise the operating system's source is used as usual.
+#[cfg(feature = "getrandom")]
+#[no_mangle]
+unsafe extern "Rust" fn __getrandom_v03_custom(
+    dest: *mut u8,
+    len: usize,
+) -> core::result::Result<(), getrandom::Error> {
+    // Botan FFI error codes are negative and small
+    fn to_getrandom_error(e: Error) -> getrandom::Error {
+        let code = e.rc().map_or(0, |rc| rc.unsigned_abs() as u16);
+        getrandom::Error::new_custom(code)
+    }
+
+    if len == 0 {
+        return Ok(());
+    }
+    // dest may be uninitialized, so it is passed on without creating a slice
+    RandomNumberGenerator::new_system()
+        .and_then(|rng| botan_call!(botan_rng_get, rng.handle(), dest, len))
+        .map_err(to_getrandom_error)
+}
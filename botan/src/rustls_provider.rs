@@ -0,0 +1,913 @@
+//! A `rustls` crypto provider backed by Botan
+//!
+//! `default_provider` returns a `rustls::crypto::CryptoProvider` which
+//! uses Botan for the TLS 1.3 cipher suites, key exchange, certificate
+//! signature verification, signing with the local private key, and
+//! random number generation. This allows using `rustls` with a Botan
+//! build which has been restricted to a particular set of algorithms.
+//!
+//! Only TLS 1.3 cipher suites are offered. The individual suites and
+//! key exchange groups are exported, so that a provider can be limited
+//! further (for example to only AES-GCM and the NIST curves).
+//!
+//! Private keys may be PKCS #8, or PKCS #1 for RSA. RSA, ECDSA with
+//! secp256r1 or secp384r1, and Ed25519 keys are supported.
+//!
+//! # Examples
+//!
+//! ```
+//! let provider = botan::rustls_provider::default_provider();
+//! assert_eq!(provider.cipher_suites.len(), 3);
+//!
+//! let builder = rustls::ClientConfig::builder_with_provider(std::sync::Arc::new(provider))
+//!     .with_protocol_versions(&[&rustls::version::TLS13])
+//!     .unwrap();
+//! ```
+
+use crate::utils::*;
+
+use crate::algo::{AeadAlgo, HashAlgo, MacAlgo};
+use crate::cipher::{Cipher, CipherDirection};
+use crate::hash::HashFunction;
+use crate::keys::EcdsaKeyPair;
+use crate::mac::MsgAuthCode;
+use crate::memutils::SecretVec;
+use crate::pubkey::{Privkey, Pubkey};
+use crate::rng::RandomNumberGenerator;
+
+use core::convert::TryFrom;
+use std::sync::Arc;
+
+use rustls::crypto::cipher::{
+    self, AeadKey, InboundOpaqueMessage, InboundPlainMessage, Iv, MessageDecrypter,
+    MessageEncrypter, OutboundOpaqueMessage, OutboundPlainMessage, PrefixedPayload,
+    Tls13AeadAlgorithm, UnsupportedOperationError,
+};
+use rustls::crypto::tls13::HkdfUsingHmac;
+use rustls::crypto::{
+    hash, hmac, ActiveKeyExchange, CipherSuiteCommon, CryptoProvider, GetRandomFailed, KeyProvider,
+    SecureRandom, SharedSecret, SupportedKxGroup, WebPkiSupportedAlgorithms,
+};
+use rustls::pki_types::{
+    alg_id, AlgorithmIdentifier, InvalidSignature, PrivateKeyDer, SignatureVerificationAlgorithm,
+};
+use rustls::sign::{self, SigningKey};
+use rustls::{
+    CipherSuite, ConnectionTrafficSecrets, ContentType, NamedGroup, PeerMisbehaved,
+    ProtocolVersion, SignatureAlgorithm, SignatureScheme, SupportedCipherSuite, Tls13CipherSuite,
+};
+
+// Results returned to rustls, rather than this crate's `Result`
+type TlsResult<T> = core::result::Result<T, rustls::Error>;
+
+fn tls_error(e: Error) -> rustls::Error {
+    rustls::Error::General(e.to_string())
+}
+
+/// Return a `CryptoProvider` using all of the cipher suites, key
+/// exchange groups and signature algorithms in this module
+#[must_use]
+pub fn default_provider() -> CryptoProvider {
+    CryptoProvider {
+        cipher_suites: ALL_CIPHER_SUITES.to_vec(),
+        kx_groups: ALL_KX_GROUPS.to_vec(),
+        signature_verification_algorithms: SUPPORTED_SIG_ALGS,
+        secure_random: &Random,
+        key_provider: &Keys,
+    }
+}
+
+/// The TLS 1.3 cipher suites supported, in order of preference
+pub static ALL_CIPHER_SUITES: &[SupportedCipherSuite] = &[
+    TLS13_AES_256_GCM_SHA384,
+    TLS13_AES_128_GCM_SHA256,
+    TLS13_CHACHA20_POLY1305_SHA256,
+];
+
+/// TLS 1.3 with AES-256/GCM and SHA-384
+pub static TLS13_AES_256_GCM_SHA384: SupportedCipherSuite =
+    SupportedCipherSuite::Tls13(&Tls13CipherSuite {
+        common: CipherSuiteCommon {
+            suite: CipherSuite::TLS13_AES_256_GCM_SHA384,
+            hash_provider: &SHA384,
+            // RFC 8446 section 5.5 allows 2^24.5 records, but like other
+            // providers a lower limit is used which leaves a margin for
+            // records of any length (see the AEAD limits draft)
+            confidentiality_limit: 1 << 23,
+        },
+        hkdf_provider: &HkdfUsingHmac(&HMAC_SHA384),
+        aead_alg: &Aead(AeadAlgo::Aes256Gcm),
+        quic: None,
+    });
+
+/// TLS 1.3 with AES-128/GCM and SHA-256
+pub static TLS13_AES_128_GCM_SHA256: SupportedCipherSuite =
+    SupportedCipherSuite::Tls13(&Tls13CipherSuite {
+        common: CipherSuiteCommon {
+            suite: CipherSuite::TLS13_AES_128_GCM_SHA256,
+            hash_provider: &SHA256,
+            confidentiality_limit: 1 << 23,
+        },
+        hkdf_provider: &HkdfUsingHmac(&HMAC_SHA256),
+        aead_alg: &Aead(AeadAlgo::Aes128Gcm),
+        quic: None,
+    });
+
+/// TLS 1.3 with ChaCha20Poly1305 and SHA-256
+pub static TLS13_CHACHA20_POLY1305_SHA256: SupportedCipherSuite =
+    SupportedCipherSuite::Tls13(&Tls13CipherSuite {
+        common: CipherSuiteCommon {
+            suite: CipherSuite::TLS13_CHACHA20_POLY1305_SHA256,
+            hash_provider: &SHA256,
+            confidentiality_limit: u64::MAX,
+        },
+        hkdf_provider: &HkdfUsingHmac(&HMAC_SHA256),
+        aead_alg: &Aead(AeadAlgo::ChaCha20Poly1305),
+        quic: None,
+    });
+
+/// The key exchange groups supported, in order of preference
+pub static ALL_KX_GROUPS: &[&dyn SupportedKxGroup] = &[X25519, SECP256R1, SECP384R1];
+
+/// Key exchange using X25519
+pub static X25519: &dyn SupportedKxGroup = &KxGroup {
+    name: NamedGroup::X25519,
+    algo: "X25519",
+    params: "",
+    secret_len: 32,
+};
+
+/// Key exchange using ECDH on secp256r1
+pub static SECP256R1: &dyn SupportedKxGroup = &KxGroup {
+    name: NamedGroup::secp256r1,
+    algo: "ECDH",
+    params: "secp256r1",
+    secret_len: 32,
+};
+
+/// Key exchange using ECDH on secp384r1
+pub static SECP384R1: &dyn SupportedKxGroup = &KxGroup {
+    name: NamedGroup::secp384r1,
+    algo: "ECDH",
+    params: "secp384r1",
+    secret_len: 48,
+};
+
+/// The algorithms used to verify signatures in certificates and in
+/// the TLS handshake
+pub static SUPPORTED_SIG_ALGS: WebPkiSupportedAlgorithms = WebPkiSupportedAlgorithms {
+    all: &[
+        ECDSA_P256_SHA256,
+        ECDSA_P256_SHA384,
+        ECDSA_P384_SHA256,
+        ECDSA_P384_SHA384,
+        ED25519,
+        RSA_PSS_SHA256,
+        RSA_PSS_SHA384,
+        RSA_PSS_SHA512,
+        RSA_PKCS1_SHA256,
+        RSA_PKCS1_SHA384,
+        RSA_PKCS1_SHA512,
+    ],
+    mapping: &[
+        (
+            SignatureScheme::ECDSA_NISTP384_SHA384,
+            &[ECDSA_P384_SHA384, ECDSA_P256_SHA384],
+        ),
+        (
+            SignatureScheme::ECDSA_NISTP256_SHA256,
+            &[ECDSA_P256_SHA256, ECDSA_P384_SHA256],
+        ),
+        (SignatureScheme::ED25519, &[ED25519]),
+        (SignatureScheme::RSA_PSS_SHA512, &[RSA_PSS_SHA512]),
+        (SignatureScheme::RSA_PSS_SHA384, &[RSA_PSS_SHA384]),
+        (SignatureScheme::RSA_PSS_SHA256, &[RSA_PSS_SHA256]),
+        (SignatureScheme::RSA_PKCS1_SHA512, &[RSA_PKCS1_SHA512]),
+        (SignatureScheme::RSA_PKCS1_SHA384, &[RSA_PKCS1_SHA384]),
+        (SignatureScheme::RSA_PKCS1_SHA256, &[RSA_PKCS1_SHA256]),
+    ],
+};
+
+static SHA256: Hash = Hash {
+    algo: HashAlgo::Sha256,
+    algorithm: hash::HashAlgorithm::SHA256,
+    output_len: 32,
+};
+
+static SHA384: Hash = Hash {
+    algo: HashAlgo::Sha384,
+    algorithm: hash::HashAlgorithm::SHA384,
+    output_len: 48,
+};
+
+static HMAC_SHA256: Hmac = Hmac {
+    algo: MacAlgo::HmacSha256,
+    output_len: 32,
+};
+
+static HMAC_SHA384: Hmac = Hmac {
+    algo: MacAlgo::HmacSha384,
+    output_len: 48,
+};
+
+#[derive(Debug)]
+struct Hash {
+    algo: HashAlgo,
+    algorithm: hash::HashAlgorithm,
+    output_len: usize,
+}
+
+impl hash::Hash for Hash {
+    fn start(&self) -> Box<dyn hash::Context> {
+        Box::new(HashContext {
+            hash: HashFunction::new(&self.algo).ok(),
+            output_len: self.output_len,
+        })
+    }
+
+    fn hash(&self, data: &[u8]) -> hash::Output {
+        let mut ctx = self.start();
+        ctx.update(data);
+        ctx.finish()
+    }
+
+    fn algorithm(&self) -> hash::HashAlgorithm {
+        self.algorithm
+    }
+
+    fn output_len(&self) -> usize {
+        self.output_len
+    }
+}
+
+// The rustls hash and HMAC traits have no way to report an error, and
+// panicking would take down the application. If the library fails, the
+// output is instead random bytes: they do not match what the peer
+// computes, so the handshake fails, and signing them reveals nothing.
+fn failed_output(len: usize) -> Vec<u8> {
+    RandomNumberGenerator::new_system()
+        .and_then(|mut rng| rng.read(len))
+        .unwrap_or_else(|_| vec![0xFF; len])
+}
+
+// The hash is None once any operation on it has failed
+struct HashContext {
+    hash: Option<HashFunction>,
+    output_len: usize,
+}
+
+impl hash::Context for HashContext {
+    fn fork_finish(&self) -> hash::Output {
+        self.fork().finish()
+    }
+
+    fn fork(&self) -> Box<dyn hash::Context> {
+        Box::new(HashContext {
+            hash: self.hash.as_ref().and_then(|hash| hash.duplicate().ok()),
+            output_len: self.output_len,
+        })
+    }
+
+    fn finish(mut self: Box<Self>) -> hash::Output {
+        match self.hash.as_mut().map(|hash| hash.finish()) {
+            Some(Ok(output)) => hash::Output::new(&output),
+            _ => hash::Output::new(&failed_output(self.output_len)),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        if let Some(hash) = &mut self.hash {
+            if hash.update(data).is_err() {
+                self.hash = None;
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Hmac {
+    algo: MacAlgo,
+    output_len: usize,
+}
+
+impl hmac::Hmac for Hmac {
+    fn with_key(&self, key: &[u8]) -> Box<dyn hmac::Key> {
+        Box::new(HmacKey {
+            algo: self.algo.clone(),
+            key: SecretVec::from(key),
+            output_len: self.output_len,
+        })
+    }
+
+    fn hash_output_len(&self) -> usize {
+        self.output_len
+    }
+}
+
+// MsgAuthCode cannot be shared between threads, so the key is kept
+// and a new MAC object is created for each tag
+struct HmacKey {
+    algo: MacAlgo,
+    key: SecretVec,
+    output_len: usize,
+}
+
+impl HmacKey {
+    fn mac(&self, first: &[u8], middle: &[&[u8]], last: &[u8]) -> Result<Vec<u8>> {
+        let mac = MsgAuthCode::new(&self.algo)?;
+        mac.set_key(&self.key)?;
+        mac.update(first)?;
+        for m in middle {
+            mac.update(m)?;
+        }
+        mac.update(last)?;
+        mac.finish()
+    }
+}
+
+impl hmac::Key for HmacKey {
+    fn sign_concat(&self, first: &[u8], middle: &[&[u8]], last: &[u8]) -> hmac::Tag {
+        let tag = self
+            .mac(first, middle, last)
+            .unwrap_or_else(|_| failed_output(self.output_len));
+        hmac::Tag::new(&tag)
+    }
+
+    fn tag_len(&self) -> usize {
+        self.output_len
+    }
+}
+
+#[derive(Debug)]
+struct Aead(AeadAlgo);
+
+impl Aead {
+    fn cipher(&self, direction: CipherDirection, key: &AeadKey, iv: Iv) -> Tls13MessageCipher {
+        // A failure here is reported by the first encrypt or decrypt
+        let cipher = Cipher::new(&self.0, direction).and_then(|mut cipher| {
+            cipher.set_key(key.as_ref())?;
+            Ok(cipher)
+        });
+        Tls13MessageCipher {
+            cipher: cipher.ok(),
+            iv,
+        }
+    }
+}
+
+impl Tls13AeadAlgorithm for Aead {
+    fn encrypter(&self, key: AeadKey, iv: Iv) -> Box<dyn MessageEncrypter> {
+        Box::new(self.cipher(CipherDirection::Encrypt, &key, iv))
+    }
+
+    fn decrypter(&self, key: AeadKey, iv: Iv) -> Box<dyn MessageDecrypter> {
+        Box::new(self.cipher(CipherDirection::Decrypt, &key, iv))
+    }
+
+    fn key_len(&self) -> usize {
+        match self.0 {
+            AeadAlgo::Aes128Gcm => 16,
+            _ => 32,
+        }
+    }
+
+    fn extract_keys(
+        &self,
+        key: AeadKey,
+        iv: Iv,
+    ) -> core::result::Result<ConnectionTrafficSecrets, UnsupportedOperationError> {
+        match self.0 {
+            AeadAlgo::Aes128Gcm => Ok(ConnectionTrafficSecrets::Aes128Gcm { key, iv }),
+            AeadAlgo::Aes256Gcm => Ok(ConnectionTrafficSecrets::Aes256Gcm { key, iv }),
+            AeadAlgo::ChaCha20Poly1305 => {
+                Ok(ConnectionTrafficSecrets::Chacha20Poly1305 { key, iv })
+            }
+            _ => Err(UnsupportedOperationError),
+        }
+    }
+}
+
+// All of the TLS 1.3 AEADs have a 16 byte tag
+const AEAD_TAG_LEN: usize = 16;
+
+struct Tls13MessageCipher {
+    // None if the cipher could not be created
+    cipher: Option<Cipher>,
+    iv: Iv,
+}
+
+impl Tls13MessageCipher {
+    fn process(&mut self, seq: u64, aad: &[u8], input: &[u8]) -> Result<Vec<u8>> {
+        let cipher = self.cipher.as_mut().ok_or_else(|| {
+            Error::with_message(
+                ErrorType::InvalidObjectState,
+                "The TLS cipher could not be created".to_owned(),
+            )
+        })?;
+        let nonce = cipher::Nonce::new(&self.iv, seq).0;
+        cipher.set_associated_data(aad)?;
+        cipher.process(&nonce, input)
+    }
+}
+
+impl MessageEncrypter for Tls13MessageCipher {
+    fn encrypt(
+        &mut self,
+        msg: OutboundPlainMessage<'_>,
+        seq: u64,
+    ) -> TlsResult<OutboundOpaqueMessage> {
+        let total_len = self.encrypted_payload_len(msg.payload.len());
+        let mut ptext = PrefixedPayload::with_capacity(total_len);
+        ptext.extend_from_chunks(&msg.payload);
+        ptext.extend_from_slice(&[u8::from(msg.typ)]);
+
+        let aad = cipher::make_tls13_aad(total_len);
+        let ctext = self
+            .process(seq, &aad, ptext.as_ref())
+            .map_err(|_| rustls::Error::EncryptError)?;
+
+        let mut payload = PrefixedPayload::with_capacity(ctext.len());
+        payload.extend_from_slice(&ctext);
+        Ok(OutboundOpaqueMessage::new(
+            ContentType::ApplicationData,
+            ProtocolVersion::TLSv1_2,
+            payload,
+        ))
+    }
+
+    fn encrypted_payload_len(&self, payload_len: usize) -> usize {
+        payload_len + 1 + AEAD_TAG_LEN
+    }
+}
+
+impl MessageDecrypter for Tls13MessageCipher {
+    fn decrypt<'a>(
+        &mut self,
+        mut msg: InboundOpaqueMessage<'a>,
+        seq: u64,
+    ) -> TlsResult<InboundPlainMessage<'a>> {
+        let payload = &mut msg.payload;
+        let aad = cipher::make_tls13_aad(payload.len());
+        // Scrubbed when dropped, whether or not the message is valid
+        let ptext = SecretVec::new(
+            self.process(seq, &aad, &payload[..])
+                .map_err(|_| rustls::Error::DecryptError)?,
+        );
+
+        payload[..ptext.len()].copy_from_slice(&ptext);
+        payload.truncate(ptext.len());
+        msg.into_tls13_unpadded_message()
+    }
+}
+
+#[derive(Debug)]
+struct KxGroup {
+    name: NamedGroup,
+    algo: &'static str,
+    params: &'static str,
+    secret_len: usize,
+}
+
+impl SupportedKxGroup for KxGroup {
+    fn start(&self) -> TlsResult<Box<dyn ActiveKeyExchange>> {
+        botan_span!("tls_kx_start", group = ?self.name);
+        let mut rng = RandomNumberGenerator::new_system().map_err(tls_error)?;
+        let key = Privkey::create(self.algo, self.params, &mut rng).map_err(tls_error)?;
+        let public = key.key_agreement_key().map_err(tls_error)?;
+
+        Ok(Box::new(KeyExchange {
+            group: self.name,
+            key,
+            public,
+            secret_len: self.secret_len,
+        }))
+    }
+
+    fn name(&self) -> NamedGroup {
+        self.name
+    }
+}
+
+struct KeyExchange {
+    group: NamedGroup,
+    key: Privkey,
+    public: Vec<u8>,
+    secret_len: usize,
+}
+
+impl ActiveKeyExchange for KeyExchange {
+    fn complete(self: Box<Self>, peer_pub_key: &[u8]) -> TlsResult<SharedSecret> {
+        botan_span!("tls_kx_complete", group = ?self.group);
+        let invalid = rustls::Error::PeerMisbehaved(PeerMisbehaved::InvalidKeyShare);
+
+        // Also rules out compressed points, which TLS 1.3 does not allow
+        if peer_pub_key.len() != self.public.len() {
+            return Err(invalid);
+        }
+
+        let secret = self
+            .key
+            .agree(peer_pub_key, self.secret_len, b"", "Raw")
+            .map_err(|_| invalid)?;
+        Ok(SharedSecret::from(&secret[..]))
+    }
+
+    fn pub_key(&self) -> &[u8] {
+        &self.public
+    }
+
+    fn group(&self) -> NamedGroup {
+        self.group
+    }
+}
+
+#[derive(Debug)]
+struct Random;
+
+impl SecureRandom for Random {
+    fn fill(&self, buf: &mut [u8]) -> core::result::Result<(), GetRandomFailed> {
+        let mut rng = RandomNumberGenerator::new_system().map_err(|_| GetRandomFailed)?;
+        rng.fill(buf).map_err(|_| GetRandomFailed)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum SigKind {
+    Rsa,
+    // The length in bytes of each of r and s
+    Ecdsa(usize),
+    Ed25519,
+}
+
+// Like ring and webpki, RSA keys smaller than this are rejected
+const MIN_RSA_BITS: usize = 2048;
+
+#[derive(Debug)]
+struct SigAlgorithm {
+    public_key_alg_id: AlgorithmIdentifier,
+    signature_alg_id: AlgorithmIdentifier,
+    kind: SigKind,
+    padding: &'static str,
+}
+
+impl SigAlgorithm {
+    fn verify(&self, public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<bool> {
+        botan_span!("tls_verify", padding = self.padding);
+        let key = Pubkey::load_der(&encode_spki(self.public_key_alg_id.as_ref(), public_key))?;
+
+        match self.kind {
+            SigKind::Rsa => {
+                if key.get_field("n")?.bit_count()? < MIN_RSA_BITS {
+                    return Ok(false);
+                }
+                key.verify(message, signature, self.padding)
+            }
+            SigKind::Ecdsa(scalar_len) => match ecdsa_der_to_raw(signature, scalar_len) {
+                Some(signature) => key.verify(message, &signature, self.padding),
+                None => Ok(false),
+            },
+            SigKind::Ed25519 => key.verify(message, signature, self.padding),
+        }
+    }
+}
+
+impl SignatureVerificationAlgorithm for SigAlgorithm {
+    fn verify_signature(
+        &self,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> core::result::Result<(), InvalidSignature> {
+        match self.verify(public_key, message, signature) {
+            Ok(true) => Ok(()),
+            _ => Err(InvalidSignature),
+        }
+    }
+
+    fn public_key_alg_id(&self) -> AlgorithmIdentifier {
+        self.public_key_alg_id
+    }
+
+    fn signature_alg_id(&self) -> AlgorithmIdentifier {
+        self.signature_alg_id
+    }
+}
+
+static ECDSA_P256_SHA256: &dyn SignatureVerificationAlgorithm = &SigAlgorithm {
+    public_key_alg_id: alg_id::ECDSA_P256,
+    signature_alg_id: alg_id::ECDSA_SHA256,
+    kind: SigKind::Ecdsa(32),
+    padding: "EMSA1(SHA-256)",
+};
+
+static ECDSA_P256_SHA384: &dyn SignatureVerificationAlgorithm = &SigAlgorithm {
+    public_key_alg_id: alg_id::ECDSA_P256,
+    signature_alg_id: alg_id::ECDSA_SHA384,
+    kind: SigKind::Ecdsa(32),
+    padding: "EMSA1(SHA-384)",
+};
+
+static ECDSA_P384_SHA256: &dyn SignatureVerificationAlgorithm = &SigAlgorithm {
+    public_key_alg_id: alg_id::ECDSA_P384,
+    signature_alg_id: alg_id::ECDSA_SHA256,
+    kind: SigKind::Ecdsa(48),
+    padding: "EMSA1(SHA-256)",
+};
+
+static ECDSA_P384_SHA384: &dyn SignatureVerificationAlgorithm = &SigAlgorithm {
+    public_key_alg_id: alg_id::ECDSA_P384,
+    signature_alg_id: alg_id::ECDSA_SHA384,
+    kind: SigKind::Ecdsa(48),
+    padding: "EMSA1(SHA-384)",
+};
+
+static ED25519: &dyn SignatureVerificationAlgorithm = &SigAlgorithm {
+    public_key_alg_id: alg_id::ED25519,
+    signature_alg_id: alg_id::ED25519,
+    kind: SigKind::Ed25519,
+    padding: "Pure",
+};
+
+static RSA_PSS_SHA256: &dyn SignatureVerificationAlgorithm = &SigAlgorithm {
+    public_key_alg_id: alg_id::RSA_ENCRYPTION,
+    signature_alg_id: alg_id::RSA_PSS_SHA256,
+    kind: SigKind::Rsa,
+    padding: "PSSR(SHA-256,MGF1,32)",
+};
+
+static RSA_PSS_SHA384: &dyn SignatureVerificationAlgorithm = &SigAlgorithm {
+    public_key_alg_id: alg_id::RSA_ENCRYPTION,
+    signature_alg_id: alg_id::RSA_PSS_SHA384,
+    kind: SigKind::Rsa,
+    padding: "PSSR(SHA-384,MGF1,48)",
+};
+
+static RSA_PSS_SHA512: &dyn SignatureVerificationAlgorithm = &SigAlgorithm {
+    public_key_alg_id: alg_id::RSA_ENCRYPTION,
+    signature_alg_id: alg_id::RSA_PSS_SHA512,
+    kind: SigKind::Rsa,
+    padding: "PSSR(SHA-512,MGF1,64)",
+};
+
+static RSA_PKCS1_SHA256: &dyn SignatureVerificationAlgorithm = &SigAlgorithm {
+    public_key_alg_id: alg_id::RSA_ENCRYPTION,
+    signature_alg_id: alg_id::RSA_PKCS1_SHA256,
+    kind: SigKind::Rsa,
+    padding: "PKCS1v15(SHA-256)",
+};
+
+static RSA_PKCS1_SHA384: &dyn SignatureVerificationAlgorithm = &SigAlgorithm {
+    public_key_alg_id: alg_id::RSA_ENCRYPTION,
+    signature_alg_id: alg_id::RSA_PKCS1_SHA384,
+    kind: SigKind::Rsa,
+    padding: "PKCS1v15(SHA-384)",
+};
+
+static RSA_PKCS1_SHA512: &dyn SignatureVerificationAlgorithm = &SigAlgorithm {
+    public_key_alg_id: alg_id::RSA_ENCRYPTION,
+    signature_alg_id: alg_id::RSA_PKCS1_SHA512,
+    kind: SigKind::Rsa,
+    padding: "PKCS1v15(SHA-512)",
+};
+
+// The signature schemes which can be used with each kind of key, in
+// order of preference, along with the padding Botan expects
+const RSA_SCHEMES: &[(SignatureScheme, &str)] = &[
+    (SignatureScheme::RSA_PSS_SHA512, "PSSR(SHA-512,MGF1,64)"),
+    (SignatureScheme::RSA_PSS_SHA384, "PSSR(SHA-384,MGF1,48)"),
+    (SignatureScheme::RSA_PSS_SHA256, "PSSR(SHA-256,MGF1,32)"),
+    (SignatureScheme::RSA_PKCS1_SHA512, "PKCS1v15(SHA-512)"),
+    (SignatureScheme::RSA_PKCS1_SHA384, "PKCS1v15(SHA-384)"),
+    (SignatureScheme::RSA_PKCS1_SHA256, "PKCS1v15(SHA-256)"),
+];
+
+const ECDSA_P256_SCHEMES: &[(SignatureScheme, &str)] =
+    &[(SignatureScheme::ECDSA_NISTP256_SHA256, "EMSA1(SHA-256)")];
+
+const ECDSA_P384_SCHEMES: &[(SignatureScheme, &str)] =
+    &[(SignatureScheme::ECDSA_NISTP384_SHA384, "EMSA1(SHA-384)")];
+
+const ED25519_SCHEMES: &[(SignatureScheme, &str)] = &[(SignatureScheme::ED25519, "Pure")];
+
+#[derive(Debug)]
+struct Keys;
+
+impl Keys {
+    fn load(key_der: &PrivateKeyDer<'_>) -> Result<Arc<dyn SigningKey>> {
+        let key = match key_der {
+            PrivateKeyDer::Pkcs8(der) => Privkey::load_der(der.secret_pkcs8_der())?,
+            PrivateKeyDer::Pkcs1(der) => Privkey::load_rsa_pkcs1(der.secret_pkcs1_der())?,
+            _ => {
+                return Err(Error::with_message(
+                    ErrorType::NotImplemented,
+                    "Only PKCS #8 and PKCS #1 private keys are supported".to_owned(),
+                ))
+            }
+        };
+
+        let (key, algorithm, schemes) = match key.algo_name()?.as_str() {
+            "RSA" => (key, SignatureAlgorithm::RSA, RSA_SCHEMES),
+            "ECDSA" => {
+                let key = EcdsaKeyPair::try_from(key)?;
+                let schemes = match key.curve() {
+                    "secp256r1" => ECDSA_P256_SCHEMES,
+                    "secp384r1" => ECDSA_P384_SCHEMES,
+                    curve => {
+                        return Err(Error::with_message(
+                            ErrorType::NotImplemented,
+                            format!("ECDSA keys on {} cannot be used with TLS 1.3", curve),
+                        ))
+                    }
+                };
+                (key.into(), SignatureAlgorithm::ECDSA, schemes)
+            }
+            "Ed25519" => (key, SignatureAlgorithm::ED25519, ED25519_SCHEMES),
+            algo => {
+                return Err(Error::with_message(
+                    ErrorType::NotImplemented,
+                    format!("{} keys cannot be used for TLS", algo),
+                ))
+            }
+        };
+
+        Ok(Arc::new(TlsSigningKey {
+            key: Arc::new(key),
+            algorithm,
+            schemes,
+        }))
+    }
+}
+
+impl KeyProvider for Keys {
+    fn load_private_key(&self, key_der: PrivateKeyDer<'static>) -> TlsResult<Arc<dyn SigningKey>> {
+        Keys::load(&key_der).map_err(tls_error)
+    }
+}
+
+#[derive(Debug)]
+struct TlsSigningKey {
+    key: Arc<Privkey>,
+    algorithm: SignatureAlgorithm,
+    schemes: &'static [(SignatureScheme, &'static str)],
+}
+
+impl SigningKey for TlsSigningKey {
+    fn choose_scheme(&self, offered: &[SignatureScheme]) -> Option<Box<dyn sign::Signer>> {
+        let &(scheme, padding) = self
+            .schemes
+            .iter()
+            .find(|(scheme, _)| offered.contains(scheme))?;
+
+        Some(Box::new(TlsSigner {
+            key: Arc::clone(&self.key),
+            scheme,
+            padding,
+            der_signature: self.algorithm == SignatureAlgorithm::ECDSA,
+        }))
+    }
+
+    fn algorithm(&self) -> SignatureAlgorithm {
+        self.algorithm
+    }
+}
+
+#[derive(Debug)]
+struct TlsSigner {
+    key: Arc<Privkey>,
+    scheme: SignatureScheme,
+    padding: &'static str,
+    // TLS uses DER encoded ECDSA signatures
+    der_signature: bool,
+}
+
+impl sign::Signer for TlsSigner {
+    fn sign(&self, message: &[u8]) -> TlsResult<Vec<u8>> {
+        botan_span!("tls_sign", scheme = ?self.scheme);
+        let mut rng = RandomNumberGenerator::new_system().map_err(tls_error)?;
+        let signature = self
+            .key
+            .sign(message, self.padding, &mut rng)
+            .map_err(tls_error)?;
+
+        if self.der_signature {
+            Ok(ecdsa_raw_to_der(&signature))
+        } else {
+            Ok(signature)
+        }
+    }
+
+    fn scheme(&self) -> SignatureScheme {
+        self.scheme
+    }
+}
+
+// Just enough DER to move keys and ECDSA signatures between the
+// encodings used by TLS and those Botan expects
+const DER_INTEGER: u8 = 0x02;
+const DER_BIT_STRING: u8 = 0x03;
+const DER_SEQUENCE: u8 = 0x30;
+
+fn der_header(out: &mut Vec<u8>, tag: u8, len: usize) {
+    out.push(tag);
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+}
+
+// Split a DER element with the expected tag from the front of `data`,
+// returning its contents and whatever follows it
+fn der_take(data: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+    if data.len() < 2 || data[0] != tag {
+        return None;
+    }
+
+    let (len, header_len) = match data[1] {
+        len if len < 0x80 => (len as usize, 2),
+        0x81 if data.len() > 2 && data[2] >= 0x80 => (data[2] as usize, 3),
+        _ => return None,
+    };
+
+    if data.len() - header_len < len {
+        return None;
+    }
+
+    let (contents, rest) = data[header_len..].split_at(len);
+    Some((contents, rest))
+}
+
+// Return the value of a DER INTEGER which must be non-negative and
+// minimally encoded, without any leading zero byte
+fn der_unsigned(int: &[u8]) -> Option<&[u8]> {
+    match int {
+        [] => None,
+        [first, ..] if first & 0x80 != 0 => None,
+        [0, next, ..] if next & 0x80 == 0 => None,
+        [0, rest @ ..] => Some(rest),
+        _ => Some(int),
+    }
+}
+
+// A certificate holds just the subjectPublicKey bits, while Botan
+// loads a complete SubjectPublicKeyInfo
+fn encode_spki(alg_id: &[u8], public_key: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    der_header(&mut body, DER_SEQUENCE, alg_id.len());
+    body.extend_from_slice(alg_id);
+    der_header(&mut body, DER_BIT_STRING, public_key.len() + 1);
+    body.push(0); // no unused bits
+    body.extend_from_slice(public_key);
+
+    let mut spki = Vec::new();
+    der_header(&mut spki, DER_SEQUENCE, body.len());
+    spki.extend_from_slice(&body);
+    spki
+}
+
+// Convert a DER encoded ECDSA signature to the fixed length r || s
+// form used by Botan
+fn ecdsa_der_to_raw(signature: &[u8], scalar_len: usize) -> Option<Vec<u8>> {
+    let (seq, rest) = der_take(signature, DER_SEQUENCE)?;
+    let (r, seq) = der_take(seq, DER_INTEGER)?;
+    let (s, seq) = der_take(seq, DER_INTEGER)?;
+    if !rest.is_empty() || !seq.is_empty() {
+        return None;
+    }
+
+    let mut raw = vec![0; 2 * scalar_len];
+    for (int, out) in [r, s].iter().zip(raw.chunks_mut(scalar_len)) {
+        let int = der_unsigned(int)?;
+        if int.len() > scalar_len {
+            return None;
+        }
+        out[scalar_len - int.len()..].copy_from_slice(int);
+    }
+    Some(raw)
+}
+
+fn ecdsa_raw_to_der(raw: &[u8]) -> Vec<u8> {
+    let mut ints = Vec::new();
+    for half in raw.chunks(raw.len() / 2) {
+        let skip = half.iter().take_while(|b| **b == 0).count();
+        let int = &half[skip..];
+        let pad = match int.first() {
+            Some(first) => first & 0x80 != 0,
+            None => true,
+        };
+
+        der_header(&mut ints, DER_INTEGER, int.len() + pad as usize);
+        if pad {
+            ints.push(0);
+        }
+        ints.extend_from_slice(int);
+    }
+
+    let mut der = Vec::new();
+    der_header(&mut der, DER_SEQUENCE, ints.len());
+    der.extend_from_slice(&ints);
+    der
+}
@@ -0,0 +1,181 @@
+//! One-call helpers which choose the algorithms for you
+//!
+//! Each function uses a fixed, conservative choice of algorithm and the
+//! system RNG, so there are no algorithm strings to get wrong. Use the
+//! rest of the crate when interoperating with a particular format.
+//!
+//! # Examples
+//!
+//! ```
+//! let key = botan::simple::generate_ed25519().unwrap();
+//! let signature = botan::simple::sign_detached(key.as_privkey(), b"release.tar").unwrap();
+//! let pubkey = key.as_privkey().pubkey().unwrap();
+//! assert!(botan::simple::verify_detached(&pubkey, b"release.tar", &signature).unwrap());
+//!
+//! assert_eq!(
+//!     botan::simple::hash_hex(b"abc").unwrap(),
+//!     "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+//! );
+//! ```
+
+use crate::utils::*;
+
+use crate::aead::{AeadDecryptor, AeadEncryptor};
+use crate::algo::{AeadAlgo, HashAlgo};
+use crate::codec::hex_encode;
+use crate::hash::HashFunction;
+use crate::keys::Ed25519KeyPair;
+use crate::keystore::{
+    argon2id_key, Argon2Limits, ARGON2_ITERATIONS, ARGON2_MEMORY_KIB, ARGON2_PARALLELISM,
+};
+use crate::pubkey::{Privkey, Pubkey};
+use crate::rng::RandomNumberGenerator;
+
+// The output of encrypt_with_password is
+//
+//   version        u8, currently 1
+//   memory (KiB)   u32
+//   iterations     u32
+//   parallelism    u32
+//   salt           16 bytes
+//   nonce          12 bytes
+//   ciphertext     AES-256/GCM with the header as associated data
+//
+// where the key is derived from the password with Argon2id.
+
+const PASSWORD_FORMAT_VERSION: u8 = 1;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+const HEADER_LENGTH: usize = 1 + 3 * 4 + SALT_LENGTH + NONCE_LENGTH;
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+// The padding used with each kind of key by sign_detached
+fn signature_padding(algo: &str, order: &dyn Fn() -> Result<crate::mp::MPI>) -> Result<String> {
+    match algo {
+        "Ed25519" | "Ed448" => Ok("Pure".to_owned()),
+        "ECDSA" => {
+            let hash = match order()?.bit_count()? {
+                0..=256 => "SHA-256",
+                257..=384 => "SHA-384",
+                _ => "SHA-512",
+            };
+            Ok(format!("EMSA1({})", hash))
+        }
+        "RSA" => Ok("PSS(SHA-256)".to_owned()),
+        _ => Err(Error::with_message(
+            ErrorType::NotImplemented,
+            format!("Detached signatures are not supported for {} keys", algo),
+        )),
+    }
+}
+
+/// Hash `data` with SHA-256, returning the digest as lowercase hex
+pub fn hash_hex(data: &[u8]) -> Result<String> {
+    let mut hash = HashFunction::new(HashAlgo::Sha256)?;
+    hash.update(data)?;
+    Ok(hex_encode(&hash.finish()?)?.to_ascii_lowercase())
+}
+
+/// Generate a new Ed25519 key
+pub fn generate_ed25519() -> Result<Ed25519KeyPair> {
+    let mut rng = RandomNumberGenerator::new_system()?;
+    Ed25519KeyPair::generate(&mut rng)
+}
+
+/// Sign `message` with `key`, returning the signature
+///
+/// Ed25519 and Ed448 keys are used as is, ECDSA keys with SHA-256,
+/// SHA-384 or SHA-512 according to the size of the curve, and RSA keys
+/// with PSS and SHA-256. Other kinds of keys are not supported.
+pub fn sign_detached(key: &Privkey, message: &[u8]) -> Result<Vec<u8>> {
+    let padding = signature_padding(&key.algo_name()?, &|| key.get_field("order"))?;
+    let mut rng = RandomNumberGenerator::new_system()?;
+    key.sign(message, &padding, &mut rng)
+}
+
+/// Verify a signature made by `sign_detached`
+pub fn verify_detached(key: &Pubkey, message: &[u8], signature: &[u8]) -> Result<bool> {
+    let padding = signature_padding(&key.algo_name()?, &|| key.get_field("order"))?;
+    key.verify(message, signature, &padding)
+}
+
+/// Encrypt `plaintext` with a key derived from `password`
+///
+/// The key is derived with Argon2id (using 64 MiB of memory) and a random
+/// salt, and the data is encrypted with AES-256/GCM. The output includes
+/// everything `decrypt_with_password` needs apart from the password.
+///
+/// # Examples
+///
+/// ```
+/// let ctext = botan::simple::encrypt_with_password("password", b"secret").unwrap();
+/// let ptext = botan::simple::decrypt_with_password("password", &ctext).unwrap();
+/// assert_eq!(ptext, b"secret".to_vec());
+/// assert!(botan::simple::decrypt_with_password("wrong", &ctext).is_err());
+/// ```
+pub fn encrypt_with_password(password: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut rng = RandomNumberGenerator::new_system()?;
+    let salt = rng.read(SALT_LENGTH)?;
+    let nonce = rng.read(NONCE_LENGTH)?;
+
+    let mut header = Vec::with_capacity(HEADER_LENGTH);
+    header.push(PASSWORD_FORMAT_VERSION);
+    header.extend_from_slice(&ARGON2_MEMORY_KIB.to_be_bytes());
+    header.extend_from_slice(&ARGON2_ITERATIONS.to_be_bytes());
+    header.extend_from_slice(&ARGON2_PARALLELISM.to_be_bytes());
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce);
+
+    let key = argon2id_key(
+        password,
+        &salt,
+        ARGON2_MEMORY_KIB,
+        ARGON2_ITERATIONS,
+        ARGON2_PARALLELISM,
+    )?;
+    let mut aead = AeadEncryptor::new(AeadAlgo::Aes256Gcm)?;
+    aead.set_key(&key)?;
+    aead.set_associated_data(&header)?;
+
+    let mut out = header;
+    out.extend_from_slice(&aead.encrypt(&nonce, plaintext)?);
+    Ok(out)
+}
+
+/// Decrypt the output of `encrypt_with_password`
+///
+/// # Errors
+/// Fails with `ErrorType::BadAuthCode` if the password is wrong or the
+/// ciphertext was modified
+pub fn decrypt_with_password(password: &str, ciphertext: &[u8]) -> Result<Vec<u8>> {
+    if ciphertext.len() < HEADER_LENGTH || ciphertext[0] != PASSWORD_FORMAT_VERSION {
+        return Err(Error::with_message(
+            ErrorType::InvalidInput,
+            "Not a password encrypted message".to_owned(),
+        ));
+    }
+
+    let (header, body) = ciphertext.split_at(HEADER_LENGTH);
+    let memory_kib = read_u32(header, 1);
+    let iterations = read_u32(header, 5);
+    let parallelism = read_u32(header, 9);
+    Argon2Limits::default().check(memory_kib, iterations, parallelism)?;
+
+    let salt = &header[13..13 + SALT_LENGTH];
+    let nonce = &header[13 + SALT_LENGTH..];
+
+    let key = argon2id_key(password, salt, memory_kib, iterations, parallelism)?;
+    let mut aead = AeadDecryptor::new(AeadAlgo::Aes256Gcm)?;
+    aead.set_key(&key)?;
+    aead.set_associated_data(header)?;
+
+    aead.decrypt(nonce, body)
+}
@@ -0,0 +1,323 @@
+use crate::utils::*;
+
+use core::convert::TryFrom;
+
+use crate::cipher::Cipher;
+use crate::mac::MsgAuthCode;
+use crate::memutils::{const_time_compare, SecretVec};
+use crate::rng::RandomNumberGenerator;
+
+/// A key for a cipher, block cipher or MAC
+///
+/// Unlike a plain byte slice, a `SymmetricKey` cannot be passed where a
+/// nonce or message is expected. Its length is checked against the
+/// `KeySpec` of the algorithm when it is created, the contents are
+/// zeroized when it is dropped, and `Debug` does not show the key.
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let mut cipher = botan::Cipher::new("AES-128/GCM", botan::CipherDirection::Encrypt).unwrap();
+/// let key = botan::SymmetricKey::generate(&cipher.key_spec().unwrap(), &mut rng).unwrap();
+/// assert_eq!(key.len(), 16);
+/// assert_eq!(format!("{:?}", key), "SymmetricKey([REDACTED; 16])");
+/// cipher.set_symmetric_key(&key).unwrap();
+///
+/// assert!(botan::SymmetricKey::new(&[0; 15], &cipher.key_spec().unwrap()).is_err());
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct SymmetricKey {
+    key: SecretVec,
+}
+
+impl SymmetricKey {
+    /// Create a key, checking that its length is valid for `spec`
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::InvalidKeyLength` if the key is not a
+    /// valid length
+    pub fn new(key: &[u8], spec: &KeySpec) -> Result<SymmetricKey> {
+        if !spec.is_valid_keylength(key.len()) {
+            return Err(Error::with_message(
+                ErrorType::InvalidKeyLength,
+                format!("A {} byte key is not valid for this algorithm", key.len()),
+            ));
+        }
+
+        Ok(SymmetricKey {
+            key: SecretVec::from(key),
+        })
+    }
+
+    /// Generate a random key of a valid length for `spec`
+    ///
+    /// See `KeySpec::generate_key`.
+    pub fn generate(spec: &KeySpec, rng: &mut RandomNumberGenerator) -> Result<SymmetricKey> {
+        Ok(SymmetricKey {
+            key: SecretVec::new(spec.generate_key(rng)?),
+        })
+    }
+
+    /// Return the bytes of the key
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// Return the length of the key in bytes
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.key.len()
+    }
+
+    /// Return true if the key is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.key.is_empty()
+    }
+}
+
+impl AsRef<[u8]> for SymmetricKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.key
+    }
+}
+
+impl core::fmt::Debug for SymmetricKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "SymmetricKey([REDACTED; {}])", self.key.len())
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for SymmetricKey {
+    fn zeroize(&mut self) {
+        self.key.clear();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for SymmetricKey {}
+
+/// A nonce for a cipher
+///
+/// The length is checked against the cipher when the nonce is created.
+/// Nonces are not secret, so unlike `SymmetricKey` they are shown by
+/// `Debug` and are not zeroized.
+///
+/// # Examples
+/// ```
+/// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+/// let mut cipher = botan::Cipher::new("AES-128/GCM", botan::CipherDirection::Encrypt).unwrap();
+/// cipher.set_key(&[0; 16]).unwrap();
+///
+/// let nonce = botan::Nonce::generate(&cipher, &mut rng).unwrap();
+/// let ctext = cipher.process_with_nonce(&nonce, b"message").unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Nonce {
+    nonce: Vec<u8>,
+}
+
+impl Nonce {
+    /// Create a nonce, checking that its length is valid for `cipher`
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::BadParameter` if the nonce is not a valid
+    /// length
+    pub fn new(nonce: &[u8], cipher: &Cipher) -> Result<Nonce> {
+        if !cipher.valid_nonce_length(nonce.len())? {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                format!("A {} byte nonce is not valid for this cipher", nonce.len()),
+            ));
+        }
+
+        Ok(Nonce {
+            nonce: nonce.to_vec(),
+        })
+    }
+
+    /// Generate a random nonce of the default length for `cipher`
+    ///
+    /// See `Cipher::generate_nonce`.
+    pub fn generate(cipher: &Cipher, rng: &mut RandomNumberGenerator) -> Result<Nonce> {
+        Ok(Nonce {
+            nonce: cipher.generate_nonce(rng)?,
+        })
+    }
+
+    /// Return the bytes of the nonce
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.nonce
+    }
+
+    /// Return the length of the nonce in bytes
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nonce.len()
+    }
+
+    /// Return true if the nonce is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nonce.is_empty()
+    }
+}
+
+impl AsRef<[u8]> for Nonce {
+    fn as_ref(&self) -> &[u8] {
+        &self.nonce
+    }
+}
+
+/// The nonce length of XChaCha20Poly1305, in bytes
+pub const XCHACHA_NONCE_LENGTH: usize = 24;
+
+/// A 24 byte nonce for `XChaCha20Poly1305`
+///
+/// Since the length is part of the type, a nonce of the wrong length is
+/// rejected at compile time when created from an array. A nonce this
+/// long can safely be chosen at random for every message.
+///
+/// # Examples
+/// ```
+/// use std::convert::TryFrom;
+///
+/// let nonce = botan::XChaChaNonce::new([0x40; 24]);
+/// assert_eq!(nonce.as_bytes().len(), 24);
+///
+/// assert!(botan::XChaChaNonce::try_from(&[0x40; 24][..]).is_ok());
+/// assert!(botan::XChaChaNonce::try_from(&[0x40; 12][..]).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct XChaChaNonce {
+    nonce: [u8; XCHACHA_NONCE_LENGTH],
+}
+
+impl XChaChaNonce {
+    /// Create a nonce from its bytes
+    #[must_use]
+    pub const fn new(nonce: [u8; XCHACHA_NONCE_LENGTH]) -> XChaChaNonce {
+        XChaChaNonce { nonce }
+    }
+
+    /// Generate a random nonce
+    pub fn generate(rng: &mut RandomNumberGenerator) -> Result<XChaChaNonce> {
+        let mut nonce = [0u8; XCHACHA_NONCE_LENGTH];
+        rng.fill(&mut nonce)?;
+        Ok(XChaChaNonce { nonce })
+    }
+
+    /// Return the bytes of the nonce
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; XCHACHA_NONCE_LENGTH] {
+        &self.nonce
+    }
+}
+
+impl From<[u8; XCHACHA_NONCE_LENGTH]> for XChaChaNonce {
+    fn from(nonce: [u8; XCHACHA_NONCE_LENGTH]) -> XChaChaNonce {
+        XChaChaNonce::new(nonce)
+    }
+}
+
+impl TryFrom<&[u8]> for XChaChaNonce {
+    type Error = Error;
+
+    fn try_from(nonce: &[u8]) -> Result<XChaChaNonce> {
+        if nonce.len() != XCHACHA_NONCE_LENGTH {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                format!(
+                    "XChaCha20Poly1305 takes a {} byte nonce, not {}",
+                    XCHACHA_NONCE_LENGTH,
+                    nonce.len()
+                ),
+            ));
+        }
+        let mut out = [0u8; XCHACHA_NONCE_LENGTH];
+        out.copy_from_slice(nonce);
+        Ok(XChaChaNonce { nonce: out })
+    }
+}
+
+impl AsRef<[u8]> for XChaChaNonce {
+    fn as_ref(&self) -> &[u8] {
+        &self.nonce
+    }
+}
+
+/// An authentication tag produced by a MAC
+///
+/// Comparisons between tags run in constant time.
+///
+/// # Examples
+/// ```
+/// let mac = botan::MsgAuthCode::new("HMAC(SHA-256)").unwrap();
+/// mac.set_key(&[0; 32]).unwrap();
+/// mac.update(b"message").unwrap();
+/// let tag = mac.finish_tag().unwrap();
+///
+/// let received = botan::AuthTag::new(tag.as_bytes(), &mac).unwrap();
+/// mac.update(b"message").unwrap();
+/// assert!(mac.verify_tag(&received).unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct AuthTag {
+    tag: Vec<u8>,
+}
+
+impl AuthTag {
+    /// Create a tag, checking that its length matches the output of `mac`
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::BadParameter` if the tag is the wrong length
+    pub fn new(tag: &[u8], mac: &MsgAuthCode) -> Result<AuthTag> {
+        if tag.len() != mac.output_length()? {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                format!("A {} byte tag is not valid for this MAC", tag.len()),
+            ));
+        }
+
+        Ok(AuthTag { tag: tag.to_vec() })
+    }
+
+    pub(crate) fn from_vec(tag: Vec<u8>) -> AuthTag {
+        AuthTag { tag }
+    }
+
+    /// Return the bytes of the tag
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.tag
+    }
+
+    /// Return the length of the tag in bytes
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tag.len()
+    }
+
+    /// Return true if the tag is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tag.is_empty()
+    }
+}
+
+impl AsRef<[u8]> for AuthTag {
+    fn as_ref(&self) -> &[u8] {
+        &self.tag
+    }
+}
+
+impl PartialEq for AuthTag {
+    fn eq(&self, other: &AuthTag) -> bool {
+        const_time_compare(&self.tag, &other.tag)
+    }
+}
+
+impl Eq for AuthTag {}
@@ -0,0 +1,143 @@
+use crate::utils::*;
+use botan_sys::*;
+
+#[cfg(feature = "no-std")]
+use alloc::sync::Arc;
+#[cfg(not(feature = "no-std"))]
+use std::sync::Arc;
+
+use crate::capability::Capability;
+use crate::rng::RandomNumberGenerator;
+
+// The FFI of Botan 3 exposes TPM 2.0 contexts, sessions and the TPM's
+// random number generator. It does not (yet) expose keys held in the
+// TPM, so those cannot be used through Privkey, Signer or Decryptor.
+
+// The library keeps only a pointer to the RNG passed to
+// enable_crypto_backend, and uses it for as long as the TPM context
+// exists. Sessions and RNGs created from a context keep it alive, so
+// each of them shares ownership of that RNG.
+pub(crate) type CryptoBackendRng = Arc<BackendRngSlot>;
+
+#[derive(Debug)]
+pub(crate) struct BackendRngSlot(Option<RandomNumberGenerator>);
+
+// Only the library uses the RNG once it is shared; this crate accesses it
+// solely through Arc::get_mut
+unsafe impl Sync for BackendRngSlot {}
+
+/// A connection to a TPM 2.0
+///
+/// This requires Botan 3.6 or later built with TPM2 support, and the `tpm2` feature
+///
+/// # Examples
+/// ```no_run
+/// let tpm = botan::Tpm2Context::new(Some("device:/dev/tpmrm0")).unwrap();
+/// let session = botan::Tpm2Session::unauthenticated(&tpm).unwrap();
+/// let mut rng = botan::RandomNumberGenerator::new_tpm2(&tpm, Some(&session)).unwrap();
+/// let random = rng.read(32).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Tpm2Context {
+    obj: botan_tpm2_ctx_t,
+    crypto_backend_rng: CryptoBackendRng,
+}
+
+botan_impl_drop!(Tpm2Context, botan_tpm2_ctx_destroy);
+
+/// A session with a TPM 2.0, used to protect the commands sent to it
+#[derive(Debug)]
+pub struct Tpm2Session {
+    obj: botan_tpm2_session_t,
+    _crypto_backend_rng: CryptoBackendRng,
+}
+
+botan_impl_drop!(Tpm2Session, botan_tpm2_session_destroy);
+
+impl Tpm2Context {
+    pub(crate) fn handle(&self) -> botan_tpm2_ctx_t {
+        self.obj
+    }
+
+    pub(crate) fn crypto_backend_rng(&self) -> CryptoBackendRng {
+        Arc::clone(&self.crypto_backend_rng)
+    }
+
+    fn from_handle(obj: botan_tpm2_ctx_t) -> Tpm2Context {
+        Tpm2Context {
+            obj,
+            crypto_backend_rng: Arc::new(BackendRngSlot(None)),
+        }
+    }
+
+    /// Connect to a TPM using a TCTI configuration string, such as
+    /// `"device:/dev/tpmrm0"` or `"tabrmd"`
+    ///
+    /// With `None` the TSS library's default TCTI is used.
+    pub fn new(tcti: Option<&str>) -> Result<Tpm2Context> {
+        Capability::Tpm2.require()?;
+        let tcti = match tcti {
+            Some(tcti) => Some(make_cstr(tcti)?),
+            None => None,
+        };
+        let tcti_ptr = tcti.as_ref().map_or(ptr::null(), |t| t.as_ptr());
+        let obj = botan_init!(botan_tpm2_ctx_init, tcti_ptr)?;
+        Ok(Tpm2Context::from_handle(obj))
+    }
+
+    /// Connect to a TPM, giving the TCTI name (for example `"device"`)
+    /// and its configuration (for example `"/dev/tpmrm0"`) separately
+    pub fn new_with_tcti(name: &str, conf: &str) -> Result<Tpm2Context> {
+        Capability::Tpm2.require()?;
+        let name = make_cstr(name)?;
+        let conf = make_cstr(conf)?;
+        let obj = botan_init!(botan_tpm2_ctx_init_ex, name.as_ptr(), conf.as_ptr())?;
+        Ok(Tpm2Context::from_handle(obj))
+    }
+
+    /// Return true if the library can act as the TSS crypto backend,
+    /// see `enable_crypto_backend`
+    #[must_use]
+    pub fn supports_crypto_backend() -> bool {
+        unsafe { botan_tpm2_supports_crypto_backend() == 1 }
+    }
+
+    /// Have the TSS library use Botan (with the given RNG) for the
+    /// cryptography it performs on the host, such as session encryption
+    ///
+    /// The RNG is kept until the context and every session and RNG
+    /// created from it have been dropped.
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::InvalidObjectState` if the backend was
+    /// already enabled, or if a session or RNG was already created from
+    /// this context.
+    pub fn enable_crypto_backend(&mut self, rng: RandomNumberGenerator) -> Result<()> {
+        let slot =
+            match Arc::get_mut(&mut self.crypto_backend_rng) {
+                Some(slot) if slot.0.is_none() => slot,
+                _ => return Err(Error::with_message(
+                    ErrorType::InvalidObjectState,
+                    "The crypto backend must be enabled once, before any session or RNG is created"
+                        .to_owned(),
+                )),
+            };
+        let rng = slot.0.get_or_insert(rng);
+        botan_call!(botan_tpm2_ctx_enable_crypto_backend, self.obj, rng.handle())
+    }
+}
+
+impl Tpm2Session {
+    pub(crate) fn handle(&self) -> botan_tpm2_session_t {
+        self.obj
+    }
+
+    /// Start a session which is encrypted, but not authenticated
+    pub fn unauthenticated(ctx: &Tpm2Context) -> Result<Tpm2Session> {
+        let obj = botan_init!(botan_tpm2_unauthenticated_session_init, ctx.handle())?;
+        Ok(Tpm2Session {
+            obj,
+            _crypto_backend_rng: ctx.crypto_backend_rng(),
+        })
+    }
+}
@@ -2,7 +2,7 @@ use botan_sys::*;
 use core::fmt;
 
 #[cfg(feature = "no-std")]
-pub(crate) use alloc::{borrow::ToOwned, string::String, string::ToString, vec::Vec};
+pub(crate) use alloc::{borrow::ToOwned, boxed::Box, string::String, string::ToString, vec::Vec};
 
 #[cfg(feature = "no-std")]
 pub(crate) use cstr_core::{CStr, CString};
@@ -10,6 +10,11 @@ pub(crate) use cstr_core::{CStr, CString};
 #[cfg(not(feature = "no-std"))]
 pub(crate) use std::ffi::{CStr, CString};
 
+#[cfg(not(feature = "no-std"))]
+use std::sync::Arc;
+
+use crate::rng::RandomNumberGenerator;
+
 pub(crate) use core::mem;
 pub(crate) use core::ptr;
 pub(crate) use cty::{c_char, c_int, c_void};
@@ -22,33 +27,318 @@ pub(crate) fn make_cstr(input: &str) -> Result<CString> {
     Ok(cstr)
 }
 
+// Outputs which fit in this many bytes are first written to a buffer on
+// the stack, avoiding a zero-filled heap allocation that is then resized
+const STACK_BUFFER_SIZE: usize = 256;
+
 pub(crate) fn call_botan_ffi_returning_vec_u8(
     initial_size: usize,
     cb: &dyn Fn(*mut u8, *mut usize) -> c_int,
 ) -> Result<Vec<u8>> {
-    let mut output = vec![0; initial_size];
-    let mut out_len = output.len();
+    let mut out_len = initial_size;
+
+    let rc = if initial_size <= STACK_BUFFER_SIZE {
+        let mut stack_buf = [0u8; STACK_BUFFER_SIZE];
+        let rc = cb(stack_buf.as_mut_ptr(), &mut out_len);
+        let output = if rc == 0 {
+            assert!(out_len <= initial_size);
+            Some(stack_buf[..out_len].to_vec())
+        } else {
+            None
+        };
+        // The output may be secret; don't leave a copy behind on the stack
+        crate::memutils::scrub_mem(&mut stack_buf[..]);
+        if let Some(output) = output {
+            return Ok(output);
+        }
+        rc
+    } else {
+        let mut output = vec![0; initial_size];
+        let rc = cb(output.as_mut_ptr(), &mut out_len);
+        if rc == 0 {
+            assert!(out_len <= output.len());
+            output.truncate(out_len);
+            return Ok(output);
+        }
+        rc
+    };
 
-    let rc = cb(output.as_mut_ptr(), &mut out_len);
-    if rc == 0 {
-        assert!(out_len <= output.len());
-        output.resize(out_len, 0);
-        return Ok(output);
-    } else if rc != BOTAN_FFI_ERROR_INSUFFICIENT_BUFFER_SPACE {
+    if rc != BOTAN_FFI_ERROR_INSUFFICIENT_BUFFER_SPACE {
         return Err(Error::from_rc(rc));
     }
 
-    output.resize(out_len, 0);
+    let mut output = vec![0; out_len];
     let rc = cb(output.as_mut_ptr(), &mut out_len);
 
     if rc != 0 {
         return Err(Error::from_rc(rc));
     }
 
-    output.resize(out_len, 0);
+    output.truncate(out_len);
     Ok(output)
 }
 
+// Pass the output of one of Botan 3's "view" functions to `sink` as it
+// is produced, rather than guessing its size (and possibly encoding it
+// twice) as call_botan_ffi_returning_vec_u8 does
+#[cfg(feature = "botan3")]
+pub(crate) fn call_botan_ffi_viewing(
+    sink: &mut dyn FnMut(&[u8]) -> Result<()>,
+    cb: &dyn Fn(botan_view_ctx, botan_view_bin_fn) -> c_int,
+) -> Result<()> {
+    struct ViewState<'a> {
+        sink: &'a mut dyn FnMut(&[u8]) -> Result<()>,
+        err: Option<Error>,
+    }
+
+    extern "C" fn view(ctx: botan_view_ctx, data: *const u8, len: usize) -> c_int {
+        let state = unsafe { &mut *(ctx as *mut ViewState) };
+        let data = if len == 0 {
+            &[]
+        } else {
+            unsafe { core::slice::from_raw_parts(data, len) }
+        };
+        match (state.sink)(data) {
+            Ok(()) => 0,
+            Err(e) => {
+                state.err = Some(e);
+                BOTAN_FFI_ERROR_UNKNOWN_ERROR
+            }
+        }
+    }
+
+    let mut state = ViewState { sink, err: None };
+    let rc = cb(&mut state as *mut ViewState as botan_view_ctx, view);
+
+    if let Some(e) = state.err {
+        return Err(e);
+    }
+    if rc != 0 {
+        return Err(Error::from_rc(rc));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "no-std"))]
+const READER_CHUNK_SIZE: usize = 64 * 1024;
+
+// Pass everything read from `reader` to `process`, in chunks of
+// bounded size
+#[cfg(not(feature = "no-std"))]
+pub(crate) fn read_chunks<R: std::io::Read>(
+    mut reader: R,
+    mut process: impl FnMut(&[u8]) -> Result<()>,
+) -> Result<()> {
+    let mut buf = vec![0; READER_CHUNK_SIZE];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => process(&buf[..n])?,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+// Replace the contents of the file at `path` with `data`.
+//
+// A file holding a private key is written to a new temporary file in the
+// same directory, readable only by its owner, which is then renamed over
+// `path`. So the key never exists in a file others can read, even if
+// `path` already existed with wider permissions, and a crash part way
+// through leaves either the old contents or the new ones.
+#[cfg(not(feature = "no-std"))]
+pub(crate) fn write_file(path: &std::path::Path, data: &[u8], private: bool) -> Result<()> {
+    if private {
+        write_private_file(path, data)
+    } else {
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "no-std"))]
+fn write_private_file(path: &std::path::Path, data: &[u8]) -> Result<()> {
+    use std::io::Write;
+
+    let (tmp_path, mut file) = create_private_file(path)?;
+
+    let result = file
+        .write_all(data)
+        .and_then(|()| file.sync_all())
+        .and_then(|()| {
+            drop(file);
+            std::fs::rename(&tmp_path, path)
+        });
+
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+
+    // Make the rename itself durable
+    #[cfg(unix)]
+    {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => std::path::Path::new("."),
+        };
+        let _ = std::fs::File::open(dir).and_then(|dir| dir.sync_all());
+    }
+
+    Ok(())
+}
+
+// Create a new, empty file next to `path` which only its owner can access
+#[cfg(not(feature = "no-std"))]
+fn create_private_file(path: &std::path::Path) -> Result<(std::path::PathBuf, std::fs::File)> {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let name = path.file_name().ok_or_else(|| {
+        Error::with_message(
+            ErrorType::InvalidInput,
+            format!("{} does not name a file", path.display()),
+        )
+    })?;
+
+    loop {
+        let mut tmp_name = std::ffi::OsString::from(".");
+        tmp_name.push(name);
+        tmp_name.push(format!(
+            ".{}-{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let tmp_path = path.with_file_name(tmp_name);
+
+        // Never follows a symlink or reuses an existing file
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        #[cfg(windows)]
+        {
+            use std::os::windows::fs::OpenOptionsExt;
+            // GENERIC_WRITE | WRITE_DAC, so the ACL can be replaced below
+            options.access_mode(0x4000_0000 | 0x0004_0000);
+        }
+
+        match options.open(&tmp_path) {
+            Ok(file) => {
+                #[cfg(windows)]
+                {
+                    if let Err(e) = restrict_to_owner(&file) {
+                        drop(file);
+                        let _ = std::fs::remove_file(&tmp_path);
+                        return Err(e.into());
+                    }
+                }
+                return Ok((tmp_path, file));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+// Replace the ACL of `file` with one granting access only to its owner.
+// The ACL is protected, so nothing is inherited from the directory.
+#[cfg(all(windows, not(feature = "no-std")))]
+fn restrict_to_owner(file: &std::fs::File) -> std::io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::io::AsRawHandle;
+
+    const SDDL_REVISION_1: u32 = 1;
+    const SE_FILE_OBJECT: u32 = 1;
+    const DACL_SECURITY_INFORMATION: u32 = 0x0000_0004;
+    const PROTECTED_DACL_SECURITY_INFORMATION: u32 = 0x8000_0000;
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            sddl: *const u16,
+            revision: u32,
+            descriptor: *mut *mut c_void,
+            descriptor_len: *mut u32,
+        ) -> i32;
+        fn GetSecurityDescriptorDacl(
+            descriptor: *mut c_void,
+            present: *mut i32,
+            dacl: *mut *mut c_void,
+            defaulted: *mut i32,
+        ) -> i32;
+        fn SetSecurityInfo(
+            handle: *mut c_void,
+            object_type: u32,
+            information: u32,
+            owner: *mut c_void,
+            group: *mut c_void,
+            dacl: *mut c_void,
+            sacl: *mut c_void,
+        ) -> u32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn LocalFree(mem: *mut c_void) -> *mut c_void;
+    }
+
+    // Full access for the owner, and no other entries
+    let sddl: Vec<u16> = std::ffi::OsStr::new("D:P(A;;FA;;;OW)")
+        .encode_wide()
+        .chain(Some(0))
+        .collect();
+
+    let mut descriptor = ptr::null_mut();
+    let rc = unsafe {
+        ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            sddl.as_ptr(),
+            SDDL_REVISION_1,
+            &mut descriptor,
+            ptr::null_mut(),
+        )
+    };
+    if rc == 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    let mut present = 0;
+    let mut defaulted = 0;
+    let mut dacl = ptr::null_mut();
+    let result = if unsafe {
+        GetSecurityDescriptorDacl(descriptor, &mut present, &mut dacl, &mut defaulted)
+    } == 0
+    {
+        Err(std::io::Error::last_os_error())
+    } else {
+        let rc = unsafe {
+            SetSecurityInfo(
+                file.as_raw_handle() as *mut c_void,
+                SE_FILE_OBJECT,
+                DACL_SECURITY_INFORMATION | PROTECTED_DACL_SECURITY_INFORMATION,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                dacl,
+                ptr::null_mut(),
+            )
+        };
+        match rc {
+            0 => Ok(()),
+            err => Err(std::io::Error::from_raw_os_error(err as i32)),
+        }
+    };
+
+    unsafe { LocalFree(descriptor) };
+    result
+}
+
 fn cstr_slice_to_str(raw_cstr: &[u8]) -> Result<String> {
     let cstr = CStr::from_bytes_with_nul(raw_cstr).map_err(Error::conversion_error)?;
     Ok(cstr.to_str().map_err(Error::conversion_error)?.to_owned())
@@ -64,15 +354,35 @@ pub(crate) fn call_botan_ffi_returning_string(
     initial_size: usize,
     cb: &dyn Fn(*mut u8, *mut usize) -> c_int,
 ) -> Result<String> {
-    let v = call_botan_ffi_returning_vec_u8(initial_size, cb)?;
+    let mut required = initial_size;
+
+    if initial_size <= STACK_BUFFER_SIZE {
+        let mut stack_buf = [0u8; STACK_BUFFER_SIZE];
+        let rc = cb(stack_buf.as_mut_ptr(), &mut required);
+        if rc == 0 {
+            assert!(required <= initial_size);
+            let output = cstr_slice_to_str(&stack_buf[..required]);
+            crate::memutils::scrub_mem(&mut stack_buf[..]);
+            return output;
+        } else if rc != BOTAN_FFI_ERROR_INSUFFICIENT_BUFFER_SPACE {
+            return Err(Error::from_rc(rc));
+        }
+    }
+
+    let v = call_botan_ffi_returning_vec_u8(required, cb)?;
     cstr_slice_to_str(&v)
 }
 
 /// The library error type
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug)]
 pub struct Error {
     err_type: ErrorType,
     message: Option<String>,
+    rc: Option<c_int>,
+    function: Option<&'static str>,
+    context: Option<String>,
+    #[cfg(not(feature = "no-std"))]
+    source: Option<Arc<dyn std::error::Error + Send + Sync>>,
 }
 
 impl Error {
@@ -83,12 +393,48 @@ impl Error {
 
     /// Return an optional message specific to the error
     ///
-    /// This is only available in Botan 3.x; with older versions
-    /// it will always be None
+    /// For errors returned by the C++ library, this is the message of
+    /// the exception which caused the error. That is only available in
+    /// Botan 3.x; with older versions it will always be None.
     pub fn error_message(&self) -> Option<&str> {
         self.message.as_deref()
     }
 
+    /// Return the return code of the FFI call that failed
+    ///
+    /// This is None for errors which were detected on the Rust side.
+    pub fn rc(&self) -> Option<i32> {
+        self.rc
+    }
+
+    /// Return the name of the FFI function that failed, if known
+    pub fn function(&self) -> Option<&'static str> {
+        self.function
+    }
+
+    /// Return a description of the object and operation which failed,
+    /// if known
+    ///
+    /// For example "AES-256/GCM(16) decrypt" or "HMAC(SHA-256) set key".
+    pub fn context(&self) -> Option<&str> {
+        self.context.as_deref()
+    }
+
+    // Record the algorithm and operation which failed, unless an inner
+    // call already did
+    pub(crate) fn in_context(mut self, algo_name: &str, operation: &str) -> Self {
+        if self.context.is_none() {
+            self.context = Some(format!("{} {}", algo_name, operation));
+        }
+        self
+    }
+
+    pub(crate) fn from_rc_in(rc: c_int, function: &'static str) -> Self {
+        let mut err = Self::from_rc(rc);
+        err.function = Some(function);
+        err
+    }
+
     pub(crate) fn from_rc(rc: c_int) -> Self {
         let err_type = ErrorType::from(rc);
 
@@ -105,44 +451,74 @@ impl Error {
         #[cfg(not(feature = "botan3"))]
         let message = None;
 
-        Self { err_type, message }
+        Self {
+            err_type,
+            message,
+            rc: Some(rc),
+            function: None,
+            context: None,
+            #[cfg(not(feature = "no-std"))]
+            source: None,
+        }
     }
 
     pub(crate) fn with_message(err_type: ErrorType, message: String) -> Self {
         Self {
             err_type,
             message: Some(message),
+            rc: None,
+            function: None,
+            context: None,
+            #[cfg(not(feature = "no-std"))]
+            source: None,
         }
     }
 
     #[cfg(not(feature = "no-std"))]
-    pub(crate) fn conversion_error<T: std::error::Error>(e: T) -> Self {
-        Self {
-            err_type: ErrorType::ConversionError,
-            message: Some(format!("{}", e)),
-        }
+    pub(crate) fn conversion_error<T: std::error::Error + Send + Sync + 'static>(e: T) -> Self {
+        let mut err = Self::with_message(ErrorType::ConversionError, format!("{}", e));
+        err.source = Some(Arc::new(e));
+        err
     }
 
     // Hack to deal with missing std::error::Error in no-std
     #[cfg(feature = "no-std")]
     pub(crate) fn conversion_error<T: core::fmt::Display>(e: T) -> Self {
-        Self {
-            err_type: ErrorType::ConversionError,
-            message: Some(format!("{}", e)),
-        }
+        Self::with_message(ErrorType::ConversionError, format!("{}", e))
+    }
+}
+
+// The source error (if any) is not compared
+impl PartialEq for Error {
+    fn eq(&self, other: &Error) -> bool {
+        self.err_type == other.err_type
+            && self.message == other.message
+            && self.rc == other.rc
+            && self.function == other.function
+            && self.context == other.context
     }
 }
 
+impl Eq for Error {}
+
 impl core::fmt::Display for Error {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        match &self.message {
-            Some(m) => write!(f, "{} ({})", self.err_type, m),
-            None => write!(f, "{}", self.err_type),
+        if let Some(context) = &self.context {
+            write!(f, "{}: ", context)?;
+        }
+        write!(f, "{}", self.err_type)?;
+        if let Some(m) = &self.message {
+            write!(f, " ({})", m)?;
         }
+        if let Some(function) = self.function {
+            write!(f, " in {}", function)?;
+        }
+        Ok(())
     }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 /// Possible error categories
 pub enum ErrorType {
     /// A provided authentication code was incorrect
@@ -185,6 +561,15 @@ pub enum ErrorType {
     TlsError,
     /// An error occurred during an HTTP transaction
     HttpError,
+    /// A requested value was not available
+    NoValue,
+    /// An error occurred in Roughtime
+    RoughtimeError,
+    /// An error occurred while communicating with a TPM
+    TpmError,
+    /// The operation is not supported by the version of the library
+    /// in use, see `Capability`
+    RequiresNewerVersion,
 }
 
 impl fmt::Display for ErrorType {
@@ -216,6 +601,10 @@ impl fmt::Display for ErrorType {
             Self::ConversionError => "An error occured while converting data to C",
             Self::TlsError => "An error occurred in TLS",
             Self::HttpError => "An error occurred during an HTTP transaction",
+            Self::NoValue => "A requested value was not available",
+            Self::RoughtimeError => "An error occurred in Roughtime",
+            Self::TpmError => "An error occurred while communicating with a TPM",
+            Self::RequiresNewerVersion => "The operation requires a newer version of the library",
         };
 
         write!(f, "{}", msg)
@@ -223,7 +612,23 @@ impl fmt::Display for ErrorType {
 }
 
 #[cfg(not(feature = "no-std"))]
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.source {
+            Some(e) => Some(e.as_ref()),
+            None => None,
+        }
+    }
+}
+
+#[cfg(not(feature = "no-std"))]
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        let mut err = Self::with_message(ErrorType::SystemError, format!("{}", e));
+        err.source = Some(Arc::new(e));
+        err
+    }
+}
 
 impl From<i32> for ErrorType {
     fn from(err: i32) -> Self {
@@ -241,10 +646,14 @@ impl From<i32> for ErrorType {
             BOTAN_FFI_ERROR_INVALID_OBJECT_STATE => Self::InvalidObjectState,
             BOTAN_FFI_ERROR_KEY_NOT_SET => Self::KeyNotSet,
             BOTAN_FFI_ERROR_NOT_IMPLEMENTED => Self::NotImplemented,
+            BOTAN_FFI_ERROR_NO_VALUE => Self::NoValue,
             BOTAN_FFI_ERROR_NULL_POINTER => Self::NullPointer,
             BOTAN_FFI_ERROR_OUT_OF_MEMORY => Self::OutOfMemory,
+            BOTAN_FFI_ERROR_ROUGHTIME_ERROR => Self::RoughtimeError,
+            BOTAN_FFI_ERROR_STRING_CONVERSION_ERROR => Self::ConversionError,
             BOTAN_FFI_ERROR_SYSTEM_ERROR => Self::SystemError,
             BOTAN_FFI_ERROR_TLS_ERROR => Self::TlsError,
+            BOTAN_FFI_ERROR_TPM_ERROR => Self::TpmError,
             BOTAN_FFI_ERROR_UNKNOWN_ERROR => Self::UnknownError,
             BOTAN_FFI_INVALID_VERIFIER => Self::InvalidVerifier,
             _ => Self::UnknownError,
@@ -253,6 +662,7 @@ impl From<i32> for ErrorType {
 }
 
 /// Specifies valid keylengths for symmetric ciphers/MACs
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct KeySpec {
     min_keylen: usize,
     max_keylen: usize,
@@ -300,4 +710,38 @@ impl KeySpec {
     pub fn keylength_multiple(&self) -> usize {
         self.mod_keylen
     }
+
+    // Algorithms with a variable key length get 32 byte keys where
+    // possible, rather than (for example) HMAC's minimum of zero bytes
+    fn generated_keylength(&self) -> usize {
+        const PREFERRED_KEYLEN: usize = 32;
+
+        if self.max_keylen <= PREFERRED_KEYLEN {
+            return self.max_keylen;
+        }
+
+        let mut len = core::cmp::max(self.min_keylen, PREFERRED_KEYLEN);
+        let remainder = len % self.mod_keylen;
+        if remainder > 0 {
+            len += self.mod_keylen - remainder;
+        }
+        core::cmp::min(len, self.max_keylen)
+    }
+
+    /// Generate a random key of a valid length
+    ///
+    /// If several lengths are valid, a 32 byte key is generated if
+    /// possible, otherwise the closest valid length.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut rng = botan::RandomNumberGenerator::new_system().unwrap();
+    /// let mac = botan::MsgAuthCode::new("HMAC(SHA-256)").unwrap();
+    /// let key = mac.key_spec().unwrap().generate_key(&mut rng).unwrap();
+    /// assert_eq!(key.len(), 32);
+    /// mac.set_key(&key).unwrap();
+    /// ```
+    pub fn generate_key(&self, rng: &mut RandomNumberGenerator) -> Result<Vec<u8>> {
+        rng.read(self.generated_keylength())
+    }
 }
@@ -1,7 +1,12 @@
 use crate::utils::*;
 use botan_sys::*;
 
-#[derive(Debug)]
+/// FFI API versions known to this crate, oldest first
+///
+/// These correspond to Botan 2.0, 2.8, 2.13, 2.18 and 3.0
+const KNOWN_FFI_VERSIONS: [u32; 5] = [20150515, 20180713, 20191214, 20210220, 20230403];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 /// Information about the library version
 pub struct Version {
     /// The major version of the library
@@ -59,4 +64,44 @@ impl Version {
         let rc = unsafe { botan_ffi_supports_api(version) };
         rc == 0
     }
+
+    /// Return true if the linked library supports the specified FFI
+    /// API version
+    ///
+    /// This reflects the library actually in use at runtime, which may
+    /// differ from the one selected by Cargo features at build time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let version = botan::Version::current().unwrap();
+    /// assert!(version.supports_ffi(20180713));
+    /// ```
+    #[must_use]
+    pub fn supports_ffi(&self, version: u32) -> bool {
+        version <= self.ffi_api && Self::supports_version(version)
+    }
+
+    /// Return the FFI API versions known to this crate which the
+    /// linked library supports, oldest first
+    #[must_use]
+    pub fn supported_ffi_versions(&self) -> Vec<u32> {
+        KNOWN_FFI_VERSIONS
+            .iter()
+            .cloned()
+            .filter(|v| self.supports_ffi(*v))
+            .collect()
+    }
+
+    /// Return true if the linked library is Botan 3.x or later
+    #[must_use]
+    pub fn is_botan3(&self) -> bool {
+        self.major >= 3
+    }
+}
+
+impl core::fmt::Display for Version {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
 }
@@ -11,6 +11,9 @@ pub struct Certificate {
 
 botan_impl_drop!(Certificate, botan_x509_cert_destroy);
 
+unsafe impl Send for Certificate {}
+unsafe impl Sync for Certificate {}
+
 impl Clone for Certificate {
     fn clone(&self) -> Certificate {
         self.duplicate()
@@ -131,6 +134,16 @@ impl Certificate {
         Ok(Certificate { obj })
     }
 
+    /// Load an X.509 certificate from a file
+    ///
+    /// The file may be either PEM or DER; the format is detected from
+    /// its contents. Unlike `from_file`, any path can be used, including
+    /// ones which are not valid UTF-8.
+    #[cfg(not(feature = "no-std"))]
+    pub fn load_file<P: AsRef<std::path::Path>>(path: P) -> Result<Certificate> {
+        Certificate::load(&std::fs::read(path)?)
+    }
+
     /// Return the serial number of this certificate
     pub fn serial_number(&self) -> Result<Vec<u8>> {
         let sn_len = 32; // PKIX upper bound is 20
@@ -185,6 +198,8 @@ impl Certificate {
     pub fn public_key(&self) -> Result<Pubkey> {
         let mut key = ptr::null_mut();
         botan_call!(botan_x509_cert_get_public_key, self.obj, &mut key)?;
+        #[cfg(all(feature = "debug-ffi", not(feature = "no-std")))]
+        crate::debug_ffi::track(key as usize, "botan_x509_cert_get_public_key");
         Ok(Pubkey::from_handle(key))
     }
 
@@ -206,6 +221,9 @@ impl Certificate {
     }
 
     /// Attempt to verify this certificate
+    ///
+    /// `reference_time` is in seconds since the Unix epoch, with `None`
+    /// meaning the current time; see also `verify_at`
     pub fn verify(
         &self,
         intermediates: &[&Certificate],
@@ -214,6 +232,13 @@ impl Certificate {
         hostname: Option<&str>,
         reference_time: Option<u64>,
     ) -> Result<CertValidationStatus> {
+        botan_span!(
+            "cert_verify",
+            intermediates = intermediates.len(),
+            trusted = trusted.len(),
+            trusted_path = trusted_path.unwrap_or(""),
+            hostname = hostname.unwrap_or(""),
+        );
         let required_key_strength = 110;
 
         let trusted_path = make_cstr(trusted_path.unwrap_or(""))?;
@@ -248,6 +273,8 @@ impl Certificate {
             )
         };
 
+        botan_event!(rc, status = result, "certificate verification finished");
+
         if rc == 0 {
             Ok(CertValidationStatus::Success(result))
         } else if rc == 1 {
@@ -257,6 +284,40 @@ impl Certificate {
         }
     }
 
+    /// Attempt to verify this certificate as of the given time
+    ///
+    /// This is `verify` with the reference time as a `SystemTime`, for
+    /// example from `Clock::now`.
+    ///
+    /// # Errors
+    /// Fails with `ErrorType::BadParameter` for a time before one second
+    /// past the Unix epoch, since the library takes a time of zero to
+    /// mean the current time.
+    #[cfg(not(feature = "no-std"))]
+    pub fn verify_at(
+        &self,
+        intermediates: &[&Certificate],
+        trusted: &[&Certificate],
+        trusted_path: Option<&str>,
+        hostname: Option<&str>,
+        reference_time: std::time::SystemTime,
+    ) -> Result<CertValidationStatus> {
+        let reference_time = crate::clock::unix_time(reference_time)?;
+        if reference_time == 0 {
+            return Err(Error::with_message(
+                ErrorType::BadParameter,
+                "Certificates cannot be verified as of the Unix epoch".to_owned(),
+            ));
+        }
+        self.verify(
+            intermediates,
+            trusted,
+            trusted_path,
+            hostname,
+            Some(reference_time),
+        )
+    }
+
     /// Return true if the provided hostname is valid for this certificate
     pub fn matches_hostname(&self, hostname: &str) -> Result<bool> {
         let hostname = make_cstr(hostname)?;
@@ -271,3 +332,71 @@ impl Certificate {
         }
     }
 }
+
+/// Load a certificate, either DER or PEM
+impl<'a> core::convert::TryFrom<&'a [u8]> for Certificate {
+    type Error = Error;
+
+    fn try_from(data: &'a [u8]) -> Result<Certificate> {
+        Certificate::load(data)
+    }
+}
+
+/// Load a PEM encoded certificate
+impl core::str::FromStr for Certificate {
+    type Err = Error;
+
+    fn from_str(pem: &str) -> Result<Certificate> {
+        Certificate::load(pem.as_bytes())
+    }
+}
+
+// The FFI offers no way to encode a certificate, so only deserialization
+// (from PEM or DER) is supported
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Certificate {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Certificate, D::Error> {
+        struct CertificateVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for CertificateVisitor {
+            type Value = Certificate;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("a PEM or DER encoded X.509 certificate")
+            }
+
+            fn visit_str<E: serde::de::Error>(
+                self,
+                v: &str,
+            ) -> core::result::Result<Certificate, E> {
+                Certificate::load(v.as_bytes()).map_err(E::custom)
+            }
+
+            fn visit_bytes<E: serde::de::Error>(
+                self,
+                v: &[u8],
+            ) -> core::result::Result<Certificate, E> {
+                Certificate::load(v).map_err(E::custom)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(CertificateVisitor)
+        } else {
+            deserializer.deserialize_bytes(CertificateVisitor)
+        }
+    }
+}
+
+// As with serde, the FFI offers no way to encode a certificate, so it can
+// only be converted from an `openssl` certificate and not back again
+#[cfg(feature = "openssl")]
+impl core::convert::TryFrom<&openssl::x509::X509> for Certificate {
+    type Error = Error;
+
+    fn try_from(cert: &openssl::x509::X509) -> Result<Certificate> {
+        Certificate::load(&cert.to_der().map_err(Error::conversion_error)?)
+    }
+}